@@ -0,0 +1,488 @@
+//! Passing arguments and return values in the Procedure Call Standard
+//! for the Arm 64-bit Architecture (AAPCS64).
+//!
+//! See section 6.8 of the standard for the full details:
+//! https://github.com/ARM-software/abi-aa/blob/main/aapcs64/aapcs64.rst
+//!
+//! Integers and pointers are passed in the general purpose registers x0-x7,
+//! and floating point values (including `long double`, which is binary128) in v0-v7.
+//! A struct or union made up of one to four floating point values of the same type
+//! is a homogeneous floating-point aggregate (HFA), passed in one vector register per member.
+//! Other structs and unions of up to 16 bytes are passed in one general purpose register
+//! per doubleword; larger ones are copied by the caller and passed by reference.
+//! Return values are passed the same way, except that large structs are written through
+//! a hidden pointer in x8 (`sret`).
+//!
+//! Unlike the System V ABI, an argument that doesn't fit in the registers that are left
+//! goes on the stack, and so does every later argument in the same class of registers.
+//! Variadic arguments are passed like named ones, except on Apple platforms,
+//! where they always go on the stack; see `Conventions`.
+use cranelift::codegen::ir::{types, Type as IrType};
+use target_lexicon::{CallingConvention, Triple};
+
+use super::abi::{aggregate_size, FunctionAbi, PassMode, EIGHTBYTE};
+use saltwater_parser::arch::TargetInfo;
+use saltwater_parser::data::{types::ArrayType, types::FunctionType, *};
+
+/// The number of general purpose registers used for arguments (x0-x7)
+const GP_REGISTERS: u32 = 8;
+/// The number of vector registers used for arguments (v0-v7)
+const FP_REGISTERS: u32 = 8;
+/// The most members a homogeneous floating-point aggregate can have
+const HFA_MEMBERS: u64 = 4;
+/// Structs and unions larger than this are passed by reference
+const MAX_REGISTER_AGGREGATE: u64 = 16;
+
+/// The ways a platform's calling convention differs from the standard.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) struct Conventions {
+    /// Whether variadic arguments always go on the stack, in a whole doubleword each,
+    /// instead of being passed like named arguments.
+    pub(super) variadic_on_stack: bool,
+    /// Whether named arguments on the stack only take up their own size,
+    /// instead of a whole doubleword.
+    pub(super) packed_stack: bool,
+    /// Whether a struct aligned to 16 bytes starts at an even-numbered register (rule C.8).
+    pub(super) even_register_pairs: bool,
+}
+
+impl Conventions {
+    /// The standard as written, used by Linux and most other platforms.
+    pub(super) const STANDARD: Conventions = Conventions {
+        variadic_on_stack: false,
+        packed_stack: false,
+        even_register_pairs: true,
+    };
+    /// Apple's variant, used by macOS and iOS.
+    ///
+    /// https://developer.apple.com/documentation/xcode/writing-arm64-code-for-apple-platforms
+    pub(super) const APPLE: Conventions = Conventions {
+        variadic_on_stack: true,
+        packed_stack: true,
+        even_register_pairs: false,
+    };
+
+    /// The conventions Cranelift uses for `triple`.
+    pub(super) fn for_triple(triple: &Triple) -> Conventions {
+        match triple.default_calling_convention() {
+            Ok(CallingConvention::AppleAarch64) => Conventions::APPLE,
+            _ => Conventions::STANDARD,
+        }
+    }
+}
+
+/// How a value is passed, before registers are assigned.
+#[derive(Clone, Debug, PartialEq)]
+enum Class {
+    /// A scalar passed in a general purpose register
+    Integer(IrType),
+    /// A scalar passed in a vector register
+    Float(IrType),
+    /// A homogeneous floating-point aggregate with this many members of this type
+    Hfa(IrType, u32),
+    /// A struct or union of up to 16 bytes, passed in general purpose registers
+    Composite,
+    /// A larger struct or union, passed by reference
+    Indirect,
+}
+
+fn classify(ctype: &Type, target: &TargetInfo) -> Class {
+    match ctype {
+        Type::Struct(_) | Type::Union(_) => {
+            let size = ctype
+                .sizeof(target)
+                .expect("parser should not allow passing incomplete types");
+            if let Some(base) = hfa_base(ctype) {
+                let base_size = base.sizeof(target).expect("floating types have a size");
+                let members = size / base_size;
+                if (1..=HFA_MEMBERS).contains(&members) && members * base_size == size {
+                    return Class::Hfa(base.as_ir_type(target), members as u32);
+                }
+            }
+            if size > MAX_REGISTER_AGGREGATE {
+                Class::Indirect
+            } else {
+                Class::Composite
+            }
+        }
+        _ if ctype.is_floating() => Class::Float(ctype.as_ir_type(target)),
+        _ => Class::Integer(ctype.as_ir_type(target)),
+    }
+}
+
+/// If every scalar in `ctype` has the same floating point type, return that type.
+fn hfa_base(ctype: &Type) -> Option<Type> {
+    match ctype {
+        Type::Struct(stype) | Type::Union(stype) => {
+            let mut base = None;
+            for member in stype.members().iter() {
+                let member_base = hfa_base(&member.ctype)?;
+                if base.get_or_insert_with(|| member_base.clone()) != &member_base {
+                    return None;
+                }
+            }
+            base
+        }
+        Type::Array(of, ArrayType::Fixed(_)) => hfa_base(of),
+        Type::Float | Type::Double | Type::LongDouble => Some(ctype.clone()),
+        _ => None,
+    }
+}
+
+/// Assigns registers and stack space to arguments, in order.
+struct Allocator<'a> {
+    conventions: Conventions,
+    target: &'a TargetInfo,
+    /// The next general purpose register (NGRN)
+    gp: u32,
+    /// The next vector register (NSRN)
+    fp: u32,
+    /// The size of the arguments passed on the stack so far (NSAA)
+    stack: u32,
+}
+
+impl<'a> Allocator<'a> {
+    fn new(conventions: Conventions, target: &'a TargetInfo) -> Allocator<'a> {
+        Allocator {
+            conventions,
+            target,
+            gp: 0,
+            fp: 0,
+            stack: 0,
+        }
+    }
+
+    fn argument(&mut self, ctype: &Type, variadic: bool) -> PassMode {
+        if variadic && self.conventions.variadic_on_stack {
+            return self.variadic_on_stack(ctype);
+        }
+        match classify(ctype, self.target) {
+            Class::Integer(ty) => self.scalar(ty, false),
+            Class::Float(ty) => self.scalar(ty, true),
+            Class::Indirect => {
+                self.scalar(Type::ptr_type(self.target), false);
+                PassMode::Indirect
+            }
+            Class::Hfa(ty, members) => {
+                if self.fp + members <= FP_REGISTERS {
+                    self.fp += members;
+                    return PassMode::Hfa(ty, members);
+                }
+                // C.3: the rest of the vector registers are unused
+                let mut padding = self.fill_registers(true);
+                padding.extend(self.stack_padding(ctype));
+                padded(padding, self.memory(ctype))
+            }
+            Class::Composite => {
+                let mut padding = Vec::new();
+                let align = ctype.alignof(self.target).unwrap_or(EIGHTBYTE);
+                if align == 16
+                    && self.conventions.even_register_pairs
+                    && self.gp % 2 == 1
+                    && self.gp < GP_REGISTERS
+                {
+                    padding.push(types::I64);
+                    self.gp += 1;
+                }
+                let size = aggregate_size(ctype, self.target);
+                let doublewords = size / EIGHTBYTE as u32;
+                if self.gp + doublewords <= GP_REGISTERS {
+                    self.gp += doublewords;
+                    return padded(
+                        padding,
+                        PassMode::Eightbytes(vec![types::I64; doublewords as usize]),
+                    );
+                }
+                // C.13: the rest of the general purpose registers are unused
+                padding.extend(self.fill_registers(false));
+                padding.extend(self.stack_padding(ctype));
+                padded(padding, self.memory(ctype))
+            }
+        }
+    }
+
+    /// Apple passes every variadic argument on the stack, even if there are registers left.
+    fn variadic_on_stack(&mut self, ctype: &Type) -> PassMode {
+        let mut padding = self.fill_registers(false);
+        padding.extend(self.fill_registers(true));
+        let mode = match classify(ctype, self.target) {
+            // the caller widens integers to take up a whole doubleword
+            Class::Integer(_) => {
+                self.stack_scalar(types::I64, false);
+                PassMode::Direct(types::I64)
+            }
+            Class::Indirect => {
+                self.stack_scalar(Type::ptr_type(self.target), false);
+                PassMode::Indirect
+            }
+            Class::Float(ty) => {
+                self.stack_scalar(ty, false);
+                PassMode::Direct(ty)
+            }
+            Class::Hfa(..) | Class::Composite => {
+                padding.extend(self.stack_padding(ctype));
+                self.memory(ctype)
+            }
+        };
+        padded(padding, mode)
+    }
+
+    /// Pass a scalar in the next register of its class, or on the stack if there are none left.
+    fn scalar(&mut self, ty: IrType, float: bool) -> PassMode {
+        let (next, registers) = if float {
+            (&mut self.fp, FP_REGISTERS)
+        } else {
+            (&mut self.gp, GP_REGISTERS)
+        };
+        if *next < registers {
+            *next += 1;
+        } else {
+            self.stack_scalar(ty, self.conventions.packed_stack);
+        }
+        PassMode::Direct(ty)
+    }
+
+    /// Cranelift aligns scalars on the stack to their size,
+    /// which is at least a doubleword unless the stack is `packed`.
+    fn stack_scalar(&mut self, ty: IrType, packed: bool) {
+        let size = if packed {
+            ty.bytes()
+        } else {
+            ty.bytes().max(EIGHTBYTE as u32)
+        };
+        self.stack = align_to(self.stack, size) + size;
+    }
+
+    /// Pass a struct or union on the stack.
+    fn memory(&mut self, ctype: &Type) -> PassMode {
+        let size = aggregate_size(ctype, self.target);
+        self.stack += size;
+        PassMode::Memory(size)
+    }
+
+    /// Unused parameters that take up the rest of the registers in a class.
+    fn fill_registers(&mut self, float: bool) -> Vec<IrType> {
+        let (next, registers, ty) = if float {
+            (&mut self.fp, FP_REGISTERS, types::F64)
+        } else {
+            (&mut self.gp, GP_REGISTERS, types::I64)
+        };
+        let unused = registers - *next;
+        *next = registers;
+        vec![ty; unused as usize]
+    }
+
+    /// Unused parameters that align the stack for `ctype`, which goes on the stack next.
+    ///
+    /// Cranelift doesn't align structs passed on the stack, so this has to be done here.
+    fn stack_padding(&mut self, ctype: &Type) -> Vec<IrType> {
+        // C.14: the NSAA is rounded up to the larger of 8 and the alignment of the argument
+        let align = ctype
+            .alignof(self.target)
+            .unwrap_or(EIGHTBYTE)
+            .max(EIGHTBYTE) as u32;
+        // the padding has to go on the stack too, so use a class with no registers left
+        let sizes: &[IrType] = if self.gp == GP_REGISTERS {
+            &[types::I64, types::I32, types::I16, types::I8]
+        } else {
+            &[types::F64, types::F32]
+        };
+        let mut padding = Vec::new();
+        while !self.stack.is_multiple_of(align) {
+            let ty = *sizes
+                .iter()
+                .find(|ty| self.stack.is_multiple_of(ty.bytes()))
+                .expect("the stack is always aligned to at least a single float");
+            self.stack_scalar(ty, self.conventions.packed_stack);
+            padding.push(ty);
+        }
+        padding
+    }
+}
+
+fn align_to(offset: u32, align: u32) -> u32 {
+    offset.div_ceil(align) * align
+}
+
+fn padded(padding: Vec<IrType>, mode: PassMode) -> PassMode {
+    if padding.is_empty() {
+        mode
+    } else {
+        PassMode::Padded(padding, Box::new(mode))
+    }
+}
+
+/// How the return value is passed.
+fn return_mode(ctype: &Type, target: &TargetInfo) -> PassMode {
+    match classify(ctype, target) {
+        Class::Integer(ty) | Class::Float(ty) => PassMode::Direct(ty),
+        Class::Hfa(ty, members) => PassMode::Hfa(ty, members),
+        Class::Composite => {
+            let doublewords = aggregate_size(ctype, target) / EIGHTBYTE as u32;
+            PassMode::Eightbytes(vec![types::I64; doublewords as usize])
+        }
+        Class::Indirect => PassMode::Memory(aggregate_size(ctype, target)),
+    }
+}
+
+/// Classify the arguments and return value of a call to a function of type `ftype`.
+///
+/// Only the first `named` parameters were declared; the rest are variadic arguments.
+pub(super) fn function_abi(
+    ftype: &FunctionType,
+    named: usize,
+    conventions: Conventions,
+    target: &TargetInfo,
+) -> FunctionAbi {
+    let ret = if ftype.should_return() {
+        Some(return_mode(&ftype.return_type, target))
+    } else {
+        None
+    };
+    let mut allocator = Allocator::new(conventions, target);
+    let mut params = Vec::new();
+    if ftype.has_params() {
+        for (i, param) in ftype.params.iter().enumerate() {
+            params.push(allocator.argument(&param.get().ctype, i >= named));
+        }
+    }
+    FunctionAbi {
+        ret,
+        params,
+        registers: (allocator.gp, allocator.fp),
+        stack_size: allocator.stack,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use saltwater_parser::{check_semantics, Opt};
+    use PassMode::*;
+
+    /// Classify the last function declared in `src`,
+    /// treating all but the first `named` parameters as variadic arguments.
+    fn abi(src: &str, named: Option<usize>, conventions: Conventions) -> FunctionAbi {
+        let target = TargetInfo::new("aarch64-unknown-linux-gnu".parse().unwrap());
+        let opt = Opt {
+            target: target.clone(),
+            ..Opt::default()
+        };
+        let decls = check_semantics(src, opt).result.unwrap();
+        let ctype = decls.last().unwrap().data.symbol.get().ctype.clone();
+        let ftype = match ctype {
+            Type::Function(ftype) => ftype,
+            other => panic!("expected a function, got {}", other),
+        };
+        let named = named.unwrap_or(ftype.params.len());
+        function_abi(&ftype, named, conventions, &target)
+    }
+
+    fn params(src: &str) -> Vec<PassMode> {
+        abi(src, None, Conventions::STANDARD).params
+    }
+
+    #[test]
+    fn registers() {
+        let src = "
+            struct hfa { float a, b, c; };
+            struct pair { long a; int b; };
+            struct big { long a, b, c; };
+            void f(int, struct hfa, struct pair, struct big, double, long double);";
+        assert_eq!(
+            params(src),
+            vec![
+                Direct(types::I32),
+                Hfa(types::F32, 3),
+                Eightbytes(vec![types::I64, types::I64]),
+                Indirect,
+                Direct(types::F64),
+                Direct(types::I64X2),
+            ]
+        );
+    }
+
+    #[test]
+    fn hfas() {
+        let hfa = |members: &str| {
+            let src = format!("struct s {{ {} }}; void f(struct s);", members);
+            params(&src).remove(0)
+        };
+        assert_eq!(hfa("double d[2];"), Hfa(types::F64, 2));
+        assert_eq!(hfa("struct { float x, y; } a, b;"), Hfa(types::F32, 4));
+        assert_eq!(hfa("long double x;"), Hfa(types::I64X2, 1));
+        // too many members
+        assert_eq!(hfa("float x[5];"), Indirect);
+        // members of different types
+        assert_eq!(hfa("float x; double y;"), Eightbytes(vec![types::I64; 2]));
+        assert_eq!(hfa("float x; int y;"), Eightbytes(vec![types::I64]));
+        let src = "union u { float f; double d; }; void f(union u);";
+        assert_eq!(params(src), vec![Eightbytes(vec![types::I64])]);
+    }
+
+    #[test]
+    fn stack() {
+        // an HFA that doesn't fit goes on the stack, and so does everything after it
+        let src = "
+            struct hfa { double a, b, c; };
+            void f(double, double, double, double, double, double, struct hfa, float);";
+        let modes = params(src);
+        assert_eq!(modes[6], Padded(vec![types::F64; 2], Box::new(Memory(24))));
+        assert_eq!(modes[7], Direct(types::F32));
+        // a struct is never split between registers and the stack
+        let src = "
+            struct pair { long a, b; };
+            void f(long, long, long, long, long, long, long, struct pair, long);";
+        let modes = params(src);
+        assert_eq!(modes[7], Padded(vec![types::I64], Box::new(Memory(16))));
+        assert_eq!(modes[8], Direct(types::I64));
+    }
+
+    #[test]
+    fn returns() {
+        let ret = |src: &str| abi(src, None, Conventions::STANDARD).ret;
+        assert_eq!(ret("void f();"), None);
+        assert_eq!(
+            ret("struct s { float x, y; } f();"),
+            Some(Hfa(types::F32, 2))
+        );
+        assert_eq!(
+            ret("struct s { char c[12]; } f();"),
+            Some(Eightbytes(vec![types::I64; 2]))
+        );
+        assert_eq!(ret("struct s { char c[17]; } f();"), Some(Memory(24)));
+        assert_eq!(ret("long double f();"), Some(Direct(types::I64X2)));
+    }
+
+    #[test]
+    fn variadic() {
+        let src = "
+            struct pair { long a, b; };
+            int printf(const char *, int, double, struct pair);";
+        // like named arguments
+        let linux = abi(src, Some(1), Conventions::STANDARD);
+        assert_eq!(
+            linux.params,
+            vec![
+                Direct(types::I64),
+                Direct(types::I32),
+                Direct(types::F64),
+                Eightbytes(vec![types::I64; 2]),
+            ]
+        );
+        // always on the stack, in whole doublewords
+        let apple = abi(src, Some(1), Conventions::APPLE);
+        let mut padding = vec![types::I64; 7];
+        padding.extend(vec![types::F64; 8]);
+        assert_eq!(
+            apple.params,
+            vec![
+                Direct(types::I64),
+                Padded(padding, Box::new(Direct(types::I64))),
+                Direct(types::F64),
+                Memory(16),
+            ]
+        );
+        assert_eq!(apple.stack_size, 32);
+    }
+}
@@ -0,0 +1,494 @@
+//! Passing arguments and return values in the System V x86_64 ABI.
+//!
+//! See section 3.2.3 of the ABI for the full details:
+//! https://gitlab.com/x86-psABIs/x86-64-ABI
+//!
+//! Scalars are passed in a single register of their own type.
+//! Structs and unions of up to two eightbytes are split into eightbytes,
+//! each of which is classified as INTEGER (passed in a general purpose register)
+//! or SSE (passed in a vector register).
+//! Anything larger is classified as MEMORY: arguments are copied onto the stack (`byval`),
+//! and return values are written through a hidden pointer passed by the caller (`sret`).
+//! `long double` arguments, and aggregates containing them, are also passed in memory;
+//! see the `long_double` module for how they are returned.
+//!
+//! On AArch64 and RISC-V, arguments are classified by the `aapcs64` and `riscv64` modules instead.
+//!
+//! Cranelift knows nothing about C types, so the classification has to happen here.
+//! Structs and unions are always represented by their address while compiling,
+//! so they have to be copied into or out of registers at function boundaries.
+use cranelift::codegen::ir::{
+    types, AbiParam, ArgumentExtension, ArgumentPurpose, MemFlags, Signature, Type as IrType,
+};
+use cranelift::codegen::isa::CallConv;
+use cranelift::prelude::{
+    FunctionBuilder, InstBuilder, StackSlotData, StackSlotKind, Value as IrValue,
+};
+use cranelift_module::Module;
+use std::convert::TryFrom;
+use target_lexicon::Architecture;
+
+use super::{aapcs64, riscv64, Compiler};
+use saltwater_parser::arch::TargetInfo;
+use saltwater_parser::data::{types::ArrayType, types::FunctionType, *};
+
+/// The number of integer registers used for arguments (rdi, rsi, rdx, rcx, r8, r9)
+pub(super) const GP_REGISTERS: u32 = 6;
+/// The number of SSE registers used for arguments (xmm0-xmm7)
+pub(super) const FP_REGISTERS: u32 = 8;
+/// Every argument passed on the stack takes up a multiple of an eightbyte
+pub(super) const EIGHTBYTE: u64 = 8;
+
+/// How a single argument or return value is passed.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum PassMode {
+    /// A scalar, passed in a single register (or stack slot) of this type.
+    Direct(IrType),
+    /// An integer narrower than a register, which is extended to fill it.
+    Extended(IrType, ArgumentExtension),
+    /// A struct or union passed in one register per eightbyte.
+    ///
+    /// INTEGER eightbytes are `I64` and SSE eightbytes are `F64`.
+    Eightbytes(Vec<IrType>),
+    /// A struct or union passed in memory.
+    ///
+    /// The size is rounded up to a whole number of eightbytes.
+    Memory(u32),
+    /// Like `Memory`, but for arguments which have to start on a 16-byte boundary
+    /// of the stack, and would not otherwise: an eightbyte of padding is passed first.
+    PaddedMemory(u32),
+    /// A struct or union made up of this many floating point members of the same type,
+    /// passed in one vector register per member.
+    Hfa(IrType, u32),
+    /// A struct whose members are passed as if they were separate arguments,
+    /// with the type and offset of each one.
+    Fields(Vec<(IrType, i32)>),
+    /// A struct or union copied by the caller, passed as a pointer to the copy.
+    Indirect,
+    /// Unused parameters of these types, followed by the argument.
+    ///
+    /// Cranelift assigns registers to each parameter on its own,
+    /// so this is how an argument is kept out of the registers that are left.
+    Padded(Vec<IrType>, Box<PassMode>),
+    /// A `long double` returned in `st(0)`.
+    ///
+    /// Cranelift can't use the x87 registers, so the IR function returns nothing;
+    /// see `Compiler::push_st0` and `Compiler::pop_st0`.
+    X87,
+}
+
+/// The class of a single eightbyte of an aggregate.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Class {
+    /// NO_CLASS: only padding, so far
+    Empty,
+    Integer,
+    Sse,
+    /// X87 and X87UP, the two halves of a `long double`
+    X87,
+}
+
+impl Class {
+    /// 3.2.3: merge the classes of two fields sharing the same eightbyte
+    fn merge(self, other: Class) -> Class {
+        use Class::*;
+        match (self, other) {
+            (Empty, class) | (class, Empty) => class,
+            (X87, _) | (_, X87) => X87,
+            (Integer, _) | (_, Integer) => Integer,
+            (Sse, Sse) => Sse,
+        }
+    }
+}
+
+impl PassMode {
+    fn new(ctype: &Type, target: &TargetInfo) -> PassMode {
+        match ctype {
+            Type::Struct(_) | Type::Union(_) => match classify(ctype, target) {
+                Some(eightbytes) => PassMode::Eightbytes(eightbytes),
+                None => PassMode::Memory(aggregate_size(ctype, target)),
+            },
+            Type::LongDouble => PassMode::Memory(aggregate_size(ctype, target)),
+            _ => PassMode::Direct(ctype.as_ir_type(target)),
+        }
+    }
+    /// The number of (integer, SSE) registers needed to pass this value.
+    pub(super) fn registers(&self) -> (u32, u32) {
+        let count = |ty: &IrType| if ty.is_float() { (0, 1) } else { (1, 0) };
+        let sum = |types: &[IrType]| {
+            types
+                .iter()
+                .map(count)
+                .fold((0, 0), |(gp, fp), (a, b)| (gp + a, fp + b))
+        };
+        match self {
+            PassMode::Direct(ty) | PassMode::Extended(ty, _) => count(ty),
+            PassMode::Eightbytes(eightbytes) => sum(eightbytes),
+            PassMode::Fields(fields) => {
+                let types: Vec<_> = fields.iter().map(|&(ty, _)| ty).collect();
+                sum(&types)
+            }
+            PassMode::Memory(_) | PassMode::PaddedMemory(_) | PassMode::X87 => (0, 0),
+            PassMode::Hfa(_, members) => (0, *members),
+            PassMode::Indirect => (1, 0),
+            PassMode::Padded(padding, mode) => {
+                let (gp, fp) = sum(padding);
+                let (mode_gp, mode_fp) = mode.registers();
+                (gp + mode_gp, fp + mode_fp)
+            }
+        }
+    }
+    /// The padding passed before this argument, and how the argument itself is passed.
+    pub(super) fn split_padding(&self) -> (&[IrType], &PassMode) {
+        match self {
+            PassMode::Padded(padding, mode) => (padding, mode),
+            mode => (&[], mode),
+        }
+    }
+    /// For a struct or union passed in registers, the type of each register
+    /// and the offset of the part of the struct it holds.
+    pub(super) fn parts(&self) -> Vec<(IrType, i32)> {
+        match self {
+            PassMode::Eightbytes(eightbytes) => eightbytes
+                .iter()
+                .zip((0..).step_by(EIGHTBYTE as usize))
+                .map(|(&ty, offset)| (ty, offset))
+                .collect(),
+            PassMode::Hfa(ty, members) => (0..*members)
+                .map(|i| (*ty, (i * ty.bytes()) as i32))
+                .collect(),
+            PassMode::Fields(fields) => fields.clone(),
+            _ => unreachable!("only structs passed in registers have parts"),
+        }
+    }
+    /// The IR parameters for this argument.
+    fn abi_params(&self, target: &TargetInfo) -> Vec<AbiParam> {
+        let ptr = Type::ptr_type(target);
+        match self {
+            PassMode::Direct(ty) => vec![AbiParam::new(*ty)],
+            PassMode::Extended(ty, extension) => {
+                let mut param = AbiParam::new(*ty);
+                param.extension = *extension;
+                vec![param]
+            }
+            PassMode::Eightbytes(_) | PassMode::Hfa(..) | PassMode::Fields(_) => self
+                .parts()
+                .into_iter()
+                .map(|(ty, _)| AbiParam::new(ty))
+                .collect(),
+            PassMode::Memory(size) => {
+                vec![AbiParam::special(
+                    ptr,
+                    ArgumentPurpose::StructArgument(*size),
+                )]
+            }
+            PassMode::PaddedMemory(size) => [EIGHTBYTE as u32, *size]
+                .iter()
+                .map(|&size| AbiParam::special(ptr, ArgumentPurpose::StructArgument(size)))
+                .collect(),
+            PassMode::Indirect => vec![AbiParam::new(ptr)],
+            PassMode::X87 => vec![],
+            PassMode::Padded(padding, mode) => {
+                let mut params: Vec<_> = padding.iter().map(|&ty| AbiParam::new(ty)).collect();
+                params.extend(mode.abi_params(target));
+                params
+            }
+        }
+    }
+}
+
+/// The size of a struct or union in memory, rounded up to a whole number of eightbytes.
+pub(super) fn aggregate_size(ctype: &Type, target: &TargetInfo) -> u32 {
+    let size = ctype
+        .sizeof(target)
+        .expect("parser should not allow passing incomplete types");
+    let rounded = size.div_ceil(EIGHTBYTE) * EIGHTBYTE;
+    u32::try_from(rounded).expect("structs larger than 4 GB are not supported")
+}
+
+/// Classify each eightbyte of a struct or union,
+/// or return `None` if it has to be passed in memory.
+fn classify(ctype: &Type, target: &TargetInfo) -> Option<Vec<IrType>> {
+    let size = ctype.sizeof(target).ok()?;
+    // 3.2.3: If the size of an object is larger than two eightbytes ... it has class MEMORY
+    if size > 2 * EIGHTBYTE {
+        return None;
+    }
+    let mut classes = vec![Class::Empty; size.div_ceil(EIGHTBYTE) as usize];
+    classify_at(ctype, 0, &mut classes, target);
+    // 3.2.3: If one of the classes is X87 ... the whole argument is passed in memory.
+    if classes.contains(&Class::X87) {
+        return None;
+    }
+    let eightbytes = classes
+        .into_iter()
+        .map(|class| match class {
+            Class::Sse => types::F64,
+            Class::Integer | Class::Empty => types::I64,
+            Class::X87 => unreachable!("long doubles are always passed in memory"),
+        })
+        .collect();
+    Some(eightbytes)
+}
+
+/// Merge the classes of all the scalars in `ctype`, which starts `offset` bytes into the aggregate.
+fn classify_at(ctype: &Type, offset: u64, classes: &mut [Class], target: &TargetInfo) {
+    match ctype {
+        Type::Struct(stype) | Type::Union(stype) => {
+            for member in stype.members().iter() {
+                // unnamed bitfields are only padding
+                if member.id == Default::default() {
+                    continue;
+                }
+                let member_offset = ctype
+                    .member_offset(member.id, target)
+                    .expect("structs should always have offsets for their members");
+                classify_at(&member.ctype, offset + member_offset, classes, target);
+            }
+        }
+        Type::Array(of, ArrayType::Fixed(length)) => {
+            let size = of
+                .sizeof(target)
+                .expect("array elements should have a size");
+            for i in 0..*length {
+                classify_at(of, offset + i * size, classes, target);
+            }
+        }
+        scalar => {
+            let class = if *scalar == Type::LongDouble {
+                Class::X87
+            } else if scalar.is_floating() {
+                Class::Sse
+            } else {
+                Class::Integer
+            };
+            let eightbyte = &mut classes[(offset / EIGHTBYTE) as usize];
+            *eightbyte = eightbyte.merge(class);
+        }
+    }
+}
+
+/// How every argument and the return value of a call are passed.
+#[derive(Clone, Debug)]
+pub(super) struct FunctionAbi {
+    /// `None` for functions returning `void`
+    pub(super) ret: Option<PassMode>,
+    pub(super) params: Vec<PassMode>,
+    /// The number of (integer, SSE) registers used by the parameters
+    pub(super) registers: (u32, u32),
+    /// The number of bytes of parameters passed on the stack
+    pub(super) stack_size: u32,
+}
+
+impl FunctionAbi {
+    pub(super) fn new(ftype: &FunctionType, target: &TargetInfo) -> FunctionAbi {
+        FunctionAbi::with_named_params(ftype, ftype.params.len(), target)
+    }
+
+    /// Like `new`, but only the first `named` parameters were declared by the function;
+    /// the rest are the variadic arguments of a call.
+    pub(super) fn with_named_params(
+        ftype: &FunctionType,
+        named: usize,
+        target: &TargetInfo,
+    ) -> FunctionAbi {
+        match target.triple.architecture {
+            Architecture::Aarch64(_) => {
+                let conventions = aapcs64::Conventions::for_triple(&target.triple);
+                aapcs64::function_abi(ftype, named, conventions, target)
+            }
+            Architecture::Riscv64(_) => riscv64::function_abi(ftype, named, target),
+            _ => FunctionAbi::system_v(ftype, target),
+        }
+    }
+
+    fn system_v(ftype: &FunctionType, target: &TargetInfo) -> FunctionAbi {
+        let ret = if ftype.should_return() {
+            Some(match &*ftype.return_type {
+                Type::LongDouble => PassMode::X87,
+                ctype => PassMode::new(ctype, target),
+            })
+        } else {
+            None
+        };
+        // the hidden `sret` pointer takes up the first integer register
+        let mut gp = match ret {
+            Some(PassMode::Memory(_)) => 1,
+            _ => 0,
+        };
+        let (mut fp, mut stack_size) = (0, 0);
+        let mut params = Vec::new();
+        if ftype.has_params() {
+            for param in &ftype.params {
+                let mut mode = PassMode::new(&param.get().ctype, target);
+                let (needed_gp, needed_fp) = mode.registers();
+                if let PassMode::Eightbytes(_) = mode {
+                    // 3.2.3: If there are no registers available for any eightbyte of an argument,
+                    // the whole argument is passed on the stack.
+                    if gp + needed_gp > GP_REGISTERS || fp + needed_fp > FP_REGISTERS {
+                        mode = PassMode::Memory(aggregate_size(&param.get().ctype, target));
+                    }
+                }
+                match mode {
+                    PassMode::Memory(size) => {
+                        let align = param.get().ctype.alignof(target).unwrap_or(EIGHTBYTE);
+                        if align > EIGHTBYTE && stack_size % 16 != 0 {
+                            mode = PassMode::PaddedMemory(size);
+                            stack_size += EIGHTBYTE as u32;
+                        }
+                        stack_size += size;
+                    }
+                    _ if gp + needed_gp > GP_REGISTERS || fp + needed_fp > FP_REGISTERS => {
+                        stack_size += EIGHTBYTE as u32
+                    }
+                    _ => {
+                        gp += needed_gp;
+                        fp += needed_fp;
+                    }
+                }
+                params.push(mode);
+            }
+        }
+        FunctionAbi {
+            ret,
+            params,
+            registers: (gp, fp),
+            stack_size,
+        }
+    }
+
+    /// Generate the IR function signature.
+    pub(super) fn signature(&self, call_conv: CallConv, target: &TargetInfo) -> Signature {
+        let ptr = Type::ptr_type(target);
+        let mut signature = Signature::new(call_conv);
+        if let Some(PassMode::Memory(_)) = self.ret {
+            let sret = AbiParam::special(ptr, ArgumentPurpose::StructReturn);
+            signature.params.push(sret);
+        }
+        for param in &self.params {
+            signature.params.extend(param.abi_params(target));
+        }
+        match &self.ret {
+            // Cranelift takes care of returning the `sret` pointer in rax
+            Some(PassMode::Memory(_)) | None => {}
+            Some(ret) => signature.returns.extend(ret.abi_params(target)),
+        }
+        signature
+    }
+}
+
+impl<M: Module> Compiler<M> {
+    /// Generate the IR function signature for a function of type `ftype`.
+    pub(super) fn signature(&self, ftype: &FunctionType) -> Signature {
+        FunctionAbi::new(ftype, &self.target)
+            .signature(self.module.isa().default_call_conv(), &self.target)
+    }
+
+    /// Convert the value of a `return` statement into the IR return values.
+    pub(super) fn return_values(
+        &mut self,
+        val: IrValue,
+        ctype: &Type,
+        builder: &mut FunctionBuilder,
+    ) -> Vec<IrValue> {
+        match self.ret.clone() {
+            Some(ret @ PassMode::Eightbytes(_))
+            | Some(ret @ PassMode::Hfa(..))
+            | Some(ret @ PassMode::Fields(_)) => self.load_parts(val, ctype, &ret.parts(), builder),
+            Some(PassMode::Memory(_)) => {
+                let sret = self
+                    .sret
+                    .expect("functions returning in memory should have an sret pointer");
+                self.copy_aggregate(sret, val, ctype, builder);
+                vec![]
+            }
+            _ => vec![val],
+        }
+    }
+
+    /// Load the `parts` of the struct or union at `addr` so they can be passed in registers.
+    pub(super) fn load_parts(
+        &mut self,
+        addr: IrValue,
+        ctype: &Type,
+        parts: &[(IrType, i32)],
+        builder: &mut FunctionBuilder,
+    ) -> Vec<IrValue> {
+        let size = ctype
+            .sizeof(&self.target)
+            .expect("aggregates passed in registers have a size");
+        // don't read past the end of the object if the last eightbyte is only partly used
+        let end = parts
+            .last()
+            .map_or(0, |&(ty, offset)| offset as u64 + u64::from(ty.bytes()));
+        let addr = if end > size {
+            let copy = self.aggregate_slot(ctype, builder);
+            self.copy_aggregate(copy, addr, ctype, builder);
+            copy
+        } else {
+            addr
+        };
+        parts
+            .iter()
+            .map(|&(ty, offset)| builder.ins().load(ty, MemFlags::new(), addr, offset))
+            .collect()
+    }
+
+    /// Store the `parts` of a struct or union received in registers,
+    /// returning the address of the stored copy.
+    pub(super) fn store_parts(
+        &mut self,
+        ctype: &Type,
+        parts: &[(IrType, i32)],
+        values: &[IrValue],
+        builder: &mut FunctionBuilder,
+    ) -> IrValue {
+        let addr = self.aggregate_slot(ctype, builder);
+        for (&val, &(_, offset)) in values.iter().zip(parts) {
+            builder.ins().store(MemFlags::new(), val, addr, offset);
+        }
+        addr
+    }
+
+    /// Copy a struct or union from `src` to `dest`.
+    pub(super) fn copy_aggregate(
+        &mut self,
+        dest: IrValue,
+        src: IrValue,
+        ctype: &Type,
+        builder: &mut FunctionBuilder,
+    ) {
+        let size = ctype
+            .sizeof(&self.target)
+            .expect("aggregates passed by value have a size");
+        let align = ctype
+            .alignof(&self.target)
+            .expect("if sizeof() succeeds so should alignof()") as u8;
+        builder.emit_small_memory_copy(
+            self.module.target_config(),
+            dest,
+            src,
+            size,
+            align,
+            align,
+            true,
+            MemFlags::new(),
+        );
+    }
+
+    /// Allocate a stack slot large enough to hold `ctype` as a whole number of eightbytes,
+    /// returning its address.
+    pub(super) fn aggregate_slot(
+        &mut self,
+        ctype: &Type,
+        builder: &mut FunctionBuilder,
+    ) -> IrValue {
+        let slot = builder.create_sized_stack_slot(StackSlotData {
+            kind: StackSlotKind::ExplicitSlot,
+            size: aggregate_size(ctype, &self.target),
+        });
+        builder
+            .ins()
+            .stack_addr(Type::ptr_type(&self.target), slot, 0)
+    }
+}
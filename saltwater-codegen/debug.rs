@@ -0,0 +1,737 @@
+//! DWARF debug info, emitted when compiling with `-g`.
+//!
+//! While compiling, every statement and expression tags the instructions it emits with a
+//! Cranelift `SourceLoc`, which is an index into `DebugInfo::locations`.
+//! After each function is defined, the offsets of its instructions and stack slots
+//! are recorded. Once the whole translation unit has been compiled,
+//! `.debug_info`, `.debug_abbrev`, `.debug_line` and `.debug_frame` are written
+//! into the object file, with relocations against the function and data symbols.
+//!
+//! Variables live on the stack for their whole lifetime, so their locations are
+//! described relative to the frame pointer, which is always preserved.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use cranelift::codegen::{
+    self,
+    ir::{SourceLoc, StackSlot},
+    isa::unwind::UnwindInfo,
+};
+use cranelift::prelude::FunctionBuilder;
+use cranelift_module::{DataId, FuncId, Module};
+use cranelift_object::ObjectProduct;
+use gimli::write::{
+    Address, AttributeValue, CieId, CommonInformationEntry, DwarfUnit, EndianVec, Expression,
+    FrameTable, LineProgram, LineString, Range, RangeList, Sections, UnitEntryId, Writer,
+};
+use gimli::{
+    constants::*, AArch64, Encoding, Format, LineEncoding, LittleEndian, Register, RiscV,
+    SectionId, X86_64,
+};
+use object::write::{Relocation, StandardSegment, SymbolId};
+use object::{RelocationEncoding, RelocationKind, SectionKind};
+use saltwater_parser::arch::TargetInfo;
+use saltwater_parser::data::{hir::Symbol, types::ArrayType, StructType, *};
+use saltwater_parser::{presumed_location, Files};
+
+use target_lexicon::Architecture;
+
+use super::{Compiler, Id};
+
+/// The DWARF version to emit; version 4 is understood by every debugger still in use.
+const DWARF_VERSION: u16 = 4;
+
+#[derive(Default)]
+pub(crate) struct DebugInfo {
+    /// The source location of each `SourceLoc` given to Cranelift, indexed by its bits
+    locations: Vec<Location>,
+    functions: Vec<FunctionInfo>,
+    globals: Vec<(Symbol, DataId, Location)>,
+    /// The variables declared so far in the current function
+    locals: Vec<(Symbol, VariableSlot, Location)>,
+}
+
+struct FunctionInfo {
+    symbol: Symbol,
+    id: FuncId,
+    location: Location,
+    /// The size in bytes of the machine code
+    size: u32,
+    /// The offset of each instruction that starts a new source location
+    rows: Vec<(u32, Location)>,
+    /// Each variable and its offset from the frame pointer
+    variables: Vec<(Symbol, VariableSlot, i64, Location)>,
+    unwind: Option<UnwindInfo>,
+}
+
+/// Where a variable is stored, mirroring the stack variants of `Id`
+#[derive(Copy, Clone)]
+enum VariableSlot {
+    Direct(StackSlot),
+    OverAligned(StackSlot, u64),
+    Indirect(StackSlot),
+}
+
+impl VariableSlot {
+    fn slot(self) -> StackSlot {
+        match self {
+            VariableSlot::Direct(slot)
+            | VariableSlot::OverAligned(slot, _)
+            | VariableSlot::Indirect(slot) => slot,
+        }
+    }
+}
+
+impl<M: Module> Compiler<M> {
+    /// Tag all instructions emitted after this with `location`.
+    pub(super) fn set_srcloc(&mut self, location: Location, builder: &mut FunctionBuilder) {
+        if let Some(info) = &mut self.debug_info {
+            if info.locations.last() != Some(&location) {
+                info.locations.push(location);
+            }
+            builder.set_srcloc(SourceLoc::new(info.locations.len() as u32 - 1));
+        }
+    }
+
+    /// Declare a variable stored on the stack of the current function.
+    pub(super) fn declare_local(&mut self, symbol: Symbol, id: Id, location: Location) {
+        if let Some(info) = &mut self.debug_info {
+            let slot = match id {
+                Id::Local(slot) => Some(VariableSlot::Direct(slot)),
+                Id::OverAligned(slot, align) => Some(VariableSlot::OverAligned(slot, align)),
+                Id::Vla(slot) => Some(VariableSlot::Indirect(slot)),
+                Id::Function(_) | Id::Global(_) => None,
+                Id::Variable(_) => unreachable!("locals are kept in memory with debug info"),
+            };
+            if let Some(slot) = slot {
+                info.locals.push((symbol, slot, location));
+            }
+        }
+        self.declarations.insert(symbol, id);
+    }
+
+    /// Declare a variable with static storage duration that is defined in this translation unit.
+    pub(super) fn declare_global_debug_info(
+        &mut self,
+        symbol: Symbol,
+        id: DataId,
+        location: Location,
+    ) {
+        if let Some(info) = &mut self.debug_info {
+            info.globals.push((symbol, id, location));
+        }
+    }
+
+    /// Record the machine code layout of a function that was just defined.
+    pub(super) fn finish_function_debug_info(
+        &mut self,
+        symbol: Symbol,
+        id: FuncId,
+        location: Location,
+        ctx: &codegen::Context,
+    ) {
+        let info = match &mut self.debug_info {
+            Some(info) => info,
+            None => return,
+        };
+        let code = ctx
+            .compiled_code()
+            .expect("functions should be compiled before their debug info is recorded");
+        let mut rows: Vec<(u32, Location)> = Vec::new();
+        for srcloc in code.buffer.get_srclocs_sorted() {
+            if srcloc.loc.is_default() {
+                continue;
+            }
+            let location = info.locations[srcloc.loc.bits() as usize];
+            if rows.last().map(|&(_, last)| last) != Some(location) {
+                rows.push((srcloc.start, location));
+            }
+        }
+        // Stack slot offsets are relative to the stack pointer at the end of the prologue,
+        // which is `frame_size` bytes below the frame pointer.
+        let frame_size = i64::from(code.frame_size);
+        let variables = info
+            .locals
+            .drain(..)
+            .map(|(symbol, slot, location)| {
+                let offset = i64::from(code.sized_stackslot_offsets[slot.slot()]) - frame_size;
+                (symbol, slot, offset, location)
+            })
+            .collect();
+        let unwind = code
+            .create_unwind_info(self.module.isa())
+            .expect("unwind info should be valid for a compiled function");
+        info.functions.push(FunctionInfo {
+            symbol,
+            id,
+            location,
+            size: code.buffer.total_size(),
+            rows,
+            variables,
+            unwind,
+        });
+    }
+}
+
+impl DebugInfo {
+    /// Write the debug info sections into `product`.
+    pub(crate) fn emit(
+        self,
+        product: &mut ObjectProduct,
+        files: &Files,
+        filename: &Path,
+        target: &TargetInfo,
+        cie: Option<CommonInformationEntry>,
+    ) -> Result<(), gimli::write::Error> {
+        let mut symbols = Vec::new();
+        let mut dwarf = DwarfBuilder::new(files, filename, target);
+        let root = dwarf.dwarf.unit.root();
+        let mut ranges = Vec::new();
+
+        for function in &self.functions {
+            let symbol = symbols.len();
+            symbols.push(product.function_symbol(function.id));
+            let start = Address::Symbol { symbol, addend: 0 };
+            ranges.push(Range::StartLength {
+                begin: start,
+                length: u64::from(function.size),
+            });
+            dwarf.add_function(root, function, start);
+        }
+        let mut seen = Vec::new();
+        for &(var, id, location) in &self.globals {
+            if seen.contains(&id) {
+                continue;
+            }
+            seen.push(id);
+            let symbol = symbols.len();
+            symbols.push(product.data_symbol(id));
+            let mut expr = Expression::new();
+            expr.op_addr(Address::Symbol { symbol, addend: 0 });
+            let entry = dwarf.add_variable(root, DW_TAG_variable, var, location);
+            let die = dwarf.dwarf.unit.get_mut(entry);
+            die.set(DW_AT_location, AttributeValue::Exprloc(expr));
+            if var.get().storage_class != StorageClass::Static {
+                die.set(DW_AT_external, AttributeValue::Flag(true));
+            }
+        }
+
+        let ranges = dwarf.dwarf.unit.ranges.add(RangeList(ranges));
+        let root = dwarf.dwarf.unit.get_mut(root);
+        root.set(DW_AT_low_pc, AttributeValue::Address(Address::Constant(0)));
+        root.set(DW_AT_ranges, AttributeValue::RangeListRef(ranges));
+
+        let mut sections = Sections::new(DebugSection::default());
+        dwarf.dwarf.write(&mut sections)?;
+        if let Some(cie) = cie {
+            let mut frames = FrameTable::default();
+            let cie_id: CieId = frames.add_cie(cie);
+            for (i, function) in self.functions.iter().enumerate() {
+                // the function symbols were pushed first, in order
+                let start = Address::Symbol {
+                    symbol: i,
+                    addend: 0,
+                };
+                if let Some(UnwindInfo::SystemV(unwind)) = &function.unwind {
+                    frames.add_fde(cie_id, unwind.to_fde(start));
+                }
+            }
+            frames.write_debug_frame(&mut sections.debug_frame)?;
+        }
+
+        write_sections(product, sections, &symbols);
+        Ok(())
+    }
+}
+
+/// The DWARF number of the register Cranelift uses as the frame pointer.
+fn frame_pointer(architecture: Architecture) -> Register {
+    match architecture {
+        Architecture::Aarch64(_) => AArch64::X29,
+        Architecture::Riscv64(_) => RiscV::S0,
+        _ => X86_64::RBP,
+    }
+}
+
+/// Builds the DIEs and line program of a translation unit.
+struct DwarfBuilder<'a> {
+    dwarf: DwarfUnit,
+    files: &'a Files,
+    target: &'a TargetInfo,
+    /// The line program file for each filename
+    line_files: HashMap<PathBuf, gimli::write::FileId>,
+    /// Types that already have a DIE.
+    ///
+    /// `Type` can't be hashed, so this is searched linearly.
+    types: Vec<(Type, UnitEntryId)>,
+}
+
+impl<'a> DwarfBuilder<'a> {
+    fn new(files: &'a Files, filename: &Path, target: &'a TargetInfo) -> Self {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: DWARF_VERSION,
+            address_size: target.data_model.ptr_size() as u8,
+        };
+        let mut dwarf = DwarfUnit::new(encoding);
+        let comp_dir = std::env::current_dir().unwrap_or_default();
+        let name = filename.to_string_lossy().into_owned().into_bytes();
+        let comp_dir = comp_dir.to_string_lossy().into_owned().into_bytes();
+        dwarf.unit.line_program = LineProgram::new(
+            encoding,
+            LineEncoding::default(),
+            LineString::String(comp_dir.clone()),
+            LineString::String(name.clone()),
+            None,
+        );
+        let root = dwarf.unit.root();
+        let root = dwarf.unit.get_mut(root);
+        let producer = concat!("saltwater ", env!("CARGO_PKG_VERSION"));
+        root.set(DW_AT_producer, AttributeValue::String(producer.into()));
+        root.set(DW_AT_language, AttributeValue::Language(DW_LANG_C11));
+        root.set(DW_AT_name, AttributeValue::String(name));
+        root.set(DW_AT_comp_dir, AttributeValue::String(comp_dir));
+        Self {
+            dwarf,
+            files,
+            target,
+            line_files: HashMap::new(),
+            types: Vec::new(),
+        }
+    }
+
+    /// Get the line program file, line, and column of `location`, after applying `#line` directives.
+    ///
+    /// Lines and columns start from 1.
+    fn position(&mut self, location: Location) -> Option<(gimli::write::FileId, u64, u64)> {
+        let presumed = presumed_location(self.files, location.file, location.span.start)?;
+        let file = self.line_file(Path::new(presumed.filename));
+        Some((file, u64::from(presumed.line), u64::from(presumed.column)))
+    }
+
+    fn line_file(&mut self, path: &Path) -> gimli::write::FileId {
+        if let Some(&id) = self.line_files.get(path) {
+            return id;
+        }
+        let program = &mut self.dwarf.unit.line_program;
+        let directory = match path.parent() {
+            Some(parent) if parent != Path::new("") => {
+                let parent = parent.to_string_lossy().into_owned().into_bytes();
+                program.add_directory(LineString::String(parent))
+            }
+            _ => program.default_directory(),
+        };
+        let name = path
+            .file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy()
+            .into_owned()
+            .into_bytes();
+        let id = program.add_file(LineString::String(name), directory, None);
+        self.line_files.insert(path.to_owned(), id);
+        id
+    }
+
+    /// Add `DW_AT_decl_file` and `DW_AT_decl_line` to `entry`.
+    fn set_decl_location(&mut self, entry: UnitEntryId, location: Location) {
+        if let Some((file, line, _)) = self.position(location) {
+            let die = self.dwarf.unit.get_mut(entry);
+            die.set(DW_AT_decl_file, AttributeValue::FileIndex(Some(file)));
+            die.set(DW_AT_decl_line, AttributeValue::Udata(line));
+        }
+    }
+
+    fn add_function(&mut self, parent: UnitEntryId, function: &FunctionInfo, start: Address) {
+        let meta = function.symbol.get();
+        let func_type = match &meta.ctype {
+            Type::Function(func_type) => func_type,
+            _ => unreachable!("only functions should have machine code"),
+        };
+
+        let entry = self.dwarf.unit.add(parent, DW_TAG_subprogram);
+        self.set_name(entry, meta.id);
+        self.set_decl_location(entry, function.location);
+        if let Some(ty) = self.type_entry(&func_type.return_type) {
+            let die = self.dwarf.unit.get_mut(entry);
+            die.set(DW_AT_type, AttributeValue::UnitRef(ty));
+        }
+        let mut frame_base = Expression::new();
+        frame_base.op_reg(frame_pointer(self.target.triple.architecture));
+        let die = self.dwarf.unit.get_mut(entry);
+        if meta.storage_class != StorageClass::Static {
+            die.set(DW_AT_external, AttributeValue::Flag(true));
+        }
+        die.set(DW_AT_prototyped, AttributeValue::Flag(true));
+        die.set(DW_AT_low_pc, AttributeValue::Address(start));
+        die.set(
+            DW_AT_high_pc,
+            AttributeValue::Udata(u64::from(function.size)),
+        );
+        die.set(DW_AT_frame_base, AttributeValue::Exprloc(frame_base));
+
+        for &(symbol, slot, offset, location) in &function.variables {
+            let tag = if func_type.params.contains(&symbol) {
+                DW_TAG_formal_parameter
+            } else {
+                DW_TAG_variable
+            };
+            let mut expr = Expression::new();
+            expr.op_fbreg(offset);
+            match slot {
+                VariableSlot::Direct(_) => {}
+                // round the address up to the alignment
+                VariableSlot::OverAligned(_, align) => {
+                    expr.op_plus_uconst(align - 1);
+                    expr.op_consts(-(align as i64));
+                    expr.op(DW_OP_and);
+                }
+                // the slot holds the address of the storage
+                VariableSlot::Indirect(_) => expr.op_deref(),
+            }
+            let var = self.add_variable(entry, tag, symbol, location);
+            let die = self.dwarf.unit.get_mut(var);
+            die.set(DW_AT_location, AttributeValue::Exprloc(expr));
+        }
+
+        if function.rows.is_empty() {
+            return;
+        }
+        self.dwarf.unit.line_program.begin_sequence(Some(start));
+        for &(offset, location) in &function.rows {
+            let (file, line, column) = match self.position(location) {
+                Some(position) => position,
+                None => continue,
+            };
+            let row = self.dwarf.unit.line_program.row();
+            row.address_offset = u64::from(offset);
+            row.file = file;
+            row.line = line;
+            row.column = column;
+            self.dwarf.unit.line_program.generate_row();
+        }
+        self.dwarf
+            .unit
+            .line_program
+            .end_sequence(u64::from(function.size));
+    }
+
+    /// Add a DIE for a variable, without a location.
+    fn add_variable(
+        &mut self,
+        parent: UnitEntryId,
+        tag: DwTag,
+        symbol: Symbol,
+        location: Location,
+    ) -> UnitEntryId {
+        let meta = symbol.get();
+        let entry = self.dwarf.unit.add(parent, tag);
+        self.set_name(entry, meta.id);
+        self.set_decl_location(entry, location);
+        if let Some(ty) = self.type_entry(&meta.ctype) {
+            let die = self.dwarf.unit.get_mut(entry);
+            die.set(DW_AT_type, AttributeValue::UnitRef(ty));
+        }
+        entry
+    }
+
+    fn set_name(&mut self, entry: UnitEntryId, name: InternedStr) {
+        let name = name.to_string();
+        // hidden variables, like the length of a VLA, have names that aren't valid identifiers
+        if !name.is_empty() && !name.starts_with('<') {
+            let die = self.dwarf.unit.get_mut(entry);
+            die.set(DW_AT_name, AttributeValue::String(name.as_bytes().to_vec()));
+        }
+    }
+
+    /// Get the DIE describing `ctype`, creating it if necessary.
+    ///
+    /// Returns `None` for `void`, which is described by omitting `DW_AT_type`.
+    fn type_entry(&mut self, ctype: &Type) -> Option<UnitEntryId> {
+        if let Some((_, entry)) = self.types.iter().find(|(ty, _)| ty == ctype) {
+            return Some(*entry);
+        }
+        let root = self.dwarf.unit.root();
+        let entry = match ctype {
+            Type::Void | Type::Error => return None,
+            Type::Bool
+            | Type::Char(_)
+            | Type::Short(_)
+            | Type::Int(_)
+            | Type::Long(_)
+            | Type::LongLong(_)
+            | Type::Float
+            | Type::Double
+            | Type::LongDouble => {
+                let encoding = match ctype {
+                    Type::Bool => DW_ATE_boolean,
+                    Type::Char(true) => DW_ATE_signed_char,
+                    Type::Char(false) => DW_ATE_unsigned_char,
+                    ty if ty.is_floating() => DW_ATE_float,
+                    ty if ty.is_signed() => DW_ATE_signed,
+                    _ => DW_ATE_unsigned,
+                };
+                let entry = self.dwarf.unit.add(root, DW_TAG_base_type);
+                let die = self.dwarf.unit.get_mut(entry);
+                die.set(DW_AT_name, AttributeValue::String(ctype.to_string().into()));
+                die.set(DW_AT_encoding, AttributeValue::Encoding(encoding));
+                self.set_byte_size(entry, ctype);
+                entry
+            }
+            Type::Pointer(inner, _) => {
+                let entry = self.dwarf.unit.add(root, DW_TAG_pointer_type);
+                self.types.push((ctype.clone(), entry));
+                self.set_byte_size(entry, ctype);
+                self.set_type(entry, inner);
+                return Some(entry);
+            }
+            Type::Array(inner, array_type) => {
+                let entry = self.dwarf.unit.add(root, DW_TAG_array_type);
+                self.set_type(entry, inner);
+                let subrange = self.dwarf.unit.add(entry, DW_TAG_subrange_type);
+                if let ArrayType::Fixed(len) = array_type {
+                    let die = self.dwarf.unit.get_mut(subrange);
+                    die.set(DW_AT_count, AttributeValue::Udata(*len));
+                }
+                entry
+            }
+            Type::Function(func_type) => {
+                let entry = self.dwarf.unit.add(root, DW_TAG_subroutine_type);
+                self.types.push((ctype.clone(), entry));
+                self.set_type(entry, &func_type.return_type);
+                let die = self.dwarf.unit.get_mut(entry);
+                die.set(DW_AT_prototyped, AttributeValue::Flag(true));
+                if func_type.has_params() {
+                    for param in &func_type.params {
+                        let param_entry = self.dwarf.unit.add(entry, DW_TAG_formal_parameter);
+                        self.set_type(param_entry, &param.get().ctype);
+                    }
+                }
+                if func_type.varargs {
+                    self.dwarf.unit.add(entry, DW_TAG_unspecified_parameters);
+                }
+                return Some(entry);
+            }
+            Type::Struct(struct_type) | Type::Union(struct_type) => {
+                let tag = if let Type::Struct(_) = ctype {
+                    DW_TAG_structure_type
+                } else {
+                    DW_TAG_union_type
+                };
+                let entry = self.dwarf.unit.add(root, tag);
+                // add this before the members so self-referential structs terminate
+                self.types.push((ctype.clone(), entry));
+                if let StructType::Named(name, _) = struct_type {
+                    self.set_name(entry, *name);
+                }
+                if struct_type.is_empty() {
+                    let die = self.dwarf.unit.get_mut(entry);
+                    die.set(DW_AT_declaration, AttributeValue::Flag(true));
+                    return Some(entry);
+                }
+                self.set_byte_size(entry, ctype);
+                for member in struct_type.members().iter() {
+                    // unnamed bitfields are only padding
+                    if member.id == InternedStr::default() {
+                        continue;
+                    }
+                    let member_entry = self.dwarf.unit.add(entry, DW_TAG_member);
+                    self.set_name(member_entry, member.id);
+                    self.set_type(member_entry, &member.ctype);
+                    let offset = ctype
+                        .member_offset(member.id, self.target)
+                        .expect("structs and unions should have member offsets");
+                    let die = self.dwarf.unit.get_mut(member_entry);
+                    match ctype.member_bitfield(member.id, self.target) {
+                        Some(bitfield) => {
+                            let bit_offset = offset * 8 + u64::from(bitfield.shift);
+                            die.set(DW_AT_data_bit_offset, AttributeValue::Udata(bit_offset));
+                            let width = u64::from(bitfield.width);
+                            die.set(DW_AT_bit_size, AttributeValue::Udata(width));
+                        }
+                        None => {
+                            die.set(DW_AT_data_member_location, AttributeValue::Udata(offset));
+                        }
+                    }
+                }
+                return Some(entry);
+            }
+            Type::Enum(name, members) => {
+                let entry = self.dwarf.unit.add(root, DW_TAG_enumeration_type);
+                if let Some(name) = name {
+                    self.set_name(entry, *name);
+                }
+                self.set_byte_size(entry, ctype);
+                for &(name, value) in members {
+                    let enumerator = self.dwarf.unit.add(entry, DW_TAG_enumerator);
+                    self.set_name(enumerator, name);
+                    let die = self.dwarf.unit.get_mut(enumerator);
+                    die.set(DW_AT_const_value, AttributeValue::Sdata(value));
+                }
+                entry
+            }
+            Type::VaList => {
+                let entry = self.dwarf.unit.add(root, DW_TAG_structure_type);
+                let die = self.dwarf.unit.get_mut(entry);
+                die.set(DW_AT_name, AttributeValue::String("__va_list_tag".into()));
+                self.set_byte_size(entry, ctype);
+                entry
+            }
+        };
+        self.types.push((ctype.clone(), entry));
+        Some(entry)
+    }
+
+    fn set_type(&mut self, entry: UnitEntryId, ctype: &Type) {
+        if let Some(ty) = self.type_entry(ctype) {
+            let die = self.dwarf.unit.get_mut(entry);
+            die.set(DW_AT_type, AttributeValue::UnitRef(ty));
+        }
+    }
+
+    fn set_byte_size(&mut self, entry: UnitEntryId, ctype: &Type) {
+        if let Ok(size) = ctype.sizeof(self.target) {
+            let die = self.dwarf.unit.get_mut(entry);
+            die.set(DW_AT_byte_size, AttributeValue::Udata(size));
+        }
+    }
+}
+
+/// A relocation in a debug section that hasn't been written to the object file yet.
+#[derive(Clone)]
+struct DebugRelocation {
+    offset: usize,
+    size: u8,
+    target: RelocationTarget,
+    addend: i64,
+}
+
+#[derive(Clone)]
+enum RelocationTarget {
+    /// An index into the symbols of the functions and globals that have debug info
+    Symbol(usize),
+    /// The start of another debug section
+    Section(SectionId),
+}
+
+/// A `gimli::write::Writer` that records relocations instead of resolving addresses.
+#[derive(Clone)]
+struct DebugSection {
+    data: EndianVec<LittleEndian>,
+    relocations: Vec<DebugRelocation>,
+}
+
+impl Default for DebugSection {
+    fn default() -> Self {
+        Self {
+            data: EndianVec::new(LittleEndian),
+            relocations: Vec::new(),
+        }
+    }
+}
+
+impl Writer for DebugSection {
+    type Endian = LittleEndian;
+
+    fn endian(&self) -> Self::Endian {
+        LittleEndian
+    }
+    fn len(&self) -> usize {
+        self.data.len()
+    }
+    fn write(&mut self, bytes: &[u8]) -> gimli::write::Result<()> {
+        self.data.write(bytes)
+    }
+    fn write_at(&mut self, offset: usize, bytes: &[u8]) -> gimli::write::Result<()> {
+        self.data.write_at(offset, bytes)
+    }
+    fn write_address(&mut self, address: Address, size: u8) -> gimli::write::Result<()> {
+        match address {
+            Address::Constant(value) => self.write_udata(value, size),
+            Address::Symbol { symbol, addend } => {
+                self.relocations.push(DebugRelocation {
+                    offset: self.len(),
+                    size,
+                    target: RelocationTarget::Symbol(symbol),
+                    addend,
+                });
+                self.write_udata(0, size)
+            }
+        }
+    }
+    fn write_offset(
+        &mut self,
+        val: usize,
+        section: SectionId,
+        size: u8,
+    ) -> gimli::write::Result<()> {
+        self.relocations.push(DebugRelocation {
+            offset: self.len(),
+            size,
+            target: RelocationTarget::Section(section),
+            addend: val as i64,
+        });
+        self.write_udata(0, size)
+    }
+    fn write_offset_at(
+        &mut self,
+        offset: usize,
+        val: usize,
+        section: SectionId,
+        size: u8,
+    ) -> gimli::write::Result<()> {
+        self.relocations.push(DebugRelocation {
+            offset,
+            size,
+            target: RelocationTarget::Section(section),
+            addend: val as i64,
+        });
+        self.write_udata_at(offset, 0, size)
+    }
+}
+
+/// Add each non-empty section to the object file, along with its relocations.
+fn write_sections(
+    product: &mut ObjectProduct,
+    sections: Sections<DebugSection>,
+    symbols: &[SymbolId],
+) {
+    let object = &mut product.object;
+    let mut section_ids = HashMap::new();
+    // every section has to exist before relocations can refer to it
+    sections
+        .for_each(|id, section| -> Result<(), ()> {
+            if section.len() != 0 {
+                let segment = object.segment_name(StandardSegment::Debug).to_vec();
+                let name = id.name().as_bytes().to_vec();
+                let section_id = object.add_section(segment, name, SectionKind::Debug);
+                object.set_section_data(section_id, section.data.slice().to_vec(), 1);
+                section_ids.insert(id, section_id);
+            }
+            Ok(())
+        })
+        .expect("adding sections cannot fail");
+    sections
+        .for_each(|id, section| -> Result<(), ()> {
+            for relocation in &section.relocations {
+                let symbol = match relocation.target {
+                    RelocationTarget::Symbol(index) => symbols[index],
+                    RelocationTarget::Section(target) => {
+                        object.section_symbol(section_ids[&target])
+                    }
+                };
+                let relocation = Relocation {
+                    offset: relocation.offset as u64,
+                    size: relocation.size * 8,
+                    kind: RelocationKind::Absolute,
+                    encoding: RelocationEncoding::Generic,
+                    symbol,
+                    addend: relocation.addend,
+                };
+                object
+                    .add_relocation(section_ids[&id], relocation)
+                    .expect("debug relocations should be valid");
+            }
+            Ok(())
+        })
+        .expect("adding relocations cannot fail");
+}
@@ -0,0 +1,211 @@
+//! Initializers for variables on the stack.
+//!
+//! Unlike static initializers, these can contain arbitrary expressions,
+//! so each element has to be compiled and stored separately.
+//! Anything not mentioned in an initializer list is zero-initialized (6.7.9p21).
+use std::convert::TryFrom;
+
+use cranelift::codegen::ir::MemFlags;
+use cranelift::prelude::{FunctionBuilder, InstBuilder, Value as IrValue};
+use cranelift_module::Module;
+
+use super::Compiler;
+use saltwater_parser::data::{hir::*, *};
+
+impl<M: Module> Compiler<M> {
+    /// Initialize the object of type `ctype` stored at `addr`.
+    pub(super) fn store_aggregate(
+        &mut self,
+        init: Initializer,
+        ctype: &Type,
+        addr: IrValue,
+        location: Location,
+        builder: &mut FunctionBuilder,
+    ) -> CompileResult<()> {
+        if let Initializer::InitializerList(_) = init {
+            let size = match ctype.sizeof(&self.target) {
+                Ok(size) => size,
+                Err(err) => semantic_err!(err.into(), location),
+            };
+            let align = ctype
+                .alignof(&self.target)
+                .expect("if sizeof() succeeds so should alignof()") as u8;
+            builder.emit_small_memset(
+                self.module.target_config(),
+                addr,
+                0,
+                size,
+                align,
+                MemFlags::new(),
+            );
+        }
+        self.store_init(init, ctype, addr, 0, location, builder)
+    }
+
+    /// Store `init` at `offset` bytes past `addr`.
+    ///
+    /// The storage must already be zeroed if `init` is an initializer list.
+    fn store_init(
+        &mut self,
+        init: Initializer,
+        ctype: &Type,
+        addr: IrValue,
+        offset: i32,
+        location: Location,
+        builder: &mut FunctionBuilder,
+    ) -> CompileResult<()> {
+        let list = match init {
+            Initializer::InitializerList(list) => list,
+            Initializer::Scalar(expr) => {
+                return self.store_expr(*expr, ctype, addr, offset, builder)
+            }
+            Initializer::FunctionBody(_) => {
+                unreachable!("functions can't be stored on the stack")
+            }
+        };
+        match ctype {
+            Type::Array(inner, _) => {
+                let inner_size = match inner.sizeof(&self.target) {
+                    Ok(size) => size,
+                    Err(err) => semantic_err!(err.into(), location),
+                };
+                for (designator, init) in list {
+                    let index = match designator {
+                        Designator::Index(index) => index,
+                        Designator::Member(_) => {
+                            unreachable!("parser should not allow members of arrays")
+                        }
+                    };
+                    let element_offset = i32::try_from(index * inner_size)
+                        .expect("arrays on the stack should be smaller than 4 GB");
+                    self.store_init(
+                        init,
+                        inner,
+                        addr,
+                        offset + element_offset,
+                        location,
+                        builder,
+                    )?;
+                }
+            }
+            Type::Struct(struct_type) | Type::Union(struct_type) => {
+                let members = struct_type.members();
+                for (designator, init) in list {
+                    let member = match designator {
+                        Designator::Member(id) => members
+                            .iter()
+                            .find(|member| member.id == id)
+                            .expect("parser should only allow designators for members"),
+                        Designator::Index(_) => {
+                            unreachable!("parser should not allow indexing a struct")
+                        }
+                    };
+                    self.store_member(init, ctype, member, addr, offset, location, builder)?;
+                }
+            }
+            // int i = { 1 };
+            _ => {
+                for (_, init) in list {
+                    self.store_init(init, ctype, addr, offset, location, builder)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Initialize `member` of the struct or union `struct_type` stored at `offset` bytes past `addr`.
+    #[allow(clippy::too_many_arguments)]
+    fn store_member(
+        &mut self,
+        init: Initializer,
+        struct_type: &Type,
+        member: &Variable,
+        addr: IrValue,
+        offset: i32,
+        location: Location,
+        builder: &mut FunctionBuilder,
+    ) -> CompileResult<()> {
+        let member_offset = struct_type
+            .member_offset(member.id, &self.target)
+            .expect("only structs and unions can have members");
+        let offset = offset
+            + i32::try_from(member_offset)
+                .expect("structs on the stack should be smaller than 4 GB");
+        let bitfield = match struct_type.member_bitfield(member.id, &self.target) {
+            Some(bitfield) => bitfield,
+            None => return self.store_init(init, &member.ctype, addr, offset, location, builder),
+        };
+        let mut init = init;
+        // struct { int i: 3; } s = { { 1 } };
+        while let Initializer::InitializerList(mut list) = init {
+            init = list.remove(0).1;
+        }
+        let expr = match init {
+            Initializer::Scalar(expr) => expr,
+            _ => unreachable!("bitfields always have scalar type"),
+        };
+        let value = self.compile_expr(*expr, builder)?;
+        // only overwrite the bits belonging to the field, since other fields may share the unit
+        let flags = MemFlags::new();
+        let unit = builder
+            .ins()
+            .load(member.ctype.as_ir_type(&self.target), flags, addr, offset);
+        let unit = Self::insert_bitfield(unit, value.ir_val, bitfield, builder);
+        builder.ins().store(flags, unit, addr, offset);
+        Ok(())
+    }
+
+    /// Store the value of `expr` at `offset` bytes past `addr`.
+    fn store_expr(
+        &mut self,
+        expr: Expr,
+        ctype: &Type,
+        addr: IrValue,
+        offset: i32,
+        builder: &mut FunctionBuilder,
+    ) -> CompileResult<()> {
+        match ctype {
+            // char s[] = "hello";
+            Type::Array(_, _) => {
+                let mut string = match expr.expr {
+                    ExprType::Literal(LiteralValue::Str(string)) => string,
+                    _ => unreachable!("parser should only allow strings to initialize arrays"),
+                };
+                let size = ctype
+                    .sizeof(&self.target)
+                    .expect("parser should complete arrays initialized by a string");
+                // char s[3] = "abc"; leaves out the null terminator
+                string.resize(size as usize, 0);
+                let str_id = self.compile_string(string, expr.location)?;
+                let str_addr = self.module.declare_data_in_func(str_id, builder.func);
+                let src = builder
+                    .ins()
+                    .global_value(Type::ptr_type(&self.target), str_addr);
+                let dest = builder.ins().iadd_imm(addr, i64::from(offset));
+                builder.emit_small_memory_copy(
+                    self.module.target_config(),
+                    dest,
+                    src,
+                    size,
+                    1,
+                    1,
+                    true,
+                    MemFlags::new(),
+                );
+            }
+            // struct point p = q;
+            Type::Struct(_) | Type::Union(_) => {
+                let src = self.compile_expr(expr, builder)?;
+                let dest = builder.ins().iadd_imm(addr, i64::from(offset));
+                self.copy_aggregate(dest, src.ir_val, ctype, builder);
+            }
+            _ => {
+                let value = self.compile_expr(expr, builder)?;
+                builder
+                    .ins()
+                    .store(MemFlags::new(), value.ir_val, addr, offset);
+            }
+        }
+        Ok(())
+    }
+}
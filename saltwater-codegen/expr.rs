@@ -1,10 +1,14 @@
 use cranelift::codegen::ir::{types, MemFlags};
+use cranelift::codegen::isa::CallConv;
+use cranelift::frontend::Variable as IrVariable;
 use cranelift::prelude::{FunctionBuilder, InstBuilder, IntCC, Type as IrType, Value as IrValue};
 use cranelift_module::Module;
 
-use super::{Compiler, Id};
+use super::abi::{FunctionAbi, PassMode};
+use super::{long_double, Compiler, Id};
+use saltwater_parser::arch::BitfieldLayout;
 use saltwater_parser::data::{
-    hir::{self, BinaryOp, Expr, ExprType, LiteralValue, Symbol, Variable},
+    hir::{self, BinaryOp, Declaration, Expr, ExprType, LiteralValue, Symbol, Variable},
     lex::ComparisonToken,
     *,
 };
@@ -28,27 +32,58 @@ impl<M: Module> Compiler<M> {
     // it can't be any smaller without supporting fewer features
     #[allow(clippy::cognitive_complexity)]
     pub(super) fn compile_expr(&mut self, expr: Expr, builder: &mut FunctionBuilder) -> IrResult {
-        let expr = expr.const_fold()?;
+        let expr = expr.const_fold(&self.target)?;
         let location = expr.location;
+        self.set_srcloc(location, builder);
         let ir_type = if expr.lval {
-            Type::ptr_type()
+            Type::ptr_type(&self.target)
         } else {
-            expr.ctype.as_ir_type()
+            expr.ctype.as_ir_type(&self.target)
         };
         match expr.expr {
             ExprType::Literal(token) => {
                 self.compile_literal(ir_type, expr.ctype, token, location, builder)
             }
             ExprType::Id(var) => self.load_addr(var, builder),
+            // the storage was declared before the current statement, but it still has to be initialized
+            ExprType::CompoundLiteral(var, init) => {
+                // the storage lasts until the end of the block, so it only needs to be allocated once
+                if !self.declarations.contains_key(&var) {
+                    let decl = Declaration {
+                        symbol: var,
+                        init: None,
+                    };
+                    self.declare_stack(decl, location, builder)?;
+                }
+                let addr = self.load_addr(var, builder)?;
+                // not `expr.ctype`, which may have decayed to a pointer
+                let ctype = var.get().ctype.clone();
+                self.store_aggregate(*init, &ctype, addr.ir_val, location, builder)?;
+                Ok(addr)
+            }
 
             // unary operators
             ExprType::Deref(pointer) => {
+                // reading a variable that's kept in a register
+                if let Some(var) = self.promoted_variable(&pointer) {
+                    return Ok(Value {
+                        ir_val: builder.use_var(var),
+                        ir_type,
+                        ctype: expr.ctype,
+                    });
+                }
+                let bitfield = self.bitfield(&pointer);
                 let val = self.compile_expr(*pointer, builder)?;
                 let flags = MemFlags::new();
+                let unit = builder.ins().load(ir_type, flags, val.ir_val, 0);
+                let ir_val = match bitfield {
+                    Some(bitfield) => Self::extract_bitfield(unit, &expr.ctype, bitfield, builder),
+                    None => unit,
+                };
                 Ok(Value {
                     ir_type,
                     ctype: expr.ctype,
-                    ir_val: builder.ins().load(ir_type, flags, val.ir_val, 0),
+                    ir_val,
                 })
             }
             // NOTE: this may be an implicit cast (float f = 1.2) not an explicit cast (1 + (int)1.2)
@@ -70,11 +105,17 @@ impl<M: Module> Compiler<M> {
                 self.binary_assign_op(*left, *right, expr.ctype, op, builder)
             }
             ExprType::FuncCall(func, args) => match func.expr {
-                ExprType::Id(var) => self.call(FuncCall::Named(var), func.ctype, args, builder),
+                ExprType::Id(var) => self.call(
+                    FuncCall::Named(var),
+                    func.ctype,
+                    args,
+                    expr.location,
+                    builder,
+                ),
                 _ => {
                     let ctype = func.ctype.clone();
                     let val = self.compile_expr(*func, builder)?;
-                    self.call(FuncCall::Indirect(val), ctype, args, builder)
+                    self.call(FuncCall::Indirect(val), ctype, args, expr.location, builder)
                 }
             },
             ExprType::Comma(left, right) => {
@@ -85,9 +126,11 @@ impl<M: Module> Compiler<M> {
                 let ctype = cstruct.ctype.clone();
                 let pointer = self.compile_expr(*cstruct, builder)?;
                 let offset = ctype
-                    .member_offset(id)
+                    .member_offset(id, &self.target)
                     .expect("only structs and unions can have members");
-                let ir_offset = builder.ins().iconst(Type::ptr_type(), offset as i64);
+                let ir_offset = builder
+                    .ins()
+                    .iconst(Type::ptr_type(&self.target), offset as i64);
                 Ok(Value {
                     ir_val: builder.ins().iadd(pointer.ir_val, ir_offset),
                     ir_type,
@@ -95,31 +138,79 @@ impl<M: Module> Compiler<M> {
                 })
             }
             ExprType::PostIncrement(lval, increase) => {
-                let lval = self.compile_expr(*lval, builder)?;
-                let loaded_ctype = match lval.ctype {
-                    Type::Pointer(t, _) => *t,
-                    _ => lval.ctype,
+                let bitfield = self.bitfield(&lval);
+                // NOTE: the type of `lval` is the type of the object, not of its address
+                let loaded_ctype = lval.ctype.clone();
+                let ir_type = loaded_ctype.as_ir_type(&self.target);
+                let variable = self.promoted_variable(&lval);
+                let (address, unit) = match variable {
+                    Some(var) => (None, builder.use_var(var)),
+                    None => {
+                        let lval = self.compile_expr(*lval, builder)?;
+                        let unit = builder.ins().load(ir_type, MemFlags::new(), lval.ir_val, 0);
+                        (Some(lval.ir_val), unit)
+                    }
                 };
-                let ir_type = loaded_ctype.as_ir_type();
                 let previous_value = Value {
-                    ir_val: builder.ins().load(ir_type, MemFlags::new(), lval.ir_val, 0),
+                    ir_val: match bitfield {
+                        Some(bitfield) => {
+                            Self::extract_bitfield(unit, &loaded_ctype, bitfield, builder)
+                        }
+                        None => unit,
+                    },
                     ir_type,
                     ctype: loaded_ctype,
                 };
+                let store = |new_value, builder: &mut FunctionBuilder| match (variable, address) {
+                    (Some(var), _) => builder.def_var(var, new_value),
+                    (None, Some(address)) => {
+                        builder.ins().store(MemFlags::new(), new_value, address, 0);
+                    }
+                    (None, None) => unreachable!("lval is either a variable or an address"),
+                };
 
                 let addend = if increase { 1 } else { -1 };
-                let (addend_ir, add_func): (_, fn(_, _, _) -> _) = match previous_value.ctype {
+                if long_double::is_emulated(&previous_value.ctype, &self.target) {
+                    let addend_ir = self.long_double_const(&addend.into(), builder);
+                    let new_value = self.long_double_arithmetic(
+                        BinaryOp::Add,
+                        previous_value.ir_val,
+                        addend_ir,
+                        builder,
+                    );
+                    store(new_value, builder);
+                    return Ok(previous_value);
+                }
+                let (addend_ir, add_func): (_, fn(_, _, _) -> _) = match &previous_value.ctype {
                     Type::Double => (builder.ins().f64const(addend as f64), InstBuilder::fadd),
                     Type::Float => (builder.ins().f32const(addend as f32), InstBuilder::fadd),
+                    // `p++` moves to the next element, like `p + 1`
+                    Type::Pointer(pointee, _) => {
+                        let stride = if pointee.is_vla() {
+                            self.sizeof_runtime(pointee, location, builder)?
+                        } else {
+                            let size = pointee.sizeof(&self.target).expect(
+                                "parser should reject incrementing pointers to incomplete types",
+                            );
+                            builder.ins().iconst(ir_type, size as i64)
+                        };
+                        let func: fn(_, _, _) -> _ = if increase {
+                            InstBuilder::iadd
+                        } else {
+                            InstBuilder::isub
+                        };
+                        (stride, func)
+                    }
                     _ => (
                         builder.ins().iconst(previous_value.ir_type, addend),
                         InstBuilder::iadd,
                     ),
                 };
-                let new_value = add_func(builder.ins(), previous_value.ir_val, addend_ir);
-                builder
-                    .ins()
-                    .store(MemFlags::new(), new_value, lval.ir_val, 0);
+                let mut new_value = add_func(builder.ins(), previous_value.ir_val, addend_ir);
+                if let Some(bitfield) = bitfield {
+                    new_value = Self::insert_bitfield(unit, new_value, bitfield, builder);
+                }
+                store(new_value, builder);
                 Ok(previous_value)
             }
             ExprType::Noop(inner) => {
@@ -130,7 +221,46 @@ impl<M: Module> Compiler<M> {
             ExprType::Ternary(condition, left, right) => {
                 self.ternary(*condition, *left, *right, builder)
             }
-            ExprType::Sizeof(_) => unimplemented!("sizeof variable length arrays"),
+            ExprType::Sizeof(ctype) => Ok(Value {
+                ir_val: self.sizeof_runtime(&ctype, location, builder)?,
+                ir_type,
+                ctype: expr.ctype,
+            }),
+            ExprType::VaStart(ap) => {
+                let ap = self.compile_expr(*ap, builder)?;
+                self.va_start(ap.ir_val, location, builder)?;
+                Ok(Value {
+                    // Just a placeholder.
+                    ir_val: builder.ins().iconst(types::I32, 0),
+                    ir_type,
+                    ctype: expr.ctype,
+                })
+            }
+            ExprType::VaArg(ap) => {
+                let ap = self.compile_expr(*ap, builder)?;
+                Ok(Value {
+                    ir_val: self.va_arg(ap.ir_val, &expr.ctype, builder),
+                    ir_type,
+                    ctype: expr.ctype,
+                })
+            }
+            // nothing to clean up, but `ap` may still have side effects
+            ExprType::VaEnd(ap) => {
+                let ap = self.compile_expr(*ap, builder)?;
+                Ok(Value {
+                    ctype: expr.ctype,
+                    ..ap
+                })
+            }
+            ExprType::VaCopy(dest, src) => {
+                let dest = self.compile_expr(*dest, builder)?;
+                let src = self.compile_expr(*src, builder)?;
+                self.va_copy(dest.ir_val, src.ir_val, builder);
+                Ok(Value {
+                    ctype: expr.ctype,
+                    ..dest
+                })
+            }
             ExprType::StaticRef(_) => {
                 unreachable!("static refs can only appear in top level declarations")
             }
@@ -145,7 +275,7 @@ impl<M: Module> Compiler<M> {
         builder: &mut FunctionBuilder,
     ) -> IrResult {
         let target_block = builder.create_block();
-        let target_type = left.ctype.as_ir_type();
+        let target_type = left.ctype.as_ir_type(&self.target);
         builder.append_block_param(target_block, target_type);
 
         let condition = self.compile_expr(condition, builder)?;
@@ -183,13 +313,20 @@ impl<M: Module> Compiler<M> {
             (LiteralValue::UnsignedInt(u), _) => builder.ins().iconst(ir_type, u as i64),
             (LiteralValue::Float(f), types::F32) => builder.ins().f32const(f as f32),
             (LiteralValue::Float(f), types::F64) => builder.ins().f64const(f),
+            (LiteralValue::Float(f), types::I64X2 | types::I128) => {
+                self.long_double_const(&f.into(), builder)
+            }
+            (LiteralValue::LongDouble(f), types::F64) => builder.ins().f64const(f.to_f64()),
+            (LiteralValue::LongDouble(f), _) => self.long_double_const(&f, builder),
             (LiteralValue::Char(c), _) => builder.ins().iconst(ir_type, i64::from(c)),
             (LiteralValue::Str(string), _) => {
                 let str_id = self.compile_string(string, location)?;
                 let str_addr = self.module.declare_data_in_func(str_id, builder.func);
-                builder.ins().global_value(Type::ptr_type(), str_addr)
+                builder
+                    .ins()
+                    .global_value(Type::ptr_type(&self.target), str_addr)
             }
-            _ => unimplemented!("aggregate literals"),
+            _ => unreachable!("parser should only create literals of scalar or string type"),
         };
         Ok(Value {
             ir_val,
@@ -224,10 +361,29 @@ impl<M: Module> Compiler<M> {
             self.compile_expr(left, builder)?,
             self.compile_expr(right, builder)?,
         );
-        Self::binary_assign_ir(left, right, ctype, op, builder)
+        // `long double` needs library calls, see the `long_double` module
+        if long_double::is_emulated(&left.ctype, &self.target) {
+            let (ir_val, ctype) = match op {
+                BinaryOp::Compare(token) => (
+                    self.long_double_compare(token, left.ir_val, right.ir_val, builder),
+                    Type::Bool,
+                ),
+                _ => (
+                    self.long_double_arithmetic(op, left.ir_val, right.ir_val, builder),
+                    ctype,
+                ),
+            };
+            return Ok(Value {
+                ir_val,
+                ir_type: ctype.as_ir_type(&self.target),
+                ctype,
+            });
+        }
+        self.binary_assign_ir(left, right, ctype, op, builder)
     }
 
     fn binary_assign_ir(
+        &self,
         left: Value,
         right: Value,
         ctype: Type,
@@ -237,7 +393,7 @@ impl<M: Module> Compiler<M> {
         use cranelift::codegen::ir::InstBuilder as b;
         use BinaryOp::*;
         assert_eq!(left.ir_type, right.ir_type);
-        let ir_type = ctype.as_ir_type();
+        let ir_type = ctype.as_ir_type(&self.target);
         let signed = ctype.is_signed();
         let func = match (op, ir_type, signed) {
             (Add, ty, _) if ty.is_int() => b::iadd,
@@ -282,14 +438,24 @@ impl<M: Module> Compiler<M> {
         // calculate this here before it's moved to `compile_expr`
         let orig_signed = expr.ctype.is_signed();
         let original = self.compile_expr(expr, builder)?;
-        let cast_type = ctype.as_ir_type();
+        let cast_type = ctype.as_ir_type(&self.target);
 
         match (&original.ctype, ctype) {
             // this cast is a no-op, it's just here for the frontend
             (_, Type::Void) => Ok(original),
+            (from, ctype) if long_double::is_emulated(from, &self.target) => Ok(Value {
+                ir_val: self.cast_from_long_double(original.ir_val, &ctype, builder),
+                ir_type: cast_type,
+                ctype,
+            }),
+            (from, ctype) if long_double::is_emulated(&ctype, &self.target) => Ok(Value {
+                ir_val: self.cast_to_long_double(original.ir_val, from, builder),
+                ir_type: cast_type,
+                ctype,
+            }),
             // Bools are represented by i8, but only allow 1 bit to be set
             (from, ctype @ Type::Bool) => {
-                let zero = builder.ins().iconst(from.as_ir_type(), 0);
+                let zero = builder.ins().iconst(from.as_ir_type(&self.target), 0);
                 let ir_val = builder.ins().icmp(IntCC::NotEqual, original.ir_val, zero);
                 Ok(Value {
                     ir_val,
@@ -372,16 +538,32 @@ impl<M: Module> Compiler<M> {
     }
 
     fn negate(&mut self, expr: Expr, builder: &mut FunctionBuilder) -> IrResult {
-        self.unary_op(expr, builder, |ir_val, ir_type, _, builder| match ir_type {
-            i if i.is_int() => builder.ins().irsub_imm(ir_val, 0),
-            f if f.is_float() => builder.ins().fneg(ir_val),
+        let val = self.compile_expr(expr, builder)?;
+        let ir_val = match val.ir_type {
+            i if i.is_int() => builder.ins().irsub_imm(val.ir_val, 0),
+            f if f.is_float() => builder.ins().fneg(val.ir_val),
+            _ if long_double::is_emulated(&val.ctype, &self.target) => {
+                self.long_double_negate(val.ir_val, builder)
+            }
             _ => unreachable!("parser should catch illegal types"),
-        })
+        };
+        Ok(Value { ir_val, ..val })
+    }
+
+    /// If `lval` is a variable kept in a register, return the `Variable` holding its value.
+    fn promoted_variable(&self, lval: &Expr) -> Option<IrVariable> {
+        match lval.expr {
+            ExprType::Id(symbol) if lval.lval => match self.declarations.get(&symbol) {
+                Some(Id::Variable(var)) => Some(*var),
+                _ => None,
+            },
+            _ => None,
+        }
     }
 
-    fn load_addr(&mut self, var: Symbol, builder: &mut FunctionBuilder) -> IrResult {
+    pub(super) fn load_addr(&mut self, var: Symbol, builder: &mut FunctionBuilder) -> IrResult {
         let metadata = var.get();
-        let ptr_type = Type::ptr_type();
+        let ptr_type = Type::ptr_type(&self.target);
         let ir_val = match self
             .declarations
             .get(&var)
@@ -396,6 +578,16 @@ impl<M: Module> Compiler<M> {
                 builder.ins().global_value(ptr_type, global)
             }
             Id::Local(stack_slot) => builder.ins().stack_addr(ptr_type, *stack_slot, 0),
+            Id::OverAligned(stack_slot, align) => {
+                let addr = builder.ins().stack_addr(ptr_type, *stack_slot, 0);
+                let align = *align as i64;
+                let addr = builder.ins().iadd_imm(addr, align - 1);
+                builder.ins().band_imm(addr, -align)
+            }
+            Id::Vla(stack_slot) => builder.ins().stack_load(ptr_type, *stack_slot, 0),
+            Id::Variable(_) => {
+                unreachable!("bug in codegen: took the address of a variable kept in a register")
+            }
         };
         let ctype = Type::Pointer(Box::new(metadata.ctype.clone()), hir::Qualifiers::default());
         Ok(Value {
@@ -405,6 +597,55 @@ impl<M: Module> Compiler<M> {
         })
     }
 
+    /// If `lval` is a bitfield member, return where it is stored in its storage unit.
+    ///
+    /// Compiling `lval` gives the address of the storage unit.
+    fn bitfield(&self, lval: &Expr) -> Option<BitfieldLayout> {
+        match &lval.expr {
+            ExprType::Member(cstruct, id) => cstruct.ctype.member_bitfield(*id, &self.target),
+            _ => None,
+        }
+    }
+
+    /// Get the value of a bitfield from its storage unit, sign-extending it if `ctype` is signed.
+    fn extract_bitfield(
+        unit: IrValue,
+        ctype: &Type,
+        bitfield: BitfieldLayout,
+        builder: &mut FunctionBuilder,
+    ) -> IrValue {
+        let unit_bits = builder.func.dfg.value_type(unit).bits();
+        let width = u32::from(bitfield.width);
+        // move the most significant bit of the field to the most significant bit of the unit,
+        // then shift it back down, filling the upper bits with zeros or the sign bit
+        let shl = i64::from(unit_bits - bitfield.shift - width);
+        let shr = i64::from(unit_bits - width);
+        let val = builder.ins().ishl_imm(unit, shl);
+        // `_Bool b: 1` can only hold 0 or 1
+        if ctype.is_signed() && *ctype != Type::Bool {
+            builder.ins().sshr_imm(val, shr)
+        } else {
+            builder.ins().ushr_imm(val, shr)
+        }
+    }
+
+    /// Replace the bits of a bitfield in its storage unit with `value`, truncating `value` if necessary.
+    pub(super) fn insert_bitfield(
+        unit: IrValue,
+        value: IrValue,
+        bitfield: BitfieldLayout,
+        builder: &mut FunctionBuilder,
+    ) -> IrValue {
+        let unit_mask = u64::MAX >> (64 - builder.func.dfg.value_type(unit).bits());
+        let field_mask = (u64::MAX >> (64 - u32::from(bitfield.width))) << bitfield.shift;
+        let field = builder.ins().ishl_imm(value, i64::from(bitfield.shift));
+        let field = builder.ins().band_imm(field, field_mask as i64);
+        let rest = builder
+            .ins()
+            .band_imm(unit, (!field_mask & unit_mask) as i64);
+        builder.ins().bor(rest, field)
+    }
+
     fn compare(
         left: Value,
         right: Value,
@@ -431,15 +672,23 @@ impl<M: Module> Compiler<M> {
     fn assignment(&mut self, lval: Expr, rval: Expr, builder: &mut FunctionBuilder) -> IrResult {
         let ctype = lval.ctype.clone();
         let location = lval.location;
+        let bitfield = self.bitfield(&lval);
+        if let Some(var) = self.promoted_variable(&lval) {
+            let value = self.compile_expr(rval, builder)?;
+            builder.def_var(var, value.ir_val);
+            return Ok(value);
+        }
         let (target, value) = (
             self.compile_expr(lval, builder)?,
             self.compile_expr(rval, builder)?,
         );
         if let Type::Union(_) | Type::Struct(_) = ctype {
             use std::convert::TryInto;
-            let size = ctype.sizeof().map_err(|e| location.with(e.to_string()))?;
+            let size = ctype
+                .sizeof(&self.target)
+                .map_err(|e| location.with(e.to_string()))?;
             let align = ctype
-                .alignof()
+                .alignof(&self.target)
                 .expect("if sizeof() succeeds so should alignof()")
                 .try_into()
                 .expect("align should never be more than 255 bytes");
@@ -457,6 +706,18 @@ impl<M: Module> Compiler<M> {
             );
             return Ok(value);
         }
+        // bitfield assignment: only overwrite the bits belonging to the field
+        if let Some(bitfield) = bitfield {
+            let flags = MemFlags::new();
+            let unit = builder.ins().load(value.ir_type, flags, target.ir_val, 0);
+            let unit = Self::insert_bitfield(unit, value.ir_val, bitfield, builder);
+            builder.ins().store(flags, unit, target.ir_val, 0);
+            // the value of an assignment is the value stored, which may have been truncated
+            return Ok(Value {
+                ir_val: Self::extract_bitfield(unit, &value.ctype, bitfield, builder),
+                ..value
+            });
+        }
         // scalar assignment
         let target_val = target.ir_val;
         builder
@@ -470,92 +731,187 @@ impl<M: Module> Compiler<M> {
         func: FuncCall,
         ctype: Type,
         args: Vec<Expr>,
+        location: Location,
         builder: &mut FunctionBuilder,
     ) -> IrResult {
-        use cranelift::codegen::ir::AbiParam;
         use hir::Qualifiers;
 
         let mut ftype = match ctype {
             Type::Function(ftype) => ftype,
             _ => unreachable!("parser should only allow calling functions"),
         };
-        let mut float_variadic = 0;
+        let named = ftype.params.len();
         if ftype.varargs {
             // needs to be done before we move the args by compiling them
-            if self.module.isa().name() != "x64" {
-                unimplemented!("variadic args for architectures other than x64");
+            if !matches!(self.module.isa().name(), "x64" | "aarch64" | "riscv64") {
+                semantic_err!(
+                    format!(
+                        "calling variadic functions is not supported on {}",
+                        self.module.isa().triple()
+                    ),
+                    location,
+                );
             }
             // this is an utter hack
             // https://github.com/CraneStation/cranelift/issues/212#issuecomment-549111736
             for arg in &args[ftype.params.len()..] {
-                if arg.ctype.is_floating() {
-                    float_variadic += 1;
-                }
                 ftype.params.push(
                     Variable {
                         ctype: arg.ctype.clone(),
                         id: Default::default(),
                         qualifiers: Qualifiers::NONE,
                         storage_class: StorageClass::Auto,
+                        bitfield: None,
+                        alignment: None,
+                        packed: None,
                     }
                     .insert(),
                 );
             }
         }
-        let mut compiled_args: Vec<IrValue> = args
-            .into_iter()
-            .map(|arg| self.compile_expr(arg, builder).map(|val| val.ir_val))
-            .collect::<CompileResult<_>>()?;
-        if ftype.varargs {
-            let float_ir = builder.ins().iconst(types::I8, float_variadic);
-            compiled_args.push(float_ir);
+        let abi = FunctionAbi::with_named_params(&ftype, named, &self.target);
+        let mut compiled_args = Vec::new();
+        // the callee writes large structs through a hidden pointer to memory owned by the caller
+        let sret = match abi.ret {
+            Some(PassMode::Memory(_)) => {
+                let sret = self.aggregate_slot(&ftype.return_type, builder);
+                compiled_args.push(sret);
+                Some(sret)
+            }
+            _ => None,
+        };
+        for (arg, mode) in args.into_iter().zip(&abi.params) {
+            let arg_type = arg.ctype.clone();
+            let val = self.compile_expr(arg, builder)?.ir_val;
+            let (padding, mode) = mode.split_padding();
+            // the callee never reads the padding, so any value will do
+            for &ty in padding {
+                let unused = match ty {
+                    types::F32 => builder.ins().f32const(0.0),
+                    types::F64 => builder.ins().f64const(0.0),
+                    _ => builder.ins().iconst(ty, 0),
+                };
+                compiled_args.push(unused);
+            }
+            match mode {
+                PassMode::Direct(ty) | PassMode::Extended(ty, _) => {
+                    // variadic integers are widened to a whole register on some platforms,
+                    // and RISC-V passes floats in integer registers once it runs out of float registers
+                    let from = builder.func.dfg.value_type(val);
+                    let val = if from == *ty {
+                        val
+                    } else if from.is_float() {
+                        builder.ins().bitcast(*ty, MemFlags::new(), val)
+                    } else if arg_type.is_signed() {
+                        builder.ins().sextend(*ty, val)
+                    } else {
+                        builder.ins().uextend(*ty, val)
+                    };
+                    compiled_args.push(val)
+                }
+                PassMode::Eightbytes(_) | PassMode::Hfa(..) | PassMode::Fields(_) => {
+                    let parts = self.load_parts(val, &arg_type, &mode.parts(), builder);
+                    compiled_args.extend(parts);
+                }
+                // the callee may modify its copy
+                PassMode::Indirect => {
+                    let copy = self.aggregate_slot(&arg_type, builder);
+                    self.copy_aggregate(copy, val, &arg_type, builder);
+                    compiled_args.push(copy);
+                }
+                PassMode::Padded(..) => unreachable!("padding is only added once"),
+                PassMode::X87 => unreachable!("only return values are passed in st(0)"),
+                // Cranelift copies the struct onto the stack,
+                // but it always copies a whole number of eightbytes
+                PassMode::Memory(size) | PassMode::PaddedMemory(size) => {
+                    // `long double` is a value, not an address
+                    let addr = if arg_type == Type::LongDouble {
+                        let copy = self.aggregate_slot(&arg_type, builder);
+                        builder.ins().store(MemFlags::new(), val, copy, 0);
+                        copy
+                    } else if arg_type.sizeof(&self.target) == Ok(u64::from(*size)) {
+                        val
+                    } else {
+                        let copy = self.aggregate_slot(&arg_type, builder);
+                        self.copy_aggregate(copy, val, &arg_type, builder);
+                        copy
+                    };
+                    // the padding can be copied from anywhere, it's never read
+                    if let PassMode::PaddedMemory(_) = mode {
+                        compiled_args.push(addr);
+                    }
+                    compiled_args.push(addr);
+                }
+            }
         }
+        let signature = abi.signature(self.module.isa().default_call_conv(), &self.target);
+        // the System V x86_64 ABI passes the number of SSE registers used in `al`
+        let needs_al = ftype.varargs
+            && self.module.isa().name() == "x64"
+            && self.module.isa().default_call_conv() == CallConv::SystemV;
         let call = match func {
             FuncCall::Named(func_name) => {
-                let func_id = match self.declarations.get(&func_name) {
+                let mut func_id = match self.declarations.get(&func_name) {
                     Some(Id::Function(func_id)) => *func_id,
                     _ => panic!("parser should catch illegal function calls"),
                 };
+                if needs_al {
+                    func_id = self.varargs_trampoline(func_id);
+                }
                 let func_ref = self.module.declare_func_in_func(func_id, builder.func);
-                let call = builder.ins().call(func_ref, compiled_args.as_slice());
                 // stolen from https://github.com/bjorn3/rustc_codegen_cranelift/blob/82fde5b62281fa51a/src/abi/mod.rs#L535
+                // the declared signature only has the named parameters,
+                // so replace it with one that has the variadic arguments too
                 if ftype.varargs {
-                    let call_sig = builder.func.dfg.call_signature(call).unwrap();
-                    // let float_arg = AbiParam::special_reg(types::I8, ArgumentPurpose::Normal, al);
-                    // NOTE: this is added both here and in signature() because we overwrite the previous params
-                    let abi_params = ftype
-                        .params
-                        .into_iter()
-                        .map(|param| {
-                            let ctype = &param.get().ctype;
-                            // if ctype.is_floating() {
-                            //     panic!("Non int ty {:?} for variadic call", ctype);
-                            // }
-                            AbiParam::new(ctype.as_ir_type())
-                        })
-                        .chain(std::iter::once(AbiParam::new(types::I8)))
-                        .collect();
-                    builder.func.dfg.signatures[call_sig].params = abi_params;
+                    let call_sig = builder.func.dfg.ext_funcs[func_ref].signature;
+                    builder.func.dfg.signatures[call_sig] = signature;
                 }
-                call
+                builder.ins().call(func_ref, compiled_args.as_slice())
             }
             FuncCall::Indirect(callee) => {
-                let sig = ftype.signature(self.module.isa());
-                let sigref = builder.import_signature(sig);
+                // the trampoline jumps to a fixed function, so there's nowhere to put `callee`
+                let passes_floats = || {
+                    abi.params
+                        .iter()
+                        .skip(named)
+                        .any(|mode| mode.registers().1 > 0)
+                };
+                if needs_al && passes_floats() {
+                    semantic_err!(
+                        "passing floating-point variadic arguments through a function pointer is not supported"
+                            .into(),
+                        location,
+                    );
+                }
+                let sigref = builder.import_signature(signature);
                 builder
                     .ins()
                     .call_indirect(sigref, callee.ir_val, compiled_args.as_slice())
             }
         };
-        let ir_val = match builder.inst_results(call).first() {
+        let results = builder.inst_results(call).to_vec();
+        let return_type = *ftype.return_type;
+        let ir_val = match (abi.ret, sret) {
+            (Some(PassMode::Direct(_)), _) | (Some(PassMode::Extended(..)), _) => results[0],
+            // return the address of the struct, like any other struct value
+            (Some(ret @ PassMode::Eightbytes(_)), _)
+            | (Some(ret @ PassMode::Hfa(..)), _)
+            | (Some(ret @ PassMode::Fields(_)), _) => {
+                self.store_parts(&return_type, &ret.parts(), &results, builder)
+            }
+            (Some(PassMode::Memory(_)), Some(sret)) => sret,
+            (Some(PassMode::X87), _) => self.pop_st0(builder),
             // Just a placeholder.
-            None => builder.ins().iconst(types::I32, 0),
-            Some(ret) => *ret,
+            _ => builder.ins().iconst(types::I32, 0),
+        };
+        let ir_type = match return_type {
+            Type::Struct(_) | Type::Union(_) => Type::ptr_type(&self.target),
+            _ => return_type.as_ir_type(&self.target),
         };
         Ok(Value {
             ir_val,
-            ir_type: ftype.return_type.as_ir_type(),
-            ctype: *ftype.return_type,
+            ir_type,
+            ctype: return_type,
         })
     }
 }
@@ -10,15 +10,25 @@ macro_rules! semantic_err {
     };
 }
 
+mod aapcs64;
+mod abi;
+mod debug;
+mod dynamic_init;
 mod expr;
 mod helpers;
+mod long_double;
+mod promote;
+mod riscv64;
 mod static_init;
 mod stmt;
+mod varargs;
+mod vla;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::path::Path;
 
+use crate::abi::{FunctionAbi, PassMode};
 use crate::helpers::FunctionBuilderExt;
 use cranelift::codegen::{
     self,
@@ -29,15 +39,18 @@ use cranelift::codegen::{
         InstBuilder, UserFuncName,
     },
     isa::TargetIsa,
-    settings::{self, Configurable, Flags},
+    settings::{Configurable, Flags},
+};
+use cranelift::frontend::{Switch, Variable};
+use cranelift::prelude::{
+    Block, FunctionBuilder, FunctionBuilderContext, Type as IrType, Value as IrValue,
 };
-use cranelift::frontend::Switch;
-use cranelift::prelude::{Block, FunctionBuilder, FunctionBuilderContext};
 use cranelift_module::{self, DataId, FuncId, Linkage, Module};
 use cranelift_object::{ObjectBuilder, ObjectModule, ObjectProduct};
-use saltwater_parser::arch::TARGET;
-use saltwater_parser::{Opt, Program};
+use saltwater_parser::arch::TargetInfo;
+use saltwater_parser::{Opt, OptLevel, Program};
 use std::sync::Arc;
+use target_lexicon::{Architecture, BinaryFormat, CallingConvention, Triple};
 
 use saltwater_parser::data::{
     hir::{Declaration, Initializer, Stmt, Symbol},
@@ -45,7 +58,11 @@ use saltwater_parser::data::{
     StorageClass, *,
 };
 
-pub(crate) fn get_isa(jit: bool) -> Arc<dyn TargetIsa + 'static> {
+pub(crate) fn get_isa(
+    jit: bool,
+    triple: Triple,
+    opt_level: OptLevel,
+) -> Arc<dyn TargetIsa + 'static> {
     let mut flags_builder = cranelift::codegen::settings::builder();
     // `simplejit` requires non-PIC code
     if !jit {
@@ -55,24 +72,81 @@ pub(crate) fn get_isa(jit: bool) -> Arc<dyn TargetIsa + 'static> {
             .expect("is_pic should be a valid option");
     }
 
+    let (cranelift_level, verify) = match opt_level {
+        OptLevel::O0 => ("none", true),
+        OptLevel::O1 => ("speed", false),
+        OptLevel::O2 => ("speed_and_size", false),
+    };
+    flags_builder
+        .set("opt_level", cranelift_level)
+        .expect("opt_level should be a valid option");
     // use debug assertions
     flags_builder
-        .enable("enable_verifier")
+        .set("enable_verifier", if verify { "true" } else { "false" })
         .expect("enable_verifier should be a valid option");
     // don't emit call to __cranelift_probestack
     flags_builder
         .set("enable_probestack", "false")
         .expect("enable_probestack should be a valid option");
+    // `va_start` needs the frame pointer to find arguments passed on the stack
+    flags_builder
+        .enable("preserve_frame_pointers")
+        .expect("preserve_frame_pointers should be a valid option");
     let flags = Flags::new(flags_builder);
-    cranelift::codegen::isa::lookup(TARGET)
-        .unwrap_or_else(|_| panic!("platform not supported: {}", TARGET))
+    let mut isa_builder = cranelift::codegen::isa::lookup(triple.clone())
+        .unwrap_or_else(|_| panic!("platform not supported: {}", triple));
+    // the LP64D ABI passes `float` and `double` in floating point registers,
+    // so assume the extensions in the `riscv64gc` baseline
+    if let Architecture::Riscv64(_) = triple.architecture {
+        for extension in &[
+            "has_m",
+            "has_a",
+            "has_f",
+            "has_d",
+            "has_zicsr",
+            "has_zifencei",
+        ] {
+            isa_builder
+                .enable(extension)
+                .expect("RISC-V extensions should be valid options");
+        }
+    }
+    isa_builder
         .finish(flags)
         .expect("Failed to create target ISA")
 }
 
-pub fn initialize_aot_module(name: String) -> ObjectModule {
+/// Whether object files can be emitted for `triple`.
+///
+/// Only the System V x86_64 ABI, AAPCS64 and the RISC-V LP64D ABI are implemented
+/// (see the `abi` module), so this rejects targets like Windows even though Cranelift supports them.
+pub fn is_supported(triple: &Triple) -> bool {
+    let object_format = matches!(
+        triple.binary_format,
+        BinaryFormat::Elf | BinaryFormat::Macho
+    );
+    let abi = matches!(
+        (triple.architecture, triple.default_calling_convention()),
+        (Architecture::X86_64, Ok(CallingConvention::SystemV))
+            | (
+                Architecture::Aarch64(_),
+                Ok(CallingConvention::SystemV | CallingConvention::AppleAarch64)
+            )
+            | (Architecture::Riscv64(_), Ok(CallingConvention::SystemV))
+    );
+    object_format && abi && cranelift::codegen::isa::lookup(triple.clone()).is_ok()
+}
+
+/// Create a module which emits an object file for `target`, optimized at `opt_level`.
+///
+/// This panics if `target` is not supported; use [`is_supported`] to check first.
+pub fn initialize_aot_module(
+    name: String,
+    target: &TargetInfo,
+    opt_level: OptLevel,
+) -> ObjectModule {
     let builder = ObjectBuilder::new(
-        get_isa(false),
+        get_isa(false, target.triple.clone(), opt_level),
         name,
         cranelift_module::default_libcall_names(),
     );
@@ -83,6 +157,16 @@ enum Id {
     Function(FuncId),
     Global(DataId),
     Local(StackSlot),
+    /// A stack slot with room to align a variable more strictly than Cranelift aligns stack slots.
+    ///
+    /// The address of the variable is the address of the slot rounded up to the alignment.
+    OverAligned(StackSlot, u64),
+    /// A stack slot holding the address of a variable length array
+    Vla(StackSlot),
+    /// A local whose address is never taken, so it can be kept in a register.
+    ///
+    /// See the `promote` module for details.
+    Variable(Variable),
 }
 
 struct Compiler<M: Module> {
@@ -98,17 +182,59 @@ struct Compiler<M: Module> {
     // we didn't see a default case
     switches: Vec<(Switch, Option<Block>, Block)>,
     labels: HashMap<InternedStr, Block>,
+    // if the current function is variadic, where its variadic arguments are stored
+    varargs: Option<varargs::VarargsFrame>,
+    // how the current function returns its value
+    ret: Option<abi::PassMode>,
+    // if the current function returns a struct in memory, the address to write it to
+    sret: Option<IrValue>,
+    // the variable length arrays declared so far in the current function
+    vlas: Vec<StackSlot>,
+    // variadic functions and the trampolines used to call them, see `varargs_trampoline`
+    trampolines: HashMap<FuncId, FuncId>,
+    // the functions that move `long double`s in and out of `st(0)`, see `x87_helpers`
+    x87_helpers: Option<(FuncId, FuncId)>,
+    // locals in the current function that don't need a stack slot
+    promoted: HashSet<Symbol>,
+    // the number of `Variable`s declared so far in the current function
+    variables: u32,
+    opt_level: OptLevel,
+    // the platform being compiled for, which determines the sizes of types and the ABI
+    target: TargetInfo,
+    // what to describe in the DWARF debug info, if it was requested
+    debug_info: Option<debug::DebugInfo>,
     error_handler: ErrorHandler,
 }
 
 impl<M: Module> Compiler<M> {
-    fn new(module: M, debug: bool) -> Compiler<M> {
+    fn new(
+        module: M,
+        debug: bool,
+        debug_info: bool,
+        opt_level: OptLevel,
+        target: TargetInfo,
+    ) -> Compiler<M> {
         Compiler {
             module,
             declarations: HashMap::new(),
             loops: Vec::new(),
             switches: Vec::new(),
             labels: HashMap::new(),
+            varargs: None,
+            ret: None,
+            sret: None,
+            vlas: Vec::new(),
+            trampolines: HashMap::new(),
+            x87_helpers: None,
+            promoted: HashSet::new(),
+            variables: 0,
+            opt_level,
+            target,
+            debug_info: if debug_info {
+                Some(Default::default())
+            } else {
+                None
+            },
             // the initial value doesn't really matter
             last_saw_loop: true,
             strings: Default::default(),
@@ -126,7 +252,12 @@ impl<M: Module> Compiler<M> {
     // 1. should declare `id` a import unless specified as `static`.
     // 3. should always declare `id` as export or local.
     // 2. and 4. should be a no-op.
-    fn declare_func(&mut self, symbol: Symbol, is_definition: bool) -> CompileResult<FuncId> {
+    fn declare_func(
+        &mut self,
+        symbol: Symbol,
+        is_definition: bool,
+        location: Location,
+    ) -> CompileResult<FuncId> {
         use saltwater_parser::get_str;
         if !is_definition {
             // case 2 and 4
@@ -139,17 +270,28 @@ impl<M: Module> Compiler<M> {
             Type::Function(func_type) => func_type,
             _ => unreachable!("bug in backend: only functions should be passed to `declare_func`"),
         };
-        let signature = func_type.signature(self.module.isa());
+        let signature = self.signature(func_type);
         let linkage = match metadata.storage_class {
             StorageClass::Auto | StorageClass::Extern if is_definition => Linkage::Export,
             StorageClass::Auto | StorageClass::Extern => Linkage::Import,
             StorageClass::Static => Linkage::Local,
             StorageClass::Register | StorageClass::Typedef => unreachable!(),
         };
-        let func_id = self
+        let func_id = match self
             .module
             .declare_function(get_str!(metadata.id), linkage, &signature)
-            .unwrap_or_else(|err| panic!("{}", err));
+        {
+            Ok(func_id) => func_id,
+            // int free(int); when `free` was already imported for a VLA
+            Err(cranelift_module::ModuleError::IncompatibleSignature(name, ..)) => semantic_err!(
+                format!(
+                    "'{}' conflicts with the standard library function of the same name",
+                    name
+                ),
+                location,
+            ),
+            Err(err) => semantic_err!(err.to_string(), location),
+        };
         self.declarations.insert(symbol, Id::Function(func_id));
         Ok(func_id)
     }
@@ -166,10 +308,22 @@ impl<M: Module> Compiler<M> {
             return Ok(());
         }
         if let Type::Function(_) = &meta.ctype {
-            self.declare_func(decl.symbol, false)?;
+            self.declare_func(decl.symbol, false, location)?;
+            return Ok(());
+        }
+        if meta.ctype.is_vla() {
+            return self.declare_vla(decl.symbol, location, builder);
+        }
+        if self.promoted.contains(&decl.symbol) {
+            let var = self.declare_variable(meta.ctype.as_ir_type(&self.target), builder);
+            self.declare_local(decl.symbol, Id::Variable(var), location);
+            if let Some(Initializer::Scalar(expr)) = decl.init {
+                let val = self.compile_expr(*expr, builder)?;
+                builder.def_var(var, val.ir_val);
+            }
             return Ok(());
         }
-        let u64_size = match meta.ctype.sizeof() {
+        let u64_size = match meta.ctype.sizeof(&self.target) {
             Ok(size) => size,
             Err(err) => {
                 return Err(CompileError::semantic(Locatable {
@@ -186,54 +340,98 @@ impl<M: Module> Compiler<M> {
                 location,
             }))
         };
+        let align = meta
+            .alignof(&self.target)
+            .expect("if sizeof() succeeds so should alignof()");
+        // Cranelift only aligns stack slots to the size of a pointer
+        if align > u64::from(Type::ptr_type(&self.target).bytes()) {
+            let data = StackSlotData {
+                kind,
+                size: size + align as u32 - 1,
+            };
+            let stack_slot = builder.create_sized_stack_slot(data);
+            let id = Id::OverAligned(stack_slot, align);
+            self.declare_local(decl.symbol, id, location);
+            if let Some(init) = decl.init {
+                let addr = self.load_addr(decl.symbol, builder)?;
+                self.store_aggregate(init, &meta.ctype, addr.ir_val, location, builder)?;
+            }
+            return Ok(());
+        }
         let data = StackSlotData { kind, size };
         let stack_slot = builder.create_sized_stack_slot(data);
-        self.declarations.insert(decl.symbol, Id::Local(stack_slot));
+        self.declare_local(decl.symbol, Id::Local(stack_slot), location);
         if let Some(init) = decl.init {
-            self.store_stack(init, stack_slot, builder)?;
+            self.store_stack(init, &meta.ctype, stack_slot, location, builder)?;
         }
         Ok(())
     }
 
+    /// Create a `Variable` in the current function.
+    fn declare_variable(&mut self, ir_type: IrType, builder: &mut FunctionBuilder) -> Variable {
+        let var = Variable::from_u32(self.variables);
+        self.variables += 1;
+        builder.declare_var(var, ir_type);
+        var
+    }
+
     fn store_stack(
         &mut self,
         init: Initializer,
+        ctype: &Type,
         stack_slot: StackSlot,
+        location: Location,
         builder: &mut FunctionBuilder,
     ) -> CompileResult<()> {
         match init {
-            Initializer::Scalar(expr) => {
+            Initializer::Scalar(expr) if ctype.is_scalar() => {
                 let val = self.compile_expr(*expr, builder)?;
                 builder.ins().stack_store(val.ir_val, stack_slot, 0);
             }
-            Initializer::InitializerList(_) => unimplemented!("aggregate dynamic initialization"),
             Initializer::FunctionBody(_) => unreachable!("functions can't be stored on the stack"),
+            init => {
+                let addr = builder
+                    .ins()
+                    .stack_addr(Type::ptr_type(&self.target), stack_slot, 0);
+                self.store_aggregate(init, ctype, addr, location, builder)?;
+            }
         }
         Ok(())
     }
 
     // TODO: this is grossly inefficient, ask Cranelift devs if
     // there's an easier way to make parameters modifiable.
-    fn store_stack_params(
+    /// Store the parameters of the current function on the stack,
+    /// returning the IR parameters that were left over.
+    fn store_stack_params<'a>(
         &mut self,
         params: &[Symbol],
-        func_start: Block,
+        abi: &FunctionAbi,
+        mut ir_vals: &'a [IrValue],
         location: &Location,
         builder: &mut FunctionBuilder,
-    ) -> CompileResult<()> {
-        // Cranelift requires that all block params are declared up front
-        let ir_vals: Vec<_> = params
-            .iter()
-            .map(|param| {
-                let ir_type = param.get().ctype.as_ir_type();
-                Ok(builder.append_block_param(func_start, ir_type))
-            })
-            .collect::<CompileResult<_>>()?;
-        for (&param, ir_val) in params.iter().zip(ir_vals) {
-            let u64_size = match param.get().ctype.sizeof() {
+    ) -> CompileResult<&'a [IrValue]> {
+        for (&param, mode) in params.iter().zip(&abi.params) {
+            let (padding, mode) = mode.split_padding();
+            ir_vals = &ir_vals[padding.len()..];
+            let ctype = param.get().ctype.clone();
+            if let PassMode::Direct(ir_type) | PassMode::Extended(ir_type, _) = mode {
+                if self.promoted.contains(&param) && *ir_type == ctype.as_ir_type(&self.target) {
+                    let var = self.declare_variable(*ir_type, builder);
+                    builder.def_var(var, ir_vals[0]);
+                    ir_vals = &ir_vals[1..];
+                    self.declare_local(param, Id::Variable(var), *location);
+                    continue;
+                }
+            }
+            let mut u64_size = match ctype.sizeof(&self.target) {
                 Err(data) => semantic_err!(data.into(), *location),
                 Ok(size) => size,
             };
+            // make room to store whole eightbytes
+            if let PassMode::Eightbytes(eightbytes) = mode {
+                u64_size = u64_size.max(8 * eightbytes.len() as u64);
+            }
             let u32_size = match u32::try_from(u64_size) {
                 Err(_) => semantic_err!(
                     format!(
@@ -249,10 +447,40 @@ impl<M: Module> Compiler<M> {
                 size: u32_size,
             };
             let slot = builder.create_sized_stack_slot(stack_data);
-            builder.ins().stack_store(ir_val, slot, 0);
-            self.declarations.insert(param, Id::Local(slot));
+            let used = match mode {
+                PassMode::Direct(_) | PassMode::Extended(..) => {
+                    builder.ins().stack_store(ir_vals[0], slot, 0);
+                    1
+                }
+                PassMode::Eightbytes(_) | PassMode::Hfa(..) | PassMode::Fields(_) => {
+                    let parts = mode.parts();
+                    for (&ir_val, &(_, offset)) in ir_vals.iter().zip(&parts) {
+                        builder.ins().stack_store(ir_val, slot, offset);
+                    }
+                    parts.len()
+                }
+                // the caller made a copy, but it doesn't belong to this function
+                PassMode::Memory(_) | PassMode::Indirect => {
+                    let addr = builder
+                        .ins()
+                        .stack_addr(Type::ptr_type(&self.target), slot, 0);
+                    self.copy_aggregate(addr, ir_vals[0], &ctype, builder);
+                    1
+                }
+                PassMode::PaddedMemory(_) => {
+                    let addr = builder
+                        .ins()
+                        .stack_addr(Type::ptr_type(&self.target), slot, 0);
+                    self.copy_aggregate(addr, ir_vals[1], &ctype, builder);
+                    2
+                }
+                PassMode::Padded(..) => unreachable!("padding is only added once"),
+                PassMode::X87 => unreachable!("only return values are passed in st(0)"),
+            };
+            ir_vals = &ir_vals[used..];
+            self.declare_local(param, Id::Local(slot), *location);
         }
-        Ok(())
+        Ok(ir_vals)
     }
 
     fn compile_func(
@@ -262,10 +490,14 @@ impl<M: Module> Compiler<M> {
         stmts: Vec<Stmt>,
         location: Location,
     ) -> CompileResult<()> {
-        let func_id = self.declare_func(symbol, true)?;
+        let func_id = self.declare_func(symbol, true, location)?;
         // TODO: make declare_func should take a `signature` after all?
         // This just calculates it twice, it's probably fine
-        let signature = func_type.signature(self.module.isa());
+        let abi = FunctionAbi::new(func_type, &self.target);
+        let mut signature = abi.signature(self.module.isa().default_call_conv(), &self.target);
+        if func_type.varargs {
+            varargs::definition_signature(&mut signature, &abi);
+        }
 
         // external name is meant to be a lookup in a symbol table,
         // but we just give it garbage values
@@ -276,24 +508,46 @@ impl<M: Module> Compiler<M> {
         let mut builder = FunctionBuilder::new(&mut func, &mut ctx);
 
         let func_start = builder.create_block();
+        // Cranelift requires that all block params are declared up front
+        builder.append_block_params_for_function_params(func_start);
         builder.switch_to_block(func_start);
+        self.set_srcloc(location, &mut builder);
+        let mut ir_vals = builder.block_params(func_start);
 
         let should_ret = func_type.should_return();
-        if func_type.has_params() {
-            self.store_stack_params(
-                // TODO: get rid of this clone
-                &func_type.params,
-                func_start,
-                &location,
-                &mut builder,
-            )?;
-        }
+        self.ret = abi.ret.clone();
+        self.sret = match abi.ret {
+            Some(PassMode::Memory(_)) => {
+                let sret = ir_vals[0];
+                ir_vals = &ir_vals[1..];
+                Some(sret)
+            }
+            _ => None,
+        };
+        let ir_vals = ir_vals.to_vec();
+        // debug info can only describe variables in memory, so keep every local there with `-g`
+        self.promoted = if self.opt_level > OptLevel::O0 && self.debug_info.is_none() {
+            promote::promotable_locals(&func_type.params, &stmts)
+        } else {
+            HashSet::new()
+        };
+        self.variables = 0;
+        let register_args =
+            self.store_stack_params(&func_type.params, &abi, &ir_vals, &location, &mut builder)?;
+        self.varargs = if func_type.varargs {
+            let frame = self.varargs_prologue(&abi, register_args, location, &mut builder)?;
+            Some(frame)
+        } else {
+            None
+        };
+        self.vlas.clear();
         self.compile_all(stmts, &mut builder)?;
         if !builder.is_filled() {
             let id = symbol.get().id;
             if id == InternedStr::get_or_intern("main") {
-                let ir_int = func_type.return_type.as_ir_type();
+                let ir_int = func_type.return_type.as_ir_type(&self.target);
                 let zero = [builder.ins().iconst(ir_int, 0)];
+                self.free_vlas(&mut builder);
                 builder.ins().return_(&zero);
             } else if should_ret {
                 semantic_err!(
@@ -305,23 +559,24 @@ impl<M: Module> Compiler<M> {
                 );
             } else {
                 // void function, return nothing
+                self.free_vlas(&mut builder);
                 builder.ins().return_(&[]);
             }
         }
         builder.seal_all_blocks();
         builder.finalize();
 
-        let flags = settings::Flags::new(settings::builder());
-
         if self.debug {
             println!("ir: {}", func);
         }
 
-        if let Err(err) = codegen::verify_function(&func, &flags) {
-            panic!(
-                "verification error: {}\nnote: while compiling {}",
-                err, func
-            );
+        if self.module.isa().flags().enable_verifier() {
+            if let Err(err) = codegen::verify_function(&func, self.module.isa()) {
+                panic!(
+                    "verification error: {}\nnote: while compiling {}",
+                    err, func
+                );
+            }
         }
 
         let mut ctx = codegen::Context::for_function(func);
@@ -331,16 +586,59 @@ impl<M: Module> Compiler<M> {
                 err, ctx.func
             );
         }
+        self.finish_function_debug_info(symbol, func_id, location, &ctx);
 
         Ok(())
     }
 }
 
 /// Compile and return the declarations and warnings.
+///
+/// `opt.debug_info` is ignored, since debug info can only be written to object files.
+/// Use [`compile_object`] to emit it.
 pub fn compile<M: Module>(module: M, buf: &str, opt: Opt) -> Program<M> {
+    let program = compile_program(module, buf, opt, false);
+    Program {
+        result: program.result.map(|compiler| compiler.module),
+        warnings: program.warnings,
+        files: program.files,
+    }
+}
+
+/// Compile to an object file, including DWARF debug info if `opt.debug_info` is set.
+pub fn compile_object(module: ObjectModule, buf: &str, opt: Opt) -> Program<ObjectProduct> {
+    let debug_info = opt.debug_info;
+    let filename = opt.filename.clone();
+    let program = compile_program(module, buf, opt, debug_info);
+    let files = program.files;
+    let result = program.result.map(|compiler| {
+        let cie = compiler.module.isa().create_systemv_cie();
+        let mut product = compiler.module.finish();
+        if let Some(info) = compiler.debug_info {
+            info.emit(&mut product, &files, &filename, &compiler.target, cie)
+                .expect("debug info should always be valid DWARF");
+        }
+        product
+    });
+    Program {
+        result,
+        warnings: program.warnings,
+        files,
+    }
+}
+
+fn compile_program<M: Module>(
+    module: M,
+    buf: &str,
+    opt: Opt,
+    debug_info: bool,
+) -> Program<Compiler<M>> {
     use saltwater_parser::{check_semantics, vec_deque};
 
     let debug_asm = opt.debug_asm;
+    let opt_level = opt.opt_level;
+    let target = opt.target.clone();
+    let warning_options = opt.warning_options.clone();
     let mut program = check_semantics(buf, opt);
     let hir = match program.result {
         Ok(hir) => hir,
@@ -354,7 +652,7 @@ pub fn compile<M: Module>(module: M, buf: &str, opt: Opt) -> Program<M> {
     };
     // really we'd like to have all errors but that requires a refactor
     let mut err = None;
-    let mut compiler = Compiler::new(module, debug_asm);
+    let mut compiler = Compiler::new(module, debug_asm, debug_info, opt_level, target);
     for decl in hir {
         let meta = decl.data.symbol.get();
         if let StorageClass::Typedef = meta.storage_class {
@@ -365,7 +663,9 @@ pub fn compile<M: Module>(module: M, buf: &str, opt: Opt) -> Program<M> {
                 Some(Initializer::FunctionBody(stmts)) => {
                     compiler.compile_func(decl.data.symbol, &func_type, stmts, decl.location)
                 }
-                None => compiler.declare_func(decl.data.symbol, false).map(|_| ()),
+                None => compiler
+                    .declare_func(decl.data.symbol, false, decl.location)
+                    .map(|_| ()),
                 _ => unreachable!("functions can only be initialized by a FunctionBody"),
             },
             Type::Void | Type::Error => unreachable!("parser let an incomplete type through"),
@@ -381,18 +681,19 @@ pub fn compile<M: Module>(module: M, buf: &str, opt: Opt) -> Program<M> {
             break;
         }
     }
-    let warns = compiler.error_handler.warnings;
-    let (result, ir_warnings) = if let Some(err) = err {
-        (Err(err), warns)
-    } else {
-        (Ok(compiler.module), warns)
+    program
+        .warnings
+        .extend(std::mem::take(&mut compiler.error_handler.warnings));
+    let result = match err {
+        Some(err) => Err(err),
+        None => Ok(compiler),
     };
-    program.warnings.extend(ir_warnings);
     Program {
         result: result.map_err(|errs| vec_deque![errs]),
         warnings: program.warnings,
         files: program.files,
     }
+    .apply_warning_options(&warning_options)
 }
 
 pub fn assemble(product: ObjectProduct, output: &Path) -> Result<(), saltwater_parser::Error> {
@@ -405,13 +706,18 @@ pub fn assemble(product: ObjectProduct, output: &Path) -> Result<(), saltwater_p
         .map_err(io::Error::into)
 }
 
-pub fn link(obj_file: &Path, output: &Path) -> Result<(), std::io::Error> {
+/// Link object files (and any archives or shared libraries) into an executable.
+///
+/// All of `obj_files` are passed to the linker together, so symbols with external linkage
+/// can be defined in one translation unit and used in another.
+pub fn link<P: AsRef<Path>>(obj_files: &[P], output: &Path) -> Result<(), std::io::Error> {
     use std::io::{Error, ErrorKind};
     use std::process::Command;
 
-    // link the .o file using host linker
+    // link the .o files using host linker
     let status = Command::new("cc")
-        .args(&[&obj_file, Path::new("-o"), output])
+        .args(obj_files.iter().map(AsRef::as_ref))
+        .args(&[Path::new("-o"), output])
         .status()
         .map_err(|err| {
             if err.kind() == ErrorKind::NotFound {
@@ -440,9 +746,10 @@ mod jit {
     use std::convert::TryFrom;
     use std::rc::Rc;
 
-    pub fn initialize_jit_module() -> JITModule {
+    pub fn initialize_jit_module(opt_level: OptLevel) -> JITModule {
         let libcall_names = cranelift_module::default_libcall_names();
-        let builder = JITBuilder::with_isa(get_isa(true), libcall_names);
+        let isa = get_isa(true, Triple::host(), opt_level);
+        let builder = JITBuilder::with_isa(isa, libcall_names);
         JITModule::new(builder)
     }
 
@@ -479,7 +786,7 @@ mod jit {
             opt: Opt,
         ) -> Program<Self, saltwater_parser::Error> {
             let source = source.into();
-            let module = initialize_jit_module();
+            let module = initialize_jit_module(opt.opt_level);
             let program = compile(module, &source, opt);
             let result = match program.result {
                 Ok(module) => Ok(JIT::from(module)),
@@ -564,3 +871,21 @@ fn test_compile_error_semantic() {
         Error::Semantic(SemanticError::Generic("".to_string())),
     );
 }
+
+#[cfg(test)]
+#[test]
+fn test_opt_level_flags() {
+    for &(opt_level, cranelift_level, verify) in &[
+        (OptLevel::O0, codegen::settings::OptLevel::None, true),
+        (OptLevel::O1, codegen::settings::OptLevel::Speed, false),
+        (
+            OptLevel::O2,
+            codegen::settings::OptLevel::SpeedAndSize,
+            false,
+        ),
+    ] {
+        let isa = get_isa(false, Triple::host(), opt_level);
+        assert_eq!(isa.flags().opt_level(), cranelift_level, "{:?}", opt_level);
+        assert_eq!(isa.flags().enable_verifier(), verify, "{:?}", opt_level);
+    }
+}
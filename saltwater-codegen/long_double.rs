@@ -0,0 +1,383 @@
+//! `long double` arithmetic.
+//!
+//! In the System V x86_64 ABI, `long double` is the x87 80-bit extended precision format,
+//! stored in the low 10 bytes of a 16-byte object.
+//! Cranelift can't use the x87 registers, so the bytes are carried around in an `I64X2`
+//! and never operated on directly.
+//! Instead, every operation converts its operands to IEEE binary128 (which has the same
+//! exponent range and a wider significand, so the conversion is exact) and calls the
+//! soft-float routines in libgcc (`__addtf3` and friends), which take binary128 in SSE registers.
+//! The result is then rounded back to 64 bits of significand.
+//!
+//! On AArch64 and RISC-V, `long double` is binary128 to begin with,
+//! so the same routines are called without converting anything.
+//! RISC-V has no vector registers in its base ISA, and passes binary128 in a pair
+//! of integer registers, so there the bytes are carried around in an `I128` instead.
+//!
+//! In the System V ABI, arguments of type `long double` are passed in memory,
+//! and return values in `st(0)`. Cranelift can't express the latter, so `long double`
+//! functions return nothing in the IR, and small hand-written functions move the value
+//! between memory and `st(0)` right before returning and right after calling;
+//! see `x87_helpers`. This works because Cranelift never touches the x87 registers.
+//! AAPCS64 passes and returns `long double` in a vector register, and RISC-V in a pair of
+//! integer registers, both of which Cranelift can do.
+//!
+//! In Apple's AArch64 ABI, `long double` is the same as `double`, so none of this is used.
+use cranelift::codegen::ir::{
+    types, AbiParam, ConstantData, Function, MemFlags, Signature, Type as IrType, UserFuncName,
+};
+use cranelift::prelude::{
+    FunctionBuilder, InstBuilder, IntCC, StackSlotData, StackSlotKind, Value as IrValue,
+};
+use cranelift_module::{FuncId, Module};
+
+use super::Compiler;
+use saltwater_parser::arch::{LongDoubleFormat, TargetInfo};
+use saltwater_parser::data::{hir::BinaryOp, lex::ComparisonToken, *};
+
+/// The size of a `long double` in memory
+const SIZE: u32 = 16;
+/// The exponent of infinity and NaN, in both the x87 format and binary128
+const EXPONENT_MAX: i64 = 0x7fff;
+/// The number of fraction bits binary128 has that the x87 format doesn't
+const EXTRA_BITS: i64 = 112 - 63;
+
+/// Whether `long double` is binary128 on `target`, rather than the x87 format.
+fn is_binary128(target: &TargetInfo) -> bool {
+    target.long_double_format() == LongDoubleFormat::Binary128
+}
+
+/// Whether `ctype` is a `long double` that needs the library calls in this module,
+/// rather than being the same as `double`.
+pub(super) fn is_emulated(ctype: &Type, target: &TargetInfo) -> bool {
+    *ctype == Type::LongDouble && target.long_double_format() != LongDoubleFormat::Double
+}
+
+/// The bytes of `value` as a `long double` on `target`.
+pub(super) fn long_double_bytes(
+    value: &LongDoubleValue,
+    target: &TargetInfo,
+) -> [u8; SIZE as usize] {
+    value.to_bits(target.long_double_format()).to_le_bytes()
+}
+
+impl<M: Module> Compiler<M> {
+    /// A `long double` constant.
+    pub(super) fn long_double_const(
+        &mut self,
+        value: &LongDoubleValue,
+        builder: &mut FunctionBuilder,
+    ) -> IrValue {
+        let bytes = long_double_bytes(value, &self.target);
+        if self.ir_type() == types::I128 {
+            let bits = u128::from_le_bytes(bytes);
+            let low = builder.ins().iconst(types::I64, bits as u64 as i64);
+            let high = builder.ins().iconst(types::I64, (bits >> 64) as u64 as i64);
+            return builder.ins().iconcat(low, high);
+        }
+        let constant = ConstantData::from(bytes.to_vec());
+        let handle = builder.func.dfg.constants.insert(constant);
+        builder.ins().vconst(types::I64X2, handle)
+    }
+
+    /// The IR type holding the bytes of a `long double`.
+    fn ir_type(&self) -> IrType {
+        Type::LongDouble.as_ir_type(&self.target)
+    }
+
+    /// `left op right`, where `op` is one of `+`, `-`, `*`, or `/`
+    pub(super) fn long_double_arithmetic(
+        &mut self,
+        op: BinaryOp,
+        left: IrValue,
+        right: IrValue,
+        builder: &mut FunctionBuilder,
+    ) -> IrValue {
+        let name = match op {
+            BinaryOp::Add => "__addtf3",
+            BinaryOp::Sub => "__subtf3",
+            BinaryOp::Mul => "__multf3",
+            BinaryOp::Div => "__divtf3",
+            _ => unreachable!("bug in parser: {} is not defined for long double", op),
+        };
+        let (left, right) = (
+            self.to_binary128(left, builder),
+            self.to_binary128(right, builder),
+        );
+        let ty = self.ir_type();
+        let func = self.libc_func(name, &[ty, ty], Some(ty), builder);
+        let call = builder.ins().call(func, &[left, right]);
+        let result = builder.inst_results(call)[0];
+        self.to_x87(result, builder)
+    }
+
+    /// Compare two `long double`s, returning an `I8` which is either 0 or 1.
+    pub(super) fn long_double_compare(
+        &mut self,
+        token: ComparisonToken,
+        left: IrValue,
+        right: IrValue,
+        builder: &mut FunctionBuilder,
+    ) -> IrValue {
+        use ComparisonToken::*;
+        // each of these returns an integer which compares to 0 the same way `left` compares to `right`
+        let (name, code) = match token {
+            Less => ("__lttf2", IntCC::SignedLessThan),
+            LessEqual => ("__letf2", IntCC::SignedLessThanOrEqual),
+            Greater => ("__gttf2", IntCC::SignedGreaterThan),
+            GreaterEqual => ("__getf2", IntCC::SignedGreaterThanOrEqual),
+            EqualEqual => ("__eqtf2", IntCC::Equal),
+            NotEqual => ("__netf2", IntCC::NotEqual),
+        };
+        let (left, right) = (
+            self.to_binary128(left, builder),
+            self.to_binary128(right, builder),
+        );
+        let ty = self.ir_type();
+        let func = self.libc_func(name, &[ty, ty], Some(types::I32), builder);
+        let call = builder.ins().call(func, &[left, right]);
+        let result = builder.inst_results(call)[0];
+        builder.ins().icmp_imm(code, result, 0)
+    }
+
+    /// `-val`
+    pub(super) fn long_double_negate(
+        &self,
+        val: IrValue,
+        builder: &mut FunctionBuilder,
+    ) -> IrValue {
+        let addr = self.long_double_slot(builder);
+        let flags = MemFlags::new();
+        // the sign is the top bit of the last two bytes of either format
+        let offset = if is_binary128(&self.target) { 14 } else { 8 };
+        builder.ins().store(flags, val, addr, 0);
+        let sign_exponent = builder.ins().load(types::I16, flags, addr, offset);
+        let negated = builder.ins().bxor_imm(sign_exponent, 0x8000);
+        builder.ins().store(flags, negated, addr, offset);
+        builder.ins().load(self.ir_type(), flags, addr, 0)
+    }
+
+    /// Convert an integer or floating point value of type `from` to a `long double`.
+    pub(super) fn cast_to_long_double(
+        &mut self,
+        val: IrValue,
+        from: &Type,
+        builder: &mut FunctionBuilder,
+    ) -> IrValue {
+        if *from == Type::LongDouble {
+            return val;
+        }
+        let from_ir = from.as_ir_type(&self.target);
+        let (name, param, val) = match from_ir {
+            types::F32 => ("__extendsftf2", from_ir, val),
+            types::F64 => ("__extenddftf2", from_ir, val),
+            _ => {
+                let val = if from_ir == types::I64 {
+                    val
+                } else if from.is_signed() {
+                    builder.ins().sextend(types::I64, val)
+                } else {
+                    builder.ins().uextend(types::I64, val)
+                };
+                let name = if from.is_signed() {
+                    "__floatditf"
+                } else {
+                    "__floatunditf"
+                };
+                (name, types::I64, val)
+            }
+        };
+        let func = self.libc_func(name, &[param], Some(self.ir_type()), builder);
+        let call = builder.ins().call(func, &[val]);
+        let result = builder.inst_results(call)[0];
+        self.to_x87(result, builder)
+    }
+
+    /// Convert a `long double` to an integer or floating point value of type `to`.
+    pub(super) fn cast_from_long_double(
+        &mut self,
+        val: IrValue,
+        to: &Type,
+        builder: &mut FunctionBuilder,
+    ) -> IrValue {
+        let binary128 = self.to_binary128(val, builder);
+        if *to == Type::Bool {
+            let ty = self.ir_type();
+            let func = self.libc_func("__netf2", &[ty, ty], Some(types::I32), builder);
+            let zero = self.long_double_const(&0.0.into(), builder);
+            let call = builder.ins().call(func, &[binary128, zero]);
+            let result = builder.inst_results(call)[0];
+            return builder.ins().icmp_imm(IntCC::NotEqual, result, 0);
+        }
+        if *to == Type::LongDouble {
+            return val;
+        }
+        let to_ir = to.as_ir_type(&self.target);
+        let (name, ret) = match to_ir {
+            types::F32 => ("__trunctfsf2", to_ir),
+            types::F64 => ("__trunctfdf2", to_ir),
+            _ if to.is_signed() => ("__fixtfdi", types::I64),
+            _ => ("__fixunstfdi", types::I64),
+        };
+        let func = self.libc_func(name, &[self.ir_type()], Some(ret), builder);
+        let call = builder.ins().call(func, &[binary128]);
+        let result = builder.inst_results(call)[0];
+        if ret == types::I64 && to_ir != types::I64 {
+            builder.ins().ireduce(to_ir, result)
+        } else {
+            result
+        }
+    }
+
+    /// Load the `long double` `val` into `st(0)`, so it can be returned.
+    ///
+    /// This must come after every call in the function, since calls may use `st(0)`.
+    pub(super) fn push_st0(&mut self, val: IrValue, builder: &mut FunctionBuilder) {
+        let addr = self.long_double_slot(builder);
+        builder.ins().store(MemFlags::new(), val, addr, 0);
+        let (push, _) = self.x87_helpers();
+        let push = self.module.declare_func_in_func(push, builder.func);
+        builder.ins().call(push, &[addr]);
+    }
+
+    /// Pop the `long double` returned by the previous call from `st(0)`.
+    pub(super) fn pop_st0(&mut self, builder: &mut FunctionBuilder) -> IrValue {
+        let addr = self.long_double_slot(builder);
+        let (_, pop) = self.x87_helpers();
+        let pop = self.module.declare_func_in_func(pop, builder.func);
+        builder.ins().call(pop, &[addr]);
+        builder.ins().load(types::I64X2, MemFlags::new(), addr, 0)
+    }
+
+    /// Return functions which load `st(0)` from a pointer, and store and pop `st(0)` to a pointer.
+    fn x87_helpers(&mut self) -> (FuncId, FuncId) {
+        if let Some(helpers) = self.x87_helpers {
+            return helpers;
+        }
+        let mut signature = Signature::new(self.module.isa().default_call_conv());
+        signature
+            .params
+            .push(AbiParam::new(Type::ptr_type(&self.target)));
+        let mut define = |code: &[u8]| {
+            let id = self
+                .module
+                .declare_anonymous_function(&signature)
+                .expect("anonymous functions should never conflict");
+            let func = Function::with_name_signature(UserFuncName::user(0, 0), signature.clone());
+            self.module
+                .define_function_bytes(id, &func, 16, code, &[])
+                .expect("x87 helpers should always be valid functions");
+            id
+        };
+        // fld tbyte ptr [rdi]; ret
+        let push = define(&[0xdb, 0x2f, 0xc3]);
+        // fstp tbyte ptr [rdi]; ret
+        let pop = define(&[0xdb, 0x3f, 0xc3]);
+        self.x87_helpers = Some((push, pop));
+        (push, pop)
+    }
+
+    /// Allocate scratch space for converting between formats, returning its address.
+    fn long_double_slot(&self, builder: &mut FunctionBuilder) -> IrValue {
+        let slot = builder.create_sized_stack_slot(StackSlotData {
+            kind: StackSlotKind::ExplicitSlot,
+            size: SIZE,
+        });
+        builder
+            .ins()
+            .stack_addr(Type::ptr_type(&self.target), slot, 0)
+    }
+
+    /// Split a 16-byte value into its low and high eightbytes.
+    fn split(&self, val: IrValue, builder: &mut FunctionBuilder) -> (IrValue, IrValue, IrValue) {
+        let addr = self.long_double_slot(builder);
+        let flags = MemFlags::new();
+        builder.ins().store(flags, val, addr, 0);
+        let low = builder.ins().load(types::I64, flags, addr, 0);
+        let high = builder.ins().load(types::I64, flags, addr, 8);
+        (addr, low, high)
+    }
+
+    /// Join two eightbytes into a 16-byte value, using the scratch space at `addr`.
+    fn join(addr: IrValue, low: IrValue, high: IrValue, builder: &mut FunctionBuilder) -> IrValue {
+        let flags = MemFlags::new();
+        builder.ins().store(flags, low, addr, 0);
+        builder.ins().store(flags, high, addr, 8);
+        builder.ins().load(types::I64X2, flags, addr, 0)
+    }
+
+    /// Convert a `long double` to binary128, if it isn't already.
+    ///
+    /// The exponents have the same bias and the same range, including subnormals,
+    /// so this only has to drop the explicit integer bit and move the fraction over.
+    fn to_binary128(&self, val: IrValue, builder: &mut FunctionBuilder) -> IrValue {
+        if is_binary128(&self.target) {
+            return val;
+        }
+        let (addr, significand, sign_exponent) = self.split(val, builder);
+        // the padding after the exponent is garbage
+        let sign_exponent = builder.ins().band_imm(sign_exponent, 0xffff);
+        let fraction = builder.ins().band_imm(significand, i64::MAX);
+        let low = builder.ins().ishl_imm(fraction, EXTRA_BITS);
+        let high_fraction = builder.ins().ushr_imm(fraction, 64 - EXTRA_BITS);
+        let high = builder.ins().ishl_imm(sign_exponent, 48);
+        let high = builder.ins().bor(high, high_fraction);
+        Self::join(addr, low, high, builder)
+    }
+
+    /// Convert a binary128 value to a `long double`, if the target uses the x87 format,
+    /// rounding the fraction to nearest, ties to even.
+    fn to_x87(&self, val: IrValue, builder: &mut FunctionBuilder) -> IrValue {
+        if is_binary128(&self.target) {
+            return val;
+        }
+        let (addr, low, high) = self.split(val, builder);
+        let sign_exponent = builder.ins().ushr_imm(high, 48);
+        let exponent = builder.ins().band_imm(sign_exponent, EXPONENT_MAX);
+        let high_fraction = builder.ins().band_imm(high, (1 << 48) - 1);
+
+        // the top 63 bits of the fraction
+        let truncated = builder.ins().ishl_imm(high_fraction, 64 - EXTRA_BITS);
+        let low_fraction = builder.ins().ushr_imm(low, EXTRA_BITS);
+        let truncated = builder.ins().bor(truncated, low_fraction);
+
+        // round to nearest, ties to even
+        let remainder = builder.ins().band_imm(low, (1 << EXTRA_BITS) - 1);
+        let half = 1 << (EXTRA_BITS - 1);
+        let above_half = builder
+            .ins()
+            .icmp_imm(IntCC::UnsignedGreaterThan, remainder, half);
+        let is_half = builder.ins().icmp_imm(IntCC::Equal, remainder, half);
+        let odd = builder.ins().band_imm(truncated, 1);
+        let odd = builder.ins().icmp_imm(IntCC::NotEqual, odd, 0);
+        let tie_to_odd = builder.ins().band(is_half, odd);
+        let round_up = builder.ins().bor(above_half, tie_to_odd);
+        let round_up = builder.ins().uextend(types::I64, round_up);
+        let rounded = builder.ins().iadd(truncated, round_up);
+        // if rounding overflowed the fraction, carry into the exponent;
+        // this turns the largest finite values into infinity, as it should
+        let carry = builder.ins().ushr_imm(rounded, 63);
+        let rounded_fraction = builder.ins().band_imm(rounded, i64::MAX);
+        let rounded_exponent = builder.ins().iadd(sign_exponent, carry);
+
+        // NaNs must not be rounded into infinity, so keep them quiet instead
+        let all_ones = builder.ins().icmp_imm(IntCC::Equal, exponent, EXPONENT_MAX);
+        let any_fraction = builder.ins().bor(high_fraction, low);
+        let any_fraction = builder.ins().icmp_imm(IntCC::NotEqual, any_fraction, 0);
+        let is_nan = builder.ins().band(all_ones, any_fraction);
+        let quiet = builder.ins().bor_imm(truncated, 1 << 62);
+        let fraction = builder.ins().select(is_nan, quiet, rounded_fraction);
+        let sign_exponent = builder
+            .ins()
+            .select(is_nan, sign_exponent, rounded_exponent);
+
+        // the x87 format stores the integer bit explicitly: it is set for everything but
+        // zeros and subnormals
+        let exponent = builder.ins().band_imm(sign_exponent, EXPONENT_MAX);
+        let normal = builder.ins().icmp_imm(IntCC::NotEqual, exponent, 0);
+        let integer_bit = builder.ins().uextend(types::I64, normal);
+        let integer_bit = builder.ins().ishl_imm(integer_bit, 63);
+        let significand = builder.ins().bor(fraction, integer_bit);
+        Self::join(addr, significand, sign_exponent, builder)
+    }
+}
@@ -0,0 +1,240 @@
+//! Escape analysis for local variables.
+//!
+//! By default, every local variable gets its own stack slot, and every read and write goes through memory.
+//! A local whose address is never taken can instead be kept in a Cranelift `Variable`,
+//! which lets the register allocator keep it out of memory entirely.
+//!
+//! In the HIR, `ExprType::Id` always evaluates to the _address_ of a variable.
+//! Reading a variable is `Deref(Id)`, assigning to it is `Assign(Id, value)`,
+//! and `i++` is `PostIncrement(Id)`.
+//! Any other use of `Id`, such as `&i`, an array decaying to a pointer,
+//! or the hidden pointer used to desugar `*p += 1`, means the address escapes.
+
+use std::collections::HashSet;
+
+use saltwater_parser::data::hir::{BinaryOp, Expr, ExprType, Initializer, Stmt, StmtType, Symbol};
+use saltwater_parser::data::StorageClass;
+
+/// Find the parameters and locals of a function which never have their address taken.
+pub(crate) fn promotable_locals(params: &[Symbol], body: &[Stmt]) -> HashSet<Symbol> {
+    let mut analysis = EscapeAnalysis::default();
+    for &param in params {
+        if is_candidate(param) {
+            analysis.candidates.insert(param);
+        }
+    }
+    for stmt in body {
+        analysis.stmt(stmt);
+    }
+    let EscapeAnalysis {
+        candidates,
+        escaped,
+    } = analysis;
+    candidates.difference(&escaped).copied().collect()
+}
+
+/// Whether `symbol` could be kept in a register if its address is never taken.
+fn is_candidate(symbol: Symbol) -> bool {
+    let meta = symbol.get();
+    // `volatile` objects have to be read from memory each time they are accessed
+    matches!(
+        meta.storage_class,
+        StorageClass::Auto | StorageClass::Register
+    ) && meta.ctype.is_scalar()
+        && !meta.qualifiers.volatile
+}
+
+/// Whether `expr` is a variable used as an lvalue, i.e. the address of the variable.
+fn is_variable(expr: &Expr) -> bool {
+    matches!(expr.expr, ExprType::Id(_)) && expr.lval
+}
+
+#[derive(Default)]
+struct EscapeAnalysis {
+    /// Locals which could be promoted to a `Variable`
+    candidates: HashSet<Symbol>,
+    /// Variables which have had their address taken
+    escaped: HashSet<Symbol>,
+}
+
+impl EscapeAnalysis {
+    fn stmt(&mut self, stmt: &Stmt) {
+        match &stmt.data {
+            StmtType::Compound(stmts) => {
+                for stmt in stmts {
+                    self.stmt(stmt);
+                }
+            }
+            StmtType::If(condition, then, otherwise) => {
+                self.expr(condition);
+                self.stmt(then);
+                if let Some(otherwise) = otherwise {
+                    self.stmt(otherwise);
+                }
+            }
+            StmtType::Do(body, condition) | StmtType::While(condition, body) => {
+                self.expr(condition);
+                self.stmt(body);
+            }
+            StmtType::For(init, condition, post_loop, body) => {
+                self.stmt(init);
+                for expr in condition.iter().chain(post_loop) {
+                    self.expr(expr);
+                }
+                self.stmt(body);
+            }
+            StmtType::Switch(condition, body) => {
+                self.expr(condition);
+                self.stmt(body);
+            }
+            StmtType::Label(_, inner) | StmtType::Case(_, inner) | StmtType::Default(inner) => {
+                self.stmt(inner)
+            }
+            StmtType::Expr(expr) | StmtType::Return(Some(expr)) => self.expr(expr),
+            StmtType::Decl(decls) => {
+                for decl in decls {
+                    let symbol = decl.data.symbol;
+                    match &decl.data.init {
+                        Some(Initializer::Scalar(init)) => {
+                            self.expr(init);
+                            if is_candidate(symbol) {
+                                self.candidates.insert(symbol);
+                            }
+                        }
+                        // `int i = { 1 };` is stored like an aggregate
+                        Some(init) => self.initializer(init),
+                        None if is_candidate(symbol) => {
+                            self.candidates.insert(symbol);
+                        }
+                        None => {}
+                    }
+                }
+            }
+            StmtType::Goto(_) | StmtType::Continue | StmtType::Break | StmtType::Return(None) => {}
+        }
+    }
+
+    fn initializer(&mut self, init: &Initializer) {
+        match init {
+            Initializer::Scalar(expr) => self.expr(expr),
+            Initializer::InitializerList(list) => {
+                for (_, init) in list {
+                    self.initializer(init);
+                }
+            }
+            Initializer::FunctionBody(_) => {
+                unreachable!("functions can't be declared inside other functions")
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match &expr.expr {
+            ExprType::Id(symbol) => {
+                self.escaped.insert(*symbol);
+            }
+            // reading or incrementing a variable doesn't use its address
+            ExprType::Deref(inner) | ExprType::PostIncrement(inner, _) if is_variable(inner) => {}
+            ExprType::Binary(BinaryOp::Assign, target, value) if is_variable(target) => {
+                self.expr(value)
+            }
+            ExprType::Literal(_) | ExprType::Sizeof(_) => {}
+            ExprType::FuncCall(func, args) => {
+                self.expr(func);
+                for arg in args {
+                    self.expr(arg);
+                }
+            }
+            ExprType::CompoundLiteral(_, init) => self.initializer(init),
+            ExprType::Member(inner, _)
+            | ExprType::PostIncrement(inner, _)
+            | ExprType::Cast(inner)
+            | ExprType::Deref(inner)
+            | ExprType::Negate(inner)
+            | ExprType::BitwiseNot(inner)
+            | ExprType::StaticRef(inner)
+            | ExprType::Noop(inner)
+            | ExprType::VaStart(inner)
+            | ExprType::VaArg(inner)
+            | ExprType::VaEnd(inner) => self.expr(inner),
+            ExprType::Binary(_, left, right)
+            | ExprType::Comma(left, right)
+            | ExprType::VaCopy(left, right) => {
+                self.expr(left);
+                self.expr(right);
+            }
+            ExprType::Ternary(condition, then, otherwise) => {
+                self.expr(condition);
+                self.expr(then);
+                self.expr(otherwise);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use saltwater_parser::{check_semantics, get_str, Opt};
+
+    /// The names of the promotable locals in the last function of `program`.
+    fn promoted(program: &str) -> Vec<String> {
+        let hir = check_semantics(program, Opt::default())
+            .result
+            .expect("test programs should compile");
+        let decl = hir.into_iter().last().unwrap().data;
+        let params = match &decl.symbol.get().ctype {
+            saltwater_parser::Type::Function(func_type) => func_type.params.clone(),
+            _ => panic!("expected the last declaration to be a function"),
+        };
+        let body = match decl.init {
+            Some(Initializer::FunctionBody(body)) => body,
+            _ => panic!("expected a function definition"),
+        };
+        let mut names: Vec<_> = promotable_locals(&params, &body)
+            .into_iter()
+            .map(|symbol| get_str!(symbol.get().id).to_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn reads_and_writes() {
+        let program = "int f(int n) {
+            int sum = 0;
+            for (int i = 0; i < n; i++) {
+                sum += i;
+                ++sum;
+            }
+            return sum;
+        }";
+        assert_eq!(promoted(program), ["i", "n", "sum"]);
+    }
+
+    #[test]
+    fn address_taken() {
+        let program = "void g(int *);
+        int f(int n) {
+            int i = 1, j = 2;
+            g(&i);
+            int *p = &n;
+            return *p + i + j;
+        }";
+        assert_eq!(promoted(program), ["j", "p"]);
+    }
+
+    #[test]
+    fn not_scalar() {
+        let program = "struct s { int x; };
+        int f(void) {
+            int a[2] = { 1, 2 };
+            struct s s = { 3 };
+            volatile int v = 4;
+            static int st = 5;
+            int braced = { 6 };
+            return a[0] + s.x + v + st + braced;
+        }";
+        assert!(promoted(program).is_empty());
+    }
+}
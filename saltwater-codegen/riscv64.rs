@@ -0,0 +1,418 @@
+//! Passing arguments and return values in the RISC-V LP64D calling convention.
+//!
+//! See the calling convention chapter of the RISC-V ELF psABI for the full details:
+//! https://github.com/riscv-non-isa/riscv-elf-psabi-doc/blob/master/riscv-cc.adoc
+//!
+//! Integers and pointers are passed in the integer registers a0-a7, and `float` and `double`
+//! in the floating point registers fa0-fa7. Once the floating point registers run out,
+//! floating point values are passed in integer registers instead, as if they were integers
+//! of the same size. Integers narrower than a register are extended to 32 bits according to
+//! their type and then sign-extended, so even `unsigned int` is sign-extended.
+//! `long double` is binary128, which is too wide for the floating point registers,
+//! so it is passed in a pair of integer registers.
+//!
+//! A struct whose only scalars are one or two floating point values, or one floating point
+//! value and one integer, is passed as if those scalars were separate arguments,
+//! as long as there are registers left for all of them.
+//! Other structs and unions of up to 16 bytes are passed in one integer register per
+//! doubleword, and larger ones are copied by the caller and passed by reference.
+//! Return values are passed the same way in a0-a1 and fa0-fa1, except that large structs
+//! are written through a hidden pointer in a0 (`sret`).
+//!
+//! Variadic arguments always use the integer registers, and the ones aligned to 16 bytes
+//! start at an even-numbered register.
+//! Unlike AAPCS64, an argument that needs two registers when only one is left is split
+//! between the last register and the stack, which is what Cranelift does anyway.
+use cranelift::codegen::ir::{types, ArgumentExtension, Type as IrType};
+
+use super::abi::{aggregate_size, FunctionAbi, PassMode, EIGHTBYTE};
+use saltwater_parser::arch::TargetInfo;
+use saltwater_parser::data::{types::ArrayType, types::FunctionType, *};
+
+/// The number of integer registers used for arguments (a0-a7)
+const GP_REGISTERS: u32 = 8;
+/// The number of floating point registers used for arguments (fa0-fa7)
+const FP_REGISTERS: u32 = 8;
+/// Structs and unions larger than two registers are passed by reference
+const MAX_REGISTER_AGGREGATE: u64 = 16;
+/// Arguments with this alignment are passed in an aligned pair of registers when variadic,
+/// and at an aligned offset when on the stack
+const PAIR_ALIGN: u64 = 16;
+
+/// How an integer (or enum, or pointer) is passed: narrow integers are extended
+/// to a whole register.
+fn integer_mode(ctype: &Type, target: &TargetInfo) -> PassMode {
+    let ty = ctype.as_ir_type(target);
+    match ty.bits() {
+        64 => PassMode::Direct(ty),
+        32 => PassMode::Extended(ty, ArgumentExtension::Sext),
+        _ if ctype.is_signed() => PassMode::Extended(ty, ArgumentExtension::Sext),
+        _ => PassMode::Extended(ty, ArgumentExtension::Uext),
+    }
+}
+
+/// If `ctype` is a struct that is passed as separate scalars in the floating point registers,
+/// return the type and offset of each scalar.
+fn flatten(ctype: &Type, target: &TargetInfo) -> Option<Vec<(IrType, i32)>> {
+    // unions always use the integer calling convention
+    if let Type::Union(_) = ctype {
+        return None;
+    }
+    let mut fields = Vec::new();
+    flatten_at(ctype, 0, &mut fields, target)?;
+    let floats = fields.iter().filter(|(ty, _)| ty.is_float()).count();
+    // one or two floats, or one float and one integer
+    match (fields.len(), floats) {
+        (1, 1) | (2, 1) | (2, 2) => Some(fields),
+        _ => None,
+    }
+}
+
+/// Add the scalars of `ctype`, which starts `offset` bytes into the struct, to `fields`.
+///
+/// Returns `None` if the struct can't be flattened.
+fn flatten_at(
+    ctype: &Type,
+    offset: u64,
+    fields: &mut Vec<(IrType, i32)>,
+    target: &TargetInfo,
+) -> Option<()> {
+    match ctype {
+        Type::Struct(stype) => {
+            for member in stype.members().iter() {
+                if member.bitfield.is_some() {
+                    return None;
+                }
+                let member_offset = ctype
+                    .member_offset(member.id, target)
+                    .expect("structs should always have offsets for their members");
+                flatten_at(&member.ctype, offset + member_offset, fields, target)?;
+            }
+        }
+        Type::Array(of, ArrayType::Fixed(length)) => {
+            let size = of
+                .sizeof(target)
+                .expect("array elements should have a size");
+            for i in 0..*length {
+                flatten_at(of, offset + i * size, fields, target)?;
+            }
+        }
+        // `long double` is wider than the floating point registers
+        Type::Float | Type::Double => {
+            fields.push((ctype.as_ir_type(target), offset as i32));
+        }
+        // pointers don't count as integers here
+        _ if ctype.is_integral() => {
+            fields.push((ctype.as_ir_type(target), offset as i32));
+        }
+        _ => return None,
+    }
+    if fields.len() > 2 {
+        None
+    } else {
+        Some(())
+    }
+}
+
+/// Assigns registers and stack space to arguments, in order.
+struct Allocator<'a> {
+    target: &'a TargetInfo,
+    /// The next integer register
+    gp: u32,
+    /// The next floating point register
+    fp: u32,
+    /// The size of the arguments passed on the stack so far
+    stack: u32,
+}
+
+impl<'a> Allocator<'a> {
+    fn new(target: &'a TargetInfo) -> Allocator<'a> {
+        Allocator {
+            target,
+            gp: 0,
+            fp: 0,
+            stack: 0,
+        }
+    }
+
+    fn argument(&mut self, ctype: &Type, variadic: bool) -> PassMode {
+        match ctype {
+            Type::Struct(_) | Type::Union(_) => self.aggregate(ctype, variadic),
+            Type::Float | Type::Double => {
+                let ty = ctype.as_ir_type(self.target);
+                if !variadic && self.fp < FP_REGISTERS {
+                    self.fp += 1;
+                    return PassMode::Direct(ty);
+                }
+                // the caller passes the bits of the float as an integer
+                self.integer_registers(1, EIGHTBYTE, variadic);
+                PassMode::Direct(ty.as_int())
+            }
+            Type::LongDouble => {
+                let padding = self.integer_registers(2, PAIR_ALIGN, variadic);
+                padded(padding, PassMode::Direct(ctype.as_ir_type(self.target)))
+            }
+            _ => {
+                self.integer_registers(1, EIGHTBYTE, variadic);
+                integer_mode(ctype, self.target)
+            }
+        }
+    }
+
+    fn aggregate(&mut self, ctype: &Type, variadic: bool) -> PassMode {
+        let size = ctype
+            .sizeof(self.target)
+            .expect("parser should not allow passing incomplete types");
+        if size > MAX_REGISTER_AGGREGATE {
+            self.integer_registers(1, EIGHTBYTE, variadic);
+            return PassMode::Indirect;
+        }
+        if !variadic {
+            if let Some(fields) = flatten(ctype, self.target) {
+                let floats = fields.iter().filter(|(ty, _)| ty.is_float()).count() as u32;
+                let integers = fields.len() as u32 - floats;
+                if self.fp + floats <= FP_REGISTERS && self.gp + integers <= GP_REGISTERS {
+                    self.fp += floats;
+                    self.gp += integers;
+                    return PassMode::Fields(fields);
+                }
+            }
+        }
+        let align = ctype.alignof(self.target).unwrap_or(EIGHTBYTE);
+        let doublewords = aggregate_size(ctype, self.target) / EIGHTBYTE as u32;
+        let padding = self.integer_registers(doublewords, align, variadic);
+        padded(
+            padding,
+            PassMode::Eightbytes(vec![types::I64; doublewords as usize]),
+        )
+    }
+
+    /// Take `count` integer registers for an argument aligned to `align`,
+    /// spilling to the stack once they run out.
+    ///
+    /// Returns unused parameters that have to be passed first to align the argument.
+    fn integer_registers(&mut self, count: u32, align: u64, variadic: bool) -> Vec<IrType> {
+        let mut padding = Vec::new();
+        if align == PAIR_ALIGN && variadic && self.gp % 2 == 1 && self.gp < GP_REGISTERS {
+            padding.push(types::I64);
+            self.gp += 1;
+        }
+        // nothing goes on the stack until the integer registers are used up,
+        // so this padding goes on the stack too
+        if align == PAIR_ALIGN
+            && self.gp == GP_REGISTERS
+            && !self.stack.is_multiple_of(PAIR_ALIGN as u32)
+        {
+            padding.push(types::I64);
+            self.stack += EIGHTBYTE as u32;
+        }
+        for _ in 0..count {
+            if self.gp < GP_REGISTERS {
+                self.gp += 1;
+            } else {
+                self.stack += EIGHTBYTE as u32;
+            }
+        }
+        padding
+    }
+}
+
+fn padded(padding: Vec<IrType>, mode: PassMode) -> PassMode {
+    if padding.is_empty() {
+        mode
+    } else {
+        PassMode::Padded(padding, Box::new(mode))
+    }
+}
+
+/// How the return value is passed.
+fn return_mode(ctype: &Type, target: &TargetInfo) -> PassMode {
+    match ctype {
+        Type::Struct(_) | Type::Union(_) => {
+            let size = ctype
+                .sizeof(target)
+                .expect("parser should not allow returning incomplete types");
+            if size > MAX_REGISTER_AGGREGATE {
+                return PassMode::Memory(aggregate_size(ctype, target));
+            }
+            match flatten(ctype, target) {
+                Some(fields) => PassMode::Fields(fields),
+                None => {
+                    let doublewords = aggregate_size(ctype, target) / EIGHTBYTE as u32;
+                    PassMode::Eightbytes(vec![types::I64; doublewords as usize])
+                }
+            }
+        }
+        Type::Float | Type::Double | Type::LongDouble => PassMode::Direct(ctype.as_ir_type(target)),
+        _ => integer_mode(ctype, target),
+    }
+}
+
+/// Classify the arguments and return value of a call to a function of type `ftype`.
+///
+/// Only the first `named` parameters were declared; the rest are variadic arguments.
+pub(super) fn function_abi(ftype: &FunctionType, named: usize, target: &TargetInfo) -> FunctionAbi {
+    let ret = if ftype.should_return() {
+        Some(return_mode(&ftype.return_type, target))
+    } else {
+        None
+    };
+    let mut allocator = Allocator::new(target);
+    // the hidden `sret` pointer takes up a0
+    if let Some(PassMode::Memory(_)) = ret {
+        allocator.gp = 1;
+    }
+    let mut params = Vec::new();
+    if ftype.has_params() {
+        for (i, param) in ftype.params.iter().enumerate() {
+            params.push(allocator.argument(&param.get().ctype, i >= named));
+        }
+    }
+    FunctionAbi {
+        ret,
+        params,
+        registers: (allocator.gp, allocator.fp),
+        stack_size: allocator.stack,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use saltwater_parser::{check_semantics, Opt};
+    use ArgumentExtension::{Sext, Uext};
+    use PassMode::*;
+
+    /// Classify the last function declared in `src`,
+    /// treating all but the first `named` parameters as variadic arguments.
+    fn abi(src: &str, named: Option<usize>) -> FunctionAbi {
+        let target = TargetInfo::new("riscv64gc-unknown-linux-gnu".parse().unwrap());
+        let opt = Opt {
+            target: target.clone(),
+            ..Opt::default()
+        };
+        let decls = check_semantics(src, opt).result.unwrap();
+        let ctype = decls.last().unwrap().data.symbol.get().ctype.clone();
+        let ftype = match ctype {
+            Type::Function(ftype) => ftype,
+            other => panic!("expected a function, got {}", other),
+        };
+        let named = named.unwrap_or(ftype.params.len());
+        function_abi(&ftype, named, &target)
+    }
+
+    fn params(src: &str) -> Vec<PassMode> {
+        abi(src, None).params
+    }
+
+    #[test]
+    fn registers() {
+        let src = "
+            struct pair { long a; int b; };
+            struct big { long a, b, c; };
+            void f(int, unsigned char, unsigned, struct pair, struct big, double, long double, char *);";
+        assert_eq!(
+            params(src),
+            vec![
+                Extended(types::I32, Sext),
+                Extended(types::I8, Uext),
+                Extended(types::I32, Sext),
+                Eightbytes(vec![types::I64, types::I64]),
+                Indirect,
+                Direct(types::F64),
+                Direct(types::I128),
+                Direct(types::I64),
+            ]
+        );
+    }
+
+    #[test]
+    fn flattening() {
+        let flat = |members: &str| {
+            let src = format!("struct s {{ {} }}; void f(struct s);", members);
+            params(&src).remove(0)
+        };
+        assert_eq!(flat("float x;"), Fields(vec![(types::F32, 0)]));
+        assert_eq!(
+            flat("struct { double d[1]; } a; float b;"),
+            Fields(vec![(types::F64, 0), (types::F32, 8)])
+        );
+        assert_eq!(
+            flat("char c; double d;"),
+            Fields(vec![(types::I8, 0), (types::F64, 8)])
+        );
+        // too many scalars, no floats, or members that don't count
+        assert_eq!(flat("float x[3];"), Eightbytes(vec![types::I64; 2]));
+        assert_eq!(flat("int a, b;"), Eightbytes(vec![types::I64]));
+        assert_eq!(flat("float f; char *p;"), Eightbytes(vec![types::I64; 2]));
+        assert_eq!(flat("float f; int i : 3;"), Eightbytes(vec![types::I64]));
+        assert_eq!(flat("long double ld;"), Eightbytes(vec![types::I64; 2]));
+        let src = "union u { float f; double d; }; void f(union u);";
+        assert_eq!(params(src), vec![Eightbytes(vec![types::I64])]);
+    }
+
+    #[test]
+    fn out_of_registers() {
+        // floats use the integer registers once the floating point registers run out,
+        // and a flattened struct needs registers for all of its members
+        let src = "
+            struct two { double a, b; };
+            void f(double, double, double, double, double, double, double, struct two, double, long);";
+        let modes = params(src);
+        assert_eq!(modes[7], Eightbytes(vec![types::I64; 2]));
+        assert_eq!(modes[8], Direct(types::F64));
+        assert_eq!(modes[9], Direct(types::I64));
+        let src = "void f(double, double, double, double, double, double, double, double, double);";
+        assert_eq!(params(src)[8], Direct(types::I64));
+        // a pair is split between the last register and the stack
+        let src = "void f(long, long, long, long, long, long, long, long double, long double);";
+        let abi = abi(src, None);
+        assert_eq!(abi.params[7], Direct(types::I128));
+        // ... and the stack is aligned for the next one
+        assert_eq!(
+            abi.params[8],
+            Padded(vec![types::I64], Box::new(Direct(types::I128)))
+        );
+        assert_eq!(abi.stack_size, 32);
+    }
+
+    #[test]
+    fn returns() {
+        let ret = |src: &str| abi(src, None).ret;
+        assert_eq!(ret("void f();"), None);
+        assert_eq!(ret("unsigned f();"), Some(Extended(types::I32, Sext)));
+        assert_eq!(
+            ret("struct s { float x; int i; } f();"),
+            Some(Fields(vec![(types::F32, 0), (types::I32, 4)]))
+        );
+        assert_eq!(
+            ret("struct s { char c[12]; } f();"),
+            Some(Eightbytes(vec![types::I64; 2]))
+        );
+        assert_eq!(ret("struct s { char c[17]; } f();"), Some(Memory(24)));
+        assert_eq!(ret("long double f();"), Some(Direct(types::I128)));
+        // the `sret` pointer takes up a0
+        let src = "struct s { long l[3]; } f(long);";
+        assert_eq!(abi(src, None).registers, (2, 0));
+    }
+
+    #[test]
+    fn variadic() {
+        let src = "
+            struct two { double a, b; };
+            int printf(const char *, int, double, struct two, long double);";
+        let abi = abi(src, Some(1));
+        assert_eq!(
+            abi.params,
+            vec![
+                Direct(types::I64),
+                Extended(types::I32, Sext),
+                Direct(types::I64),
+                Eightbytes(vec![types::I64; 2]),
+                // a0-a4 are used, so this skips a5 to start at a6
+                Padded(vec![types::I64], Box::new(Direct(types::I128))),
+            ]
+        );
+        assert_eq!(abi.registers, (8, 0));
+    }
+}
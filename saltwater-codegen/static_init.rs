@@ -7,17 +7,13 @@ use cranelift::codegen::ir::types;
 use cranelift_module::{DataDescription, DataId, Linkage, Module};
 
 use super::{Compiler, Id};
-use saltwater_parser::arch::{PTR_SIZE, TARGET};
-use saltwater_parser::const_assert;
+use saltwater_parser::arch::TargetInfo;
 use saltwater_parser::data::{
-    hir::{Expr, ExprType, Initializer, LiteralValue, Symbol},
+    hir::{Designator, Expr, ExprType, Initializer, LiteralValue, Symbol, Variable},
     types::ArrayType,
     StorageClass, *,
 };
 
-const_assert!(PTR_SIZE <= std::usize::MAX as u16);
-const ZERO_PTR: [u8; PTR_SIZE as usize] = [0; PTR_SIZE as usize];
-
 macro_rules! cast {
     ($i: expr, $from: ty, $to: ty, $ctype: expr, $location: expr, $handler: expr) => {{
         let cast = $i as $to;
@@ -62,8 +58,7 @@ impl<M: Module> Compiler<M> {
             location,
         };
         let align = metadata
-            .ctype
-            .alignof()
+            .alignof(&self.target)
             .map_err(|err| err.to_string())
             // .and_then(|size| {
             //     // size.try_into()
@@ -96,22 +91,10 @@ impl<M: Module> Compiler<M> {
         }
 
         let mut ctx = DataDescription::new();
-        // TODO: all of this should happen in the `analyze` module
+        ctx.set_align(align);
         if let Some(init) = init {
-            let mut ctype = metadata.ctype.clone();
-            if let Type::Array(_, size @ ArrayType::Unbounded) = &mut ctype {
-                if let Some(len) = match &init {
-                    Initializer::InitializerList(list) => Some(list.len()),
-                    Initializer::Scalar(expr) => match &expr.expr {
-                        ExprType::Literal(LiteralValue::Str(s)) => Some(s.len()),
-                        _ => None,
-                    },
-                    _ => None,
-                } {
-                    *size = ArrayType::Fixed(len.try_into().unwrap());
-                };
-            }
-            let size_t = ctype.sizeof().map_err(|err| Locatable {
+            let ctype = metadata.ctype.clone();
+            let size_t = ctype.sizeof(&self.target).map_err(|err| Locatable {
                 data: err.to_string(),
                 location,
             })?;
@@ -126,7 +109,7 @@ impl<M: Module> Compiler<M> {
             ctx.define_zeroinit(
                 metadata
                     .ctype
-                    .sizeof()
+                    .sizeof(&self.target)
                     .map_err(|err| err_closure(err.to_string()))? as usize,
             );
         };
@@ -135,7 +118,9 @@ impl<M: Module> Compiler<M> {
                 data: format!("error defining static variable: {}", err),
                 location,
             })
-        })
+        })?;
+        self.declare_global_debug_info(symbol, id, location);
+        Ok(())
     }
     pub(super) fn compile_string(
         &mut self,
@@ -181,7 +166,7 @@ impl<M: Module> Compiler<M> {
         offset: u32,
         expr: Expr,
     ) -> CompileResult<()> {
-        let expr = expr.const_fold()?;
+        let expr = expr.const_fold(&self.target)?;
         // static address-of
         match expr.expr {
             ExprType::StaticRef(inner) => match inner.expr {
@@ -191,12 +176,12 @@ impl<M: Module> Compiler<M> {
                     let str_addr = self.module.declare_data_in_data(str_id, ctx);
                     ctx.write_data_addr(offset, str_addr, 0);
                 }
-                ExprType::Literal(ref token) if token.is_zero() => buf.copy_from_slice(&ZERO_PTR),
-                ExprType::Cast(ref inner) if inner.is_zero() => buf.copy_from_slice(&ZERO_PTR),
+                ExprType::Literal(ref token) if token.is_zero() => buf.fill(0),
+                ExprType::Cast(ref inner) if inner.is_zero() => buf.fill(0),
                 ExprType::Member(struct_expr, member) => {
                     let member_offset = struct_expr
                         .ctype
-                        .member_offset(member)
+                        .member_offset(member, &self.target)
                         .expect("parser shouldn't allow Member for non-struct types");
                     if let ExprType::Id(symbol) = struct_expr.expr {
                         self.static_ref(symbol, member_offset.try_into().unwrap(), offset, ctx);
@@ -209,9 +194,20 @@ impl<M: Module> Compiler<M> {
                 }
                 _ => semantic_err!("cannot take the address of an rvalue".into(), expr.location),
             },
+            // char s[5] = "ab"; the remaining elements are already zeroed
+            // char s[2] = "ab"; leaves out the null terminator
+            ExprType::Literal(LiteralValue::Str(string)) => {
+                let len = string.len().min(buf.len());
+                buf[..len].copy_from_slice(&string[..len]);
+            }
             ExprType::Literal(token) => {
-                let bytes =
-                    into_bytes(token, &expr.ctype, &expr.location, &mut self.error_handler)?;
+                let bytes = into_bytes(
+                    token,
+                    &expr.ctype,
+                    &expr.location,
+                    &self.target,
+                    &mut self.error_handler,
+                )?;
                 buf.copy_from_slice(&bytes);
             }
             _ => semantic_err!(
@@ -239,7 +235,12 @@ impl<M: Module> Compiler<M> {
                 let global_val = self.module.declare_data_in_data(*data_id, ctx);
                 ctx.write_data_addr(offset, global_val, member_offset);
             }
-            Some(Id::Local(_)) => unreachable!("cannot have local variable at global scope"),
+            Some(Id::Local(_))
+            | Some(Id::OverAligned(..))
+            | Some(Id::Vla(_))
+            | Some(Id::Variable(_)) => {
+                unreachable!("cannot have local variable at global scope")
+            }
             None => unreachable!("parser should catch undeclared variables"),
         }
     }
@@ -248,60 +249,34 @@ impl<M: Module> Compiler<M> {
         &mut self,
         ctx: &mut DataDescription,
         buf: &mut [u8],
-        mut offset: u32,
+        offset: u32,
         initializer: Initializer,
         ctype: &Type,
         location: &Location,
     ) -> CompileResult<()> {
         match initializer {
-            Initializer::InitializerList(mut initializers) => match ctype {
-                Type::Array(ty, ArrayType::Unbounded) => {
-                    self.init_array(ctx, buf, offset, initializers, ty, location)
-                }
-                Type::Array(ty, ArrayType::Fixed(size)) => {
-                    if initializers.len() as u64 > *size {
-                        Err(CompileError::semantic(Locatable {
-                            data: format!(
-                                "too many elements for array (expected {}, got {})",
-                                size,
-                                initializers.len()
-                            ),
-                            // TODO: this location points to the declarator, not the initializer
-                            location: *location,
-                        }))
-                    } else {
-                        self.init_array(ctx, buf, offset, initializers, ty, location)
-                    }
-                }
+            Initializer::InitializerList(initializers) => match ctype {
+                Type::Array(ty, _) => self.init_array(ctx, buf, offset, initializers, ty, location),
+                // int i = { 1 };
                 ty if ty.is_scalar() => {
-                    assert_eq!(initializers.len(), 1);
-                    self.init_symbol(ctx, buf, offset, initializers.remove(0), ctype, location)
+                    for (_, init) in initializers {
+                        self.init_symbol(ctx, buf, offset, init, ctype, location)?;
+                    }
+                    Ok(())
                 }
-                Type::Union(struct_type) => self.init_symbol(
-                    ctx,
-                    buf,
-                    offset,
-                    initializers.remove(0),
-                    &struct_type.members().first().unwrap().ctype,
-                    location,
-                ),
-                Type::Struct(struct_ref) => {
-                    let mut current_offset = 0;
-                    for (member, init) in struct_ref.members().iter().zip(initializers.into_iter())
-                    {
-                        let size_host: usize = member
-                            .ctype
-                            .sizeof()
-                            .map_err(|err| CompileError::semantic(location.with(err.to_string())))?
-                            .try_into()
-                            .expect("cannot initialize struct larger than u32");
-                        let size: u32 = size_host
-                            .try_into()
-                            .expect("cannot intialize struct larger than host address space");
-                        let buf_slice = &mut buf[current_offset..current_offset + size_host];
-                        self.init_symbol(ctx, buf_slice, offset, init, &member.ctype, location)?;
-                        offset += size;
-                        current_offset += size_host;
+                Type::Struct(struct_type) | Type::Union(struct_type) => {
+                    let members = struct_type.members();
+                    for (designator, init) in initializers {
+                        let member = match designator {
+                            Designator::Member(id) => members
+                                .iter()
+                                .find(|member| member.id == id)
+                                .expect("parser should only allow designators for members"),
+                            Designator::Index(_) => {
+                                unreachable!("parser should not allow indexing a struct")
+                            }
+                        };
+                        self.init_member(ctx, buf, offset, init, ctype, member, location)?;
                     }
                     Ok(())
                 }
@@ -316,12 +291,87 @@ impl<M: Module> Compiler<M> {
         }
     }
 
+    /// Initialize the member `member` of `struct_type`.
+    ///
+    /// `buf` and `offset` refer to the whole struct, not just the member.
+    #[allow(clippy::too_many_arguments)]
+    fn init_member(
+        &mut self,
+        ctx: &mut DataDescription,
+        buf: &mut [u8],
+        offset: u32,
+        initializer: Initializer,
+        struct_type: &Type,
+        member: &Variable,
+        location: &Location,
+    ) -> CompileResult<()> {
+        let member_offset = struct_type
+            .member_offset(member.id, &self.target)
+            .expect("only structs and unions can have members");
+        let member_offset: u32 = member_offset
+            .try_into()
+            .expect("cannot initialize struct larger than u32");
+        let size_host: usize = member
+            .ctype
+            .sizeof(&self.target)
+            .map_err(|err| CompileError::semantic(location.with(err.to_string())))?
+            .try_into()
+            .expect("cannot initialize struct larger than host address space");
+        let start = member_offset as usize;
+        let unit = &mut buf[start..start + size_host];
+        let offset = offset + member_offset;
+        let bitfield = match struct_type.member_bitfield(member.id, &self.target) {
+            Some(bitfield) => bitfield,
+            None => {
+                return self.init_symbol(ctx, unit, offset, initializer, &member.ctype, location)
+            }
+        };
+        // initialize a copy of the storage unit, then copy over only the bits in the bitfield,
+        // since the storage unit may be shared with other bitfields
+        let mut value = vec![0; size_host];
+        self.init_symbol(
+            ctx,
+            &mut value,
+            offset,
+            initializer,
+            &member.ctype,
+            location,
+        )?;
+        let big_endian = self
+            .target
+            .triple
+            .endianness()
+            .expect("target should be big or little endian")
+            == target_lexicon::Endianness::Big;
+        let read = |bytes: &[u8]| {
+            let fold = |n: u64, &byte: &u8| n << 8 | u64::from(byte);
+            if big_endian {
+                bytes.iter().fold(0, fold)
+            } else {
+                bytes.iter().rev().fold(0, fold)
+            }
+        };
+        let field_mask = (u64::MAX >> (64 - u32::from(bitfield.width))) << bitfield.shift;
+        let packed = read(unit) & !field_mask | (read(&value) << bitfield.shift) & field_mask;
+        let packed = if big_endian {
+            packed.to_be_bytes()
+        } else {
+            packed.to_le_bytes()
+        };
+        if big_endian {
+            unit.copy_from_slice(&packed[packed.len() - size_host..]);
+        } else {
+            unit.copy_from_slice(&packed[..size_host]);
+        }
+        Ok(())
+    }
+
     fn init_array(
         &mut self,
         ctx: &mut DataDescription,
         buf: &mut [u8],
-        mut offset: u32,
-        initializers: Vec<Initializer>,
+        offset: u32,
+        initializers: Vec<(Designator, Initializer)>,
         inner_type: &Type,
         location: &Location,
     ) -> CompileResult<()> {
@@ -332,27 +382,30 @@ impl<M: Module> Compiler<M> {
             );
         }
         let inner_size: usize = inner_type
-            .sizeof()
+            .sizeof(&self.target)
             .map_err(|err| Locatable {
                 data: err.to_string(),
                 location: *location,
             })?
             .try_into()
             .expect("cannot initialize array larger than address space of host");
-        let mut element_offset: usize = 0;
-        for init in initializers {
+        for (designator, init) in initializers {
+            let index = match designator {
+                Designator::Index(index) => index as usize,
+                Designator::Member(_) => unreachable!("parser should not allow members of arrays"),
+            };
+            let element_offset = index * inner_size;
             self.init_symbol(
                 ctx,
                 // pass a buffer of size `inner_size` to `init_symbol`
                 &mut buf[element_offset..element_offset + inner_size],
-                offset,
+                offset
+                    + u32::try_from(element_offset)
+                        .expect("cannot initialize array larger than 2^32 bytes"),
                 init,
                 inner_type,
                 location,
             )?;
-            offset +=
-                u32::try_from(inner_size).expect("cannot initialize array larger than 2^32 bytes");
-            element_offset += inner_size;
         }
         // zero-init should already have been taken care of by init_symbol
         Ok(())
@@ -363,10 +416,12 @@ fn into_bytes(
     value: LiteralValue,
     ctype: &Type,
     location: &Location,
+    target: &TargetInfo,
     error_handler: &mut ErrorHandler,
 ) -> CompileResult<Box<[u8]>> {
-    let ir_type = ctype.as_ir_type();
-    let big_endian = TARGET
+    let ir_type = ctype.as_ir_type(target);
+    let big_endian = target
+        .triple
         .endianness()
         .expect("target should be big or little endian")
         == target_lexicon::Endianness::Big;
@@ -418,8 +473,15 @@ fn into_bytes(
                 bytes!(float_as_int, big_endian)
             }
             types::F64 => bytes!(f.to_bits(), big_endian),
+            types::I64X2 | types::I128 => {
+                Box::new(super::long_double::long_double_bytes(&f.into(), target))
+            }
             x => unreachable!("ir_type {} for float {} is not of integer type", x, f),
         }),
+        LiteralValue::LongDouble(f) => Ok(match ir_type {
+            types::F64 => bytes!(f.to_f64().to_bits(), big_endian),
+            _ => Box::new(super::long_double::long_double_bytes(&f, target)),
+        }),
         LiteralValue::Str(string) => Ok(string.into_boxed_slice()),
         LiteralValue::Char(c) => Ok(Box::new([c])),
     }
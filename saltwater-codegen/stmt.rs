@@ -4,6 +4,7 @@ use cranelift::frontend::Switch;
 use cranelift::prelude::{Block, FunctionBuilder, InstBuilder};
 use cranelift_module::Module;
 
+use super::abi::PassMode;
 use super::Compiler;
 use saltwater_parser::data::{
     hir::{Expr, Stmt, StmtType},
@@ -30,6 +31,7 @@ impl<M: Module> Compiler<M> {
         if builder.is_filled() && !is_jump_target(&stmt.data) {
             return Err(stmt.location.error(SemanticError::UnreachableStatement));
         }
+        self.set_srcloc(stmt.location, builder);
         match stmt.data {
             StmtType::Compound(stmts) => self.compile_all(stmts, builder),
             // INVARIANT: symbol has not yet been declared in this scope
@@ -46,8 +48,16 @@ impl<M: Module> Compiler<M> {
             StmtType::Return(expr) => {
                 let mut ret = vec![];
                 if let Some(e) = expr {
+                    let ctype = e.ctype.clone();
                     let val = self.compile_expr(e, builder)?;
-                    ret.push(val.ir_val);
+                    ret = self.return_values(val.ir_val, &ctype, builder);
+                }
+                // the return value might have been read from a VLA, so free them afterwards
+                self.free_vlas(builder);
+                if let (Some(PassMode::X87), Some(&val)) = (&self.ret, ret.first()) {
+                    // this has to come after every other call, since they could use `st(0)`
+                    self.push_st0(val, builder);
+                    ret.clear();
                 }
                 builder.ins().return_(&ret);
                 Ok(())
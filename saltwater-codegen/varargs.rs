@@ -0,0 +1,296 @@
+//! Variadic function definitions for the System V x86_64 ABI.
+//!
+//! See section 3.5.7 of the ABI for the full details:
+//! https://gitlab.com/x86-psABIs/x86-64-ABI
+//!
+//! `va_list` is a pointer to the following struct:
+//! ```c
+//! struct __va_list_tag {
+//!     unsigned gp_offset;       // offset into `reg_save_area` of the next integer register
+//!     unsigned fp_offset;       // offset into `reg_save_area` of the next SSE register
+//!     void *overflow_arg_area;  // the next argument passed on the stack
+//!     void *reg_save_area;      // spilled copies of all argument registers
+//! };
+//! ```
+use cranelift::codegen::binemit::Reloc;
+use cranelift::codegen::ir::{
+    types, AbiParam, Function, MemFlags, Signature, StackSlot, UserFuncName,
+};
+use cranelift::codegen::isa::CallConv;
+use cranelift::codegen::MachReloc;
+use cranelift::prelude::{
+    FunctionBuilder, InstBuilder, IntCC, StackSlotData, StackSlotKind, Value as IrValue,
+};
+use cranelift_module::{FuncId, Module};
+
+use super::abi::{FunctionAbi, FP_REGISTERS, GP_REGISTERS};
+use super::Compiler;
+use saltwater_parser::data::*;
+
+/// Each integer register takes 8 bytes in the register save area
+const GP_SIZE: u32 = 8;
+/// Each SSE register takes 16 bytes in the register save area
+const FP_SIZE: u32 = 16;
+/// The integer registers come first, followed by the SSE registers
+const REG_SAVE_AREA_SIZE: u32 = GP_REGISTERS * GP_SIZE + FP_REGISTERS * FP_SIZE;
+/// Every argument passed on the stack takes up at least one eightbyte
+const STACK_SLOT_SIZE: u32 = 8;
+/// The first stack argument is above the saved frame pointer and the return address
+const FIRST_STACK_ARG: u32 = 16;
+
+// offsets of the members of `struct __va_list_tag`
+const GP_OFFSET: i32 = 0;
+const FP_OFFSET: i32 = 4;
+const OVERFLOW_ARG_AREA: i32 = 8;
+const REG_SAVE_AREA: i32 = 16;
+
+/// Where to find the variadic arguments of the function currently being compiled.
+///
+/// These are the initial values `va_start` stores into a `va_list`.
+#[derive(Copy, Clone, Debug)]
+pub(super) struct VarargsFrame {
+    reg_save_area: StackSlot,
+    /// the offset of the first integer register not used by a named parameter
+    gp_offset: u32,
+    /// the offset of the first SSE register not used by a named parameter
+    fp_offset: u32,
+    /// the offset from the frame pointer of the first variadic argument passed on the stack
+    overflow_arg_area: u32,
+}
+
+/// Modify the signature of a variadic function so it can be defined.
+///
+/// Cranelift has no way to access arguments that aren't in the signature,
+/// so this adds enough parameters to receive every argument register the caller could have used.
+/// Those parameters are never used directly: they're spilled to the register save area
+/// in `Compiler::varargs_prologue`.
+pub(super) fn definition_signature(signature: &mut Signature, abi: &FunctionAbi) {
+    let (gp, fp) = abi.registers;
+    let gp_padding = GP_REGISTERS.saturating_sub(gp);
+    let fp_padding = FP_REGISTERS.saturating_sub(fp);
+    signature
+        .params
+        .extend((0..gp_padding).map(|_| AbiParam::new(types::I64)));
+    signature
+        .params
+        .extend((0..fp_padding).map(|_| AbiParam::new(types::F64)));
+}
+
+impl<M: Module> Compiler<M> {
+    /// Save the argument registers of a variadic function so `va_arg` can find them later.
+    ///
+    /// `register_args` are the parameters added by `definition_signature`.
+    pub(super) fn varargs_prologue(
+        &mut self,
+        abi: &FunctionAbi,
+        register_args: &[IrValue],
+        location: Location,
+        builder: &mut FunctionBuilder,
+    ) -> CompileResult<VarargsFrame> {
+        let isa = self.module.isa();
+        if isa.name() != "x64" || isa.default_call_conv() != CallConv::SystemV {
+            semantic_err!(
+                "defining variadic functions is only supported for the System V x86_64 ABI".into(),
+                location,
+            );
+        }
+        let (gp, fp) = abi.registers;
+        let reg_save_area = builder.create_sized_stack_slot(StackSlotData {
+            kind: StackSlotKind::ExplicitSlot,
+            size: REG_SAVE_AREA_SIZE,
+        });
+        // registers used by named parameters are never read by `va_arg`, so don't bother saving them
+        let gp_offsets = (gp..GP_REGISTERS).map(|reg| reg * GP_SIZE);
+        let fp_offsets = (fp..FP_REGISTERS).map(|reg| GP_REGISTERS * GP_SIZE + reg * FP_SIZE);
+        for (&val, offset) in register_args.iter().zip(gp_offsets.chain(fp_offsets)) {
+            builder.ins().stack_store(val, reg_save_area, offset as i32);
+        }
+        Ok(VarargsFrame {
+            reg_save_area,
+            gp_offset: gp.min(GP_REGISTERS) * GP_SIZE,
+            fp_offset: GP_REGISTERS * GP_SIZE + fp.min(FP_REGISTERS) * FP_SIZE,
+            overflow_arg_area: FIRST_STACK_ARG + abi.stack_size,
+        })
+    }
+
+    /// `va_start(ap)`: initialize `ap` to point to the first variadic argument
+    pub(super) fn va_start(
+        &mut self,
+        ap: IrValue,
+        location: Location,
+        builder: &mut FunctionBuilder,
+    ) -> CompileResult<()> {
+        let frame = match self.varargs {
+            Some(frame) => frame,
+            None => semantic_err!(
+                "va_start used in function with fixed arguments".into(),
+                location
+            ),
+        };
+        let ptr = Type::ptr_type(&self.target);
+        let flags = MemFlags::trusted();
+
+        let gp_offset = builder.ins().iconst(types::I32, i64::from(frame.gp_offset));
+        builder.ins().store(flags, gp_offset, ap, GP_OFFSET);
+        let fp_offset = builder.ins().iconst(types::I32, i64::from(frame.fp_offset));
+        builder.ins().store(flags, fp_offset, ap, FP_OFFSET);
+
+        let frame_pointer = builder.ins().get_frame_pointer(ptr);
+        let overflow_arg_area = builder
+            .ins()
+            .iadd_imm(frame_pointer, i64::from(frame.overflow_arg_area));
+        builder
+            .ins()
+            .store(flags, overflow_arg_area, ap, OVERFLOW_ARG_AREA);
+        let reg_save_area = builder.ins().stack_addr(ptr, frame.reg_save_area, 0);
+        builder.ins().store(flags, reg_save_area, ap, REG_SAVE_AREA);
+        Ok(())
+    }
+
+    /// `va_arg(ap, ctype)`: load the next variadic argument and advance `ap`
+    ///
+    /// `ctype` must be an integer, a pointer, `double`, or `long double`.
+    pub(super) fn va_arg(
+        &mut self,
+        ap: IrValue,
+        ctype: &Type,
+        builder: &mut FunctionBuilder,
+    ) -> IrValue {
+        let ptr = Type::ptr_type(&self.target);
+        let flags = MemFlags::trusted();
+        // `long double` is always passed on the stack, at a 16-byte boundary
+        if *ctype == Type::LongDouble {
+            let addr = builder.ins().load(ptr, flags, ap, OVERFLOW_ARG_AREA);
+            let addr = builder.ins().iadd_imm(addr, 15);
+            let addr = builder.ins().band_imm(addr, -16);
+            let next_arg = builder.ins().iadd_imm(addr, 16);
+            builder.ins().store(flags, next_arg, ap, OVERFLOW_ARG_AREA);
+            return builder.ins().load(types::I64X2, MemFlags::new(), addr, 0);
+        }
+        // which register class the argument would have been passed in
+        let (offset_field, end, step) = if ctype.is_floating() {
+            (FP_OFFSET, REG_SAVE_AREA_SIZE, FP_SIZE)
+        } else {
+            (GP_OFFSET, GP_REGISTERS * GP_SIZE, GP_SIZE)
+        };
+
+        let (in_registers, on_stack, done) = (
+            builder.create_block(),
+            builder.create_block(),
+            builder.create_block(),
+        );
+        let arg_addr = builder.append_block_param(done, ptr);
+
+        let offset = builder.ins().load(types::I32, flags, ap, offset_field);
+        let has_registers_left =
+            builder
+                .ins()
+                .icmp_imm(IntCC::UnsignedLessThan, offset, i64::from(end));
+        builder
+            .ins()
+            .brif(has_registers_left, in_registers, &[], on_stack, &[]);
+
+        // the argument was passed in a register
+        builder.switch_to_block(in_registers);
+        let reg_save_area = builder.ins().load(ptr, flags, ap, REG_SAVE_AREA);
+        let extended_offset = builder.ins().uextend(ptr, offset);
+        let addr = builder.ins().iadd(reg_save_area, extended_offset);
+        let next_offset = builder.ins().iadd_imm(offset, i64::from(step));
+        builder.ins().store(flags, next_offset, ap, offset_field);
+        builder.ins().jump(done, &[addr]);
+
+        // the argument was passed on the stack
+        builder.switch_to_block(on_stack);
+        let addr = builder.ins().load(ptr, flags, ap, OVERFLOW_ARG_AREA);
+        let next_arg = builder.ins().iadd_imm(addr, i64::from(STACK_SLOT_SIZE));
+        builder.ins().store(flags, next_arg, ap, OVERFLOW_ARG_AREA);
+        builder.ins().jump(done, &[addr]);
+
+        builder.switch_to_block(done);
+        // integers smaller than an eightbyte are stored in the low bytes
+        builder
+            .ins()
+            .load(ctype.as_ir_type(&self.target), MemFlags::new(), arg_addr, 0)
+    }
+
+    /// `va_copy(dest, src)`: make `dest` point to the same argument as `src`
+    pub(super) fn va_copy(&mut self, dest: IrValue, src: IrValue, builder: &mut FunctionBuilder) {
+        let size = Type::VaList
+            .sizeof(&self.target)
+            .expect("va_list should have a known size");
+        let align = Type::VaList
+            .alignof(&self.target)
+            .expect("va_list should have a known alignment") as u8;
+        builder.emit_small_memory_copy(
+            self.module.target_config(),
+            dest,
+            src,
+            size,
+            align,
+            align,
+            // `va_copy(ap, ap)` is silly but allowed
+            false,
+            MemFlags::new(),
+        );
+    }
+
+    /// Return a function which sets `al`, then jumps to the variadic function `callee`.
+    ///
+    /// Callers of variadic functions have to pass an upper bound on the number of SSE registers used in `al`,
+    /// or the callee may not save the SSE registers that hold floating-point arguments.
+    /// Cranelift has no way to pass a value in `rax`, so calls go through this trampoline instead,
+    /// which sets `al` to 8 (the largest possible bound) and leaves every argument register alone.
+    /// Each trampoline jumps to a single function, so calls through function pointers can't use one.
+    pub(super) fn varargs_trampoline(&mut self, callee: FuncId) -> FuncId {
+        if let Some(&trampoline) = self.trampolines.get(&callee) {
+            return trampoline;
+        }
+        let signature = self
+            .module
+            .declarations()
+            .get_function_decl(callee)
+            .signature
+            .clone();
+        let trampoline = self
+            .module
+            .declare_anonymous_function(&signature)
+            .expect("anonymous functions should never conflict");
+        // the function is only used to name `callee` in the relocation
+        let mut func = Function::with_name_signature(UserFuncName::user(0, 0), signature);
+        let func_ref = self.module.declare_func_in_func(callee, &mut func);
+        let name = func.dfg.ext_funcs[func_ref].name.clone();
+        // mov al, 8
+        let mut code = vec![0xb0, FP_REGISTERS as u8];
+        let reloc = if self.module.isa().flags().is_pic() {
+            // jmp callee@PLT
+            code.push(0xe9);
+            let reloc = MachReloc {
+                offset: code.len() as u32,
+                kind: Reloc::X86CallPLTRel4,
+                name,
+                // the jump is relative to the end of the instruction
+                addend: -4,
+            };
+            code.extend_from_slice(&[0; 4]);
+            reloc
+        } else {
+            // movabs r11, callee
+            code.extend_from_slice(&[0x49, 0xbb]);
+            let reloc = MachReloc {
+                offset: code.len() as u32,
+                kind: Reloc::Abs8,
+                name,
+                addend: 0,
+            };
+            code.extend_from_slice(&[0; 8]);
+            // jmp r11
+            code.extend_from_slice(&[0x41, 0xff, 0xe3]);
+            reloc
+        };
+        self.module
+            .define_function_bytes(trampoline, &func, 16, &code, &[reloc])
+            .expect("trampolines should always be valid functions");
+        self.trampolines.insert(callee, trampoline);
+        trampoline
+    }
+}
@@ -0,0 +1,217 @@
+//! Variable length arrays.
+//!
+//! Cranelift has no way to allocate a stack slot whose size is only known at runtime,
+//! so each VLA gets a fixed-size stack buffer, and storage that does not fit in the buffer
+//! is allocated with `malloc` instead.
+//! The address of the storage is kept in a stack slot (`Id::Vla`),
+//! and the address of any heap storage is kept in a second slot,
+//! which is null until the declaration is reached.
+//!
+//! If a declaration is reached more than once (e.g. in a loop),
+//! the previous heap storage is freed before allocating more.
+//! All heap storage is freed when the function returns.
+use cranelift::codegen::cursor::{Cursor, FuncCursor};
+use cranelift::codegen::ir::{types, AbiParam, FuncRef, Signature};
+use cranelift::prelude::{
+    FunctionBuilder, InstBuilder, IntCC, StackSlotData, StackSlotKind, Value as IrValue,
+};
+use cranelift_module::{Linkage, Module, ModuleError};
+
+use super::{Compiler, Id};
+use saltwater_parser::data::{hir::Symbol, types::ArrayType, *};
+
+/// The size of the stack buffer for each VLA.
+const VLA_STACK_SIZE: u32 = 1024;
+
+impl<M: Module> Compiler<M> {
+    /// Allocate storage for a variable length array.
+    pub(super) fn declare_vla(
+        &mut self,
+        symbol: Symbol,
+        location: Location,
+        builder: &mut FunctionBuilder,
+    ) -> CompileResult<()> {
+        let ptr_type = Type::ptr_type(&self.target);
+        let (storage_slot, heap_slot) = (
+            builder.create_sized_stack_slot(StackSlotData {
+                kind: StackSlotKind::ExplicitSlot,
+                size: ptr_type.bytes(),
+            }),
+            builder.create_sized_stack_slot(StackSlotData {
+                kind: StackSlotKind::ExplicitSlot,
+                size: ptr_type.bytes(),
+            }),
+        );
+        let align = symbol
+            .get()
+            .alignof(&self.target)
+            .expect("VLAs should have a known alignment");
+        // Cranelift only aligns stack slots to the size of a pointer
+        let over_aligned = align > u64::from(ptr_type.bytes());
+        let buffer = builder.create_sized_stack_slot(StackSlotData {
+            kind: StackSlotKind::ExplicitSlot,
+            size: if over_aligned {
+                VLA_STACK_SIZE + align as u32 - 1
+            } else {
+                VLA_STACK_SIZE
+            },
+        });
+        // The heap slot has to be null even if the declaration is never reached,
+        // so that it can be freed unconditionally when the function returns.
+        let entry = builder
+            .func
+            .layout
+            .entry_block()
+            .expect("declarations should only be compiled inside a function");
+        let mut cursor = FuncCursor::new(builder.func).at_first_insertion_point(entry);
+        let null = cursor.ins().iconst(ptr_type, 0);
+        cursor.ins().stack_store(null, heap_slot, 0);
+
+        let free = self.vla_libc_func("free", &[ptr_type], None, location, builder)?;
+        let malloc =
+            self.vla_libc_func("malloc", &[ptr_type], Some(ptr_type), location, builder)?;
+        let size = self.sizeof_runtime(&symbol.get().ctype, location, builder)?;
+        let old = builder.ins().stack_load(ptr_type, heap_slot, 0);
+        builder.ins().call(free, &[old]);
+
+        let (on_stack, on_heap, done) = (
+            builder.create_block(),
+            builder.create_block(),
+            builder.create_block(),
+        );
+        let storage = builder.append_block_param(done, ptr_type);
+        let heap = builder.append_block_param(done, ptr_type);
+        let fits = builder.ins().icmp_imm(
+            IntCC::UnsignedLessThanOrEqual,
+            size,
+            i64::from(VLA_STACK_SIZE),
+        );
+        builder.ins().brif(fits, on_stack, &[], on_heap, &[]);
+
+        // the array fits in the stack buffer
+        builder.switch_to_block(on_stack);
+        let mut addr = builder.ins().stack_addr(ptr_type, buffer, 0);
+        if over_aligned {
+            let align = align as i64;
+            addr = builder.ins().iadd_imm(addr, align - 1);
+            addr = builder.ins().band_imm(addr, -align);
+        }
+        let null = builder.ins().iconst(ptr_type, 0);
+        builder.ins().jump(done, &[addr, null]);
+
+        // the array is too large for the stack buffer
+        builder.switch_to_block(on_heap);
+        let call = builder.ins().call(malloc, &[size]);
+        let addr = builder.inst_results(call)[0];
+        builder.ins().jump(done, &[addr, addr]);
+
+        builder.switch_to_block(done);
+        builder.ins().stack_store(storage, storage_slot, 0);
+        builder.ins().stack_store(heap, heap_slot, 0);
+
+        self.vlas.push(heap_slot);
+        self.declare_local(symbol, Id::Vla(storage_slot), location);
+        Ok(())
+    }
+
+    /// Free the heap storage of every VLA in the current function.
+    ///
+    /// This must be called before every return.
+    pub(super) fn free_vlas(&mut self, builder: &mut FunctionBuilder) {
+        if self.vlas.is_empty() {
+            return;
+        }
+        let ptr_type = Type::ptr_type(&self.target);
+        // this was already declared with the same signature by `declare_vla`
+        let free = self.libc_func("free", &[ptr_type], None, builder);
+        for &slot in &self.vlas {
+            let storage = builder.ins().stack_load(ptr_type, slot, 0);
+            builder.ins().call(free, &[storage]);
+        }
+    }
+
+    /// Import `malloc` or `free`, reporting an error if the program defines
+    /// a function with the same name and a different signature.
+    fn vla_libc_func(
+        &mut self,
+        name: &str,
+        params: &[types::Type],
+        ret: Option<types::Type>,
+        location: Location,
+        builder: &mut FunctionBuilder,
+    ) -> CompileResult<FuncRef> {
+        let signature = self.libc_signature(params, ret);
+        match self
+            .module
+            .declare_function(name, Linkage::Import, &signature)
+        {
+            Ok(func_id) => Ok(self.module.declare_func_in_func(func_id, builder.func)),
+            Err(ModuleError::IncompatibleSignature(..)) => semantic_err!(
+                format!(
+                    "variable length arrays need the standard library function '{}', \
+                     but it was redefined with a different signature",
+                    name
+                ),
+                location,
+            ),
+            Err(err) => semantic_err!(err.to_string(), location),
+        }
+    }
+
+    /// Calculate `sizeof(ctype)` for a type whose size may only be known at runtime.
+    pub(super) fn sizeof_runtime(
+        &mut self,
+        ctype: &Type,
+        location: Location,
+        builder: &mut FunctionBuilder,
+    ) -> CompileResult<IrValue> {
+        let size_t = self.target.size_t().as_ir_type(&self.target);
+        let size = match ctype {
+            Type::Array(of, ArrayType::Variable(length)) => {
+                let length = match self.declarations.get(length) {
+                    Some(Id::Local(slot)) => builder.ins().stack_load(size_t, *slot, 0),
+                    Some(Id::Variable(var)) => builder.use_var(*var),
+                    _ => {
+                        unreachable!("bug in parser: used the length of a VLA before declaring it")
+                    }
+                };
+                let of = self.sizeof_runtime(of, location, builder)?;
+                builder.ins().imul(length, of)
+            }
+            Type::Array(of, ArrayType::Fixed(length)) if of.is_vla() => {
+                let of = self.sizeof_runtime(of, location, builder)?;
+                builder.ins().imul_imm(of, *length as i64)
+            }
+            _ => match ctype.sizeof(&self.target) {
+                Ok(size) => builder.ins().iconst(size_t, size as i64),
+                Err(err) => semantic_err!(err.into(), location),
+            },
+        };
+        Ok(size)
+    }
+
+    /// Import a function from the C standard library.
+    pub(super) fn libc_func(
+        &mut self,
+        name: &str,
+        params: &[types::Type],
+        ret: Option<types::Type>,
+        builder: &mut FunctionBuilder,
+    ) -> FuncRef {
+        let signature = self.libc_signature(params, ret);
+        let func_id = self
+            .module
+            .declare_function(name, Linkage::Import, &signature)
+            .unwrap_or_else(|err| panic!("{}", err));
+        self.module.declare_func_in_func(func_id, builder.func)
+    }
+
+    fn libc_signature(&self, params: &[types::Type], ret: Option<types::Type>) -> Signature {
+        let mut signature = Signature::new(self.module.isa().default_call_conv());
+        signature
+            .params
+            .extend(params.iter().map(|&param| AbiParam::new(param)));
+        signature.returns.extend(ret.map(AbiParam::new));
+        signature
+    }
+}
@@ -1,6 +1,6 @@
 use super::PureAnalyzer;
-use crate::arch;
-use crate::data::{hir::*, lex::ComparisonToken, *};
+use crate::arch::{self, TargetInfo};
+use crate::data::{error::Warning, hir::*, lex::ComparisonToken, *};
 use crate::intern::InternedStr;
 
 impl PureAnalyzer {
@@ -16,9 +16,19 @@ impl PureAnalyzer {
             Id(id) => self.parse_id(id, expr.location),
             // (int)x
             Cast(ctype, inner) => {
-                let ctype = self.parse_typename(ctype, expr.location);
+                let (ctype, qualifiers) = self.parse_qualified_typename(ctype, expr.location);
+                // (const int)x
+                if qualifiers != Qualifiers::default() {
+                    self.warn(Warning::IgnoredQualifier(qualifiers), expr.location);
+                }
                 self.explicit_cast(*inner, ctype)
             }
+            // (struct point){ 1, 2 }
+            CompoundLiteral(ctype, init) => self.compound_literal(ctype, *init, expr.location),
+            // _Generic(x, int: 1, default: 2)
+            Generic(controlling, associations) => {
+                self.generic_selection(*controlling, associations, expr.location)
+            }
             Shift(left, right, direction) => {
                 let op = if direction {
                     BinaryOp::Shl
@@ -116,10 +126,14 @@ impl PureAnalyzer {
                     // > The operand of the unary & operator shall be either a function designator,
                     // > the result of a [] or unary * operator,
                     // > or an lvalue that designates an object that is not a bit-field and is not declared with the register storage-class specifier.
+                    _ if inner.is_bitfield() => {
+                        self.err(SemanticError::InvalidAddressOf("bit-field"), expr.location);
+                        inner
+                    }
                     _ if inner.lval => Expr {
                         lval: false,
                         location: expr.location,
-                        ctype: Type::Pointer(Box::new(inner.ctype.clone()), Qualifiers::default()),
+                        ctype: Type::Pointer(Box::new(inner.ctype.clone()), inner.qualifiers()),
                         expr: inner.expr,
                     },
                     _ => {
@@ -144,6 +158,12 @@ impl PureAnalyzer {
             }
             AlignofExpr(inner) => {
                 let inner = self.expr(*inner);
+                // _Alignas(16) char c; _Alignof(c)
+                if let ExprType::Id(symbol) = &inner.expr {
+                    if let Some(alignment) = symbol.get().alignment {
+                        return literal(LiteralValue::UnsignedInt(alignment), expr.location);
+                    }
+                }
                 self.align(inner.ctype, expr.location)
             }
             SizeofType(type_name) => {
@@ -152,6 +172,13 @@ impl PureAnalyzer {
             }
             SizeofExpr(inner) => {
                 let inner = self.expr(*inner);
+                // 6.5.3.4p1: The sizeof operator shall not be applied to ... an expression that designates a bit-field member.
+                if inner.is_bitfield() {
+                    self.err(
+                        SemanticError::from("cannot take `sizeof` a bit-field"),
+                        expr.location,
+                    );
+                }
                 self.sizeof(inner.ctype, expr.location)
             }
             BitwiseNot(inner) => self.bitwise_not(*inner),
@@ -185,8 +212,76 @@ impl PureAnalyzer {
             Ternary(condition, then, otherwise) => {
                 self.ternary(*condition, *then, *otherwise, expr.location)
             }
+            // va_start(ap, last_param)
+            // the backend knows where the variadic arguments start,
+            // so the last named parameter is only checked for validity
+            VaStart(ap, param) => {
+                let ap = self.va_list(*ap);
+                self.expr(*param);
+                Expr {
+                    ctype: Type::Void,
+                    lval: false,
+                    expr: ExprType::VaStart(Box::new(ap)),
+                    location: expr.location,
+                }
+            }
+            VaArg(ap, type_name) => {
+                let ap = self.va_list(*ap);
+                let ctype = self.parse_typename(type_name, expr.location);
+                // `float` is promoted to `double` when passed through `...`,
+                // so it will never be in the argument list
+                if !(ctype.is_integral()
+                    || ctype.is_pointer()
+                    || ctype == Type::Double
+                    || ctype == Type::LongDouble)
+                {
+                    if ctype != Type::Error {
+                        self.err(SemanticError::InvalidVaArg(ctype), expr.location);
+                    }
+                    return Expr {
+                        ctype: Type::Error,
+                        ..ap
+                    };
+                }
+                Expr {
+                    ctype,
+                    lval: false,
+                    expr: ExprType::VaArg(Box::new(ap)),
+                    location: expr.location,
+                }
+            }
+            VaEnd(ap) => {
+                let ap = self.va_list(*ap);
+                Expr {
+                    ctype: Type::Void,
+                    lval: false,
+                    expr: ExprType::VaEnd(Box::new(ap)),
+                    location: expr.location,
+                }
+            }
+            VaCopy(dest, src) => {
+                let (dest, src) = (self.va_list(*dest), self.va_list(*src));
+                Expr {
+                    ctype: Type::Void,
+                    lval: false,
+                    expr: ExprType::VaCopy(Box::new(dest), Box::new(src)),
+                    location: expr.location,
+                }
+            }
         }
     }
+    // the `ap` in `va_arg(ap, int)`
+    //
+    // `va_list` is an array, so this will always be a pointer to the underlying `Type::VaList`
+    fn va_list(&mut self, ap: ast::Expr) -> Expr {
+        let ap = self.expr(ap).rval();
+        match &ap.ctype {
+            Type::Pointer(inner, _) if **inner == Type::VaList => {}
+            Type::Error => {}
+            other => self.err(SemanticError::NotAVaList(other.clone()), ap.location),
+        }
+        ap
+    }
     // only meant for use with `expr`
     // TODO: change ast::Expr to use `ExprType::Binary` as well, which would make this unnecessary
     // TODO: these functions should have the locations of the parent expression, not the children
@@ -221,7 +316,8 @@ impl PureAnalyzer {
                 self.err(SemanticError::NonIntegralExpr(ctype.clone()), location);
             }
         }
-        let (promoted_expr, next) = Expr::binary_promote(left, right, &mut self.error_handler);
+        let (promoted_expr, next) =
+            Expr::binary_promote(left, right, &self.target, &mut self.error_handler);
         Expr {
             ctype: next.ctype.clone(),
             expr: ExprType::Binary(op, Box::new(promoted_expr), Box::new(next)),
@@ -284,7 +380,7 @@ impl PureAnalyzer {
 
         // i == i
         if left.ctype.is_arithmetic() && right.ctype.is_arithmetic() {
-            let tmp = Expr::binary_promote(left, right, &mut self.error_handler);
+            let tmp = Expr::binary_promote(left, right, &self.target, &mut self.error_handler);
             left = tmp.0;
             right = tmp.1;
         } else {
@@ -354,7 +450,8 @@ impl PureAnalyzer {
                 location,
             );
         }
-        let (left, right) = Expr::binary_promote(left, right, &mut self.error_handler);
+        let (left, right) =
+            Expr::binary_promote(left, right, &self.target, &mut self.error_handler);
         Expr {
             ctype: left.ctype.clone(),
             location,
@@ -374,7 +471,7 @@ impl PureAnalyzer {
             | (Type::Array(to, _), i) if i.is_integral() && to.is_complete() => {
                 let to = to.clone();
                 let (left, right) = (left.rval(), right.rval());
-                return self.pointer_arithmetic(left, right, &*to, location);
+                return self.pointer_arithmetic(left, right, &*to, op, location);
             }
             // `i + p`
             (i, Type::Pointer(to, _))
@@ -382,13 +479,13 @@ impl PureAnalyzer {
             | (i, Type::Array(to, _)) if i.is_integral() && is_add && to.is_complete() => {
                 let to = to.clone();
                 let (left, right) = (left.rval(), right.rval());
-                return self.pointer_arithmetic(right, left, &*to, location);
+                return self.pointer_arithmetic(right, left, &*to, op, location);
             }
             _ => {}
         };
         // `i + i`
         let (ctype, lval) = if left.ctype.is_arithmetic() && right.ctype.is_arithmetic() {
-            let tmp = Expr::binary_promote(left, right, &mut self.error_handler);
+            let tmp = Expr::binary_promote(left, right, &self.target, &mut self.error_handler);
             left = tmp.0;
             right = tmp.1;
             (left.ctype.clone(), false)
@@ -415,6 +512,143 @@ impl PureAnalyzer {
             expr: ExprType::Binary(op, Box::new(left), Box::new(right)),
         }
     }
+    // (struct point){ 1, 2 }
+    // 6.5.2.5 Compound literals
+    //
+    // A compound literal is an unnamed object.
+    // At file scope it has static storage duration and is declared like any other variable.
+    // At block scope it is automatic and must be initialized each time the literal is evaluated,
+    // so only the storage is declared ahead of time.
+    // _Generic(x, int: 1, default: 2)
+    //
+    // 6.5.1.1 Generic selection
+    fn generic_selection(
+        &mut self,
+        controlling: ast::Expr,
+        associations: Vec<(Option<ast::TypeName>, ast::Expr)>,
+        location: Location,
+    ) -> Expr {
+        // The controlling expression is never evaluated;
+        // only its type after lvalue conversion matters.
+        // Lvalue conversion also removes the top-level qualifiers: `const int i; _Generic(i, int: 1)` is 1.
+        // The qualifiers of a pointer's target are kept: `const char *p; _Generic(p, char *: 1)` has no match.
+        let ctype = self.expr(controlling).rval().ctype;
+        let mut seen = Vec::new();
+        let mut default = None;
+        let mut selected = None;
+        for (type_name, expr) in associations {
+            let expr = self.expr(expr);
+            let assoc_type = match type_name {
+                Some(type_name) => {
+                    let (ctype, qualifiers) =
+                        self.parse_qualified_typename(type_name, expr.location);
+                    (ctype, qualifiers.without_func())
+                }
+                // _Generic(x, default: 1, default: 2)
+                None if default.is_some() => {
+                    self.err(SemanticError::DuplicateGenericDefault, expr.location);
+                    continue;
+                }
+                None => {
+                    default = Some(expr);
+                    continue;
+                }
+            };
+            // _Generic(x, int: 1, int: 2)
+            if seen.contains(&assoc_type) {
+                self.err(
+                    SemanticError::DuplicateGenericType(assoc_type.0),
+                    expr.location,
+                );
+                continue;
+            }
+            // _Generic(x, const int: 1) never matches, since `x` has no qualifiers after conversion
+            if assoc_type.1 == Qualifiers::default() && assoc_type.0 == ctype {
+                selected = Some(expr);
+            }
+            seen.push(assoc_type);
+        }
+        match selected.or(default) {
+            Some(expr) => expr,
+            None => {
+                if ctype != Type::Error {
+                    self.err(SemanticError::NoGenericMatch(ctype), location);
+                }
+                let mut pretend_zero = Expr::zero(location);
+                pretend_zero.ctype = Type::Error;
+                pretend_zero
+            }
+        }
+    }
+    fn compound_literal(
+        &mut self,
+        type_name: ast::TypeName,
+        init: ast::Initializer,
+        location: Location,
+    ) -> Expr {
+        let parsed = self.parse_type(type_name.specifiers, type_name.declarator.decl, location);
+        if let Some(sc) = parsed.storage_class {
+            self.err(SemanticError::IllegalStorageClass(sc), location);
+        }
+        if let Some(id) = type_name.declarator.id {
+            self.err(SemanticError::IdInTypeName(id), location);
+        }
+        if parsed.alignment.is_some() {
+            self.err(SemanticError::IllegalAlignas("compound literals"), location);
+        }
+        let mut ctype = parsed.ctype;
+        // 6.5.2.5p1: The type name shall specify a complete object type or an array of unknown size,
+        // but not a variable length array type.
+        if ctype.is_function() {
+            self.err(
+                SemanticError::InvalidCompoundLiteral("function type"),
+                location,
+            );
+            ctype = Type::Error;
+        } else if ctype.is_vla() {
+            self.err(
+                SemanticError::InvalidCompoundLiteral("variable length array type"),
+                location,
+            );
+            ctype = Type::Error;
+        }
+        let init = self.parse_initializer(init, &ctype, location);
+        super::init::complete_array_type(&mut ctype, &init);
+
+        let is_global = self.scope.is_global();
+        let id = format!("<compound literal {}>", self.compound_literals);
+        self.compound_literals += 1;
+        let symbol = Variable {
+            id: id.into(),
+            ctype: ctype.clone(),
+            qualifiers: parsed.qualifiers,
+            storage_class: if is_global {
+                StorageClass::Static
+            } else {
+                StorageClass::Auto
+            },
+            bitfield: None,
+            alignment: None,
+            packed: None,
+        }
+        .insert();
+        let expr = if is_global {
+            let decl = Declaration {
+                symbol,
+                init: Some(init),
+            };
+            self.decl_side_channel.push(Locatable::new(decl, location));
+            ExprType::Id(symbol)
+        } else {
+            ExprType::CompoundLiteral(symbol, Box::new(init))
+        };
+        Expr {
+            expr,
+            ctype,
+            lval: true,
+            location,
+        }
+    }
     // (int)i
     // 6.5.4 Cast operators
     fn explicit_cast(&mut self, expr: ast::Expr, ctype: Type) -> Expr {
@@ -455,13 +689,14 @@ impl PureAnalyzer {
             location,
         }
     }
-    // `base + index`, where `pointee` is the type of `*base`
+    // `base + index` or `base - index`, where `pointee` is the type of `*base`
     // 6.5.6 Additive operators
     fn pointer_arithmetic(
         &mut self,
         base: Expr,
         index: Expr,
         pointee: &Type,
+        op: BinaryOp,
         location: Location,
     ) -> Expr {
         // the idea is to desugar to `base + sizeof(base)*index`
@@ -472,17 +707,22 @@ impl PureAnalyzer {
             ctype: base.ctype.clone(),
         }
         .rval();
-        let size = match pointee.sizeof() {
-            Ok(s) => s,
-            Err(_) => {
-                self.err(
-                    SemanticError::PointerAddUnknownSize(base.ctype.clone()),
-                    location,
-                );
-                1
-            }
+        let size_literal = if pointee.is_vla() {
+            // `double (*p)[n]; p + 1`: the stride is only known at runtime
+            self.sizeof(pointee.clone(), offset.location)
+        } else {
+            let size = match pointee.sizeof(&self.target) {
+                Ok(s) => s,
+                Err(_) => {
+                    self.err(
+                        SemanticError::PointerAddUnknownSize(base.ctype.clone()),
+                        location,
+                    );
+                    1
+                }
+            };
+            literal(LiteralValue::UnsignedInt(size), offset.location)
         };
-        let size_literal = literal(LiteralValue::UnsignedInt(size), offset.location);
         let size_cast = Expr {
             lval: false,
             location: offset.location,
@@ -499,7 +739,7 @@ impl PureAnalyzer {
             lval: false,
             location,
             ctype: base.ctype.clone(),
-            expr: ExprType::Binary(BinaryOp::Add, Box::new(base), Box::new(offset)),
+            expr: ExprType::Binary(op, Box::new(base), Box::new(offset)),
         }
     }
     // `func(args)`
@@ -564,7 +804,7 @@ impl PureAnalyzer {
     /// 'default promotions' from 6.5.2.2p6
     fn default_promote(&mut self, expr: Expr) -> Expr {
         let expr = expr.rval();
-        let ctype = expr.ctype.clone().default_promote();
+        let ctype = expr.ctype.clone().default_promote(&self.target);
         expr.implicit_cast(&ctype, &mut self.error_handler)
     }
     // parse a struct member
@@ -629,11 +869,17 @@ impl PureAnalyzer {
         }
         // ++i is syntactic sugar for i+=1
         if prefix {
-            let rval = Expr {
-                lval: false,
-                ctype: expr.ctype.clone(),
-                location,
-                expr: ExprType::Cast(Box::new(literal(LiteralValue::Int(1), location))),
+            let one = literal(LiteralValue::Int(1), location);
+            // `p += 1` is pointer arithmetic, so `1` has to stay an integer
+            let rval = if expr.ctype.is_pointer() {
+                one
+            } else {
+                Expr {
+                    lval: false,
+                    ctype: expr.ctype.clone(),
+                    location,
+                    expr: ExprType::Cast(Box::new(one)),
+                }
             };
             let op = if increment {
                 AssignmentToken::AddEqual
@@ -646,6 +892,14 @@ impl PureAnalyzer {
         // evaluate the rvalue of `i` and as a side effect, increment the value at the stored address
         // ex: `int i = 0, j; j = i++;` leaves a value of 0 in j and a value of 1 in i
         } else {
+            if let Type::Pointer(pointee, _) = &expr.ctype {
+                if !pointee.is_vla() && pointee.sizeof(&self.target).is_err() {
+                    self.err(
+                        SemanticError::PointerAddUnknownSize(expr.ctype.clone()),
+                        location,
+                    );
+                }
+            }
             Expr {
                 lval: false,
                 ctype: expr.ctype.clone(),
@@ -674,30 +928,47 @@ impl PureAnalyzer {
                 return left;
             }
         };
-        let mut addr = self.pointer_arithmetic(array, index, &target_type, location);
-        addr.ctype = target_type;
+        let addr = self.pointer_arithmetic(array, index, &target_type, BinaryOp::Add, location);
         // `p + i` -> `*(p + i)`
-        addr.lval = true;
-        addr
+        // NOTE: the addition itself keeps the pointer type,
+        // otherwise `double *p; p[i]` would use floating point addition
+        addr.indirection(true, target_type)
     }
     // _Alignof(int)
     fn align(&mut self, ctype: Type, location: Location) -> Expr {
-        let align = ctype.alignof().unwrap_or_else(|err| {
+        let align = ctype.alignof(&self.target).unwrap_or_else(|err| {
             self.err(err.into(), location);
             1
         });
-        literal(LiteralValue::UnsignedInt(align), location)
+        self.size_t_literal(align, location)
     }
     // sizeof(int)
     // 6.5.3.4 The sizeof and _Alignof operators
     fn sizeof(&mut self, ctype: Type, location: Location) -> Expr {
-        let align = ctype.sizeof().unwrap_or_else(|err| {
+        // 6.5.3.4p2: If the type of the operand is a variable length array type,
+        // the operand is evaluated; otherwise ... the result is an integer constant.
+        if ctype.is_vla() {
+            return Expr {
+                lval: false,
+                location,
+                ctype: self.target.size_t(),
+                expr: ExprType::Sizeof(ctype),
+            };
+        }
+        let align = ctype.sizeof(&self.target).unwrap_or_else(|err| {
             if ctype != Type::Error {
                 self.err(err.into(), location);
             }
             1
         });
-        literal(LiteralValue::UnsignedInt(align), location)
+        self.size_t_literal(align, location)
+    }
+    /// The result of `sizeof` or `_Alignof`, which has type `size_t`.
+    fn size_t_literal(&self, value: arch::SIZE_T, location: Location) -> Expr {
+        Expr {
+            ctype: self.target.size_t(),
+            ..literal(LiteralValue::UnsignedInt(value), location)
+        }
     }
     // ~expr
     // 6.5.3.3 Unary arithmetic operators
@@ -713,7 +984,7 @@ impl PureAnalyzer {
             }
             expr
         } else {
-            let expr = expr.integer_promote(&mut self.error_handler);
+            let expr = expr.integer_promote(&self.target, &mut self.error_handler);
             Expr {
                 lval: false,
                 ctype: expr.ctype.clone(),
@@ -733,7 +1004,7 @@ impl PureAnalyzer {
             }
             return expr;
         }
-        let expr = expr.integer_promote(&mut self.error_handler);
+        let expr = expr.integer_promote(&self.target, &mut self.error_handler);
         if add {
             Expr {
                 lval: false,
@@ -796,7 +1067,8 @@ impl PureAnalyzer {
         let mut otherwise = self.expr(otherwise).rval();
 
         if then.ctype.is_arithmetic() && otherwise.ctype.is_arithmetic() {
-            let (tmp1, tmp2) = Expr::binary_promote(then, otherwise, &mut self.error_handler);
+            let (tmp1, tmp2) =
+                Expr::binary_promote(then, otherwise, &self.target, &mut self.error_handler);
             then = tmp1;
             otherwise = tmp2;
         } else if !pointer_promote(&mut then, &mut otherwise)
@@ -842,25 +1114,51 @@ impl PureAnalyzer {
                 expr: ExprType::Binary(BinaryOp::Assign, Box::new(lval), Box::new(rval)),
             };
         }
+        // Evaluating a variable has no side effects, so `i += 1` can be `i = i + 1`.
+        // This also keeps `i` from having its address taken, so the backend can keep it in a register.
+        if let ExprType::Id(_) = lval.expr {
+            if lval.lval {
+                let new_val = self
+                    .desugar_op(lval.clone().rval(), rval.rval(), token)
+                    .implicit_cast(&lval.ctype, &mut self.error_handler);
+                return Expr {
+                    ctype: lval.ctype.clone(),
+                    lval: false,
+                    location,
+                    expr: ExprType::Binary(BinaryOp::Assign, Box::new(lval), Box::new(new_val)),
+                };
+            }
+        }
         // Complex assignment is tricky because the left side needs to be evaluated only once
         // Consider e.g. `*f() += 1`: `f()` should only be called once.
         // The hack implemented here is to treat `*f()` as a variable then load and store it to memory:
         // `tmp = &f(); *tmp = *tmp + 1;`
         // see also footnote 113 which has a similar algorithm (but is more convoluted because of atomics)
 
+        // Bit-fields don't have an address, so use the address of the containing struct instead:
+        // `tmp = &s; tmp->bf = tmp->bf + 1;`
+        let ctype = lval.ctype.clone();
+        let (lval, member) = match lval.expr {
+            ExprType::Member(struct_, id) if lval.is_bitfield() => (*struct_, Some(id)),
+            expr => (Expr { expr, ..lval }, None),
+        };
+        let addressed_type = lval.ctype.clone();
+
         // declare tmp in a new hidden scope
         // We really should only be modifying the scope in `FunctionAnalyzer`,
         // but assignment expressions can never appear in an initializer anyway.
         self.scope.enter();
         let tmp_name = "tmp".into();
-        let ctype = lval.ctype.clone();
         // TODO: we could probably make these qualifiers stronger
-        let ptr_type = Type::Pointer(Box::new(ctype.clone()), Qualifiers::default());
+        let ptr_type = Type::Pointer(Box::new(addressed_type.clone()), Qualifiers::default());
         let meta = Variable {
             id: tmp_name,
             ctype: ptr_type.clone(),
             qualifiers: Qualifiers::NONE,
             storage_class: StorageClass::Register,
+            bitfield: None,
+            alignment: None,
+            packed: None,
         };
         let tmp_var = self.declare(meta, true, location);
 
@@ -885,17 +1183,21 @@ impl PureAnalyzer {
         // instead we want the address of the lval
         .rval();
 
+        // `*tmp` in an lval context
+        let mut target = tmp.indirection(true, addressed_type);
+        // `tmp->bf`
+        if let Some(id) = member {
+            target = Expr {
+                ctype: ctype.clone(),
+                lval: true,
+                location,
+                expr: ExprType::Member(Box::new(target), id),
+            };
+        }
         // before we had `&sum`, now we have `sum`
         // `*tmp`, i.e. `*f()`
-        let lval_as_rval = Expr {
-            ctype: ctype.clone(),
-            lval: false,
-            location,
-            // this clone is pretty cheap since `tmp_assign_expr` is just an id
-            expr: ExprType::Deref(Box::new(tmp.clone())),
-        };
-        // `*tmp` in an lval context
-        let target = tmp.indirection(true, ctype.clone());
+        // this clone is pretty cheap since `target` is just `*tmp` or `tmp->bf`
+        let lval_as_rval = target.clone().rval();
         // `*tmp + 1`
         let new_val = self
             .desugar_op(lval_as_rval, rval.rval(), token)
@@ -937,6 +1239,7 @@ pub(super) fn literal(literal: LiteralValue, location: Location) -> Expr {
         LiteralValue::Int(_) => Type::Long(true),
         LiteralValue::UnsignedInt(_) => Type::Long(false),
         LiteralValue::Float(_) => Type::Double,
+        LiteralValue::LongDouble(_) => Type::LongDouble,
         LiteralValue::Str(s) => {
             let len = s.len() as arch::SIZE_T;
             Type::Array(Box::new(Type::Char(true)), ArrayType::Fixed(len))
@@ -1002,7 +1305,7 @@ impl Type {
     fn sign(&self) -> Result<bool, ()> {
         use Type::*;
         match self {
-            Char(sign) | Short(sign) | Int(sign) | Long(sign) => Ok(*sign),
+            Char(sign) | Short(sign) | Int(sign) | Long(sign) | LongLong(sign) => Ok(*sign),
             Bool => Ok(false),
             // TODO: allow enums with values of UINT_MAX
             Enum(_, _) => Ok(true),
@@ -1017,6 +1320,7 @@ impl Type {
     /// Examples:
     /// ```ignore
     /// use saltwater::data::types::Type::*;
+    /// assert!(LongLong(true).rank() > Long(true).rank());
     /// assert!(Long(true).rank() > Int(true).rank());
     /// assert!(Int(false).rank() > Short(false).rank());
     /// assert!(Short(true).rank() > Char(true).rank());
@@ -1032,14 +1336,14 @@ impl Type {
             Short(_) => 2,
             Int(_) => 3,
             Long(_) => 4,
-            // don't make this 5 in case we add `long long` at some point
+            LongLong(_) => 5,
             _ => usize::MAX,
         }
     }
     // Subclause 2 of 6.3.1.1 Boolean, characters, and integers
-    fn integer_promote(self) -> Type {
+    fn integer_promote(self, target: &TargetInfo) -> Type {
         if self.rank() <= Type::Int(true).rank() {
-            if Type::Int(true).can_represent(&self) {
+            if Type::Int(true).can_represent(&self, target) {
                 Type::Int(true)
             } else {
                 Type::Int(false)
@@ -1049,15 +1353,17 @@ impl Type {
         }
     }
     // 6.3.1.8 Usual arithmetic conversions
-    fn binary_promote(mut left: Type, mut right: Type) -> Result<Type, Type> {
+    fn binary_promote(mut left: Type, mut right: Type, target: &TargetInfo) -> Result<Type, Type> {
         use Type::*;
-        if left == Double || right == Double {
+        if left == LongDouble || right == LongDouble {
+            return Ok(LongDouble);
+        } else if left == Double || right == Double {
             return Ok(Double); // toil and trouble
         } else if left == Float || right == Float {
             return Ok(Float);
         }
-        left = left.integer_promote();
-        right = right.integer_promote();
+        left = left.integer_promote(target);
+        right = right.integer_promote(target);
         // TODO: we know that `left` can't be used after a move,
         // but rustc isn't smart enough to figure it out and let us remove the clone
         let signs = (
@@ -1077,7 +1383,7 @@ impl Type {
         } else {
             (right, left)
         };
-        if signed.can_represent(&unsigned) {
+        if signed.can_represent(&unsigned, target) {
             Ok(signed)
         } else {
             Ok(unsigned)
@@ -1088,9 +1394,9 @@ impl Type {
     /// > the integer promotions are performed on each argument,
     /// > and arguments that have type float are promoted to double.
     /// > These are called the default argument promotions.
-    fn default_promote(self) -> Type {
+    fn default_promote(self, target: &TargetInfo) -> Type {
         if self.is_integral() {
-            self.integer_promote()
+            self.integer_promote(target)
         } else if self == Type::Float {
             Type::Double
         } else {
@@ -1182,18 +1488,23 @@ impl Expr {
     // Perform an integer conversion, including all relevant casts.
     //
     // See `Type::integer_promote` for conversion rules.
-    fn integer_promote(self, error_handler: &mut ErrorHandler) -> Expr {
+    fn integer_promote(self, target: &TargetInfo, error_handler: &mut ErrorHandler) -> Expr {
         let expr = self.rval();
-        let ctype = expr.ctype.clone().integer_promote();
+        let ctype = expr.ctype.clone().integer_promote(target);
         expr.implicit_cast(&ctype, error_handler)
     }
 
     // Perform a binary conversion, including all relevant casts.
     //
     // See `Type::binary_promote` for conversion rules.
-    fn binary_promote(left: Expr, right: Expr, error_handler: &mut ErrorHandler) -> (Expr, Expr) {
+    fn binary_promote(
+        left: Expr,
+        right: Expr,
+        target: &TargetInfo,
+        error_handler: &mut ErrorHandler,
+    ) -> (Expr, Expr) {
         let (left, right) = (left.rval(), right.rval());
-        let ctype = Type::binary_promote(left.ctype.clone(), right.ctype.clone());
+        let ctype = Type::binary_promote(left.ctype.clone(), right.ctype.clone(), target);
         match ctype {
             Ok(promoted) => (
                 left.implicit_cast(&promoted, error_handler),
@@ -1218,20 +1529,15 @@ impl Expr {
     pub(super) fn rval(self) -> Expr {
         match self.ctype {
             // a + 1 is the same as &a + 1
-            Type::Array(to, _) => Expr {
+            // const int a[3]; a + 1 has type `const int *`
+            Type::Array(ref to, _) => Expr {
                 lval: false,
-                ctype: Type::Pointer(to, Qualifiers::default()),
+                ctype: Type::Pointer(to.clone(), self.qualifiers()),
                 ..self
             },
             Type::Function(_) => Expr {
                 lval: false,
-                ctype: Type::Pointer(
-                    Box::new(self.ctype),
-                    Qualifiers {
-                        c_const: true,
-                        ..Qualifiers::default()
-                    },
-                ),
+                ctype: Type::Pointer(Box::new(self.ctype), Qualifiers::default()),
                 ..self
             },
             // HACK: structs can't be dereferenced since they're not scalar, so we just fake it
@@ -1275,6 +1581,17 @@ impl Expr {
         let mut expr = self.rval();
         if &expr.ctype == ctype {
             expr
+        // allow implicit casts of const pointers
+        // Standard (in the context `left = right`, i.e. casting `right` to `left`)
+        // > the left operand has atomic, qualified, or unqualified pointer type,
+        // > and (considering the type the left operand would have after lvalue conversion)
+        // > both operands are pointers to qualified or unqualified versions of compatible types,
+        // > and the type pointed to by the left has all the qualifiers of the type pointed to by the right;
+        } else if matches!((&expr.ctype, ctype), (Type::Pointer(a, from), Type::Pointer(b, to))
+                if a == b && to.contains_all(*from))
+        {
+            expr.ctype = ctype.clone();
+            expr
         // int -> long
         } else if expr.ctype.is_arithmetic() && ctype.is_arithmetic()
             // NULL -> int*
@@ -1301,18 +1618,6 @@ impl Expr {
         } else if expr.ctype == Type::Error {
             expr
         } else {
-            // allow implicit casts of const pointers
-            // Standard (in the context `left = right`, i.e. casting `right` to `left`)
-            // > the left operand has atomic, qualified, or unqualified pointer type,
-            // > and (considering the type the left operand would have after lvalue conversion)
-            // > both operands are pointers to qualified or unqualified versions of compatible types,
-            // > and the type pointed to by the left has all the qualifiers of the type pointed to by the right;
-            if let (Type::Pointer(a, from), Type::Pointer(b, to)) = (&expr.ctype, ctype) {
-                if *a == *b && from.contains_all(*to) {
-                    expr.ctype = ctype.clone();
-                    return expr;
-                }
-            }
             // There is probably a better way to do this
             // don't report cascading errors
             if *ctype != Type::Error {
@@ -1324,6 +1629,49 @@ impl Expr {
             expr
         }
     }
+    /// The qualifiers of the object this lvalue designates.
+    ///
+    /// `const int *p; *p` is `const`, as is `s.a` if `s` or its member `a` is `const`.
+    fn qualifiers(&self) -> Qualifiers {
+        match &self.expr {
+            ExprType::Id(sym) => sym.get().qualifiers.without_func(),
+            // *p
+            ExprType::Noop(pointer) => match &pointer.ctype {
+                Type::Pointer(_, qualifiers) => *qualifiers,
+                _ => Qualifiers::default(),
+            },
+            ExprType::Member(struct_, id) => {
+                let outer = struct_.qualifiers();
+                let member = match &struct_.ctype {
+                    Type::Struct(stype) | Type::Union(stype) => stype
+                        .members()
+                        .iter()
+                        .find(|member| member.id == *id)
+                        .map_or_else(Qualifiers::default, |member| member.qualifiers),
+                    _ => Qualifiers::default(),
+                };
+                Qualifiers {
+                    c_const: outer.c_const || member.c_const,
+                    volatile: outer.volatile || member.volatile,
+                    ..Qualifiers::default()
+                }
+            }
+            _ => Qualifiers::default(),
+        }
+    }
+    /// Return whether this is a struct or union member declared as a bit-field.
+    fn is_bitfield(&self) -> bool {
+        match &self.expr {
+            ExprType::Member(struct_, id) => match &struct_.ctype {
+                Type::Struct(stype) | Type::Union(stype) => stype
+                    .members()
+                    .iter()
+                    .any(|member| member.id == *id && member.bitfield.is_some()),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
     /// See section 6.3.2.1 of the C Standard. In particular:
     /// "A modifiable lvalue is an lvalue that does not have array type,
     /// does not  have an incomplete type, does not have a const-qualified type,
@@ -1339,12 +1687,13 @@ impl Expr {
             return err(format!("expression with incomplete type '{}'", self.ctype));
         }
         // const-qualified type
-        // TODO: handle `*const`
         if let ExprType::Id(sym) = &self.expr {
             let meta = sym.get();
             if meta.qualifiers.c_const {
                 return err(format!("variable '{}' with `const` qualifier", meta.id));
             }
+        } else if self.qualifiers().c_const {
+            return err("lvalue with `const` qualifier".to_string());
         }
         match &self.ctype {
             // array type
@@ -1432,6 +1781,9 @@ mod test {
             id: InternedStr::get_or_intern("x"),
             qualifiers: Default::default(),
             storage_class: Default::default(),
+            bitfield: None,
+            alignment: None,
+            packed: None,
         }
         .insert();
         let parsed = expr_with_scope("x", &[x]);
@@ -1450,6 +1802,13 @@ mod test {
         assert_type("1*1.0", Type::Double);
         assert_type("1*2.0 / 1.3", Type::Double);
         assert_type("3%2", Type::Long(true));
+        assert_type("(long long)1 * 2", Type::LongLong(true));
+        assert_type(
+            "(unsigned long long)1 * (long long)2",
+            Type::LongLong(false),
+        );
+        assert_type("(long double)1 * 2.0", Type::LongDouble);
+        assert_type("(long long)1 * (float)2", Type::Float);
     }
     #[test]
     fn test_funcall() {
@@ -1463,11 +1822,17 @@ mod test {
                     id: Default::default(),
                     qualifiers: Default::default(),
                     storage_class: StorageClass::Auto,
+                    bitfield: None,
+                    alignment: None,
+                    packed: None,
                 }
                 .insert()],
                 return_type: Box::new(Type::Int(true)),
                 varargs: false,
             }),
+            bitfield: None,
+            alignment: None,
+            packed: None,
         }
         .insert();
         assert!(expr_with_scope("f(1,2,3)", &[f]).is_err());
@@ -1492,6 +1857,8 @@ mod test {
         assert_type("(unsigned int)4.2", Type::Int(false));
         assert_type("(float)4.2", Type::Float);
         assert_type("(double)4.2", Type::Double);
+        assert_type("(long double)4.2", Type::LongDouble);
+        assert_type("(long long)4.2", Type::LongLong(true));
         assert!(expr("(int*)4.2").is_err());
         assert_type(
             "(int*)(int)4.2",
@@ -12,20 +12,24 @@ impl PureAnalyzer {
     ) -> Initializer {
         use ast::Initializer::{Aggregate, Scalar};
         // initializer_list
-        let mut expr = match init {
-            Aggregate(list) => return self.check_aggregate_overflow(list, ctype, location),
+        let expr = match init {
+            // char s[] = { "hello" };
+            Aggregate(mut list) if ctype.is_char_array() && is_braced_string(&list) => {
+                return self.parse_initializer(list.remove(0).1, ctype, location);
+            }
+            Aggregate(list) => return self.aggregate_initializer(list, ctype, location),
             Scalar(expr) => self.expr(*expr),
         };
+        self.scalar_initializer(expr, ctype)
+    }
+
+    // See section 6.7.9 of the C11 standard:
+    // The initializer for a scalar shall be a single expression, optionally enclosed in braces.
+    // The initial value of the object is that of the expression (after conversion)
+    fn scalar_initializer(&mut self, mut expr: Expr, ctype: &Type) -> Initializer {
         // The only time (that I know of) that an expression will initialize a non-scalar
         // is for character literals.
-        let is_char_array = match ctype {
-            Type::Array(inner, _) => inner.is_char(),
-            _ => false,
-        };
-        // See section 6.7.9 of the C11 standard:
-        // The initializer for a scalar shall be a single expression, optionally enclosed in braces.
-        // The initial value of the object is that of the expression (after conversion)
-        if !is_char_array {
+        if !(ctype.is_char_array() && expr.is_string_literal()) {
             expr = expr
                 .rval()
                 // if ctype is not a scalar, this will report an error, so we don't have to handle it specially
@@ -42,99 +46,276 @@ impl PureAnalyzer {
         Initializer::Scalar(Box::new(expr))
     }
 
-    fn check_aggregate_overflow(
-        &mut self,
-        list: Vec<ast::Initializer>,
-        ctype: &Type,
-        location: Location,
-    ) -> Initializer {
-        let len = list.len();
-        let mut iter = list.into_iter().peekable();
-        let init = self.aggregate_initializer(&mut iter, ctype, location);
-        let leftover = iter.count();
-        if leftover > 0 {
-            self.err(SemanticError::TooManyMembers(len - leftover, len), location);
-        }
-        init
-    }
-
     // handle char[][3] = {{1,2,3}}, but also = {1,2,3} and {{1}, 2, 3}
-    // NOTE: this does NOT consume {} except for sub-elements
     // see p17: "Each brace-enclosed initializer list has an associated current object"
-    // For each subobject of the enclosing object (`type_at`), initialize it, possibly recursively.
+    //
+    // This keeps track of the subobject that will be initialized next (the 'cursor'),
+    // as a list of aggregates and the index of the next subobject in each one.
+    // Designators move the cursor; everything else advances it in order.
     fn aggregate_initializer(
         &mut self,
-        list: &mut std::iter::Peekable<impl Iterator<Item = ast::Initializer>>,
-        elem_type: &Type,
+        list: Vec<(Vec<ast::Designator>, ast::Initializer)>,
+        ctype: &Type,
         location: Location,
     ) -> Initializer {
         use ast::Initializer::{Aggregate, Scalar};
 
-        let mut elems = vec![];
-        if list.peek().is_none() {
+        if list.is_empty() {
             self.err(SemanticError::EmptyInitializer, location);
-            return Initializer::InitializerList(elems);
-        }
-        // char [][3] = {1};
-        while let Some(elem) = list.peek() {
-            let inner = elem_type.type_at(elems.len()).unwrap_or_else(|err| {
-                // int a[1] = {1, 2};
-                self.err(err, location);
-                Type::Error
-            });
-            // int a[][3] = {{1,2,3}}
-            //               ^
-            // initializer is aggregate, type errors will be caught later
-
-            // If the initializer of a subaggregate or contained union begins with a left brace,
-            // the initializers enclosed by that brace and its matching right brace initialize
-            // the elements or members of the subaggregate or the contained union.
-            let next = match elem {
-                Aggregate(_) => match list.next() {
-                    Some(Aggregate(inner_list)) => {
-                        self.check_aggregate_overflow(inner_list, &inner, location)
-                    }
-                    _ => unreachable!(),
-                },
-                Scalar(_) => {
+            return Initializer::InitializerList(Vec::new());
+        }
+        let len = list.len();
+        let mut slots = Vec::new();
+        let mut cursor = vec![(ctype.clone(), 0)];
+        for (i, (designators, init)) in list.into_iter().enumerate() {
+            if !designators.is_empty() {
+                match self.designation(designators, ctype, location) {
+                    Some(designated) => cursor = designated,
+                    None => continue,
+                }
+            // int a[1] = {1, 2};
+            } else if cursor[0].1 >= ctype.type_len() && !ctype.is_unbounded_array() {
+                self.err(SemanticError::TooManyMembers(i, len), location);
+                break;
+            }
+            let init = match init {
+                // int a[][3] = {{1,2,3}}
+                //               ^
+                // If the initializer of a subaggregate or contained union begins with a left brace,
+                // the initializers enclosed by that brace and its matching right brace initialize
+                // the elements or members of the subaggregate or the contained union.
+                Aggregate(_) => {
+                    let inner = current_type(&cursor);
+                    self.parse_initializer(init, &inner, location)
+                }
+                Scalar(expr) => {
+                    let expr = self.expr(*expr);
                     // int a[][3] = {1,2,3}
                     //               ^
-                    // type is aggregate and initializer is scalar
-                    // see if we can short circuit int[][3] -> int[3]
-                    if inner != Type::Error && !inner.is_scalar() {
-                        // Note: this element is _not_ consumed
-                        self.aggregate_initializer(list, &inner, location)
-                    // type is scalar and initializer is scalar
-                    // int a[][3] = {{1,2,3}}
-                    } else {
-                        let expr = match list.next() {
-                            Some(Scalar(expr)) => self
-                                .expr(*expr)
-                                .rval()
-                                .implicit_cast(&inner, &mut self.error_handler),
-                            _ => unreachable!(),
-                        };
-                        Initializer::Scalar(Box::new(expr))
+                    // Otherwise, only enough initializers from the list are taken
+                    // to account for the elements or members of the subaggregate
+                    // or the first member of the contained union;
+                    // any remaining initializers are left to initialize the next
+                    // element or member of the aggregate of which the current
+                    // subaggregate or contained union is a part.
+                    loop {
+                        let inner = current_type(&cursor);
+                        let initializes_whole = inner.is_scalar()
+                            || inner == Type::Error
+                            // struct s a[] = { s1, s2 };
+                            || (matches!(inner, Type::Struct(_) | Type::Union(_)) && inner == expr.ctype)
+                            // char a[][3] = { "hi", "yo" };
+                            || (inner.is_char_array() && expr.is_string_literal());
+                        if initializes_whole {
+                            break;
+                        } else if inner.type_len() == 0 {
+                            // struct s { int len; int flex[]; } = { 1, 2 };
+                            self.err(SemanticError::TooManyMembers(0, 1), expr.location);
+                            break;
+                        }
+                        cursor.push((inner, 0));
                     }
+                    let inner = current_type(&cursor);
+                    self.scalar_initializer(expr, &inner)
                 }
             };
-            elems.push(next);
-
-            // Otherwise, only enough initializers from the list are taken
-            // to account for the elements or members of the subaggregate
-            // or the first member of the contained union;
-            // any remaining initializers are left to initialize the next
-            // element or member of the aggregate of which the current
-            // subaggregate or contained union is a part.
-            if elems.len() == elem_type.type_len() {
-                break;
-            }
+            insert_initializer(&mut slots, &cursor, init);
+            advance(&mut cursor);
         }
-        Initializer::InitializerList(elems)
+        finish_initializer(slots, ctype)
+    }
+
+    /// Move the cursor to the subobject described by `designators` (6.7.9p17-18).
+    ///
+    /// Returns `None` if any of the designators were invalid.
+    fn designation(
+        &mut self,
+        designators: Vec<ast::Designator>,
+        ctype: &Type,
+        location: Location,
+    ) -> Option<Vec<(Type, usize)>> {
+        let mut cursor = Vec::new();
+        let mut current = ctype.clone();
+        for designator in designators {
+            let index = match (designator, &current) {
+                // [3] = 1
+                (ast::Designator::Index(expr), Type::Array(_, size)) => {
+                    let expr = self.expr(expr);
+                    let index = match Self::const_uint(expr, &self.target) {
+                        Ok(index) => index,
+                        Err(err) => {
+                            self.error_handler.push_back(err);
+                            return None;
+                        }
+                    };
+                    if let types::ArrayType::Fixed(size) = size {
+                        if index >= *size {
+                            self.err(SemanticError::DesignatorOutOfBounds(index, *size), location);
+                            return None;
+                        }
+                    }
+                    index as usize
+                }
+                (ast::Designator::Index(_), _) => {
+                    self.err(SemanticError::IndexDesignatorNotArray(current), location);
+                    return None;
+                }
+                // .x = 1
+                (ast::Designator::Member(id), Type::Struct(struct_type))
+                | (ast::Designator::Member(id), Type::Union(struct_type)) => {
+                    let members = struct_type.members();
+                    // unnamed bitfields are skipped during initialization
+                    let named = members.iter().filter(|member| !member.id.is_empty());
+                    match named.enumerate().find(|(_, member)| member.id == id) {
+                        Some((index, _)) => index,
+                        None => {
+                            self.err(SemanticError::NotAMember(id, current), location);
+                            return None;
+                        }
+                    }
+                }
+                (ast::Designator::Member(_), _) => {
+                    self.err(SemanticError::NotAStruct(current), location);
+                    return None;
+                }
+            };
+            let inner = current.type_at(index).unwrap_or(Type::Error);
+            cursor.push((current, index));
+            current = inner;
+        }
+        Some(cursor)
+    }
+}
+
+/// An initializer list whose designators have not yet been resolved,
+/// indexed by the position of each subobject.
+enum Slot {
+    Init(Initializer),
+    List(Vec<Option<Slot>>),
+}
+
+/// The type of the subobject the cursor points to.
+fn current_type(cursor: &[(Type, usize)]) -> Type {
+    let (aggregate, index) = cursor.last().expect("cursor should never be empty");
+    // errors were already reported when the cursor was moved
+    aggregate.type_at(*index).unwrap_or(Type::Error)
+}
+
+/// Move the cursor to the next subobject, leaving any aggregates that are completely initialized.
+///
+/// The outermost aggregate is never left, even if it is complete,
+/// so that excess initializers can be reported.
+fn advance(cursor: &mut Vec<(Type, usize)>) {
+    while let Some((aggregate, index)) = cursor.last_mut() {
+        *index += 1;
+        if *index < aggregate.type_len() || cursor.len() == 1 {
+            break;
+        }
+        cursor.pop();
+    }
+}
+
+/// Store `init` for the subobject the cursor points to,
+/// overriding any previous initializer for the same subobject.
+fn insert_initializer(slots: &mut Vec<Option<Slot>>, cursor: &[(Type, usize)], init: Initializer) {
+    let mut slots = slots;
+    let (last, parents) = cursor.split_last().expect("cursor should never be empty");
+    for (aggregate, index) in parents {
+        let slot = get_slot(slots, aggregate, *index);
+        if !matches!(slot, Some(Slot::List(_))) {
+            *slot = Some(Slot::List(Vec::new()));
+        }
+        slots = match slot {
+            Some(Slot::List(list)) => list,
+            _ => unreachable!(),
+        };
+    }
+    *get_slot(slots, &last.0, last.1) = Some(Slot::Init(init));
+}
+
+fn get_slot<'a>(
+    slots: &'a mut Vec<Option<Slot>>,
+    aggregate: &Type,
+    index: usize,
+) -> &'a mut Option<Slot> {
+    // only one member of a union can be initialized: `union { int i; float f; } u = { .i = 1, .f = 2 };`
+    if let Type::Union(_) = aggregate {
+        slots.clear();
+    }
+    if slots.len() <= index {
+        slots.resize_with(index + 1, || None);
+    }
+    &mut slots[index]
+}
+
+/// Convert the slots for `ctype` into an initializer, leaving out any that were never initialized.
+fn finish_initializer(slots: Vec<Option<Slot>>, ctype: &Type) -> Initializer {
+    let list = slots
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, slot)| {
+            let init = match slot? {
+                Slot::Init(init) => init,
+                Slot::List(slots) => {
+                    finish_initializer(slots, &ctype.type_at(index).unwrap_or(Type::Error))
+                }
+            };
+            Some((ctype.designator_at(index), init))
+        })
+        .collect();
+    Initializer::InitializerList(list)
+}
+
+fn is_braced_string(list: &[(Vec<ast::Designator>, ast::Initializer)]) -> bool {
+    match list {
+        [(designators, ast::Initializer::Scalar(expr))] => {
+            designators.is_empty()
+                && matches!(expr.data, ast::ExprType::Literal(LiteralValue::Str(_)))
+        }
+        _ => false,
+    }
+}
+
+/// 6.7.9p22: If an array of unknown size is initialized,
+/// its size is determined by the largest indexed element with an explicit initializer.
+pub(super) fn complete_array_type(ctype: &mut Type, init: &Initializer) {
+    if let Type::Array(_, size @ types::ArrayType::Unbounded) = ctype {
+        let len = match init {
+            Initializer::InitializerList(list) => list
+                .iter()
+                .map(|(designator, _)| match designator {
+                    Designator::Index(index) => index + 1,
+                    Designator::Member(_) => 1,
+                })
+                .max(),
+            Initializer::Scalar(expr) => match &expr.expr {
+                ExprType::Literal(LiteralValue::Str(s)) => Some(s.len() as u64),
+                _ => None,
+            },
+            Initializer::FunctionBody(_) => None,
+        };
+        if let Some(len) = len {
+            *size = types::ArrayType::Fixed(len);
+        }
+    }
+}
+
+impl Expr {
+    fn is_string_literal(&self) -> bool {
+        matches!(self.expr, ExprType::Literal(LiteralValue::Str(_)))
     }
 }
 
 impl Type {
+    fn is_char_array(&self) -> bool {
+        match self {
+            Type::Array(inner, _) => inner.is_char(),
+            _ => false,
+        }
+    }
+    fn is_unbounded_array(&self) -> bool {
+        matches!(self, Type::Array(_, types::ArrayType::Unbounded))
+    }
     /// Given a type, return the maximum number of initializers for that type
     fn type_len(&self) -> usize {
         use types::ArrayType;
@@ -142,11 +323,33 @@ impl Type {
             ty if ty.is_scalar() => 1,
             Type::Array(_, ArrayType::Fixed(size)) => *size as usize,
             Type::Array(_, ArrayType::Unbounded) => 0,
-            Type::Struct(st) | Type::Union(st) => st.members().len(),
+            // unnamed bitfields are skipped during initialization
+            Type::Struct(st) => st
+                .members()
+                .iter()
+                .filter(|member| !member.id.is_empty())
+                .count(),
+            // only one member of a union can be initialized
+            Type::Union(_) => 1,
             Type::Function { .. } | Type::Error => 1,
             _ => unimplemented!("type checking for {}", self),
         }
     }
+    /// The designator for the subobject at `index`, as used by `type_at`.
+    fn designator_at(&self, index: usize) -> Designator {
+        match self {
+            Type::Struct(struct_type) | Type::Union(struct_type) => {
+                let members = struct_type.members();
+                let member = members
+                    .iter()
+                    .filter(|member| !member.id.is_empty())
+                    .nth(index)
+                    .expect("designators should only be created for existing members");
+                Designator::Member(member.id)
+            }
+            _ => Designator::Index(index as u64),
+        }
+    }
     /// Given a type and an index,
     /// return the type expected at that index in the initializer.
     ///
@@ -167,18 +370,19 @@ impl Type {
             Type::Array(inner, _) => Ok((**inner).clone()),
             Type::Struct(struct_type) => {
                 let symbols = struct_type.members();
-                symbols.get(index).map_or_else(
-                    || Err(SemanticError::TooManyMembers(symbols.len(), index)),
+                // unnamed bitfields are skipped during initialization
+                let mut named = symbols.iter().filter(|symbol| !symbol.id.is_empty());
+                named.nth(index).map_or_else(
+                    || Err(SemanticError::TooManyMembers(self.type_len(), index)),
                     |symbol| Ok(symbol.ctype.clone()),
                 )
             }
             Type::Union(struct_type) => {
-                if index != 0 {
-                    return Err("can only initialize first member of an enum".into());
-                }
                 let members = struct_type.members();
                 Ok(members
-                    .first()
+                    .iter()
+                    .filter(|m| !m.id.is_empty())
+                    .nth(index)
                     .map(|m| m.ctype.clone())
                     .unwrap_or(Type::Error))
             }
@@ -249,4 +453,26 @@ mod test {
         );
         assert_errs_decls("struct s { int *p; } s = { 1.0 }", 1, 0, 1);
     }
+    #[test]
+    fn test_designators() {
+        assert_same("int a[] = {[2] = 3, [0] = 1};", "int a[3] = {1, [2] = 3};");
+        assert_same("int a[] = {[1] = 2, 3};", "int a[3] = {[2] = 3, [1] = 2};");
+        assert_same(
+            "struct { int x, y; } s = {.y = 2, .x = 1};",
+            "struct { int x, y; } s = {1, 2};",
+        );
+        assert_same(
+            "int a[2][2] = {[1][0] = 3, 4};",
+            "int a[2][2] = {[1] = {3, 4}};",
+        );
+        for err in &[
+            "int a[2] = {[2] = 1};",
+            "int i = {.x = 1};",
+            "struct { int x; } s = {[0] = 1};",
+            "struct { int x; } s = {.y = 1};",
+            "int a[2] = {[-1] = 1};",
+        ] {
+            assert!(decl(err).is_err(), "{} should be an error", err);
+        }
+    }
 }
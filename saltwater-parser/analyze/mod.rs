@@ -7,6 +7,7 @@ use std::convert::TryInto;
 
 use counter::Counter;
 
+use crate::arch::TargetInfo;
 use crate::data::{error::Warning, hir::*, lex::Keyword, *};
 use crate::intern::InternedStr;
 use crate::parse::{Lexer, Parser};
@@ -63,15 +64,25 @@ pub struct PureAnalyzer {
     recursion_guard: RecursionGuard,
     /// Hack to make compound assignment work
     ///
-    /// For `a += b`, `a` must only be evaluated once.
+    /// For `*a += b`, `*a` must only be evaluated once.
     /// The way `assignment_expr` handles this is by desugaring to
-    /// `tmp = &a; *tmp = *tmp + b;`
+    /// `tmp = &*a; *tmp = *tmp + b;`
+    /// (Plain variables are desugared to `a = a + b` instead.)
     /// However, the backend still has to see the declaration.
     /// There's no way to return a statement from an expression,
     /// so instead we store it in a side channel.
     ///
     /// TODO: this should be a field on `FunctionAnalyzer`, not `Analyzer`
     decl_side_channel: Vec<Locatable<Declaration>>,
+    /// The hidden lengths of variable length arrays in the parameters
+    /// of the most recently parsed function declarator, e.g. `int f(int n, int a[][n])`.
+    ///
+    /// These are declared at the start of the body if the declarator was a function definition.
+    param_vla_lengths: Vec<Locatable<Declaration>>,
+    /// The number of compound literals seen so far, used to give each one a unique name
+    compound_literals: usize,
+    /// The platform being compiled for, which determines the sizes of types
+    target: TargetInfo,
 }
 
 impl<T: Lexer> Iterator for Analyzer<T> {
@@ -104,23 +115,23 @@ impl<T: Lexer> Iterator for Analyzer<T> {
 }
 
 impl<I: Lexer> Analyzer<I> {
-    pub fn new(parser: Parser<I>, debug: bool) -> Self {
+    pub fn new(parser: Parser<I>, debug: bool, target: TargetInfo) -> Self {
         Self {
             declarations: parser,
             debug,
-            inner: PureAnalyzer::new(),
+            inner: PureAnalyzer::new(target),
         }
     }
 }
 
 impl Default for PureAnalyzer {
     fn default() -> Self {
-        Self::new()
+        Self::new(TargetInfo::host())
     }
 }
 
 impl PureAnalyzer {
-    pub fn new() -> Self {
+    pub fn new(target: TargetInfo) -> Self {
         Self {
             error_handler: ErrorHandler::new(),
             scope: Scope::new(),
@@ -129,6 +140,9 @@ impl PureAnalyzer {
             initialized: HashSet::new(),
             recursion_guard: RecursionGuard::default(),
             decl_side_channel: Vec::new(),
+            param_vla_lengths: Vec::new(),
+            compound_literals: 0,
+            target,
         }
     }
 
@@ -173,8 +187,38 @@ impl PureAnalyzer {
                 vec![Locatable::new(decl, next.location)]
             }
             ExternalDeclaration::Declaration(declaration) => {
-                self.parse_declaration(declaration, next.location)
+                let decls = self.parse_declaration(declaration, next.location);
+                // compound literals at file scope have static storage duration,
+                // so they have to be declared before the declaration using them
+                let mut side_channel = std::mem::take(&mut self.decl_side_channel);
+                side_channel.extend(decls);
+                side_channel
+            }
+            ExternalDeclaration::StaticAssert(assert) => {
+                self.static_assert(assert, next.location);
+                Vec::new()
+            }
+        }
+    }
+    /// 6.7.10 Static assertions: `_Static_assert(sizeof(int) == 4, "int must be 4 bytes");`
+    fn static_assert(&mut self, assert: ast::StaticAssert, location: Location) {
+        let condition = self.expr(assert.condition);
+        if !condition.ctype.is_integral() {
+            if condition.ctype != Type::Error {
+                self.err(SemanticError::NonIntegralExpr(condition.ctype), location);
+            }
+            return;
+        }
+        match Self::const_literal(condition, &self.target) {
+            Ok(value) if value.is_zero() => {
+                let mut message = assert.message;
+                // remove the null terminator
+                message.pop();
+                let message = String::from_utf8_lossy(&message).into_owned();
+                self.err(SemanticError::StaticAssertFailed(message), location);
             }
+            Ok(_) => {}
+            Err(err) => self.error_handler.push_back(err),
         }
     }
     /// A list of declarations: `int i, j, k;`
@@ -192,9 +236,13 @@ impl PureAnalyzer {
         // TODO: this is such a hack: https://github.com/jyn514/rcc/issues/371
         let sc = original.storage_class.unwrap_or(StorageClass::Auto);
         let mut decls = Vec::new();
-        for d in declaration.declarators {
-            let mut ctype =
-                self.parse_declarator(original.ctype.clone(), d.data.declarator.decl, d.location);
+        for mut d in declaration.declarators {
+            let (mut ctype, qualifiers) = self.parse_declarator(
+                original.ctype.clone(),
+                original.qualifiers,
+                d.data.declarator.decl,
+                d.location,
+            );
 
             if !ctype.is_function() && original.qualifiers.func != FunctionQualifiers::default() {
                 self.err(
@@ -218,16 +266,42 @@ impl PureAnalyzer {
                 self.err(SemanticError::VoidType, location);
                 ctype = Type::Error;
             }
+            if ctype.is_vla() {
+                // 6.7.6.2p2: static int a[n];
+                if sc == StorageClass::Static || sc == StorageClass::Extern {
+                    self.err(
+                        format!("variable length array cannot be declared {}", sc).into(),
+                        d.location,
+                    );
+                }
+                // 6.7.9p3: int a[n] = {0};
+                if d.data.init.take().is_some() {
+                    self.err(
+                        "variable length arrays cannot be initialized".into(),
+                        d.location,
+                    );
+                }
+            }
+            let alignment = self.check_alignment(original.alignment, &ctype, sc, d.location);
             let init = if let Some(init) = d.data.init {
                 Some(self.parse_initializer(init, &ctype, d.location))
             } else {
                 None
             };
+            if let Some(init) = &init {
+                init::complete_array_type(&mut ctype, init);
+            }
             let symbol = Variable {
                 ctype,
                 id,
-                qualifiers: original.qualifiers,
+                qualifiers: Qualifiers {
+                    func: original.qualifiers.func,
+                    ..qualifiers
+                },
                 storage_class: sc,
+                bitfield: None,
+                alignment,
+                packed: None,
             };
             let symbol = self.declare(symbol, init.is_some(), d.location);
             if init.is_some() {
@@ -254,21 +328,31 @@ impl PureAnalyzer {
     ///
     /// 6.7.7 Type names
     fn parse_typename(&mut self, ctype: ast::TypeName, location: Location) -> Type {
+        self.parse_qualified_typename(ctype, location).0
+    }
+    /// Same as `parse_typename`, but also return the top-level qualifiers of the type.
+    ///
+    /// `_Generic(x, const int: 1)` needs these, and casts warn that they are ignored.
+    fn parse_qualified_typename(
+        &mut self,
+        ctype: ast::TypeName,
+        location: Location,
+    ) -> (Type, Qualifiers) {
         let parsed = self.parse_type(ctype.specifiers, ctype.declarator.decl, location);
         // TODO: should these be syntax errors instead?
         // extern int
         if let Some(sc) = parsed.storage_class {
             self.err(SemanticError::IllegalStorageClass(sc), location);
         }
-        // const int
-        if parsed.qualifiers != Qualifiers::default() {
-            self.warn(Warning::IgnoredQualifier(parsed.qualifiers), location);
-        }
         // int i
         if let Some(id) = ctype.declarator.id {
             self.err(SemanticError::IdInTypeName(id), location);
         }
-        parsed.ctype
+        // (_Alignas(8) int)i
+        if parsed.alignment.is_some() {
+            self.err(SemanticError::IllegalAlignas("type names"), location);
+        }
+        (parsed.ctype, parsed.qualifiers)
     }
     /// Parse a single type, given the specifiers and declarator.
     fn parse_type(
@@ -278,7 +362,13 @@ impl PureAnalyzer {
         location: Location,
     ) -> ParsedType {
         let mut specs = self.parse_specifiers(specifiers, location);
-        specs.ctype = self.parse_declarator(specs.ctype, declarator, location);
+        let (ctype, qualifiers) =
+            self.parse_declarator(specs.ctype, specs.qualifiers, declarator, location);
+        specs.ctype = ctype;
+        specs.qualifiers = Qualifiers {
+            func: specs.qualifiers.func,
+            ..qualifiers
+        };
 
         if !specs.ctype.is_function() && specs.qualifiers.func != FunctionQualifiers::default() {
             self.err(
@@ -340,11 +430,10 @@ impl PureAnalyzer {
                         ctype = Some(Type::Long(signed));
                     }
                 }
-                // TODO: implement `long long` as a separate type
-                2 => ctype = Some(Type::Long(signed)),
+                2 => ctype = Some(Type::LongLong(signed)),
                 _ => {
                     self.err(SemanticError::TooLong(long_count), location);
-                    ctype = Some(Type::Long(signed));
+                    ctype = Some(Type::LongLong(signed));
                 }
             }
         }
@@ -385,11 +474,21 @@ impl PureAnalyzer {
             (Short, Type::Short(signed)),
             // already handled `long` when we handled `long long`
             (Float, Type::Float),
-            // NOTE: if we saw `long double` before, we'll set `ctype` to `double` now
-            // TODO: make `long double` different from `double`
-            (Double, Type::Double),
+            // NOTE: `long` was skipped above if we saw `long double`
+            (
+                Double,
+                if counter.get(&Long).is_some() {
+                    Type::LongDouble
+                } else {
+                    Type::Double
+                },
+            ),
             (Void, Type::Void),
-            (VaList, Type::VaList),
+            // the SysV ABI defines `va_list` as `struct __va_list_tag[1]`
+            (
+                VaList,
+                Type::Array(Box::new(Type::VaList), types::ArrayType::Fixed(1)),
+            ),
         ] {
             if counter.get(&spec).is_some() {
                 match (spec, ctype) {
@@ -411,7 +510,7 @@ impl PureAnalyzer {
             match ctype {
                 None => ctype = Some(Type::Int(signed)),
                 // `long int` is valid
-                Some(Type::Short(_)) | Some(Type::Long(_)) => {}
+                Some(Type::Short(_)) | Some(Type::Long(_)) | Some(Type::LongLong(_)) => {}
                 Some(existing) => {
                     self.err(
                         SemanticError::ConflictingType(existing, Type::Int(signed)),
@@ -422,9 +521,31 @@ impl PureAnalyzer {
             }
         }
         let mut declared_compound_type = false;
+        // 6.7.5 Alignment specifier
+        // If there are multiple alignment specifiers, the strictest one is used.
+        let mut alignment = None;
         for compound in compounds {
             let parsed = match compound {
                 Unit(_) => unreachable!("already caught"),
+                AlignasExpr(expr) => {
+                    let requested = self.alignas_expr(*expr, location);
+                    alignment = alignment.max(requested);
+                    continue;
+                }
+                AlignasType(type_name) => {
+                    let ctype = self.parse_typename(*type_name, location);
+                    let requested = match ctype.alignof(&self.target) {
+                        Ok(align) => Some(align),
+                        Err(err) => {
+                            if ctype != Type::Error {
+                                self.err(err.into(), location);
+                            }
+                            None
+                        }
+                    };
+                    alignment = alignment.max(requested);
+                    continue;
+                }
                 DeclarationSpecifier::Typedef(name) => {
                     let meta = self
                         .scope
@@ -455,8 +576,11 @@ impl PureAnalyzer {
         if counter.get(&Signed).is_some() || counter.get(&Unsigned).is_some() {
             match &ctype {
                 // unsigned int
-                Some(Type::Char(_)) | Some(Type::Short(_)) | Some(Type::Int(_))
-                | Some(Type::Long(_)) => {}
+                Some(Type::Char(_))
+                | Some(Type::Short(_))
+                | Some(Type::Int(_))
+                | Some(Type::Long(_))
+                | Some(Type::LongLong(_)) => {}
                 // unsigned float
                 Some(other) => {
                     let err = SemanticError::CannotBeSigned(other.clone());
@@ -476,6 +600,59 @@ impl PureAnalyzer {
             storage_class,
             ctype,
             declared_compound_type,
+            alignment,
+        }
+    }
+    /// The alignment requested by `_Alignas(expr)`, or `None` if it has no effect.
+    fn alignas_expr(&mut self, expr: ast::Expr, location: Location) -> Option<u64> {
+        let requested = match Self::const_uint(self.expr(expr), &self.target) {
+            Ok(requested) => requested,
+            Err(err) => {
+                self.error_handler.push_back(err);
+                return None;
+            }
+        };
+        // 6.7.5p6: An alignment specification of zero has no effect.
+        if requested == 0 {
+            None
+        } else if !requested.is_power_of_two() {
+            self.err(SemanticError::AlignmentNotPowerOfTwo(requested), location);
+            None
+        } else {
+            Some(requested)
+        }
+    }
+    /// Check that `_Alignas` is allowed for an object of type `ctype` (6.7.5p2-4).
+    ///
+    /// Returns the alignment that should be used for the object, if it is valid.
+    fn check_alignment(
+        &mut self,
+        alignment: Option<u64>,
+        ctype: &Type,
+        storage_class: StorageClass,
+        location: Location,
+    ) -> Option<u64> {
+        let alignment = alignment?;
+        let illegal = match storage_class {
+            StorageClass::Typedef => Some("typedefs"),
+            StorageClass::Register => Some("register variables"),
+            _ if ctype.is_function() => Some("functions"),
+            _ => None,
+        };
+        if let Some(illegal) = illegal {
+            self.err(SemanticError::IllegalAlignas(illegal), location);
+            return None;
+        }
+        match ctype.alignof(&self.target) {
+            // _Alignas(1) int i;
+            Ok(natural) if alignment < natural => {
+                let err = SemanticError::AlignmentTooSmall(alignment, natural, ctype.clone());
+                self.err(err, location);
+                None
+            }
+            Ok(_) => Some(alignment),
+            // the error will be reported elsewhere
+            Err(_) => None,
         }
     }
     // 6.7.2.1 Structure and union specifiers
@@ -531,12 +708,23 @@ impl PureAnalyzer {
                 };
             }
         };
+        let packed = struct_spec.packed.map(u64::from);
         let members: Vec<_> = ast_members
             .into_iter()
-            .map(|m| self.struct_declarator_list(m, location).into_iter())
+            .map(|m| match m {
+                ast::StructDeclaration::Members(m) => {
+                    self.struct_declarator_list(m, packed, location)
+                }
+                // struct s { int i; _Static_assert(sizeof(int) == 4, "int must be 4 bytes"); };
+                ast::StructDeclaration::StaticAssert(assert) => {
+                    self.static_assert(assert, location);
+                    Vec::new()
+                }
+            })
             .flatten()
             .collect();
-        if members.is_empty() {
+        // struct { int: 5; }
+        if members.iter().all(|member| member.id.is_empty()) {
             self.err(SemanticError::from("cannot have empty struct"), location);
             return Type::Error;
         }
@@ -587,6 +775,7 @@ impl PureAnalyzer {
     fn struct_declarator_list(
         &mut self,
         members: ast::StructDeclarationList,
+        packed: Option<u64>,
         location: Location,
     ) -> Vec<Variable> {
         let parsed_type = self.parse_specifiers(members.specifiers, location);
@@ -600,55 +789,104 @@ impl PureAnalyzer {
         let mut parsed_members = Vec::new();
         // A member of a structure or union may have any complete object type other than a variably modified type.
         for ast::StructDeclarator { decl, bitfield } in members.declarators {
-            let decl = match decl {
+            let (id, (ctype, qualifiers)) = match decl {
+                Some(d) => (
+                    d.id.expect("struct members should have an id"),
+                    self.parse_declarator(
+                        parsed_type.ctype.clone(),
+                        parsed_type.qualifiers,
+                        d.decl,
+                        location,
+                    ),
+                ),
                 // 12 A bit-field declaration with no declarator, but only a colon and a width, indicates an unnamed bit-field.
-                // TODO: this should give an error if `bitfield` is None.
+                // Unnamed bit-fields are kept as members because they still affect the layout of the struct.
+                None if bitfield.is_some() => (
+                    InternedStr::default(),
+                    (parsed_type.ctype.clone(), parsed_type.qualifiers),
+                ),
+                // TODO: this should give an error
                 None => continue,
-                Some(d) => d,
             };
-            let ctype = match self.parse_declarator(parsed_type.ctype.clone(), decl.decl, location)
-            {
+            let ctype = match ctype {
                 Type::Void => {
                     // TODO: catch this error for types besides void?
                     self.err(SemanticError::VoidType, location);
                     Type::Error
                 }
+                // struct s { int a[n]; };
+                other if other.is_vla() => {
+                    self.err(
+                        "struct members cannot be variable length arrays".into(),
+                        location,
+                    );
+                    Type::Error
+                }
                 other => other,
             };
+            // struct s { _Alignas(4) int i: 5; };
+            let alignment = if bitfield.is_some() && parsed_type.alignment.is_some() {
+                self.err(SemanticError::IllegalAlignas("bit-fields"), location);
+                None
+            } else {
+                self.check_alignment(parsed_type.alignment, &ctype, StorageClass::Auto, location)
+            };
             let mut symbol = Variable {
                 storage_class: StorageClass::Auto,
-                qualifiers: parsed_type.qualifiers,
+                qualifiers: Qualifiers {
+                    func: parsed_type.qualifiers.func,
+                    ..qualifiers
+                },
                 ctype,
-                id: decl.id.expect("struct members should have an id"),
+                id,
+                bitfield: None,
+                alignment,
+                packed,
             };
             // struct s { int i: 5 };
             if let Some(bitfield) = bitfield {
-                let bit_size = match Self::const_uint(self.expr(bitfield)) {
+                let bit_size = match Self::const_uint(self.expr(bitfield), &self.target) {
                     Ok(e) => e,
                     Err(err) => {
                         self.error_handler.push_back(err);
                         1
                     }
                 };
-                let type_size = symbol.ctype.sizeof().unwrap_or(0);
-                if bit_size == 0 {
+                // 6.7.2.1p5: A bit-field shall have a type that is a qualified or unqualified version of
+                // _Bool, signed int, unsigned int, or some other implementation-defined type.
+                // Like GCC, we allow any integer type.
+                let max_width = match symbol.ctype {
+                    // 6.2.6.1p2: the width of _Bool is 1
+                    Type::Bool => 1,
+                    ref ctype => {
+                        ctype.sizeof(&self.target).unwrap_or(0) * u64::from(crate::arch::CHAR_BIT)
+                    }
+                };
+                if !symbol.ctype.is_integral() {
+                    if symbol.ctype != Type::Error {
+                        let err = SemanticError::from(format!(
+                            "bitfield '{}' has non-integer type '{}'",
+                            symbol.id, symbol.ctype
+                        ));
+                        self.err(err, location);
+                    }
+                // struct s { int i: 0 }
+                } else if bit_size == 0 && !symbol.id.is_empty() {
                     let err = SemanticError::from(format!(
                         "C does not have zero-sized types. hint: omit the declarator {}",
                         symbol.id
                     ));
                     self.err(err, location);
                 // struct s { int i: 65 }
-                } else if bit_size > type_size * u64::from(crate::arch::CHAR_BIT) {
+                } else if bit_size > max_width {
                     let err = SemanticError::from(format!(
                         "cannot have bitfield {} with size {} larger than containing type {}",
                         symbol.id, bit_size, symbol.ctype
                     ));
                     self.err(err, location);
+                } else {
+                    symbol.bitfield = Some(bit_size as u16);
                 }
-                self.error_handler.warn(
-                    "bitfields are not implemented and will be ignored",
-                    location,
-                );
             }
             match symbol.ctype {
                 Type::Struct(StructType::Named(_, inner_members))
@@ -730,10 +968,11 @@ impl PureAnalyzer {
         for (name, maybe_value) in ast_members {
             // enum E { A = 5 };
             if let Some(value) = maybe_value {
-                discriminant = Self::const_sint(self.expr(value)).unwrap_or_else(|err| {
-                    self.error_handler.push_back(err);
-                    i64::MIN
-                });
+                discriminant =
+                    Self::const_sint(self.expr(value), &self.target).unwrap_or_else(|err| {
+                        self.error_handler.push_back(err);
+                        i64::MIN
+                    });
             }
             members.push((name, discriminant));
             // TODO: this is such a hack
@@ -745,6 +984,9 @@ impl PureAnalyzer {
                 },
                 storage_class: StorageClass::Register,
                 ctype: Type::Enum(None, vec![(name, discriminant)]),
+                bitfield: None,
+                alignment: None,
+                packed: None,
             };
             self.declare(tmp_symbol, false, location);
             discriminant = discriminant.checked_add(1).unwrap_or_else(|| {
@@ -781,6 +1023,9 @@ impl PureAnalyzer {
                             storage_class: StorageClass::Register,
                             qualifiers: Qualifiers::NONE,
                             ctype: ctype.clone(),
+                            bitfield: None,
+                            alignment: None,
+                            packed: None,
                         }
                         .insert(),
                     );
@@ -828,8 +1073,12 @@ impl PureAnalyzer {
         self.tag_scope.insert(ident, entry);
         tag_type(StructType::Named(ident, struct_ref))
     }
-    /// Parse the declarator for a variable, given a starting type.
-    /// e.g. for `int *p`, takes `start: Type::Int(true)` and returns `Type::Pointer(Type::Int(true))`
+    /// Parse the declarator for a variable, given a starting type and its qualifiers.
+    /// e.g. for `const int *p`, takes `start: Type::Int(true)` and `const`,
+    /// and returns `Type::Pointer(Type::Int(true), const)` with no qualifiers.
+    ///
+    /// The qualifiers of a pointer's target are stored in the pointer type,
+    /// so only the qualifiers of the declared object itself are returned.
     ///
     /// The parser generated a linked list `DeclaratorType`,
     /// which we now transform into the recursive `Type`.
@@ -838,19 +1087,21 @@ impl PureAnalyzer {
     fn parse_declarator(
         &mut self,
         current: Type,
+        current_qualifiers: Qualifiers,
         decl: ast::DeclaratorType,
         location: Location,
-    ) -> Type {
+    ) -> (Type, Qualifiers) {
         use crate::data::ast::DeclaratorType::*;
         use crate::data::types::{ArrayType, FunctionType};
 
         let _guard = self.recursion_check();
         match decl {
-            End => current,
+            End => (current, current_qualifiers.without_func()),
             Pointer { to, qualifiers } => {
                 use UnitSpecifier::*;
 
-                let inner = self.parse_declarator(current, *to, location);
+                let (inner, inner_qualifiers) =
+                    self.parse_declarator(current, current_qualifiers, *to, location);
                 // we reuse `count_specifiers` even though we really only want the qualifiers
                 let (counter, compounds) =
                     count_specifiers(qualifiers, &mut self.error_handler, location);
@@ -874,30 +1125,31 @@ impl PureAnalyzer {
                     // *struct s {}
                     self.err(SemanticError::NotAQualifier(spec), location);
                 }
-                Type::Pointer(Box::new(inner), qualifiers)
+                (
+                    Type::Pointer(Box::new(inner), inner_qualifiers),
+                    qualifiers.without_func(),
+                )
             }
             Array { of, size } => {
-                // int a[5]
                 let size = if let Some(expr) = size {
-                    let size = Self::const_uint(self.expr(*expr)).unwrap_or_else(|err| {
-                        self.error_handler.push_back(err);
-                        1
-                    });
-                    ArrayType::Fixed(size)
+                    self.array_size(*expr)
                 } else {
                     // int a[]
                     ArrayType::Unbounded
                 };
-                let of = self.parse_declarator(current, *of, location);
+                // const int a[3]: the qualifiers of the elements apply to the whole array
+                let (of, qualifiers) =
+                    self.parse_declarator(current, current_qualifiers, *of, location);
                 // int a[]()
                 if let Type::Function(_) = &of {
                     self.err(SemanticError::ArrayStoringFunction(of.clone()), location);
                 }
-                Type::Array(Box::new(of), size)
+                (Type::Array(Box::new(of), size), qualifiers)
             }
             Function(func) => {
                 // TODO: give a warning for `const int f();` somewhere
-                let return_type = self.parse_declarator(current, *func.return_type, location);
+                let (return_type, _) =
+                    self.parse_declarator(current, current_qualifiers, *func.return_type, location);
                 match &return_type {
                     // int a()[]
                     Type::Array(_, _) => self.err(
@@ -912,6 +1164,10 @@ impl PureAnalyzer {
                     _ => {}
                 }
 
+                // 6.2.1p4: parameters are in scope from the end of their declarator,
+                // so that `int f(int n, int a[n])` refers to the first parameter
+                self.scope.enter();
+                let side_channel_len = self.decl_side_channel.len();
                 let mut names = HashSet::new();
                 let mut params = Vec::new();
                 for param in func.params {
@@ -920,8 +1176,13 @@ impl PureAnalyzer {
                         self.parse_type(param.specifiers, param.declarator.decl, location);
 
                     // `int f(int a[])` -> `int f(int *a)`
+                    // `int f(const int a[])` -> `int f(const int *a)`
                     if let Type::Array(to, _) = param_type.ctype {
-                        param_type.ctype = Type::Pointer(to, Qualifiers::default());
+                        param_type.ctype = Type::Pointer(to, param_type.qualifiers.without_func());
+                        param_type.qualifiers = Qualifiers {
+                            func: param_type.qualifiers.func,
+                            ..Qualifiers::default()
+                        };
                     }
 
                     // C11 Standard 6.7.6.3 paragraph 8
@@ -937,6 +1198,13 @@ impl PureAnalyzer {
                     if let Some(sc) = param_type.storage_class {
                         self.err(SemanticError::ParameterStorageClass(sc), location);
                     }
+                    // int a(_Alignas(8) int i)
+                    if param_type.alignment.is_some() {
+                        self.err(
+                            SemanticError::IllegalAlignas("function parameters"),
+                            location,
+                        );
+                    }
                     let id = if let Some(name) = param.declarator.id {
                         // int f(int a, int a)
                         if names.contains(&name) {
@@ -953,23 +1221,27 @@ impl PureAnalyzer {
                         id,
                         qualifiers: param_type.qualifiers,
                         storage_class: StorageClass::Auto,
+                        bitfield: None,
+                        alignment: None,
+                        packed: None,
                     };
-                    params.push(meta);
+                    let symbol = meta.insert();
+                    if id != InternedStr::default() {
+                        self.scope.insert(id, symbol);
+                    }
+                    params.push(symbol);
                 }
+                self.scope.exit();
+                // The lengths of VLA parameters can only be evaluated in the function body.
+                // If this is not a function definition, they are never used.
+                self.param_vla_lengths = self.decl_side_channel.split_off(side_channel_len);
                 // int f(void);
                 let is_void = match params.as_slice() {
-                    [Variable {
-                        ctype: Type::Void, ..
-                    }] => true,
+                    [param] => param.get().ctype == Type::Void,
                     _ => false,
                 };
                 // int f(void, int) or int f(int, void) or ...
-                if !is_void
-                    && params.iter().any(|param| match param.ctype {
-                        Type::Void => true,
-                        _ => false,
-                    })
-                {
+                if !is_void && params.iter().any(|param| param.get().ctype == Type::Void) {
                     self.err(SemanticError::InvalidVoidParameter, location);
                 // int f(void, ...)
                 } else if func.varargs && is_void {
@@ -978,27 +1250,90 @@ impl PureAnalyzer {
                 } else if func.varargs && params.is_empty() {
                     self.err(SemanticError::VarargsWithoutParam, location);
                 }
-                Type::Function(FunctionType {
-                    params: params.into_iter().map(|m| m.insert()).collect(),
+                let ctype = Type::Function(FunctionType {
+                    params,
                     return_type: Box::new(return_type),
                     varargs: func.varargs,
-                })
+                });
+                (ctype, Qualifiers::default())
+            }
+        }
+    }
+    /// The length of an array declarator, either `int a[5]` or `int a[n]`.
+    ///
+    /// 6.7.6.2 Array declarators
+    fn array_size(&mut self, expr: ast::Expr) -> types::ArrayType {
+        let expr = self.expr(expr);
+        let location = expr.location;
+        let expr = match expr.const_fold(&self.target) {
+            Ok(expr) => expr,
+            Err(err) => {
+                self.error_handler.push_back(err);
+                return types::ArrayType::Fixed(1);
+            }
+        };
+        let runtime_expr = match expr.into_literal() {
+            // int a[5]
+            Ok(literal) => {
+                let size = Self::const_uint(expr::literal(literal, location), &self.target)
+                    .unwrap_or_else(|err| {
+                        self.error_handler.push_back(err);
+                        1
+                    });
+                return types::ArrayType::Fixed(size);
+            }
+            Err(runtime_expr) => runtime_expr,
+        };
+        // int a[1.0 * n]
+        if !runtime_expr.ctype.is_integral() {
+            if runtime_expr.ctype != Type::Error {
+                self.err(SemanticError::NonIntegralLength, location);
             }
+            return types::ArrayType::Fixed(1);
+        }
+        // int n; int a[n];
+        // 6.7.6.2p2: only ordinary identifiers with block or function prototype scope can have a VLA type
+        if self.scope.is_global() {
+            self.err(SemanticError::NotConstant(runtime_expr), location);
+            return types::ArrayType::Fixed(1);
         }
+        // `int a[n]` -> `size_t <vla length> = n; int a[<vla length>];`
+        // This makes sure the length is only evaluated once,
+        // even if `n` changes later in the function.
+        let size_t = self.target.size_t();
+        let length = Variable {
+            id: "<vla length>".into(),
+            ctype: size_t.clone(),
+            qualifiers: Qualifiers::NONE,
+            storage_class: StorageClass::Auto,
+            bitfield: None,
+            alignment: None,
+            packed: None,
+        }
+        .insert();
+        let init = runtime_expr.implicit_cast(&size_t, &mut self.error_handler);
+        let decl = Declaration {
+            symbol: length,
+            init: Some(Initializer::Scalar(Box::new(init))),
+        };
+        self.decl_side_channel.push(Locatable::new(decl, location));
+        types::ArrayType::Variable(length)
     }
     // used for arrays like `int a[BUF_SIZE - 1];` and enums like `enum { A = 1 }`
-    fn const_literal(expr: Expr) -> CompileResult<LiteralValue> {
+    fn const_literal(expr: Expr, target: &TargetInfo) -> CompileResult<LiteralValue> {
         let location = expr.location;
-        expr.const_fold()?.into_literal().map_err(|runtime_expr| {
-            Locatable::new(SemanticError::NotConstant(runtime_expr).into(), location)
-        })
+        expr.const_fold(target)?
+            .into_literal()
+            .map_err(|runtime_expr| {
+                Locatable::new(SemanticError::NotConstant(runtime_expr).into(), location)
+            })
     }
     /// Return an unsigned integer that can be evaluated at compile time, or an error otherwise.
-    fn const_uint(expr: Expr) -> CompileResult<crate::arch::SIZE_T> {
+    fn const_uint(expr: Expr, target: &TargetInfo) -> CompileResult<crate::arch::SIZE_T> {
         use LiteralValue::*;
 
         let location = expr.location;
-        match Self::const_literal(expr)? {
+        match Self::const_literal(expr, target)? {
             UnsignedInt(i) => Ok(i),
             Int(i) => {
                 if i < 0 {
@@ -1011,18 +1346,18 @@ impl PureAnalyzer {
                 }
             }
             Char(c) => Ok(c.into()),
-            Str(_) | Float(_) => Err(Locatable::new(
+            Str(_) | Float(_) | LongDouble(_) => Err(Locatable::new(
                 SemanticError::NonIntegralLength.into(),
                 location,
             )),
         }
     }
     /// Return a signed integer that can be evaluated at compile time, or an error otherwise.
-    fn const_sint(expr: Expr) -> CompileResult<i64> {
+    fn const_sint(expr: Expr, target: &TargetInfo) -> CompileResult<i64> {
         use LiteralValue::*;
 
         let location = expr.location;
-        match Self::const_literal(expr)? {
+        match Self::const_literal(expr, target)? {
             UnsignedInt(u) => match u.try_into() {
                 Ok(i) => Ok(i),
                 Err(_) => Err(Locatable::new(
@@ -1032,7 +1367,7 @@ impl PureAnalyzer {
             },
             Int(i) => Ok(i),
             Char(c) => Ok(c.into()),
-            Str(_) | Float(_) => Err(Locatable::new(
+            Str(_) | Float(_) | LongDouble(_) => Err(Locatable::new(
                 SemanticError::NonIntegralLength.into(),
                 location,
             )),
@@ -1199,8 +1534,12 @@ impl FunctionAnalyzer<'_> {
             id: func.id,
             qualifiers: parsed_func.qualifiers,
             storage_class: sc,
+            bitfield: None,
+            alignment: None,
+            packed: None,
         };
         let symbol = analyzer.declare(metadata, true, location);
+        let vla_lengths = std::mem::take(&mut analyzer.param_vla_lengths);
         let func_type = match parsed_func.ctype {
             Type::Function(ftype) => ftype,
             _ => unreachable!(),
@@ -1231,10 +1570,16 @@ impl FunctionAnalyzer<'_> {
             // but that requires having a mutable `Metadata`
             func_analyzer.analyzer.scope.insert(meta.id, param);
         }
-        let stmts = func
-            .body
+        // int f(int n, int a[][n]) { ... }
+        // the lengths have to be evaluated before anything else in the body
+        let vla_decls = if vla_lengths.is_empty() {
+            None
+        } else {
+            Some(Stmt::new(StmtType::Decl(vla_lengths), location))
+        };
+        let stmts = vla_decls
             .into_iter()
-            .map(|s| func_analyzer.parse_stmt(s))
+            .chain(func.body.into_iter().map(|s| func_analyzer.parse_stmt(s)))
             .collect();
         // TODO: this location should be the end of the function, not the start
         func_analyzer.leave_scope(location);
@@ -1285,6 +1630,8 @@ struct ParsedType {
     ctype: Type,
     // TODO: this is fishy
     declared_compound_type: bool,
+    // _Alignas(16)
+    alignment: Option<u64>,
 }
 
 use ast::{DeclarationSpecifier, UnitSpecifier};
@@ -1360,7 +1707,7 @@ pub(crate) mod test {
     {
         let mut p = parser(input);
         let ast = parse_func(&mut p)?;
-        let mut a = PureAnalyzer::new();
+        let mut a = PureAnalyzer::default();
         let e = analyze_func(&mut a, ast);
         if let Some(err) = a.error_handler.pop_front() {
             return Err(err);
@@ -1377,13 +1724,13 @@ pub(crate) mod test {
     }
 
     pub(crate) fn decls(s: &str) -> Vec<CompileResult<Declaration>> {
-        Analyzer::new(parser(s), false)
+        Analyzer::new(parser(s), false, TargetInfo::host())
             .map(|o| o.map(|l| l.data))
             .collect()
     }
 
     pub(crate) fn assert_errs_decls(input: &str, errs: usize, warnings: usize, decls: usize) {
-        let mut a = Analyzer::new(parser(input), false);
+        let mut a = Analyzer::new(parser(input), false, TargetInfo::host());
         let (mut a_errs, mut a_decls) = (0, 0);
         for res in &mut a {
             if res.is_err() {
@@ -1504,14 +1851,18 @@ pub(crate) mod test {
         assert!(match_type(decl("signed short i;"), Type::Short(true)));
         assert!(match_type(decl("unsigned short i;"), Type::Short(false)));
         assert!(match_type(decl("long i;"), Type::Long(true)));
-        assert!(match_type(decl("long long i;"), Type::Long(true)));
+        assert!(match_type(decl("long long i;"), Type::LongLong(true)));
+        assert!(match_type(
+            decl("unsigned long long i;"),
+            Type::LongLong(false)
+        ));
         assert!(match_type(decl("long unsigned i;"), Type::Long(false)));
         assert!(match_type(decl("int i;"), Type::Int(true)));
         assert!(match_type(decl("signed i;"), Type::Int(true)));
         assert!(match_type(decl("unsigned i;"), Type::Int(false)));
         assert!(match_type(decl("float f;"), Type::Float));
         assert!(match_type(decl("double d;"), Type::Double));
-        assert!(match_type(decl("long double d;"), Type::Double));
+        assert!(match_type(decl("long double d;"), Type::LongDouble));
         assert!(match_type(
             decl("void f();"),
             Type::Function(FunctionType {
@@ -1521,10 +1872,10 @@ pub(crate) mod test {
             })
         ));
         assert!(match_type(decl("const volatile int f;"), Type::Int(true)));
-        assert!(match_type(decl("long double d;"), Type::Double));
+        assert!(match_type(decl("long double d;"), Type::LongDouble));
         assert!(match_type(decl("short int i;"), Type::Short(true)));
         assert!(match_type(decl("long int i;"), Type::Long(true)));
-        assert!(match_type(decl("long long int i;"), Type::Long(true)));
+        assert!(match_type(decl("long long int i;"), Type::LongLong(true)));
     }
     #[test]
     fn test_bad_decl_specs() {
@@ -1577,6 +1928,31 @@ pub(crate) mod test {
         assert_same("int a[(int)1];", "int a[1];");
     }
     #[test]
+    fn variable_length_arrays() {
+        // parameters can refer to earlier parameters
+        assert_decl_display(
+            "void f(int n, double a[n][n]);",
+            "void f(int n, double (*a)[*]);",
+        );
+        assert_decl_display("void f(int n, int a[n]);", "void f(int n, int *a);");
+        // the declaration and definition are compatible
+        assert!(
+            decls("void f(int n, int a[][n]); void f(int m, int a[][m]) {}")
+                .iter()
+                .all(Result::is_ok)
+        );
+        for invalid in &[
+            "int n; int a[n];",
+            "void f(int a[n], int n);",
+            "int f(int n) { static int a[n]; }",
+            "int f(int n) { int a[n] = {0}; }",
+            "int f(int n) { struct s { int a[n]; }; }",
+            "int f(double d) { int a[d]; }",
+        ] {
+            assert!(decls(invalid).iter().any(Result::is_err), "{}", invalid);
+        }
+    }
+    #[test]
     fn test_pointers() {
         for &pointer in &[
             "void *a;",
@@ -1655,6 +2031,9 @@ pub(crate) mod test {
                         ),
                         qualifiers: Default::default(),
                         storage_class: Default::default(),
+                        bitfield: None,
+                        alignment: None,
+                        packed: None,
                     }
                     .insert()],
                     varargs: false,
@@ -1670,7 +2049,10 @@ pub(crate) mod test {
                     id: Default::default(),
                     ctype: Int(true),
                     qualifiers: Default::default(),
-                    storage_class: Default::default()
+                    storage_class: Default::default(),
+                    bitfield: None,
+                    alignment: None,
+                    packed: None,
                 }
                 .insert()],
                 varargs: true,
@@ -1688,6 +2070,9 @@ pub(crate) mod test {
                     ctype: Pointer(Box::new(Int(true)), Qualifiers::default()),
                     qualifiers: Default::default(),
                     storage_class: Default::default(),
+                    bitfield: None,
+                    alignment: None,
+                    packed: None,
                 }
                 .insert()],
                 varargs: false
@@ -1708,6 +2093,9 @@ pub(crate) mod test {
                     ctype: Type::Void,
                     qualifiers: Qualifiers::default(),
                     storage_class: StorageClass::default(),
+                    bitfield: None,
+                    alignment: None,
+                    packed: None,
                 }
                 .insert()],
                 varargs: false,
@@ -1725,6 +2113,17 @@ pub(crate) mod test {
         assert!(decl("typedef a inline int;").is_err());
     }
     #[test]
+    fn test_generic_qualifiers() {
+        let input = "const char *cp; char *p; const int ci;
+            _Static_assert(_Generic(cp, char *: 1, const char *: 2) == 2, \"\");
+            _Static_assert(_Generic(p, char *: 1, const char *: 2) == 1, \"\");
+            _Static_assert(_Generic(ci, int: 1, const int: 2) == 1, \"\");";
+        let mut a = Analyzer::new(parser(input), false, TargetInfo::host());
+        assert!(a.by_ref().all(|res| res.is_ok()));
+        // qualifiers are only ignored in casts, not in `_Generic`
+        assert!(a.inner.error_handler.warnings.is_empty());
+    }
+    #[test]
     fn test_complex() {
         // cdecl: declare bar as const pointer to array 10 of pointer to function (int) returning volatile pointer to char
         assert!(match_type(
@@ -1733,18 +2132,19 @@ pub(crate) mod test {
                 Box::new(Array(
                     Box::new(Pointer(
                         Box::new(Function(FunctionType {
+                            // the `volatile` applies to the return value itself, so it is ignored
                             return_type: Box::new(Pointer(
                                 Box::new(Char(true)),
-                                Qualifiers {
-                                    volatile: true,
-                                    ..Qualifiers::default()
-                                }
+                                Qualifiers::default()
                             )),
                             params: vec![Variable {
                                 ctype: Int(true),
                                 storage_class: Default::default(),
                                 id: Default::default(),
                                 qualifiers: Qualifiers::NONE,
+                                bitfield: None,
+                                alignment: None,
+                                packed: None,
                             }
                             .insert()],
                             varargs: false,
@@ -1753,10 +2153,8 @@ pub(crate) mod test {
                     )),
                     ArrayType::Unbounded,
                 )),
-                Qualifiers {
-                    c_const: true,
-                    ..Qualifiers::default()
-                }
+                // the `const` applies to `bar` itself, not to the array
+                Qualifiers::default()
             )
         ));
         // cdecl: declare foo as pointer to function (void) returning pointer to array 3 of int
@@ -1773,6 +2171,9 @@ pub(crate) mod test {
                         storage_class: Default::default(),
                         id: Default::default(),
                         qualifiers: Default::default(),
+                        bitfield: None,
+                        alignment: None,
+                        packed: None,
                     }
                     .insert()],
                     varargs: false,
@@ -1786,7 +2187,7 @@ pub(crate) mod test {
             Pointer(
                 Box::new(Array(Box::new(Int(true)), ArrayType::Unbounded)),
                 Qualifiers {
-                    volatile: true,
+                    c_const: true,
                     ..Qualifiers::default()
                 }
             )
@@ -1919,6 +2320,15 @@ pub(crate) mod test {
         assert!(decl("struct { int a:5; } b;").is_ok());
         assert!(decl("struct { int a:5, b:6; } c;").is_ok());
         assert!(decl("struct { extern int a:5; } d;").is_err());
+        assert!(decl("struct { int a:5, :0, :3, b:6; } e;").is_ok());
+        assert!(decl("struct { _Bool a:1; } f;").is_ok());
+        assert!(decl("struct { _Bool a:2; } g;").is_err());
+        assert!(decl("struct { float a:2; } h;").is_err());
+        assert!(decl("struct { int a:0; } i;").is_err());
+        assert!(decl("struct { int a:33; } j;").is_err());
+        let is_err = |input| decls(input).iter().any(Result::is_err);
+        assert!(is_err("struct { int a:3; } k; int *p = &k.a;"));
+        assert!(is_err("struct { int a:3; } l; int n = sizeof l.a;"));
     }
     #[test]
     fn lol() {
@@ -97,6 +97,11 @@ impl FunctionAnalyzer<'_> {
             Return(value) => self.return_statement(value, stmt.location),
             // 6.7 Declarations
             Decl(decls) => S::Decl(self.analyzer.parse_declaration(decls, stmt.location)),
+            // 6.7.10 Static assertions
+            StaticAssert(assert) => {
+                self.analyzer.static_assert(assert, stmt.location);
+                S::default()
+            }
         };
         let data = if !self.analyzer.decl_side_channel.is_empty() {
             let decls = std::mem::replace(&mut self.analyzer.decl_side_channel, Vec::new());
@@ -117,7 +122,7 @@ impl FunctionAnalyzer<'_> {
     ) -> StmtType {
         use super::expr::literal;
 
-        let expr = match self.expr(expr).const_fold() {
+        let expr = match self.expr(expr).const_fold(&self.analyzer.target) {
             Ok(e) => e,
             Err(err) => {
                 self.analyzer.error_handler.push_back(err);
@@ -0,0 +1,346 @@
+//! The macros GCC predefines for the target, such as `__SIZEOF_INT__` and `__x86_64__`.
+//!
+//! System headers use these to pick types and limits, so they must agree with the rest of `arch`.
+//! Compare the output of `gcc -dM -E - </dev/null`.
+//!
+//! Macros that claim GCC extensions, like `__GNUC__`, are deliberately not defined:
+//! headers would then use `__attribute__` and other syntax saltwater doesn't support.
+
+use target_lexicon::{Architecture, BinaryFormat, Endianness, OperatingSystem};
+
+use super::*;
+
+/// The smallest signed integer type with exactly `size` bytes, and its suffix.
+///
+/// When two types have the same size, GCC uses the first one for `__INT64_TYPE__` and friends.
+fn int_type(model: DataModel, size: u16) -> (&'static str, &'static str) {
+    let signed_types = [
+        ("signed char", CHAR_SIZE, ""),
+        ("short int", SHORT_SIZE, ""),
+        ("int", model.int_size(), ""),
+        ("long int", model.long_size(), "L"),
+        ("long long int", LONG_LONG_SIZE, "LL"),
+    ];
+    signed_types
+        .iter()
+        .find(|(_, type_size, _)| *type_size == size)
+        .map(|&(name, _, suffix)| (name, suffix))
+        .expect("every integer size should have a type")
+}
+
+/// The unsigned version of `int_type`, e.g. `long unsigned int` and `UL`.
+///
+/// Types smaller than `int` are promoted to `int`, so they have no suffix.
+fn uint_type(model: DataModel, size: u16) -> (String, String) {
+    let (name, suffix) = int_type(model, size);
+    let name = match name {
+        "signed char" => "unsigned char".to_owned(),
+        "int" => "unsigned int".to_owned(),
+        _ => name.replace(" int", " unsigned int"),
+    };
+    let suffix = if size < model.int_size() {
+        String::new()
+    } else {
+        format!("U{}", suffix)
+    };
+    (name, suffix)
+}
+
+/// The largest value of a signed type with `size` bytes, in hexadecimal like GCC.
+fn int_max(size: u16, suffix: &str) -> String {
+    let bits = u32::from(size * CHAR_BIT);
+    format!("{:#x}{}", (1u128 << (bits - 1)) - 1, suffix)
+}
+
+/// The largest value of an unsigned type with `size` bytes.
+fn uint_max(size: u16, suffix: &str) -> String {
+    let bits = u32::from(size * CHAR_BIT);
+    format!("{:#x}{}", (1u128 << bits) - 1, suffix)
+}
+
+/// The body of a macro like `__INT64_C(c)`, which gives `c` the type with `suffix`.
+fn constant(suffix: &str) -> String {
+    if suffix.is_empty() {
+        "c".to_owned()
+    } else {
+        format!("c ## {}", suffix)
+    }
+}
+
+/// The macros GCC predefines for `target`, as `(name, replacement list)` pairs.
+///
+/// Function-like macros have their parameters in the name, e.g. `__INT64_C(c)`.
+pub fn predefined_macros(target: &TargetInfo) -> Vec<(String, String)> {
+    let triple = &target.triple;
+    let model = target.data_model;
+    let (int_size, long_size, ptr_size) = (model.int_size(), model.long_size(), model.ptr_size());
+    let mut macros = Vec::new();
+    let mut define = |name: &str, value: &str| macros.push((name.to_owned(), value.to_owned()));
+
+    // target
+    match triple.architecture {
+        Architecture::X86_64 => {
+            define("__x86_64__", "1");
+            define("__x86_64", "1");
+            define("__amd64__", "1");
+            define("__amd64", "1");
+        }
+        Architecture::Aarch64(_) => define("__aarch64__", "1"),
+        Architecture::Riscv64(_) => {
+            define("__riscv", "1");
+            define("__riscv_xlen", "64");
+        }
+        _ => define(&format!("__{}__", triple.architecture), "1"),
+    }
+    match triple.operating_system {
+        OperatingSystem::Linux => {
+            define("__linux__", "1");
+            define("__linux", "1");
+            define("__gnu_linux__", "1");
+        }
+        OperatingSystem::Darwin | OperatingSystem::MacOSX { .. } | OperatingSystem::Ios => {
+            define("__APPLE__", "1");
+            define("__MACH__", "1");
+        }
+        OperatingSystem::Windows => {
+            define("_WIN32", "1");
+            if ptr_size == 8 {
+                define("_WIN64", "1");
+            }
+        }
+        _ => define(&format!("__{}__", triple.operating_system), "1"),
+    }
+    if triple.binary_format == BinaryFormat::Elf || triple.binary_format == BinaryFormat::Macho {
+        define("__unix__", "1");
+        define("__unix", "1");
+    }
+    if triple.binary_format == BinaryFormat::Elf {
+        define("__ELF__", "1");
+    }
+    if model == DataModel::LP64 {
+        define("_LP64", "1");
+        define("__LP64__", "1");
+    }
+    define("__ORDER_LITTLE_ENDIAN__", "1234");
+    define("__ORDER_BIG_ENDIAN__", "4321");
+    define("__ORDER_PDP_ENDIAN__", "3412");
+    let byte_order = match triple.endianness() {
+        Ok(Endianness::Big) => "__ORDER_BIG_ENDIAN__",
+        _ => "__ORDER_LITTLE_ENDIAN__",
+    };
+    define("__BYTE_ORDER__", byte_order);
+    define("__FLOAT_WORD_ORDER__", byte_order);
+
+    // sizes
+    define("__CHAR_BIT__", &CHAR_BIT.to_string());
+    for (name, size) in &[
+        ("SHORT", SHORT_SIZE),
+        ("INT", int_size),
+        ("LONG", long_size),
+        ("LONG_LONG", LONG_LONG_SIZE),
+        ("POINTER", ptr_size),
+        ("FLOAT", FLOAT_SIZE),
+        ("DOUBLE", DOUBLE_SIZE),
+        ("LONG_DOUBLE", target.long_double_size()),
+        ("SIZE_T", ptr_size),
+        ("PTRDIFF_T", ptr_size),
+        ("WCHAR_T", int_size),
+        ("WINT_T", int_size),
+    ] {
+        define(&format!("__SIZEOF_{}__", name), &size.to_string());
+    }
+    define(
+        "__BIGGEST_ALIGNMENT__",
+        &target.long_double_size().to_string(),
+    );
+
+    // limits of the basic types
+    for &(name, size, suffix) in &[
+        ("SCHAR", CHAR_SIZE, ""),
+        ("SHRT", SHORT_SIZE, ""),
+        ("INT", int_size, ""),
+        ("LONG", long_size, "L"),
+        ("LONG_LONG", LONG_LONG_SIZE, "LL"),
+    ] {
+        define(&format!("__{}_MAX__", name), &int_max(size, suffix));
+        define(
+            &format!("__{}_WIDTH__", name),
+            &(size * CHAR_BIT).to_string(),
+        );
+    }
+
+    // typedefs in <stddef.h>, <stdint.h>, and <wchar.h>
+    let (size_t, size_suffix) = uint_type(model, ptr_size);
+    let (ptrdiff_t, ptrdiff_suffix) = int_type(model, ptr_size);
+    let (intmax_t, intmax_suffix) = int_type(model, LONG_LONG_SIZE);
+    let (uintmax_t, uintmax_suffix) = uint_type(model, LONG_LONG_SIZE);
+    let (wchar_t, _) = int_type(model, int_size);
+    let (wint_t, wint_suffix) = uint_type(model, int_size);
+    for (name, ty, max) in &[
+        ("SIZE", size_t.as_str(), uint_max(ptr_size, &size_suffix)),
+        ("PTRDIFF", ptrdiff_t, int_max(ptr_size, ptrdiff_suffix)),
+        ("INTMAX", intmax_t, int_max(LONG_LONG_SIZE, intmax_suffix)),
+        (
+            "UINTMAX",
+            uintmax_t.as_str(),
+            uint_max(LONG_LONG_SIZE, &uintmax_suffix),
+        ),
+        ("INTPTR", ptrdiff_t, int_max(ptr_size, ptrdiff_suffix)),
+        ("UINTPTR", size_t.as_str(), uint_max(ptr_size, &size_suffix)),
+        ("WCHAR", wchar_t, int_max(int_size, "")),
+        ("WINT", wint_t.as_str(), uint_max(int_size, &wint_suffix)),
+        ("SIG_ATOMIC", wchar_t, int_max(int_size, "")),
+    ] {
+        define(&format!("__{}_TYPE__", name), ty);
+        define(&format!("__{}_MAX__", name), max);
+    }
+    define("__WCHAR_MIN__", "(-__WCHAR_MAX__ - 1)");
+    define("__WINT_MIN__", "0U");
+    define("__SIG_ATOMIC_MIN__", "(-__SIG_ATOMIC_MAX__ - 1)");
+    define("__SIZE_WIDTH__", &(ptr_size * CHAR_BIT).to_string());
+    define("__PTRDIFF_WIDTH__", &(ptr_size * CHAR_BIT).to_string());
+    define("__INTPTR_WIDTH__", &(ptr_size * CHAR_BIT).to_string());
+    define("__INTMAX_WIDTH__", &(LONG_LONG_SIZE * CHAR_BIT).to_string());
+    define("__WCHAR_WIDTH__", &(int_size * CHAR_BIT).to_string());
+    define("__CHAR16_TYPE__", &uint_type(model, SHORT_SIZE).0);
+    define("__CHAR32_TYPE__", &uint_type(model, int_size).0);
+    define("__INTMAX_C(c)", &constant(intmax_suffix));
+    define("__UINTMAX_C(c)", &constant(&uintmax_suffix));
+
+    // exact-width, least-width, and fast integer types
+    for &size in &[1, 2, 4, 8] {
+        let bits = size * CHAR_BIT;
+        let (int, int_suffix) = int_type(model, size);
+        let (uint, uint_suffix) = uint_type(model, size);
+        let max = int_max(size, int_suffix);
+        let umax = uint_max(size, &uint_suffix);
+        define(&format!("__INT{}_TYPE__", bits), int);
+        define(&format!("__UINT{}_TYPE__", bits), &uint);
+        define(&format!("__INT{}_MAX__", bits), &max);
+        define(&format!("__UINT{}_MAX__", bits), &umax);
+        define(&format!("__INT_LEAST{}_TYPE__", bits), int);
+        define(&format!("__UINT_LEAST{}_TYPE__", bits), &uint);
+        define(&format!("__INT_LEAST{}_MAX__", bits), &max);
+        define(&format!("__UINT_LEAST{}_MAX__", bits), &umax);
+        define(&format!("__INT_LEAST{}_WIDTH__", bits), &bits.to_string());
+        define(&format!("__INT{}_C(c)", bits), &constant(int_suffix));
+        define(&format!("__UINT{}_C(c)", bits), &constant(&uint_suffix));
+        // like glibc, `int_fast8_t` is a `char` and the others are as big as a pointer
+        let fast_size = if size == 1 { 1 } else { ptr_size };
+        let (fast, fast_suffix) = int_type(model, fast_size);
+        let (ufast, ufast_suffix) = uint_type(model, fast_size);
+        define(&format!("__INT_FAST{}_TYPE__", bits), fast);
+        define(&format!("__UINT_FAST{}_TYPE__", bits), &ufast);
+        define(
+            &format!("__INT_FAST{}_MAX__", bits),
+            &int_max(fast_size, fast_suffix),
+        );
+        define(
+            &format!("__UINT_FAST{}_MAX__", bits),
+            &uint_max(fast_size, &ufast_suffix),
+        );
+        define(
+            &format!("__INT_FAST{}_WIDTH__", bits),
+            &(fast_size * CHAR_BIT).to_string(),
+        );
+    }
+
+    // <float.h>
+    define("__FLT_RADIX__", "2");
+    define("__FLT_EVAL_METHOD__", "0");
+    define("__FLT_EVAL_METHOD_TS_18661_3__", "0");
+    // IEEE 754 binary32 and binary64, and either the x87 80-bit format, binary128, or binary64
+    let long_double_limits = match target.long_double_format() {
+        LongDoubleFormat::X87 => X87_LIMITS,
+        LongDoubleFormat::Binary128 => BINARY128_LIMITS,
+        LongDoubleFormat::Double => DOUBLE_AS_LONG_DOUBLE_LIMITS,
+    };
+    for (prefix, limits) in &[
+        ("FLT", FLOAT_LIMITS),
+        ("DBL", DOUBLE_LIMITS),
+        ("LDBL", long_double_limits),
+    ] {
+        for (name, value) in limits {
+            define(&format!("__{}_{}__", prefix, name), value);
+        }
+        define(&format!("__{}_HAS_DENORM__", prefix), "1");
+        define(&format!("__{}_HAS_INFINITY__", prefix), "1");
+        define(&format!("__{}_HAS_QUIET_NAN__", prefix), "1");
+    }
+    define("__DECIMAL_DIG__", "__LDBL_DECIMAL_DIG__");
+
+    macros
+}
+
+type FloatLimits = [(&'static str, &'static str); 11];
+
+const FLOAT_LIMITS: FloatLimits = [
+    ("MANT_DIG", "24"),
+    ("DIG", "6"),
+    ("MIN_EXP", "(-125)"),
+    ("MIN_10_EXP", "(-37)"),
+    ("MAX_EXP", "128"),
+    ("MAX_10_EXP", "38"),
+    ("DECIMAL_DIG", "9"),
+    ("MAX", "3.40282346638528859811704183484516925e+38F"),
+    ("MIN", "1.17549435082228750796873653722224568e-38F"),
+    ("EPSILON", "1.19209289550781250000000000000000000e-7F"),
+    ("DENORM_MIN", "1.40129846432481707092372958328991613e-45F"),
+];
+
+const DOUBLE_LIMITS: FloatLimits = [
+    ("MANT_DIG", "53"),
+    ("DIG", "15"),
+    ("MIN_EXP", "(-1021)"),
+    ("MIN_10_EXP", "(-307)"),
+    ("MAX_EXP", "1024"),
+    ("MAX_10_EXP", "308"),
+    ("DECIMAL_DIG", "17"),
+    ("MAX", "1.79769313486231570814527423731704357e+308"),
+    ("MIN", "2.22507385850720138309023271733240406e-308"),
+    ("EPSILON", "2.22044604925031308084726333618164062e-16"),
+    ("DENORM_MIN", "4.94065645841246544176568792820316939e-324"),
+];
+
+/// The same as `DOUBLE_LIMITS`, but with the `L` suffix.
+const DOUBLE_AS_LONG_DOUBLE_LIMITS: FloatLimits = [
+    ("MANT_DIG", "53"),
+    ("DIG", "15"),
+    ("MIN_EXP", "(-1021)"),
+    ("MIN_10_EXP", "(-307)"),
+    ("MAX_EXP", "1024"),
+    ("MAX_10_EXP", "308"),
+    ("DECIMAL_DIG", "17"),
+    ("MAX", "1.79769313486231570814527423731704357e+308L"),
+    ("MIN", "2.22507385850720138309023271733240406e-308L"),
+    ("EPSILON", "2.22044604925031308084726333618164062e-16L"),
+    ("DENORM_MIN", "4.94065645841246544176568792820316939e-324L"),
+];
+
+const X87_LIMITS: FloatLimits = [
+    ("MANT_DIG", "64"),
+    ("DIG", "18"),
+    ("MIN_EXP", "(-16381)"),
+    ("MIN_10_EXP", "(-4931)"),
+    ("MAX_EXP", "16384"),
+    ("MAX_10_EXP", "4932"),
+    ("DECIMAL_DIG", "21"),
+    ("MAX", "1.18973149535723176502126385303097021e+4932L"),
+    ("MIN", "3.36210314311209350626267781732175260e-4932L"),
+    ("EPSILON", "1.08420217248550443400745280086994171e-19L"),
+    ("DENORM_MIN", "3.64519953188247460252840593361941982e-4951L"),
+];
+
+const BINARY128_LIMITS: FloatLimits = [
+    ("MANT_DIG", "113"),
+    ("DIG", "33"),
+    ("MIN_EXP", "(-16381)"),
+    ("MIN_10_EXP", "(-4931)"),
+    ("MAX_EXP", "16384"),
+    ("MAX_10_EXP", "4932"),
+    ("DECIMAL_DIG", "36"),
+    ("MAX", "1.18973149535723176508575932662800702e+4932L"),
+    ("MIN", "3.36210314311209350626267781732175260e-4932L"),
+    ("EPSILON", "1.92592994438723585305597794258492732e-34L"),
+    ("DENORM_MIN", "6.47517511943802511092443895822764655e-4966L"),
+];
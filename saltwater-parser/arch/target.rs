@@ -0,0 +1,127 @@
+//! The platform being compiled for.
+
+use target_lexicon::{Architecture, CallingConvention, OperatingSystem, PointerWidth, Triple};
+
+use super::x64::DOUBLE_SIZE;
+
+use crate::data::Type;
+
+/// The sizes of `int`, `long`, and pointers, which vary between platforms.
+///
+/// https://en.wikipedia.org/wiki/64-bit_computing#64-bit_data_models
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum DataModel {
+    /// `int`, `long`, and pointers are 32 bits, as on 32-bit Linux and Windows.
+    ILP32,
+    /// `long` and pointers are 64 bits, as on 64-bit Linux and macOS.
+    LP64,
+    /// Pointers are 64 bits, but `long` is 32 bits, as on 64-bit Windows.
+    LLP64,
+}
+
+impl DataModel {
+    /// The data model the C ABI uses for `triple`.
+    pub fn for_triple(triple: &Triple) -> DataModel {
+        match triple.pointer_width() {
+            Ok(PointerWidth::U64) if triple.operating_system == OperatingSystem::Windows => {
+                DataModel::LLP64
+            }
+            Ok(PointerWidth::U64) => DataModel::LP64,
+            _ => DataModel::ILP32,
+        }
+    }
+    /// The size of `int` in bytes.
+    pub fn int_size(self) -> u16 {
+        4
+    }
+    /// The size of `long` in bytes.
+    pub fn long_size(self) -> u16 {
+        match self {
+            DataModel::LP64 => 8,
+            DataModel::ILP32 | DataModel::LLP64 => 4,
+        }
+    }
+    /// The size of a pointer in bytes.
+    pub fn ptr_size(self) -> u16 {
+        match self {
+            DataModel::ILP32 => 4,
+            DataModel::LP64 | DataModel::LLP64 => 8,
+        }
+    }
+}
+
+/// The representation of `long double`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LongDoubleFormat {
+    /// The x87 80-bit extended precision format, in the low 10 bytes of 16.
+    X87,
+    /// IEEE 754 binary128 (quadruple precision), as on AArch64 and RISC-V.
+    Binary128,
+    /// The same as `double`, as in Apple's AArch64 ABI.
+    Double,
+}
+
+/// Information about the platform being compiled for.
+///
+/// This comes from `Opt::target`, and is passed to everything that depends on the sizes of types.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TargetInfo {
+    /// The target triple.
+    ///
+    /// A "target triple" is used to represent information about a compiler target.
+    /// Traditionaly, the target triple uses this format: `<architecture>-<vendor>-<operating system>`
+    /// The target triple is represented as a struct and contains additional
+    /// information like ABI and endianness.
+    pub triple: Triple,
+    /// The sizes of the integer and pointer types.
+    pub data_model: DataModel,
+}
+
+impl TargetInfo {
+    /// The target for `triple`, using the data model of its C ABI.
+    pub fn new(triple: Triple) -> TargetInfo {
+        let data_model = DataModel::for_triple(&triple);
+        TargetInfo { triple, data_model }
+    }
+    /// The target for the machine saltwater is running on.
+    pub fn host() -> TargetInfo {
+        TargetInfo::new(Triple::host())
+    }
+    /// The representation of `long double` in the C ABI for this target.
+    pub fn long_double_format(&self) -> LongDoubleFormat {
+        match self.triple.architecture {
+            Architecture::X86_64 | Architecture::X86_32(_) => LongDoubleFormat::X87,
+            _ if self.triple.default_calling_convention()
+                == Ok(CallingConvention::AppleAarch64) =>
+            {
+                LongDoubleFormat::Double
+            }
+            _ => LongDoubleFormat::Binary128,
+        }
+    }
+    /// The size of `long double` in bytes.
+    pub fn long_double_size(&self) -> u16 {
+        match self.long_double_format() {
+            LongDoubleFormat::X87 | LongDoubleFormat::Binary128 => 16,
+            LongDoubleFormat::Double => DOUBLE_SIZE,
+        }
+    }
+    /// The type of `sizeof` expressions: an unsigned integer as large as a pointer.
+    pub fn size_t(&self) -> Type {
+        let size = self.data_model.ptr_size();
+        if size == self.data_model.int_size() {
+            Type::Int(false)
+        } else if size == self.data_model.long_size() {
+            Type::Long(false)
+        } else {
+            Type::LongLong(false)
+        }
+    }
+}
+
+impl Default for TargetInfo {
+    fn default() -> Self {
+        TargetInfo::host()
+    }
+}
@@ -1,4 +1,6 @@
-//! https://en.wikipedia.org/wiki/64-bit_computing#64-bit_data_models
+//! Sizes which are the same for every data model.
+//!
+//! The sizes of `int`, `long`, and pointers are in `DataModel`.
 #![allow(missing_docs)]
 
 #[allow(non_camel_case_types)]
@@ -9,11 +11,12 @@ pub const SIZE_MAX: SIZE_T = SIZE_T::MAX;
 pub const FLOAT_SIZE: u16 = 4;
 pub const DOUBLE_SIZE: u16 = 8;
 
-pub const LONG_SIZE: u16 = 8;
-pub const INT_SIZE: u16 = 4;
+pub const LONG_LONG_SIZE: u16 = 8;
 pub const SHORT_SIZE: u16 = 2;
 pub const BOOL_SIZE: u16 = 1;
 
-pub const PTR_SIZE: u16 = 8;
+/// The size of `struct __va_list_tag` in the System V ABI:
+/// two `unsigned` offsets followed by two pointers.
+pub const VA_LIST_SIZE: u16 = 24;
 
 pub const CHAR_BIT: u16 = 8; // number of bits in a byte
@@ -11,6 +11,15 @@ pub type Program = Vec<Declaration>;
 pub enum ExternalDeclaration {
     Function(FunctionDefinition),
     Declaration(Declaration),
+    StaticAssert(StaticAssert),
+}
+
+/// 6.7.10 Static assertions: `_Static_assert(sizeof(int) == 4, "int must be 4 bytes");`
+#[derive(Clone, Debug, PartialEq)]
+pub struct StaticAssert {
+    pub condition: Expr,
+    /// The bytes of the string literal, including the null terminator
+    pub message: Vec<u8>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -52,6 +61,10 @@ pub enum DeclarationSpecifier {
     // NOTE: _not_ the same as UnitSpecifier::Typedef
     // that represents the `typedef` keyword, this represents a name that has been typedef-ed
     Typedef(InternedStr),
+    // _Alignas(16)
+    AlignasExpr(Box<Expr>),
+    // _Alignas(double)
+    AlignasType(Box<TypeName>),
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -103,7 +116,17 @@ pub struct StructSpecifier {
     pub name: Option<InternedStr>,
     /// Some([]): `struct s {}`
     /// None: `struct s;`
-    pub members: Option<Vec<StructDeclarationList>>,
+    pub members: Option<Vec<StructDeclaration>>,
+    /// The maximum alignment of members set by `#pragma pack` where the struct was defined
+    ///
+    /// This is a `u16` to keep `UnitSpecifier` (and so `Error`) small; `#pragma pack` is at most 16.
+    pub packed: Option<u16>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum StructDeclaration {
+    Members(StructDeclarationList),
+    StaticAssert(StaticAssert),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -134,7 +157,15 @@ pub struct InitDeclarator {
 #[derive(Clone, Debug, PartialEq)]
 pub enum Initializer {
     Scalar(Box<Expr>),
-    Aggregate(Vec<Initializer>),
+    // { 1, .x = 2, [3] = 4 }
+    Aggregate(Vec<(Vec<Designator>, Initializer)>),
+}
+
+/// 6.7.9 designation: `.x` or `[3]` in `{ .x = 1, [3] = 4 }`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Designator {
+    Member(InternedStr),
+    Index(Expr),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -201,6 +232,7 @@ pub enum StmtType {
     Break,
     Return(Option<Expr>),
     Decl(Declaration),
+    StaticAssert(StaticAssert),
 }
 
 pub type Expr = Locatable<ExprType>;
@@ -220,6 +252,9 @@ pub enum ExprType {
     // a[i]
     Index(Box<Expr>, Box<Expr>),
 
+    // (struct point){ 1, 2 }
+    CompoundLiteral(TypeName, Box<Initializer>),
+
     // prefix
     PreIncrement(Box<Expr>, bool),
     Cast(TypeName, Box<Expr>),
@@ -256,6 +291,18 @@ pub enum ExprType {
     // Ternary: if ? then : else
     Ternary(Box<Expr>, Box<Expr>, Box<Expr>),
     Comma(Box<Expr>, Box<Expr>),
+    // _Generic(x, int: 1, default: 2)
+    // `None` is the `default` association
+    Generic(Box<Expr>, Vec<(Option<TypeName>, Expr)>),
+
+    // variadic intrinsics, see `stdarg.h`
+    // __builtin_va_start(ap, last_param)
+    VaStart(Box<Expr>, Box<Expr>),
+    // __builtin_va_arg(ap, type)
+    VaArg(Box<Expr>, TypeName),
+    VaEnd(Box<Expr>),
+    // __builtin_va_copy(dest, src)
+    VaCopy(Box<Expr>, Box<Expr>),
 }
 
 impl Default for StmtType {
@@ -281,6 +328,22 @@ impl Display for StructSpecifier {
     }
 }
 
+impl Display for StructDeclaration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StructDeclaration::Members(members) => write!(f, "{}", members),
+            StructDeclaration::StaticAssert(assert) => write!(f, "{}", assert),
+        }
+    }
+}
+
+impl Display for StaticAssert {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = LiteralValue::Str(self.message.clone());
+        write!(f, "_Static_assert({}, {});", self.condition, message)
+    }
+}
+
 impl Display for StructDeclarationList {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{} ", joined(&self.specifiers, " "))?;
@@ -305,6 +368,7 @@ impl Display for ExternalDeclaration {
         match self {
             ExternalDeclaration::Declaration(decl) => write!(f, "{}", decl),
             ExternalDeclaration::Function(func) => write!(f, "{}", func),
+            ExternalDeclaration::StaticAssert(assert) => write!(f, "{}", assert),
         }
     }
 }
@@ -345,6 +409,13 @@ impl Display for Initializer {
         match self {
             Initializer::Scalar(expr) => write!(f, "{}", expr),
             Initializer::Aggregate(items) => {
+                let items = items.iter().map(|(designators, init)| {
+                    if designators.is_empty() {
+                        init.to_string()
+                    } else {
+                        format!("{} = {}", joined(designators, ""), init)
+                    }
+                });
                 write!(f, "{{ ")?;
                 write!(f, "{}", joined(items, ", "))?;
                 write!(f, " }}")
@@ -353,6 +424,15 @@ impl Display for Initializer {
     }
 }
 
+impl Display for Designator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Designator::Member(id) => write!(f, ".{}", id),
+            Designator::Index(index) => write!(f, "[{}]", index),
+        }
+    }
+}
+
 impl Display for DeclarationSpecifier {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use DeclarationSpecifier::*;
@@ -384,6 +464,8 @@ impl Display for DeclarationSpecifier {
             Union(spec) => write!(f, "union {}", spec),
             Struct(spec) => write!(f, "struct {}", spec),
             Typedef(name) => write!(f, "{}", name),
+            AlignasExpr(expr) => write!(f, "_Alignas({})", expr),
+            AlignasType(ctype) => write!(f, "_Alignas({})", ctype),
         }
     }
 }
@@ -615,6 +697,7 @@ impl StmtType {
                 body.data.pretty_print(f, depth)
             }
             StmtType::Decl(decls) => write!(f, "{}", decls),
+            StmtType::StaticAssert(assert) => write!(f, "{}", assert),
             StmtType::Compound(stmts) => pretty_print_compound(f, stmts, depth),
             StmtType::Switch(condition, body) => write!(f, "switch ({}) {}", condition, body.data),
         }
@@ -625,6 +708,18 @@ impl Display for Expr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match &self.data {
             ExprType::Comma(left, right) => write!(f, "{}, {}", *left, *right),
+            ExprType::Generic(controlling, associations) => {
+                let associations = associations.iter().map(|(ctype, expr)| match ctype {
+                    Some(ctype) => format!("{}: {}", ctype, expr),
+                    None => format!("default: {}", expr),
+                });
+                write!(
+                    f,
+                    "_Generic({}, {})",
+                    controlling,
+                    joined(associations, ", ")
+                )
+            }
             ExprType::Literal(token) => write!(f, "{}", token),
             ExprType::Id(symbol) => write!(f, "{}", symbol),
             ExprType::Add(left, right) => write!(f, "({}) + ({})", left, right),
@@ -652,6 +747,7 @@ impl Display for Expr {
             }
             ExprType::FuncCall(left, params) => write!(f, "({})({})", left, joined(params, ", ")),
             ExprType::Cast(ctype, expr) => write!(f, "({})({})", ctype, expr),
+            ExprType::CompoundLiteral(ctype, init) => write!(f, "({}){}", ctype, init),
             ExprType::Member(compound, id) => write!(f, "({}).{}", compound, id),
             ExprType::DerefMember(compound, id) => write!(f, "({})->{}", compound, id),
             ExprType::PreIncrement(expr, inc) => {
@@ -667,6 +763,10 @@ impl Display for Expr {
             ExprType::SizeofType(ty) => write!(f, "sizeof({})", ty),
             ExprType::AlignofExpr(expr) => write!(f, "alignof({})", expr),
             ExprType::AlignofType(ty) => write!(f, "alignof({})", ty),
+            ExprType::VaStart(ap, param) => write!(f, "__builtin_va_start({}, {})", ap, param),
+            ExprType::VaArg(ap, ty) => write!(f, "__builtin_va_arg({}, {})", ap, ty),
+            ExprType::VaEnd(ap) => write!(f, "__builtin_va_end({})", ap),
+            ExprType::VaCopy(dest, src) => write!(f, "__builtin_va_copy({}, {})", dest, src),
         }
     }
 }
@@ -1,4 +1,4 @@
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
 use thiserror::Error;
 
 use super::hir::Expr;
@@ -99,6 +99,10 @@ pub enum Error {
 
     #[error("invalid token: {0}")]
     Lex(#[from] LexError),
+
+    /// A warning that was turned into an error with `-Werror`
+    #[error("{0} [-Werror={}]", .0.flag())]
+    Warning(Warning),
 }
 
 /// Semantic errors are non-exhaustive and may have new variants added at any time
@@ -139,6 +143,18 @@ pub enum SemanticError {
     #[error("cannot have empty program")]
     EmptyProgram,
 
+    #[error("static assertion failed: {0}")]
+    StaticAssertFailed(String),
+
+    #[error("requested alignment {0} is not a power of two")]
+    AlignmentNotPowerOfTwo(u64),
+
+    #[error("requested alignment {0} is less than the minimum alignment {1} of '{2}'")]
+    AlignmentTooSmall(u64, u64, Type),
+
+    #[error("'_Alignas' cannot be used for {0}")]
+    IllegalAlignas(&'static str),
+
     // Declarator errors
     #[error("expected an integer")]
     NonIntegralLength,
@@ -243,6 +259,12 @@ pub enum SemanticError {
     #[error("incompatible types in ternary expression: '{0}' cannot be converted to '{1}'")]
     IncompatibleTypes(Type, Type),
 
+    #[error("expected 'va_list', got '{0}'")]
+    NotAVaList(Type),
+
+    #[error("va_arg cannot read values of type '{0}' (expected an integer, pointer, or double)")]
+    InvalidVaArg(Type),
+
     // const fold errors
     #[error("{} overflow in expresson", if *(.is_positive) { "positive" } else { "negative" })]
     ConstOverflow { is_positive: bool },
@@ -313,6 +335,24 @@ pub enum SemanticError {
     #[error("too many initializers (declared with {0} elements, found {1})")]
     TooManyMembers(usize, usize),
 
+    #[error("array designator used to initialize non-array type '{0}'")]
+    IndexDesignatorNotArray(Type),
+
+    #[error("array designator index {0} is past the end of an array with {1} elements")]
+    DesignatorOutOfBounds(u64, u64),
+
+    #[error("compound literals cannot have {0}")]
+    InvalidCompoundLiteral(&'static str),
+
+    #[error("controlling expression type '{0}' is not compatible with any generic association")]
+    NoGenericMatch(Type),
+
+    #[error("type '{0}' in generic association is compatible with a previous association")]
+    DuplicateGenericType(Type),
+
+    #[error("duplicate default association in generic selection")]
+    DuplicateGenericDefault,
+
     // Function definition errors
     #[error("illegal storage class {0} for function (only `static` and `extern` are allowed)")]
     InvalidFuncStorageClass(StorageClass),
@@ -397,6 +437,12 @@ pub enum SyntaxError {
     #[error("`static` for array sizes is only allowed in function declarations")]
     StaticInConcreteArray,
 
+    #[error("expected string literal for static assertion message")]
+    ExpectedStaticAssertMessage,
+
+    #[error("_Generic must have at least one association")]
+    EmptyGeneric,
+
     #[error("overflow while parsing {}integer literal",
         if let Some(signed) = .is_signed {
             if *signed { "signed "} else { "unsigned "}
@@ -511,6 +557,10 @@ pub enum CppError {
     #[error("'##' cannot appear at {} of macro expansion", if *(.0) { "start" } else { "end"})]
     HashHashMissingParameter(bool),
 
+    /// `__VA_ARGS__` or `__VA_OPT__` was used outside the body of a variadic macro
+    #[error("{0} can only appear in the expansion of a variadic macro")]
+    VaArgsOutsideVariadic(InternedStr),
+
     /// The result of '##' is not a valid token
     #[error("token pasting formed '{0}{1}', an invalid preprocessing token")]
     HashHashInvalid(Token, Token),
@@ -589,11 +639,12 @@ pub enum Warning {
     #[error("declaration does not declare anything")]
     EmptyDeclaration,
 
-    #[error("{} does not support #pragma", env!("CARGO_PKG_NAME"))]
+    #[error("{} does not support this #pragma", env!("CARGO_PKG_NAME"))]
     IgnoredPragma,
 
-    #[error("variadic macros are not yet supported")]
-    IgnoredVariadic,
+    /// `#pragma message("...")`
+    #[error("#pragma message: {0}")]
+    PragmaMessage(String),
 
     #[error("implicit int is deprecated and may be removed in a future release")]
     ImplicitInt,
@@ -602,6 +653,173 @@ pub enum Warning {
     ExtraneousExtern,
 }
 
+impl Warning {
+    /// The name of every warning, as used in `-W<name>` and `-Wno-<name>`.
+    pub const FLAGS: &'static [&'static str] = &[
+        "generic",
+        "cpp",
+        "extra-semi",
+        "ignored-qualifiers",
+        "duplicate-decl-specifier",
+        "ignored-cast-qualifiers",
+        "empty-declaration",
+        "unknown-pragmas",
+        "implicit-int",
+        "extern-initializer",
+        "pragma-messages",
+    ];
+
+    /// Look up the name of a warning, returning the same name as `flag` would.
+    pub fn find_flag(name: &str) -> Option<&'static str> {
+        Warning::FLAGS.iter().copied().find(|&known| known == name)
+    }
+    /// The name of the flag that controls this warning.
+    ///
+    /// These are stable and follow GCC and Clang where they have an equivalent warning.
+    pub fn flag(&self) -> &'static str {
+        use Warning::*;
+        match self {
+            Generic(_) => "generic",
+            User(_) => "cpp",
+            ExtraneousSemicolon(_) => "extra-semi",
+            FunctionQualifiersIgnored(_) => "ignored-qualifiers",
+            DuplicateSpecifier(..) => "duplicate-decl-specifier",
+            IgnoredQualifier(_) => "ignored-cast-qualifiers",
+            EmptyDeclaration => "empty-declaration",
+            IgnoredPragma => "unknown-pragmas",
+            ImplicitInt => "implicit-int",
+            ExtraneousExtern => "extern-initializer",
+            PragmaMessage(_) => "pragma-messages",
+        }
+    }
+    /// The stage of compilation that found this warning, using the same names as `Error::category`.
+    ///
+    /// `Generic` warnings don't record where they came from and are reported as "semantic".
+    pub fn category(&self) -> &'static str {
+        use Warning::*;
+        match self {
+            User(_) | IgnoredPragma | PragmaMessage(_) => "cpp",
+            ExtraneousSemicolon(_) | DuplicateSpecifier(..) | EmptyDeclaration => "syntax",
+            Generic(_)
+            | FunctionQualifiersIgnored(_)
+            | IgnoredQualifier(_)
+            | ImplicitInt
+            | ExtraneousExtern => "semantic",
+        }
+    }
+}
+
+/// How a warning is reported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WarningLevel {
+    Ignored,
+    Warning,
+    Error,
+}
+
+/// A part of a file where `#pragma GCC diagnostic` changed how a warning is reported.
+///
+/// Only warnings in the same file as the pragma are affected, not files it `#include`s.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiagnosticRegion {
+    /// The file containing the pragma
+    pub file: codespan::FileId,
+    /// The byte offset just after the pragma
+    pub start: u32,
+    /// The byte offset of the `#pragma GCC diagnostic pop` that ended the region,
+    /// or `u32::MAX` if the region lasts until the end of the file.
+    pub end: u32,
+    /// The name of the warning, as returned by `Warning::flag`
+    pub flag: &'static str,
+    pub level: WarningLevel,
+}
+
+impl DiagnosticRegion {
+    fn contains(&self, location: Location) -> bool {
+        self.file == location.file && (self.start..self.end).contains(&location.span.start)
+    }
+}
+
+/// Which warnings are emitted, and which are treated as errors.
+///
+/// Every warning is enabled by default.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WarningOptions {
+    /// Warnings disabled with `-Wno-<name>`
+    disabled: HashSet<&'static str>,
+    /// Warnings turned into errors with `-Werror=<name>`
+    errors: HashSet<&'static str>,
+    /// Whether `-Werror` was given
+    all_errors: bool,
+    /// Overrides from `#pragma GCC diagnostic`, in the order they appeared
+    regions: Vec<DiagnosticRegion>,
+}
+
+impl WarningOptions {
+    /// Apply a `-W` flag, without the leading `-W`.
+    ///
+    /// The flags are `all`, `error`, `<name>`, `no-<name>` and `error=<name>`,
+    /// where `<name>` is one of `Warning::FLAGS`.
+    /// Flags are applied in order, so `-Wno-<name> -W<name>` enables `<name>`.
+    /// Like GCC, unknown names are ignored for `-Wno-<name>`, but are an error otherwise.
+    pub fn apply_flag(&mut self, flag: &str) -> Result<(), String> {
+        let lookup = |name: &str| {
+            Warning::find_flag(name).ok_or_else(|| format!("unknown warning option '-W{}'", flag))
+        };
+        match flag {
+            // everything is enabled by default
+            "all" => {}
+            "error" => self.all_errors = true,
+            _ => {
+                if let Some(name) = flag.strip_prefix("error=") {
+                    let name = lookup(name)?;
+                    self.disabled.remove(name);
+                    self.errors.insert(name);
+                } else if let Some(name) = flag.strip_prefix("no-") {
+                    if let Ok(name) = lookup(name) {
+                        self.disabled.insert(name);
+                    }
+                } else {
+                    self.disabled.remove(lookup(flag)?);
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Whether `warning` should be emitted.
+    pub fn is_enabled(&self, warning: &Warning) -> bool {
+        !self.disabled.contains(warning.flag())
+    }
+    /// Whether `warning` should be treated as an error.
+    pub fn is_error(&self, warning: &Warning) -> bool {
+        self.is_enabled(warning) && (self.all_errors || self.errors.contains(warning.flag()))
+    }
+    /// Add regions found by the preprocessor. These take precedence over the command line.
+    pub fn add_regions<I: IntoIterator<Item = DiagnosticRegion>>(&mut self, regions: I) {
+        self.regions.extend(regions);
+    }
+    /// How `warning` should be reported, taking its location into account.
+    ///
+    /// If several regions contain the warning, the last one to start wins.
+    pub fn level(&self, warning: &CompileWarning) -> WarningLevel {
+        let flag = warning.data.flag();
+        let region = self
+            .regions
+            .iter()
+            .rev()
+            .find(|region| region.flag == flag && region.contains(warning.location));
+        if let Some(region) = region {
+            region.level
+        } else if self.is_error(&warning.data) {
+            WarningLevel::Error
+        } else if self.is_enabled(&warning.data) {
+            WarningLevel::Warning
+        } else {
+            WarningLevel::Ignored
+        }
+    }
+}
+
 impl<T: Into<String>> From<T> for Warning {
     fn from(msg: T) -> Warning {
         Warning::Generic(msg.into())
@@ -624,6 +842,23 @@ impl CompileError {
 }
 
 impl Error {
+    /// The stage of compilation that found this error: "lex", "cpp", "syntax", or "semantic".
+    pub fn category(&self) -> &'static str {
+        match self {
+            Error::Lex(_) => "lex",
+            Error::PreProcessor(_) => "cpp",
+            Error::Syntax(_) => "syntax",
+            Error::Semantic(_) => "semantic",
+            Error::Warning(warning) => warning.category(),
+        }
+    }
+    /// The warning flag that turned this into an error, if it was promoted with `-Werror`.
+    pub fn flag(&self) -> Option<&'static str> {
+        match self {
+            Error::Warning(warning) => Some(warning.flag()),
+            _ => None,
+        }
+    }
     pub fn is_lex_err(&self) -> bool {
         if let Error::Lex(_) = self {
             true
@@ -735,4 +970,59 @@ mod tests {
             "invalid program: bad code"
         );
     }
+
+    #[test]
+    fn test_warning_flags() {
+        let warnings = [
+            Warning::Generic("".into()),
+            Warning::User(Vec::new()),
+            Warning::ExtraneousSemicolon(""),
+            Warning::FunctionQualifiersIgnored(Default::default()),
+            Warning::DuplicateSpecifier(ast::UnitSpecifier::Int, 2),
+            Warning::IgnoredQualifier(Default::default()),
+            Warning::EmptyDeclaration,
+            Warning::IgnoredPragma,
+            Warning::ImplicitInt,
+            Warning::ExtraneousExtern,
+            Warning::PragmaMessage(String::new()),
+        ];
+        let flags: Vec<_> = warnings.iter().map(Warning::flag).collect();
+        assert_eq!(flags, Warning::FLAGS);
+    }
+
+    #[test]
+    fn test_warning_options() {
+        let apply = |flags: &[&str]| {
+            let mut options = WarningOptions::default();
+            for flag in flags {
+                options.apply_flag(flag)?;
+            }
+            Ok::<_, String>(options)
+        };
+        let implicit = Warning::ImplicitInt;
+        let extern_init = Warning::ExtraneousExtern;
+
+        let options = apply(&["all"]).unwrap();
+        assert!(options.is_enabled(&implicit) && !options.is_error(&implicit));
+        assert_eq!(options, WarningOptions::default());
+
+        let options = apply(&["no-implicit-int", "no-not-a-warning"]).unwrap();
+        assert!(!options.is_enabled(&implicit));
+        assert!(options.is_enabled(&extern_init));
+
+        let options = apply(&["no-implicit-int", "implicit-int"]).unwrap();
+        assert!(options.is_enabled(&implicit));
+
+        let options = apply(&["error=implicit-int"]).unwrap();
+        assert!(options.is_error(&implicit));
+        assert!(!options.is_error(&extern_init));
+
+        // disabled warnings are never errors
+        let options = apply(&["error", "no-implicit-int"]).unwrap();
+        assert!(!options.is_error(&implicit));
+        assert!(options.is_error(&extern_init));
+
+        assert!(apply(&["not-a-warning"]).is_err());
+        assert!(apply(&["error=not-a-warning"]).is_err());
+    }
 }
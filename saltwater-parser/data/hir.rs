@@ -51,9 +51,21 @@ pub struct Declaration {
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Initializer {
-    Scalar(Box<Expr>),                 // int i = 5;
-    InitializerList(Vec<Initializer>), // int a[] = { 1, 2, 3 };
-    FunctionBody(Vec<Stmt>),           // int f() { return 0; }
+    Scalar(Box<Expr>),                               // int i = 5;
+    InitializerList(Vec<(Designator, Initializer)>), // int a[] = { 1, [5] = 3 };
+    FunctionBody(Vec<Stmt>),                         // int f() { return 0; }
+}
+
+/// The subobject initialized by an element of an initializer list.
+///
+/// Designators are always resolved by the analyzer, even if the original initializer had none.
+/// Subobjects that do not appear in the list are initialized to zero.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Designator {
+    /// An element of an array, or the only 'element' of a scalar: `int i = { 1 };`
+    Index(u64),
+    /// A member of a struct or union
+    Member(InternedStr),
 }
 
 /// Holds the metadata for an expression.
@@ -129,6 +141,10 @@ pub enum ExprType {
     Literal(LiteralValue),
     FuncCall(Box<Expr>, Vec<Expr>),
     Member(Box<Expr>, InternedStr),
+    // (struct point){ 1, 2 } at block scope.
+    // The symbol is never declared separately;
+    // its storage is allocated the first time the literal is compiled.
+    CompoundLiteral(Symbol, Box<Initializer>),
 
     // unary expressions
     // post increment/decrement
@@ -151,6 +167,15 @@ pub enum ExprType {
     StaticRef(Box<Expr>),
     // used to work around various bugs, see places this is constructed for details
     Noop(Box<Expr>),
+
+    // variadic intrinsics
+    // each `Expr` is a pointer to a `va_list`
+    VaStart(Box<Expr>),
+    // the type of the argument is the type of the whole expression
+    VaArg(Box<Expr>),
+    VaEnd(Box<Expr>),
+    // dest, src
+    VaCopy(Box<Expr>, Box<Expr>),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -160,6 +185,9 @@ pub enum LiteralValue {
     Int(i64),
     UnsignedInt(u64),
     Float(f64),
+    /// A `long double`, which may not fit in an `f64`
+    #[cfg_attr(test, proptest(skip))]
+    LongDouble(LongDoubleValue),
     Str(Vec<u8>),
     Char(u8),
 }
@@ -218,6 +246,18 @@ pub struct Variable {
     pub storage_class: StorageClass,
     pub qualifiers: Qualifiers,
     pub id: InternedStr,
+    /// The width in bits of a struct member declared as a bitfield, e.g. `int i: 5`.
+    ///
+    /// This is always `None` for anything other than struct and union members.
+    pub bitfield: Option<u16>,
+    /// The alignment in bytes requested with `_Alignas`, e.g. `_Alignas(16) char buf[16]`.
+    ///
+    /// This is never less strict than the alignment of `ctype`.
+    pub alignment: Option<u64>,
+    /// The maximum alignment in bytes of a struct member, set by `#pragma pack`.
+    ///
+    /// This is always `None` for anything other than struct and union members.
+    pub packed: Option<u64>,
 }
 
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
@@ -242,6 +282,13 @@ impl Qualifiers {
     pub(crate) fn has_func_qualifiers(self) -> bool {
         self.func.inline || self.func.no_return
     }
+    /// The object qualifiers (`const` and `volatile`), without `inline` or `_Noreturn`.
+    pub(crate) fn without_func(self) -> Qualifiers {
+        Qualifiers {
+            func: FunctionQualifiers::default(),
+            ..self
+        }
+    }
     // TODO: this should just be a Default
     pub const NONE: Qualifiers = Qualifiers {
         c_const: false,
@@ -257,7 +304,7 @@ impl Display for FunctionQualifiers {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match (self.inline, self.no_return) {
             (true, true) => write!(f, "{} {}", Keyword::Inline, Keyword::NoReturn),
-            (true, false) => write!(f, "{}", Keyword::NoReturn),
+            (true, false) => write!(f, "{}", Keyword::Inline),
             (false, true) => write!(f, "{}", Keyword::NoReturn),
             (false, false) => Ok(()),
         }
@@ -379,6 +426,7 @@ impl Display for Expr {
             }
             ExprType::FuncCall(left, params) => write!(f, "({})({})", left, joined(params, ", ")),
             ExprType::Cast(expr) => write!(f, "({})({})", self.ctype, expr),
+            ExprType::CompoundLiteral(_, init) => write!(f, "({}){}", self.ctype, init),
             ExprType::Sizeof(ty) => write!(f, "sizeof({})", ty),
             ExprType::Member(compound, id) => write!(f, "({}).{}", compound, id),
             ExprType::PostIncrement(expr, inc) => {
@@ -386,6 +434,10 @@ impl Display for Expr {
             }
             ExprType::StaticRef(expr) => write!(f, "&{}", expr),
             ExprType::Noop(expr) => write!(f, "{}", expr),
+            ExprType::VaStart(ap) => write!(f, "__builtin_va_start({})", ap),
+            ExprType::VaArg(ap) => write!(f, "__builtin_va_arg({}, {})", ap, self.ctype),
+            ExprType::VaEnd(ap) => write!(f, "__builtin_va_end({})", ap),
+            ExprType::VaCopy(dest, src) => write!(f, "__builtin_va_copy({}, {})", dest, src),
         }
     }
 }
@@ -395,6 +447,9 @@ impl Display for Initializer {
         match self {
             Initializer::Scalar(expr) => write!(f, "{}", expr),
             Initializer::InitializerList(list) => {
+                let list = list
+                    .iter()
+                    .map(|(designator, init)| format!("{} = {}", designator, init));
                 write!(f, "{{ ")?;
                 write!(f, "{}", joined(list, ", "),)?;
                 write!(f, " }}")
@@ -410,6 +465,15 @@ impl Display for Initializer {
     }
 }
 
+impl Display for Designator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Designator::Index(index) => write!(f, "[{}]", index),
+            Designator::Member(id) => write!(f, ".{}", id),
+        }
+    }
+}
+
 impl Display for StmtType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.pretty_print(f, 0)
@@ -485,13 +549,18 @@ impl StmtType {
 
 impl Display for Variable {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if self.qualifiers != Qualifiers::default() {
-            write!(f, "{} ", self.qualifiers)?;
+        if self.qualifiers.has_func_qualifiers() {
+            write!(f, "{} ", self.qualifiers.func)?;
         }
         if self.storage_class != StorageClass::default() {
             write!(f, "{} ", self.storage_class)?;
         }
-        super::types::print_type(&self.ctype, Some(self.id), f)
+        let qualifiers = self.qualifiers.without_func();
+        super::types::print_type(&self.ctype, qualifiers, Some(self.id), f)?;
+        if let Some(width) = self.bitfield {
+            write!(f, ": {}", width)?;
+        }
+        Ok(())
     }
 }
 
@@ -507,13 +576,7 @@ impl Display for Declaration {
                 writeln!(f, "}}")
             }
             Some(Initializer::Scalar(expr)) => write!(f, " = {};", expr),
-            Some(Initializer::InitializerList(inits)) => {
-                write!(f, " = {{")?;
-                for init in inits {
-                    write!(f, "{}, ", init)?;
-                }
-                write!(f, "}};")
-            }
+            Some(init @ Initializer::InitializerList(_)) => write!(f, " = {};", init),
             None => write!(f, ";"),
         }
     }
@@ -537,6 +600,7 @@ impl std::fmt::Display for LiteralValue {
             Int(i) => write!(f, "{}", i),
             UnsignedInt(u) => write!(f, "{}", u),
             Float(n) => write!(f, "{}", n),
+            LongDouble(n) => write!(f, "{}", n),
             Str(s) => {
                 let mut escaped = s
                     .iter()
@@ -134,6 +134,10 @@ pub enum Keyword {
     StaticAssert,
     Alignas,
     Alignof,
+    VaStart,
+    VaArg,
+    VaEnd,
+    VaCopy,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -346,6 +350,10 @@ impl std::fmt::Display for Keyword {
             Keyword::ThreadLocal => write!(f, "_Thread_local"),
             Keyword::StaticAssert => write!(f, "_Static_assert"),
             Keyword::VaList => write!(f, "va_list"),
+            Keyword::VaStart => write!(f, "__builtin_va_start"),
+            Keyword::VaArg => write!(f, "__builtin_va_arg"),
+            Keyword::VaEnd => write!(f, "__builtin_va_end"),
+            Keyword::VaCopy => write!(f, "__builtin_va_copy"),
             _ => write!(f, "{}", &format!("{:?}", self).to_lowercase()),
         }
     }
@@ -0,0 +1,853 @@
+//! `long double` constants.
+//!
+//! `long double` is wider than `f64` on most targets, so its constants can't be stored or
+//! folded in an `f64` without losing both precision and range (`LDBL_MAX` would become infinity).
+//! Instead, `LongDoubleValue` is a small software float with a 128-bit significand.
+//!
+//! Literals are parsed into the full 128 bits, rounding to odd, and are only rounded to
+//! the target's format (see `LongDoubleFormat`) when they are used.
+//! Rounding to odd first and then to nearest gives the same result as rounding to nearest
+//! directly, as long as the first precision is at least two bits more than the second;
+//! every format has at most 113 bits, so the intermediate value never changes the result.
+//!
+//! Arithmetic rounds its operands and its result to the target's format,
+//! so that folding gives the same value as doing the operation at runtime.
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Neg;
+
+use crate::arch::LongDoubleFormat;
+
+/// The exponent given to literals too large for any format, which round to infinity.
+const HUGE_EXPONENT: i32 = 1 << 20;
+/// The number of decimal digits beyond which a literal is infinite or zero in every format.
+const DECIMAL_EXPONENT_LIMIT: i64 = 5000;
+/// The number of bits the quotient of a division needs, to round to odd at 128 bits
+const QUOTIENT_BITS: u32 = 130;
+
+/// A constant of type `long double`.
+#[derive(Copy, Clone, Debug)]
+pub struct LongDoubleValue {
+    negative: bool,
+    class: Class,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Class {
+    Zero,
+    /// `significand * 2^exponent`, where the top bit of `significand` is always set.
+    Finite {
+        significand: Significand,
+        exponent: i32,
+    },
+    Infinity,
+    NaN,
+}
+
+/// A `u128` which is only aligned to 8 bytes, so that `LiteralValue` is no larger than a `Vec`.
+#[derive(Copy, Clone, Debug)]
+#[repr(C, packed(8))]
+struct Significand(u128);
+
+/// The precision, and the minimum and maximum exponents of normal numbers, of `format`.
+fn parameters(format: LongDoubleFormat) -> (u32, i32, i32) {
+    match format {
+        LongDoubleFormat::X87 => (64, -16382, 16383),
+        LongDoubleFormat::Binary128 => (113, -16382, 16383),
+        LongDoubleFormat::Double => (53, -1022, 1023),
+    }
+}
+
+impl LongDoubleValue {
+    const NAN: LongDoubleValue = LongDoubleValue {
+        negative: false,
+        class: Class::NaN,
+    };
+
+    fn zero(negative: bool) -> Self {
+        LongDoubleValue {
+            negative,
+            class: Class::Zero,
+        }
+    }
+
+    fn infinity(negative: bool) -> Self {
+        LongDoubleValue {
+            negative,
+            class: Class::Infinity,
+        }
+    }
+
+    /// `significand * 2^exponent`, exactly.
+    fn finite(negative: bool, significand: u128, exponent: i32) -> Self {
+        if significand == 0 {
+            return Self::zero(negative);
+        }
+        let shift = significand.leading_zeros();
+        LongDoubleValue {
+            negative,
+            class: Class::Finite {
+                significand: Significand(significand << shift),
+                exponent: exponent - shift as i32,
+            },
+        }
+    }
+
+    /// `n * 2^exponent`, rounded to odd at 128 bits.
+    ///
+    /// If `sticky` is set, the exact value is slightly larger in magnitude than `n`.
+    fn from_big(negative: bool, n: &Big, exponent: i32, sticky: bool) -> Self {
+        let len = n.bit_len();
+        if len <= 128 {
+            let value = Self::finite(negative, n.low_u128(), exponent);
+            return if sticky { value.set_sticky() } else { value };
+        }
+        let shift = len - 128;
+        let sticky = sticky || n.any_bits_below(shift);
+        let value = Self::finite(negative, n.shr(shift).low_u128(), exponent + shift as i32);
+        if sticky {
+            value.set_sticky()
+        } else {
+            value
+        }
+    }
+
+    fn set_sticky(mut self) -> Self {
+        if let Class::Finite {
+            significand: Significand(significand),
+            exponent,
+        } = self.class
+        {
+            self.class = Class::Finite {
+                significand: Significand(significand | 1),
+                exponent,
+            };
+        }
+        self
+    }
+
+    /// Parse a floating constant, without its suffix.
+    ///
+    /// `literal` must already be valid C syntax, either decimal or hexadecimal.
+    pub fn parse(literal: &str) -> Self {
+        let (radix, literal) = match literal
+            .strip_prefix("0x")
+            .or_else(|| literal.strip_prefix("0X"))
+        {
+            Some(hex) => (16, hex),
+            None => (10, literal),
+        };
+        let exponent_start = if radix == 16 { "pP" } else { "eE" };
+        let (mantissa, exponent) = match literal.find(|c| exponent_start.contains(c)) {
+            Some(i) => (&literal[..i], parse_exponent(&literal[i + 1..])),
+            None => (literal, 0),
+        };
+        let mut digits = Big::default();
+        let mut fraction_digits = 0;
+        let mut seen_dot = false;
+        for c in mantissa.chars() {
+            if c == '.' {
+                seen_dot = true;
+                continue;
+            }
+            let digit = c
+                .to_digit(radix)
+                .expect("lexer should only accept valid digits");
+            digits.mul_small(radix);
+            digits.add_small(digit);
+            if seen_dot {
+                fraction_digits += 1;
+            }
+        }
+        if digits.is_zero() {
+            return Self::zero(false);
+        }
+        if radix == 16 {
+            let exponent = exponent.saturating_sub(4 * fraction_digits);
+            let exponent = exponent.clamp(-i64::from(HUGE_EXPONENT), i64::from(HUGE_EXPONENT));
+            return Self::from_big(false, &digits, exponent as i32, false);
+        }
+        let exponent = exponent.saturating_sub(fraction_digits);
+        // the value is roughly 10 to the power of this, using log10(2) ~= 0.3 to count the digits
+        let magnitude = exponent.saturating_add(i64::from(digits.bit_len()) * 3 / 10 + 1);
+        if magnitude > DECIMAL_EXPONENT_LIMIT {
+            return Self::finite(false, 1, HUGE_EXPONENT);
+        } else if magnitude < -DECIMAL_EXPONENT_LIMIT {
+            return Self::finite(false, 1, -HUGE_EXPONENT);
+        }
+        let power = Big::power_of_ten(exponent.unsigned_abs() as u32);
+        if exponent >= 0 {
+            digits.mul(&power);
+            return Self::from_big(false, &digits, 0, false);
+        }
+        // divide with enough bits left over in the quotient to round
+        let shift = (QUOTIENT_BITS + power.bit_len()).saturating_sub(digits.bit_len());
+        let (quotient, remainder) = digits.shl(shift).div_rem(&power);
+        Self::from_big(false, &quotient, -(shift as i32), !remainder.is_zero())
+    }
+
+    /// Whether this is positive or negative zero.
+    pub fn is_zero(&self) -> bool {
+        matches!(self.class, Class::Zero)
+    }
+
+    /// Round to the nearest value representable in `format`, ties to even.
+    pub fn round(&self, format: LongDoubleFormat) -> Self {
+        let (significand, exponent) = match self.class {
+            Class::Finite {
+                significand: Significand(significand),
+                exponent,
+            } => (significand, exponent),
+            _ => return *self,
+        };
+        let (precision, min_exponent, max_exponent) = parameters(format);
+        // the exponent of the leading bit
+        let leading = exponent + 127;
+        // the exponent of the last bit that fits, which is fixed for subnormals
+        let quantum = leading.max(min_exponent) - (precision as i32 - 1);
+        let shift = (quantum - exponent) as u32;
+        let (kept, remainder) = if shift >= 128 {
+            (0, significand)
+        } else {
+            (significand >> shift, significand & ((1 << shift) - 1))
+        };
+        let round_up = match shift.cmp(&128) {
+            Ordering::Greater => false,
+            Ordering::Equal => remainder > 1 << 127,
+            Ordering::Less => {
+                let half = 1 << (shift - 1);
+                remainder > half || remainder == half && kept & 1 == 1
+            }
+        };
+        let kept = kept + u128::from(round_up);
+        if kept == 0 {
+            return Self::zero(self.negative);
+        }
+        let rounded = Self::finite(self.negative, kept, quantum);
+        match rounded.class {
+            Class::Finite { exponent, .. } if exponent + 127 > max_exponent => {
+                Self::infinity(self.negative)
+            }
+            _ => rounded,
+        }
+    }
+
+    /// The encoding of this value in `format`, rounded to nearest.
+    ///
+    /// For `X87`, this is the 64-bit significand followed by the sign and exponent,
+    /// as it is stored in the low 10 bytes of memory.
+    /// For `Double`, this is the bits of the `f64`.
+    pub fn to_bits(&self, format: LongDoubleFormat) -> u128 {
+        let (precision, min_exponent, max_exponent) = parameters(format);
+        // the x87 format has an explicit integer bit, the others only store the fraction
+        let (fraction_bits, explicit) = match format {
+            LongDoubleFormat::X87 => (64, 1 << 63),
+            _ => (precision - 1, 0),
+        };
+        let max_biased = 2 * max_exponent as u128 + 1;
+        let (biased_exponent, fraction) = match self.round(format).class {
+            Class::Zero => (0, 0),
+            Class::Infinity => (max_biased, explicit),
+            // a quiet NaN
+            Class::NaN => (max_biased, explicit | 1 << (precision - 2)),
+            Class::Finite {
+                significand: Significand(significand),
+                exponent,
+            } => {
+                let leading = exponent + 127;
+                let top = significand >> (128 - precision);
+                if leading >= min_exponent {
+                    let implicit = if explicit == 0 {
+                        1 << (precision - 1)
+                    } else {
+                        0
+                    };
+                    ((leading + max_exponent) as u128, top ^ implicit)
+                } else {
+                    (0, top >> (min_exponent - leading))
+                }
+            }
+        };
+        let sign = u128::from(self.negative);
+        let exponent_bits = max_biased.count_ones();
+        (sign << exponent_bits | biased_exponent) << fraction_bits | fraction
+    }
+
+    /// Convert to the nearest `f64`.
+    pub fn to_f64(&self) -> f64 {
+        f64::from_bits(self.to_bits(LongDoubleFormat::Double) as u64)
+    }
+
+    /// The integer part of the magnitude, saturating at `u64::MAX`, or `None` for NaN.
+    fn truncate(&self) -> Option<u64> {
+        match self.class {
+            Class::Zero => Some(0),
+            Class::Infinity => Some(u64::MAX),
+            Class::NaN => None,
+            Class::Finite {
+                significand: Significand(significand),
+                exponent,
+            } => Some(match exponent + 127 {
+                leading if leading < 0 => 0,
+                leading if leading >= 64 => u64::MAX,
+                leading => (significand >> (127 - leading)) as u64,
+            }),
+        }
+    }
+
+    /// Convert to an integer, rounding towards zero and saturating like `as`.
+    pub fn to_i64(&self) -> i64 {
+        match self.truncate() {
+            None => 0,
+            Some(magnitude) if self.negative => {
+                (-i128::from(magnitude)).max(i64::MIN.into()) as i64
+            }
+            Some(magnitude) => magnitude.min(i64::MAX as u64) as i64,
+        }
+    }
+
+    /// Convert to an unsigned integer, rounding towards zero and saturating like `as`.
+    pub fn to_u64(&self) -> u64 {
+        match self.truncate() {
+            Some(magnitude) if !self.negative => magnitude,
+            _ => 0,
+        }
+    }
+
+    /// `self + other` in `format`
+    pub fn add(&self, other: &Self, format: LongDoubleFormat) -> Self {
+        self.round(format)
+            .add_to_odd(&other.round(format))
+            .round(format)
+    }
+
+    /// `self - other` in `format`
+    pub fn sub(&self, other: &Self, format: LongDoubleFormat) -> Self {
+        self.add(&-*other, format)
+    }
+
+    /// `self * other` in `format`
+    pub fn mul(&self, other: &Self, format: LongDoubleFormat) -> Self {
+        self.round(format)
+            .mul_to_odd(&other.round(format))
+            .round(format)
+    }
+
+    /// `self / other` in `format`
+    pub fn div(&self, other: &Self, format: LongDoubleFormat) -> Self {
+        self.round(format)
+            .div_to_odd(&other.round(format))
+            .round(format)
+    }
+
+    fn add_to_odd(&self, other: &Self) -> Self {
+        use Class::*;
+        let (a, b) = match (self.class, other.class) {
+            (NaN, _) | (_, NaN) => return Self::NAN,
+            (Infinity, Infinity) if self.negative != other.negative => return Self::NAN,
+            (Infinity, _) | (_, Zero) if !matches!(self.class, Zero) => return *self,
+            (_, Infinity) | (Zero, _) if !matches!(other.class, Zero) => return *other,
+            // `-0 + -0` is the only sum of zeros which is negative
+            (Zero, Zero) => return Self::zero(self.negative && other.negative),
+            (
+                Finite {
+                    significand: Significand(a),
+                    exponent: a_exp,
+                },
+                Finite {
+                    significand: Significand(b),
+                    exponent: b_exp,
+                },
+            ) => ((a, a_exp), (b, b_exp)),
+            _ => unreachable!("all combinations of zero and infinity are handled above"),
+        };
+        // if one operand is much smaller than the other, it only affects the sticky bit,
+        // so replace it with something smaller that has the same effect
+        let (a, b) = if a.1 - b.1 > QUOTIENT_BITS as i32 {
+            (a, (1, a.1 - QUOTIENT_BITS as i32))
+        } else if b.1 - a.1 > QUOTIENT_BITS as i32 {
+            ((1, b.1 - QUOTIENT_BITS as i32), b)
+        } else {
+            (a, b)
+        };
+        let exponent = a.1.min(b.1);
+        let a_big = Big::from(a.0).shl((a.1 - exponent) as u32);
+        let b_big = Big::from(b.0).shl((b.1 - exponent) as u32);
+        if self.negative == other.negative {
+            let mut sum = a_big;
+            sum.add(&b_big);
+            return Self::from_big(self.negative, &sum, exponent, false);
+        }
+        match a_big.cmp(&b_big) {
+            Ordering::Equal => Self::zero(false),
+            Ordering::Greater => {
+                let mut difference = a_big;
+                difference.sub(&b_big);
+                Self::from_big(self.negative, &difference, exponent, false)
+            }
+            Ordering::Less => {
+                let mut difference = b_big;
+                difference.sub(&a_big);
+                Self::from_big(other.negative, &difference, exponent, false)
+            }
+        }
+    }
+
+    fn mul_to_odd(&self, other: &Self) -> Self {
+        use Class::*;
+        let negative = self.negative != other.negative;
+        match (self.class, other.class) {
+            (NaN, _) | (_, NaN) | (Infinity, Zero) | (Zero, Infinity) => Self::NAN,
+            (Infinity, _) | (_, Infinity) => Self::infinity(negative),
+            (Zero, _) | (_, Zero) => Self::zero(negative),
+            (
+                Finite {
+                    significand: Significand(a),
+                    exponent: a_exp,
+                },
+                Finite {
+                    significand: Significand(b),
+                    exponent: b_exp,
+                },
+            ) => {
+                let mut product = Big::from(a);
+                product.mul(&Big::from(b));
+                Self::from_big(negative, &product, a_exp + b_exp, false)
+            }
+        }
+    }
+
+    fn div_to_odd(&self, other: &Self) -> Self {
+        use Class::*;
+        let negative = self.negative != other.negative;
+        match (self.class, other.class) {
+            (NaN, _) | (_, NaN) | (Infinity, Infinity) | (Zero, Zero) => Self::NAN,
+            (Infinity, _) | (_, Zero) => Self::infinity(negative),
+            (Zero, _) | (_, Infinity) => Self::zero(negative),
+            (
+                Finite {
+                    significand: Significand(a),
+                    exponent: a_exp,
+                },
+                Finite {
+                    significand: Significand(b),
+                    exponent: b_exp,
+                },
+            ) => {
+                let (quotient, remainder) = Big::from(a).shl(QUOTIENT_BITS).div_rem(&Big::from(b));
+                let exponent = a_exp - b_exp - QUOTIENT_BITS as i32;
+                Self::from_big(negative, &quotient, exponent, !remainder.is_zero())
+            }
+        }
+    }
+}
+
+/// Parse the exponent of a floating constant, saturating if it doesn't fit.
+fn parse_exponent(exponent: &str) -> i64 {
+    let (negative, digits) = match exponent.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, exponent.trim_start_matches('+')),
+    };
+    let magnitude = digits.chars().fold(0i64, |acc, c| {
+        let digit = c
+            .to_digit(10)
+            .expect("lexer should only accept decimal exponents");
+        acc.saturating_mul(10).saturating_add(digit.into())
+    });
+    if negative {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+impl From<f64> for LongDoubleValue {
+    fn from(f: f64) -> Self {
+        let bits = f.to_bits();
+        let negative = bits >> 63 == 1;
+        let biased = ((bits >> 52) & 0x7ff) as i32;
+        let fraction = u128::from(bits & ((1 << 52) - 1));
+        match (biased, fraction) {
+            (0x7ff, 0) => Self::infinity(negative),
+            (0x7ff, _) => LongDoubleValue {
+                negative,
+                class: Class::NaN,
+            },
+            (0, _) => Self::finite(negative, fraction, -1074),
+            _ => Self::finite(negative, fraction | 1 << 52, biased - 1075),
+        }
+    }
+}
+
+impl From<u64> for LongDoubleValue {
+    fn from(u: u64) -> Self {
+        Self::finite(false, u.into(), 0)
+    }
+}
+
+impl From<i64> for LongDoubleValue {
+    fn from(i: i64) -> Self {
+        Self::finite(i < 0, i.unsigned_abs().into(), 0)
+    }
+}
+
+impl Neg for LongDoubleValue {
+    type Output = Self;
+    fn neg(self) -> Self {
+        LongDoubleValue {
+            negative: !self.negative,
+            ..self
+        }
+    }
+}
+
+impl PartialEq for LongDoubleValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for LongDoubleValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        use Class::*;
+        // the ordering of the magnitudes
+        let magnitude = match (self.class, other.class) {
+            (NaN, _) | (_, NaN) => return None,
+            (Zero, Zero) => return Some(Ordering::Equal),
+            _ if self.negative != other.negative => {
+                return Some(if self.negative {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                })
+            }
+            (Infinity, Infinity) => Ordering::Equal,
+            (Infinity, _) | (_, Zero) => Ordering::Greater,
+            (_, Infinity) | (Zero, _) => Ordering::Less,
+            (
+                Finite {
+                    significand: Significand(a),
+                    exponent: a_exp,
+                },
+                Finite {
+                    significand: Significand(b),
+                    exponent: b_exp,
+                },
+            ) => a_exp.cmp(&b_exp).then(a.cmp(&b)),
+        };
+        Some(if self.negative {
+            magnitude.reverse()
+        } else {
+            magnitude
+        })
+    }
+}
+
+/// Prints the exact value as a hexadecimal floating constant.
+impl fmt::Display for LongDoubleValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.negative { "-" } else { "" };
+        match self.class {
+            Class::Zero => write!(f, "{}0x0p+0L", sign),
+            Class::Infinity => write!(f, "{}inf", sign),
+            Class::NaN => write!(f, "NaN"),
+            Class::Finite {
+                significand: Significand(significand),
+                exponent,
+            } => {
+                let fraction = format!("{:032x}", significand << 1);
+                let fraction = fraction.trim_end_matches('0');
+                let dot = if fraction.is_empty() { "" } else { "." };
+                write!(f, "{}0x1{}{}p{:+}L", sign, dot, fraction, exponent + 127)
+            }
+        }
+    }
+}
+
+/// An arbitrary precision unsigned integer, just big enough to parse and divide with.
+///
+/// The digits are in base 2^32, least significant first, with no trailing zeros.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Big(Vec<u32>);
+
+impl From<u128> for Big {
+    fn from(mut n: u128) -> Self {
+        let mut digits = Vec::new();
+        while n != 0 {
+            digits.push(n as u32);
+            n >>= 32;
+        }
+        Big(digits)
+    }
+}
+
+impl Big {
+    fn power_of_ten(mut exponent: u32) -> Self {
+        let mut power = Big::from(1);
+        while exponent >= 9 {
+            power.mul_small(1_000_000_000);
+            exponent -= 9;
+        }
+        power.mul_small(10u32.pow(exponent));
+        power
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn trim(&mut self) {
+        while self.0.last() == Some(&0) {
+            self.0.pop();
+        }
+    }
+
+    fn bit_len(&self) -> u32 {
+        match self.0.last() {
+            Some(last) => 32 * self.0.len() as u32 - last.leading_zeros(),
+            None => 0,
+        }
+    }
+
+    fn bit(&self, i: u32) -> bool {
+        self.0
+            .get((i / 32) as usize)
+            .is_some_and(|digit| digit >> (i % 32) & 1 == 1)
+    }
+
+    /// The low 128 bits
+    fn low_u128(&self) -> u128 {
+        self.0
+            .iter()
+            .take(4)
+            .rev()
+            .fold(0, |acc, &digit| acc << 32 | u128::from(digit))
+    }
+
+    fn any_bits_below(&self, bit: u32) -> bool {
+        let whole = (bit / 32) as usize;
+        let partial = self
+            .0
+            .get(whole)
+            .map_or(0, |digit| digit & ((1 << (bit % 32)) - 1));
+        partial != 0 || self.0.iter().take(whole).any(|&digit| digit != 0)
+    }
+
+    fn shl(&self, bits: u32) -> Self {
+        let (whole, partial) = ((bits / 32) as usize, bits % 32);
+        let mut digits = vec![0; whole];
+        let mut carry = 0;
+        for &digit in &self.0 {
+            let wide = u64::from(digit) << partial | carry;
+            digits.push(wide as u32);
+            carry = wide >> 32;
+        }
+        digits.push(carry as u32);
+        let mut shifted = Big(digits);
+        shifted.trim();
+        shifted
+    }
+
+    fn shr(&self, bits: u32) -> Self {
+        let (whole, partial) = ((bits / 32) as usize, bits % 32);
+        let digits = self.0.get(whole..).unwrap_or_default();
+        let mut shifted = Big((0..digits.len())
+            .map(|i| {
+                let high = digits.get(i + 1).map_or(0, |&d| u64::from(d) << 32);
+                ((high | u64::from(digits[i])) >> partial) as u32
+            })
+            .collect());
+        shifted.trim();
+        shifted
+    }
+
+    fn mul_small(&mut self, factor: u32) {
+        let mut carry = 0;
+        for digit in &mut self.0 {
+            let wide = u64::from(*digit) * u64::from(factor) + carry;
+            *digit = wide as u32;
+            carry = wide >> 32;
+        }
+        self.0.push(carry as u32);
+        self.trim();
+    }
+
+    fn add_small(&mut self, addend: u32) {
+        self.add(&Big::from(u128::from(addend)));
+    }
+
+    fn mul(&mut self, other: &Big) {
+        let mut product = vec![0u32; self.0.len() + other.0.len()];
+        for (i, &a) in self.0.iter().enumerate() {
+            let mut carry = 0;
+            for (j, &b) in other.0.iter().enumerate() {
+                let wide = u64::from(a) * u64::from(b) + u64::from(product[i + j]) + carry;
+                product[i + j] = wide as u32;
+                carry = wide >> 32;
+            }
+            product[i + other.0.len()] = carry as u32;
+        }
+        self.0 = product;
+        self.trim();
+    }
+
+    fn add(&mut self, other: &Big) {
+        if self.0.len() < other.0.len() {
+            self.0.resize(other.0.len(), 0);
+        }
+        let mut carry = 0;
+        for (i, digit) in self.0.iter_mut().enumerate() {
+            let wide = u64::from(*digit) + u64::from(other.0.get(i).copied().unwrap_or(0)) + carry;
+            *digit = wide as u32;
+            carry = wide >> 32;
+        }
+        self.0.push(carry as u32);
+        self.trim();
+    }
+
+    /// Subtract `other`, which must be no larger than `self`.
+    fn sub(&mut self, other: &Big) {
+        let mut borrow = 0;
+        for (i, digit) in self.0.iter_mut().enumerate() {
+            let subtrahend = i64::from(other.0.get(i).copied().unwrap_or(0)) + borrow;
+            let wide = i64::from(*digit) - subtrahend;
+            borrow = i64::from(wide < 0);
+            *digit = wide.rem_euclid(1 << 32) as u32;
+        }
+        debug_assert_eq!(borrow, 0, "subtraction should never underflow");
+        self.trim();
+    }
+
+    /// Long division, one bit at a time.
+    fn div_rem(&self, divisor: &Big) -> (Big, Big) {
+        let mut quotient = Big(vec![0; self.0.len()]);
+        let mut remainder = Big::default();
+        for i in (0..self.bit_len()).rev() {
+            remainder = remainder.shl(1);
+            if self.bit(i) {
+                remainder.add_small(1);
+            }
+            if remainder >= *divisor {
+                remainder.sub(divisor);
+                quotient.0[(i / 32) as usize] |= 1 << (i % 32);
+            }
+        }
+        quotient.trim();
+        (quotient, remainder)
+    }
+}
+
+impl PartialOrd for Big {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Big {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .len()
+            .cmp(&other.0.len())
+            .then_with(|| self.0.iter().rev().cmp(other.0.iter().rev()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LongDoubleValue;
+    use crate::arch::LongDoubleFormat::{self, *};
+
+    fn bits(literal: &str, format: LongDoubleFormat) -> u128 {
+        LongDoubleValue::parse(literal).to_bits(format)
+    }
+    fn x87(significand: u64, sign_exponent: u16) -> u128 {
+        u128::from(sign_exponent) << 64 | u128::from(significand)
+    }
+
+    #[test]
+    fn test_x87_bits() {
+        let x87_bits = |f: f64| LongDoubleValue::from(f).to_bits(X87);
+        assert_eq!(x87_bits(0.0), x87(0, 0));
+        assert_eq!(x87_bits(-0.0), x87(0, 0x8000));
+        assert_eq!(x87_bits(1.0), x87(1 << 63, 0x3fff));
+        assert_eq!(x87_bits(-2.5), x87(0xa000_0000_0000_0000, 0xc000));
+        assert_eq!(x87_bits(f64::INFINITY), x87(1 << 63, 0x7fff));
+        assert_eq!(x87_bits(f64::NAN), x87(0xc000_0000_0000_0000, 0x7fff));
+        // the smallest subnormal double is 2^-1074
+        assert_eq!(x87_bits(f64::from_bits(1)), x87(1 << 63, 16383 - 1074));
+        // LDBL_MAX, LDBL_MIN, and the smallest subnormal
+        let max = "1.18973149535723176502126385303097021e+4932";
+        assert_eq!(bits(max, X87), x87(u64::MAX, 0x7ffe));
+        let min = "3.36210314311209350626267781732175260e-4932";
+        assert_eq!(bits(min, X87), x87(1 << 63, 1));
+        assert_eq!(bits("0x1p-16445", X87), x87(1, 0));
+        assert_eq!(bits("0x1p-16446", X87), x87(0, 0));
+        assert_eq!(bits("1e4933", X87), x87(1 << 63, 0x7fff));
+    }
+
+    #[test]
+    fn test_binary128_bits() {
+        let binary128_bits = |f: f64| LongDoubleValue::from(f).to_bits(Binary128);
+        assert_eq!(binary128_bits(0.0), 0);
+        assert_eq!(binary128_bits(-0.0), 1 << 127);
+        assert_eq!(binary128_bits(1.0), 0x3fff << 112);
+        assert_eq!(binary128_bits(-2.5), 0xc0004 << 108);
+        assert_eq!(binary128_bits(f64::INFINITY), 0x7fff << 112);
+        assert_eq!(binary128_bits(f64::NAN), 0x7fff8 << 108);
+        assert_eq!(binary128_bits(f64::from_bits(1)), (16383 - 1074) << 112);
+        // 2^-1073 + 2^-1074
+        assert_eq!(
+            binary128_bits(f64::from_bits(3)),
+            (16383 - 1073) << 112 | 1 << 111
+        );
+        let max = "1.18973149535723176508575932662800702e+4932";
+        assert_eq!(bits(max, Binary128), (0x7fff << 112) - 1);
+        assert_eq!(bits("6.5e-4966", Binary128), 1);
+        // 0.1 is 0x1.999...p-4, which rounds up
+        assert_eq!(
+            bits("0.1", Binary128),
+            0x3ffb_9999_9999_9999_9999_9999_9999_999a
+        );
+        assert_eq!(bits("0x1.8p1", Binary128), 0x40008 << 108);
+    }
+
+    #[test]
+    fn test_double() {
+        assert_eq!(LongDoubleValue::parse("0.1").to_f64(), 0.1);
+        assert_eq!(LongDoubleValue::parse("1e-320").to_f64(), 1e-320);
+        assert_eq!(LongDoubleValue::parse("1e400").to_f64(), f64::INFINITY);
+        assert_eq!(
+            LongDoubleValue::parse("1.7976931348623157e308").to_f64(),
+            f64::MAX
+        );
+        // halfway between 1 and the next double, which rounds to even
+        let halfway =
+            LongDoubleValue::parse("1.00000000000000011102230246251565404236316680908203125");
+        assert_eq!(halfway.to_f64(), 1.0);
+        let above =
+            LongDoubleValue::parse("1.000000000000000111022302462515654042363166809082031251");
+        assert_eq!(above.to_f64(), 1.0 + f64::EPSILON);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let one = LongDoubleValue::from(1u64);
+        let epsilon = LongDoubleValue::parse("1.08420217248550443400745280086994171e-19");
+        assert!(one.add(&epsilon, X87) > one);
+        assert!(one.add(&epsilon, Binary128) > one);
+        assert!(one.add(&epsilon, Double) == one);
+        let half_epsilon = LongDoubleValue::parse("0x1p-64");
+        assert!(one.add(&half_epsilon, X87) == one);
+        let three = LongDoubleValue::from(3i64);
+        let third = one.div(&three, X87);
+        assert_eq!(third.to_bits(X87), x87(0xaaaa_aaaa_aaaa_aaab, 0x3ffd));
+        assert_eq!(third.mul(&three, X87).to_bits(X87), x87(1 << 63, 0x3fff));
+        assert!(three.sub(&three, X87).is_zero());
+        let max = LongDoubleValue::parse("0x1.fffffffffffffffep16383");
+        assert_eq!(max.mul(&three, X87).to_bits(X87), x87(1 << 63, 0x7fff));
+        let zero = LongDoubleValue::from(0u64);
+        assert!(zero.div(&zero, X87) != zero.div(&zero, X87));
+        assert_eq!((-LongDoubleValue::parse("2.75")).to_i64(), -2);
+        assert_eq!(LongDoubleValue::parse("1e30").to_u64(), u64::MAX);
+    }
+}
@@ -2,14 +2,17 @@ pub mod ast;
 pub mod error;
 pub mod hir;
 pub mod lex;
+pub mod long_double;
 pub mod types;
 
 pub use crate::intern::InternedStr;
 pub use error::{
-    CompileError, CompileResult, CompileWarning, Error, ErrorHandler, SemanticError, SyntaxError,
+    CompileError, CompileResult, CompileWarning, DiagnosticRegion, Error, ErrorHandler,
+    SemanticError, SyntaxError, WarningLevel, WarningOptions,
 };
 pub use hir::LiteralValue;
 pub use lex::{LiteralToken, Locatable, Location, Token};
+pub use long_double::LongDoubleValue;
 pub use types::Type;
 pub use types::{StructRef, StructType};
 
@@ -123,20 +126,11 @@ mod codegen_impls {
         Type,
     };
     use crate::intern::InternedStr;
-    use cranelift_codegen::ir::Signature;
     use cranelift_codegen::ir::{
         condcodes::{FloatCC, IntCC},
         types::{self, Type as IrType},
-        AbiParam,
     };
-    use cranelift_codegen::isa::{CallConv, TargetIsa};
-
-    // TODO: make this const when const_if_match is stabilized
-    // TODO: see https://github.com/rust-lang/rust/issues/49146
-    lazy_static::lazy_static! {
-        /// The calling convention for the current target.
-        pub(crate) static ref CALLING_CONVENTION: CallConv = CallConv::triple_default(&TARGET);
-    }
+    use target_lexicon::Architecture;
 
     impl FunctionType {
         pub fn should_return(&self) -> bool {
@@ -145,22 +139,29 @@ mod codegen_impls {
     }
 
     impl Type {
-        /// Return an IR integer type large enough to contain a pointer.
-        pub fn ptr_type() -> IrType {
-            IrType::int(CHAR_BIT * PTR_SIZE).expect("pointer size should be valid")
+        /// Return an IR integer type large enough to contain a pointer on `target`.
+        pub fn ptr_type(target: &TargetInfo) -> IrType {
+            IrType::int(CHAR_BIT * target.data_model.ptr_size())
+                .expect("pointer size should be valid")
         }
-        /// Return an IR type which can represent this C type
-        pub fn as_ir_type(&self) -> IrType {
+        /// Return an IR type which can represent this C type on `target`
+        pub fn as_ir_type(&self, target: &TargetInfo) -> IrType {
             use std::convert::TryInto;
             use Type::*;
 
             match self {
                 // Integers
                 Bool => types::I8,
-                Char(_) | Short(_) | Int(_) | Long(_) | Pointer(_, _) | Enum(_, _) => {
+                Char(_)
+                | Short(_)
+                | Int(_)
+                | Long(_)
+                | LongLong(_)
+                | Pointer(_, _)
+                | Enum(_, _) => {
                     let int_size = SIZE_T::from(CHAR_BIT)
                         * self
-                            .sizeof()
+                            .sizeof(target)
                             .expect("integers should always have a valid size");
                     IrType::int(int_size.try_into().unwrap_or_else(|_| {
                         panic!("integers should never have a size larger than {}", i16::MAX)
@@ -169,25 +170,51 @@ mod codegen_impls {
                 }
 
                 // Floats
-                // TODO: this is hard-coded for x64
+                // every supported target uses IEEE 754 binary32 and binary64
                 Float => types::F32,
                 Double => types::F64,
+                // Cranelift has no 80-bit or 128-bit floats, so this holds the bytes of the
+                // x87 format or binary128. See `saltwater_codegen::long_double` for how arithmetic is done.
+                LongDouble => match target.long_double_format() {
+                    LongDoubleFormat::Double => types::F64,
+                    // RISC-V passes binary128 in a pair of integer registers
+                    _ if matches!(target.triple.architecture, Architecture::Riscv64(_)) => {
+                        types::I128
+                    }
+                    _ => types::I64X2,
+                },
 
                 // Aggregates
                 // arrays and functions decay to pointers
-                Function(_) | Array(_, _) => IrType::int(PTR_SIZE * CHAR_BIT)
-                    .unwrap_or_else(|| panic!("unsupported size of IR: {}", PTR_SIZE)),
+                Function(_) | Array(_, _) => Type::ptr_type(target),
                 // void cannot be loaded or stored
                 _ => types::INVALID,
             }
         }
-        pub fn member_offset(&self, member: InternedStr) -> Result<u64, ()> {
+        pub fn member_offset(&self, member: InternedStr, target: &TargetInfo) -> Result<u64, ()> {
             match self {
-                Type::Struct(stype) => Ok(stype.offset(member)),
+                Type::Struct(stype) => Ok(stype.offset(member, target)),
                 Type::Union(_) => Ok(0),
                 _ => Err(()),
             }
         }
+        /// If `member` is a bitfield, return where it is stored relative to `member_offset`.
+        pub fn member_bitfield(
+            &self,
+            member: InternedStr,
+            target: &TargetInfo,
+        ) -> Option<BitfieldLayout> {
+            match self {
+                Type::Struct(stype) => stype.bitfield(member, target),
+                Type::Union(stype) => stype
+                    .members()
+                    .iter()
+                    .find(|formal| formal.id == member)
+                    .and_then(|formal| formal.bitfield)
+                    .map(|width| BitfieldLayout { shift: 0, width }),
+                _ => None,
+            }
+        }
     }
 
     impl CompileError {
@@ -200,42 +227,6 @@ mod codegen_impls {
         pub fn has_params(&self) -> bool {
             !(self.params.len() == 1 && self.params[0].get().ctype == Type::Void)
         }
-
-        /// Generate the IR function signature for `self`
-        pub fn signature(&self, _isa: &dyn TargetIsa) -> Signature {
-            let mut params = if self.params.len() == 1 && self.params[0].get().ctype == Type::Void {
-                // no arguments
-                Vec::new()
-            } else {
-                self.params
-                    .iter()
-                    .map(|param| AbiParam::new(param.get().ctype.as_ir_type()))
-                    .collect()
-            };
-            if self.varargs {
-                // panic!("Unsupported");
-                // let al = isa
-                //     .register_info()
-                //     .parse_regunit("rax")
-                //     .expect("x86 should have an rax register");
-                // params.push(AbiParam::special_reg(
-                //     types::I8,
-                //     ArgumentPurpose::Normal,
-                //     al,
-                // ));
-                params.push(AbiParam::new(types::I8))
-            }
-            let return_type = if !self.should_return() {
-                vec![]
-            } else {
-                vec![AbiParam::new(self.return_type.as_ir_type())]
-            };
-            Signature {
-                call_conv: *CALLING_CONVENTION,
-                params,
-                returns: return_type,
-            }
-        }
     }
 
     impl ComparisonToken {
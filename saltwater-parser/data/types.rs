@@ -1,4 +1,4 @@
-use super::hir::{Symbol, Variable};
+use super::hir::{Qualifiers, Symbol, Variable};
 use crate::intern::InternedStr;
 #[cfg(test)]
 use proptest_derive::Arbitrary;
@@ -141,8 +141,10 @@ pub enum Type {
     Short(bool),
     Int(bool),
     Long(bool),
+    LongLong(bool),
     Float,
     Double,
+    LongDouble,
     // TODO: separate Qualifiers into LvalQualifiers and FunctionQualifiers
     Pointer(Box<Type>, super::hir::Qualifiers),
     Array(Box<Type>, ArrayType),
@@ -152,16 +154,24 @@ pub enum Type {
     /// Enums should always have members, since tentative definitions are not allowed
     Enum(Option<InternedStr>, Vec<(InternedStr, i64)>),
     /// This is the type used for variadic arguments.
+    ///
+    /// Note that `va_list` itself is an array of one `VaList`.
     VaList,
     /// A semantic error occured while parsing this type.
     Error,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, Eq)]
 #[cfg_attr(test, derive(Arbitrary))]
 pub enum ArrayType {
     Fixed(u64),
     Unbounded,
+    /// A variable length array, e.g. `int a[n]`.
+    ///
+    /// The length is evaluated once, when the array is declared,
+    /// and stored in a hidden variable of type `size_t`.
+    #[cfg_attr(test, proptest(skip))]
+    Variable(Symbol),
 }
 
 // NOTE: K&R declarations are not supported at this time
@@ -203,7 +213,13 @@ impl Type {
     pub fn is_signed(&self) -> bool {
         use Type::*;
         match self {
-            Bool | Char(true) | Short(true) | Int(true) | Long(true) | Enum(_, _) => true,
+            Bool
+            | Char(true)
+            | Short(true)
+            | Int(true)
+            | Long(true)
+            | LongLong(true)
+            | Enum(_, _) => true,
             _ => false,
         }
     }
@@ -211,14 +227,14 @@ impl Type {
     pub fn is_integral(&self) -> bool {
         use Type::*;
         match self {
-            Bool | Char(_) | Short(_) | Int(_) | Long(_) | Enum(_, _) => true,
+            Bool | Char(_) | Short(_) | Int(_) | Long(_) | LongLong(_) | Enum(_, _) => true,
             _ => false,
         }
     }
     #[inline]
     pub fn is_floating(&self) -> bool {
         match self {
-            Type::Float | Type::Double => true,
+            Type::Float | Type::Double | Type::LongDouble => true,
             _ => false,
         }
     }
@@ -240,6 +256,17 @@ impl Type {
             _ => false,
         }
     }
+    /// Return whether the size of this type is only known at runtime.
+    ///
+    /// This is true for variable length arrays and arrays of them.
+    /// Note that pointers to variable length arrays have a known size.
+    pub fn is_vla(&self) -> bool {
+        match self {
+            Type::Array(_, ArrayType::Variable(_)) => true,
+            Type::Array(of, _) => of.is_vla(),
+            _ => false,
+        }
+    }
 }
 
 impl PartialEq for FunctionType {
@@ -263,9 +290,23 @@ impl PartialEq for FunctionType {
     }
 }
 
+impl PartialEq for ArrayType {
+    fn eq(&self, other: &Self) -> bool {
+        use ArrayType::*;
+        match (self, other) {
+            (Fixed(a), Fixed(b)) => a == b,
+            (Unbounded, Unbounded) => true,
+            // 6.7.6.2p6: if either length is only known at runtime,
+            // the arrays are assumed to be compatible
+            (Variable(_), Variable(_)) | (Variable(_), Fixed(_)) | (Fixed(_), Variable(_)) => true,
+            _ => false,
+        }
+    }
+}
+
 impl std::fmt::Display for Type {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        print_type(self, None, f)
+        print_type(self, Qualifiers::default(), None, f)
     }
 }
 
@@ -285,8 +326,10 @@ fn write_struct_type(struct_type: &StructType, f: &mut Formatter) -> fmt::Result
     Ok(())
 }
 
+/// Print `ctype`, which has the top-level qualifiers `qualifiers`, in C declaration syntax.
 pub(super) fn print_type(
     ctype: &Type,
+    qualifiers: Qualifiers,
     name: Option<InternedStr>,
     f: &mut Formatter,
 ) -> fmt::Result {
@@ -309,6 +352,18 @@ pub(super) fn print_type(
     use Type::*;
 
     let unrolled_type = unroll_type(ctype);
+    // the qualifiers that apply to each type in `unrolled_type`:
+    // `const int *volatile p` is a volatile pointer to a const int
+    let mut unrolled_qualifiers = vec![qualifiers];
+    for declarator_type in &unrolled_type[..unrolled_type.len() - 1] {
+        let last = *unrolled_qualifiers.last().unwrap();
+        unrolled_qualifiers.push(match declarator_type {
+            Pointer(_, qs) => *qs,
+            Function(_) => Qualifiers::default(),
+            // `const int a[3]` is an array of const int
+            _ => last,
+        });
+    }
 
     let mut prefixes = Vec::new();
     let mut postfixes = Vec::new();
@@ -322,6 +377,7 @@ pub(super) fn print_type(
                 postfixes.push(match array_type {
                     ArrayType::Fixed(length) => format!("[{}]", length),
                     ArrayType::Unbounded => "[]".to_string(),
+                    ArrayType::Variable(_) => "[*]".to_string(),
                 });
             }
             Type::Function(function_type) => {
@@ -345,7 +401,8 @@ pub(super) fn print_type(
                 write!(buff, ")")?;
                 postfixes.push(buff);
             }
-            Pointer(_, qs) => {
+            Pointer(_, _) => {
+                let qs = unrolled_qualifiers[index];
                 let needs_parens = match unrolled_type[index + 1] {
                     Array(_, _) | Function(_) => true,
                     _ => false,
@@ -354,7 +411,7 @@ pub(super) fn print_type(
                 prefixes.push(format!(
                     "{}*{}",
                     if needs_parens { "(" } else { "" },
-                    if *qs != Default::default() {
+                    if qs != Default::default() {
                         format!("{} ", qs)
                     } else {
                         String::new()
@@ -372,8 +429,12 @@ pub(super) fn print_type(
     }
 
     let final_type = unrolled_type[unrolled_type.len() - 1];
+    let final_qualifiers = unrolled_qualifiers[unrolled_type.len() - 1];
+    if final_qualifiers != Default::default() {
+        write!(f, "{} ", final_qualifiers)?;
+    }
     match final_type {
-        Char(signed) | Short(signed) | Int(signed) | Long(signed) => {
+        Char(signed) | Short(signed) | Int(signed) | Long(signed) | LongLong(signed) => {
             write!(
                 f,
                 "{}{}",
@@ -383,6 +444,7 @@ pub(super) fn print_type(
                     Short(_) => "short",
                     Int(_) => "int",
                     Long(_) => "long",
+                    LongLong(_) => "long long",
                     _ => unreachable!(),
                 }
             )?;
@@ -390,6 +452,7 @@ pub(super) fn print_type(
         Bool => write!(f, "_Bool")?,
         Float => write!(f, "float")?,
         Double => write!(f, "double")?,
+        LongDouble => write!(f, "long double")?,
         Void => write!(f, "void")?,
         Enum(Some(ident), _) => write!(f, "enum {}", ident)?,
         Enum(None, _) => write!(f, "<anonymous enum>")?,
@@ -401,7 +464,7 @@ pub(super) fn print_type(
             write!(f, "struct ")?;
             write_struct_type(struct_type, f)?;
         }
-        VaList => write!(f, "va_list")?,
+        VaList => write!(f, "struct __va_list_tag")?,
         Error => write!(f, "<type error>")?,
         // These are unreachable because if they were part of the type, the
         // would have been unrolled. Only specifier types are valid final types
@@ -443,8 +506,10 @@ pub(crate) mod tests {
             any::<bool>().prop_map(Type::Short),
             any::<bool>().prop_map(Type::Int),
             any::<bool>().prop_map(Type::Long),
+            any::<bool>().prop_map(Type::LongLong),
             Just(Type::Float),
             Just(Type::Double),
+            Just(Type::LongDouble),
             // enum
             any::<(Option<InternedStr>, Vec<(InternedStr, i64)>)>()
                 .prop_map(|(name, members)| Type::Enum(name, members)),
@@ -1,4 +1,4 @@
-use crate::arch::CHAR_BIT;
+use crate::arch::{LongDoubleFormat, TargetInfo, CHAR_BIT};
 use crate::data::hir::LiteralValue::*;
 use crate::data::hir::*;
 use crate::data::*;
@@ -18,9 +18,11 @@ macro_rules! fold_int_bin_op {
 #[inline]
 fn fold_scalar_bin_op(
     simple: fn(f64, f64) -> f64,
+    long_double: fn(&LongDoubleValue, &LongDoubleValue, LongDoubleFormat) -> LongDoubleValue,
     overflowing: fn(i64, i64) -> (i64, bool),
     wrapping: fn(u64, u64) -> u64,
     wrapping_byte: fn(u8, u8) -> u8,
+    format: LongDoubleFormat,
 ) -> impl Fn(&LiteralValue, &LiteralValue, &Type) -> Result<Option<LiteralValue>, SemanticError> {
     move |a: &LiteralValue, b: &LiteralValue, _ctype| match (a, b) {
         (Int(a), Int(b)) => {
@@ -38,6 +40,7 @@ fn fold_scalar_bin_op(
         (UnsignedInt(a), UnsignedInt(b)) => Ok(Some(UnsignedInt(wrapping(*a, *b)))),
         (Char(a), Char(b)) => Ok(Some(Char(wrapping_byte(*a, *b)))),
         (Float(a), Float(b)) => Ok(Some(Float(simple(*a, *b)))),
+        (LongDouble(a), LongDouble(b)) => Ok(Some(LongDouble(long_double(a, b, format)))),
         // TODO: find a way to do this that allows `"hello" + 2 - 1`
         //(Str(s), Int(i)) | (Int(i), Str(s)) => {
         (_, _) => Ok(None),
@@ -45,8 +48,8 @@ fn fold_scalar_bin_op(
 }
 
 macro_rules! fold_compare_op {
-($left: expr, $right: expr, $constructor: ident, $op: tt, $compare: expr) => {{
-        let (left, right) = ($left.const_fold()?, $right.const_fold()?);
+($left: expr, $right: expr, $target: expr, $constructor: ident, $op: tt, $compare: expr) => {{
+        let (left, right) = ($left.const_fold($target)?, $right.const_fold($target)?);
         match (&left.expr, &right.expr) {
             (ExprType::Literal(a), ExprType::Literal(b)) => {
                 match (a, b) {
@@ -54,6 +57,10 @@ macro_rules! fold_compare_op {
                     (UnsignedInt(a), UnsignedInt(b)) => ExprType::Literal(Int((a $op b) as i64)),
                     #[allow(clippy::float_cmp)]
                     (Float(a), Float(b)) => ExprType::Literal(Int((a $op b) as i64)),
+                    (LongDouble(a), LongDouble(b)) => {
+                        let format = $target.long_double_format();
+                        ExprType::Literal(Int((a.round(format) $op b.round(format)) as i64))
+                    }
                     (Char(a), Char(b)) => ExprType::Literal(Int((a $op b) as i64)),
                     (_, _) => ExprType::Binary(BinaryOp::Compare($compare), Box::new(left), Box::new(right)),
                 }
@@ -70,6 +77,7 @@ impl Expr {
                 Int(i) => i == 0,
                 UnsignedInt(u) => u == 0,
                 Float(f) => f == 0.0,
+                LongDouble(f) => f.is_zero(),
                 Char(c) => c == 0,
                 _ => false,
             }
@@ -95,8 +103,11 @@ impl Expr {
             _ => Err(self),
         }
     }
-    pub(crate) fn constexpr(self) -> CompileResult<Locatable<(LiteralValue, Type)>> {
-        let folded = self.const_fold()?;
+    pub(crate) fn constexpr(
+        self,
+        target: &TargetInfo,
+    ) -> CompileResult<Locatable<(LiteralValue, Type)>> {
+        let folded = self.const_fold(target)?;
         match folded.expr {
             ExprType::Literal(token) => Ok(Locatable {
                 data: (token, folded.ctype),
@@ -105,10 +116,12 @@ impl Expr {
             _ => Err(folded.location.error(SemanticError::NotConstant(folded))),
         }
     }
-    pub fn const_fold(self) -> CompileResult<Expr> {
+    pub fn const_fold(self, target: &TargetInfo) -> CompileResult<Expr> {
         let location = self.location;
         let folded = match self.expr {
             ExprType::Literal(_) => self.expr,
+            // initializing a compound literal modifies memory
+            ExprType::CompoundLiteral(..) => self.expr,
             ExprType::Id(ref name) => match &self.ctype {
                 Type::Enum(_, members) => {
                     match members.iter().find(|member| member.0 == name.get().id) {
@@ -119,14 +132,16 @@ impl Expr {
                 // TODO: if a variable were const, could we const fold Ids?
                 _ => self.expr,
             },
+            // the size of a variable length array is only known at runtime
+            ExprType::Sizeof(ctype) if ctype.is_vla() => ExprType::Sizeof(ctype),
             ExprType::Sizeof(ctype) => {
-                let sizeof = ctype.sizeof().map_err(|data| Locatable {
+                let sizeof = ctype.sizeof(target).map_err(|data| Locatable {
                     data: data.to_string(),
                     location,
                 })?;
                 ExprType::Literal(UnsignedInt(sizeof))
             }
-            ExprType::Negate(expr) => expr.const_fold()?.map_literal(
+            ExprType::Negate(expr) => expr.const_fold(target)?.map_literal(
                 &location,
                 |token| match token {
                     Int(i) => {
@@ -142,11 +157,12 @@ impl Expr {
                     UnsignedInt(u) => Ok(UnsignedInt(u.wrapping_neg())),
                     Char(c) => Ok(Char(c.wrapping_neg())),
                     Float(f) => Ok(Float(-f)),
+                    LongDouble(f) => Ok(LongDouble(-f)),
                     _ => Ok(token),
                 },
                 ExprType::Negate,
             )?,
-            ExprType::BitwiseNot(expr) => expr.const_fold()?.map_literal(
+            ExprType::BitwiseNot(expr) => expr.const_fold(target)?.map_literal(
                 &location,
                 |token| match token {
                     Int(i) => Ok(Int(!i)),
@@ -157,10 +173,10 @@ impl Expr {
                 ExprType::BitwiseNot,
             )?,
             ExprType::Binary(op, left, right) => {
-                fold_binary(*left, *right, op, &self.ctype, location)?
+                fold_binary(*left, *right, op, &self.ctype, target, location)?
             }
             ExprType::Comma(left, right) => {
-                let (left, right) = (left.const_fold()?, right.const_fold()?);
+                let (left, right) = (left.const_fold(target)?, right.const_fold(target)?);
                 // check if we can ignore left or it has side effects
                 if left.is_constexpr() {
                     right.expr
@@ -169,11 +185,11 @@ impl Expr {
                 }
             }
             ExprType::Noop(inner) => {
-                let inner = inner.const_fold()?;
+                let inner = inner.const_fold(target)?;
                 ExprType::Noop(Box::new(inner))
             }
             ExprType::Deref(expr) => {
-                let folded = expr.const_fold()?;
+                let folded = expr.const_fold(target)?;
                 if let ExprType::Literal(Int(0)) = folded.expr {
                     return Err(Locatable::new(
                         SemanticError::NullPointerDereference.into(),
@@ -184,9 +200,9 @@ impl Expr {
             }
             ExprType::Ternary(condition, then, otherwise) => {
                 let (condition, then, otherwise) = (
-                    condition.const_fold()?,
-                    then.const_fold()?,
-                    otherwise.const_fold()?,
+                    condition.const_fold(target)?,
+                    then.const_fold(target)?,
+                    otherwise.const_fold(target)?,
                 );
                 match condition.expr {
                     ExprType::Literal(Int(0)) => otherwise.expr,
@@ -197,11 +213,11 @@ impl Expr {
                 }
             }
             ExprType::FuncCall(func, params) => {
-                let func = func.const_fold()?;
+                let func = func.const_fold(target)?;
                 #[rustfmt::skip]
                 let params: Vec<Expr> = params
                     .into_iter()
-                    .map(Self::const_fold)
+                    .map(|param| param.const_fold(target))
                     .collect::<CompileResult<_>>()?;
                 // function calls are always non-constant
                 // TODO: if we have access to the full source of a function, could we try to
@@ -209,16 +225,24 @@ impl Expr {
                 ExprType::FuncCall(Box::new(func), params)
             }
             ExprType::Member(expr, member) => {
-                let expr = expr.const_fold()?;
+                let expr = expr.const_fold(target)?;
                 ExprType::Member(Box::new(expr), member)
             }
             ExprType::PostIncrement(expr, increase) => {
-                let expr = expr.const_fold()?;
+                let expr = expr.const_fold(target)?;
                 // this isn't constant for the same reason assignment isn't constant
                 ExprType::PostIncrement(Box::new(expr), increase)
             }
-            ExprType::Cast(expr) => cast(*expr, &self.ctype)?,
-            ExprType::StaticRef(inner) => ExprType::StaticRef(Box::new(inner.const_fold()?)),
+            ExprType::Cast(expr) => cast(*expr, &self.ctype, target)?,
+            ExprType::StaticRef(inner) => ExprType::StaticRef(Box::new(inner.const_fold(target)?)),
+            // these all have side effects on the `va_list`
+            ExprType::VaStart(ap) => ExprType::VaStart(Box::new(ap.const_fold(target)?)),
+            ExprType::VaArg(ap) => ExprType::VaArg(Box::new(ap.const_fold(target)?)),
+            ExprType::VaEnd(ap) => ExprType::VaEnd(Box::new(ap.const_fold(target)?)),
+            ExprType::VaCopy(dest, src) => ExprType::VaCopy(
+                Box::new(dest.const_fold(target)?),
+                Box::new(src.const_fold(target)?),
+            ),
         };
         Ok(Expr {
             expr: folded,
@@ -234,6 +258,7 @@ impl Expr {
     fn literal_bin_op<F>(
         self,
         other: Expr,
+        target: &TargetInfo,
         location: &Location,
         fold_func: F,
         op: BinaryOp,
@@ -245,7 +270,7 @@ impl Expr {
             &Type,
         ) -> Result<Option<LiteralValue>, SemanticError>,
     {
-        let (left, right) = (self.const_fold()?, other.const_fold()?);
+        let (left, right) = (self.const_fold(target)?, other.const_fold(target)?);
         let literal: Option<ExprType> = match (&left.expr, &right.expr) {
             (ExprType::Literal(left_token), ExprType::Literal(right_token)) => {
                 match fold_func(left_token, right_token, &left.ctype) {
@@ -284,45 +309,55 @@ fn fold_binary(
     right: Expr,
     op: BinaryOp,
     parent_type: &Type,
+    target: &TargetInfo,
     location: Location,
 ) -> CompileResult<ExprType> {
     use lex::ComparisonToken::*;
     use BinaryOp::*;
 
-    let left = left.const_fold()?;
-    let right = right.const_fold()?;
+    let left = left.const_fold(target)?;
+    let right = right.const_fold(target)?;
 
     match op {
         Add => left.literal_bin_op(
             right,
+            target,
             &location,
             fold_scalar_bin_op(
                 f64::add,
+                LongDoubleValue::add,
                 i64::overflowing_add,
                 u64::wrapping_add,
                 u8::wrapping_add,
+                target.long_double_format(),
             ),
             Add,
         ),
         Sub => left.literal_bin_op(
             right,
+            target,
             &location,
             fold_scalar_bin_op(
                 f64::sub,
+                LongDoubleValue::sub,
                 i64::overflowing_sub,
                 u64::wrapping_sub,
                 u8::wrapping_sub,
+                target.long_double_format(),
             ),
             Sub,
         ),
         Mul => left.literal_bin_op(
             right,
+            target,
             &location,
             fold_scalar_bin_op(
                 f64::mul,
+                LongDoubleValue::mul,
                 i64::overflowing_mul,
                 u64::wrapping_mul,
                 u8::wrapping_mul,
+                target.long_double_format(),
             ),
             Mul,
         ),
@@ -332,12 +367,15 @@ fn fold_binary(
             }
             left.literal_bin_op(
                 right,
+                target,
                 &location,
                 fold_scalar_bin_op(
                     f64::div,
+                    LongDoubleValue::div,
                     i64::overflowing_div,
                     u64::wrapping_div,
                     u8::wrapping_div,
+                    target.long_double_format(),
                 ),
                 Div,
             )
@@ -348,6 +386,7 @@ fn fold_binary(
             }
             left.literal_bin_op(
                 right,
+                target,
                 &location,
                 |a: &LiteralValue, b: &LiteralValue, _| match (a, b) {
                     (Int(a), Int(b)) => {
@@ -367,13 +406,16 @@ fn fold_binary(
                 Mod,
             )
         }
-        Xor => left.literal_bin_op(right, &location, fold_int_bin_op!(^), Xor),
-        BitwiseAnd => left.literal_bin_op(right, &location, fold_int_bin_op!(&), BitwiseAnd),
-        BitwiseOr => left.literal_bin_op(right, &location, fold_int_bin_op!(|), BitwiseOr),
-        Shl => shift_left(left, right, parent_type, &location),
-        Shr => shift_right(left, right, parent_type, &location),
+        Xor => left.literal_bin_op(right, target, &location, fold_int_bin_op!(^), Xor),
+        BitwiseAnd => {
+            left.literal_bin_op(right, target, &location, fold_int_bin_op!(&), BitwiseAnd)
+        }
+        BitwiseOr => left.literal_bin_op(right, target, &location, fold_int_bin_op!(|), BitwiseOr),
+        Shl => shift_left(left, right, parent_type, target, &location),
+        Shr => shift_right(left, right, parent_type, target, &location),
         LogicalAnd => left.literal_bin_op(
             right,
+            target,
             &location,
             |left, right, _| match (left, right) {
                 (Int(1), Int(1)) => Ok(Some(Int(1))),
@@ -384,6 +426,7 @@ fn fold_binary(
         ),
         LogicalOr => left.literal_bin_op(
             right,
+            target,
             &location,
             |left, right, _| match (left, right) {
                 (Int(0), Int(0)) => Ok(Some(Int(0))),
@@ -401,12 +444,14 @@ fn fold_binary(
                 Box::new(right),
             ))
         }
-        Compare(Less) => Ok(fold_compare_op!(left, right, Compare, <, Less)),
-        Compare(LessEqual) => Ok(fold_compare_op!(left, right, Compare, <=, LessEqual)),
-        Compare(Greater) => Ok(fold_compare_op!(left, right, Compare, >, Greater)),
-        Compare(GreaterEqual) => Ok(fold_compare_op!(left, right, Compare, >=, GreaterEqual)),
-        Compare(EqualEqual) => Ok(fold_compare_op!(left, right, Compare, ==, EqualEqual)),
-        Compare(NotEqual) => Ok(fold_compare_op!(left, right, Compare, !=, NotEqual)),
+        Compare(Less) => Ok(fold_compare_op!(left, right, target, Compare, <, Less)),
+        Compare(LessEqual) => Ok(fold_compare_op!(left, right, target, Compare, <=, LessEqual)),
+        Compare(Greater) => Ok(fold_compare_op!(left, right, target, Compare, >, Greater)),
+        Compare(GreaterEqual) => {
+            Ok(fold_compare_op!(left, right, target, Compare, >=, GreaterEqual))
+        }
+        Compare(EqualEqual) => Ok(fold_compare_op!(left, right, target, Compare, ==, EqualEqual)),
+        Compare(NotEqual) => Ok(fold_compare_op!(left, right, target, Compare, !=, NotEqual)),
     }
 }
 
@@ -421,10 +466,10 @@ impl LiteralValue {
     }
 }
 
-fn cast(expr: Expr, ctype: &Type) -> CompileResult<ExprType> {
-    let expr = expr.const_fold()?;
+fn cast(expr: Expr, ctype: &Type, target: &TargetInfo) -> CompileResult<ExprType> {
+    let expr = expr.const_fold(target)?;
     Ok(if let ExprType::Literal(ref token) = expr.expr {
-        if let Some(token) = const_cast(token, ctype) {
+        if let Some(token) = const_cast(token, ctype, target.long_double_format()) {
             ExprType::Literal(token)
         } else {
             ExprType::Cast(Box::new(expr))
@@ -438,28 +483,52 @@ fn cast(expr: Expr, ctype: &Type) -> CompileResult<ExprType> {
 /// all this does is make sure the folded value is in a valid range
 /// TODO: when we add suffix literals, that will have type information
 /// and we can use that to store the new type
-fn const_cast(token: &LiteralValue, ctype: &Type) -> Option<LiteralValue> {
+///
+/// `long double` constants are rounded to `format` before being converted to anything else,
+/// since that is the value they would have at runtime.
+fn const_cast(
+    token: &LiteralValue,
+    ctype: &Type,
+    format: LongDoubleFormat,
+) -> Option<LiteralValue> {
     let token = match (token, ctype) {
         (Int(i), Type::Bool) => Int((*i != 0).into()),
         (Int(i), Type::Char(_)) => Char(*i as u8),
-        (Int(i), Type::Double) | (Int(i), Type::Float) => Float(*i as f64),
+        (Int(i), Type::LongDouble) => LongDouble((*i).into()),
+        (Int(i), ty) if ty.is_floating() => Float(*i as f64),
         (Int(i), ty) if ty.is_integral() && ty.is_signed() => Int(*i),
         (Int(i), ty) if ty.is_integral() => UnsignedInt(*i as u64),
 
         (UnsignedInt(u), Type::Bool) => Int((*u != 0).into()),
         (UnsignedInt(u), Type::Char(_)) => Char(*u as u8),
-        (UnsignedInt(u), Type::Double) | (UnsignedInt(u), Type::Float) => Float(*u as f64),
+        (UnsignedInt(u), Type::LongDouble) => LongDouble((*u).into()),
+        (UnsignedInt(u), ty) if ty.is_floating() => Float(*u as f64),
         (UnsignedInt(u), ty) if ty.is_integral() && ty.is_signed() => Int(*u as i64),
         (UnsignedInt(u), ty) if ty.is_integral() => UnsignedInt(*u),
 
         (Float(f), Type::Bool) => Int((*f != 0.0) as i64),
         (Float(f), Type::Char(_)) => Char(*f as u8),
-        (Float(f), Type::Double) | (Float(f), Type::Float) => Float(*f),
+        (Float(f), Type::LongDouble) => LongDouble((*f).into()),
+        (Float(f), ty) if ty.is_floating() => Float(*f),
         (Float(f), ty) if ty.is_integral() && ty.is_signed() => Int(*f as i64),
         (Float(f), ty) if ty.is_integral() => UnsignedInt(*f as u64),
 
+        (LongDouble(f), ty) => {
+            let f = f.round(format);
+            match ty {
+                Type::Bool => Int((!f.is_zero()).into()),
+                Type::Char(_) => Char(f.to_i64() as u8),
+                Type::LongDouble => LongDouble(f),
+                ty if ty.is_floating() => Float(f.to_f64()),
+                ty if ty.is_integral() && ty.is_signed() => Int(f.to_i64()),
+                ty if ty.is_integral() => UnsignedInt(f.to_u64()),
+                _ => return None,
+            }
+        }
+
         (&Char(c), Type::Bool) => Int((c != 0).into()),
-        (&Char(c), Type::Double) | (&Char(c), Type::Float) => Float(c.into()),
+        (&Char(c), Type::LongDouble) => LongDouble(u64::from(c).into()),
+        (&Char(c), ty) if ty.is_floating() => Float(c.into()),
         (&Char(c), ty) if ty.is_integral() && ty.is_signed() => Int(c.into()),
         (&Char(c), ty) if ty.is_integral() => UnsignedInt(c.into()),
 
@@ -475,9 +544,10 @@ fn shift_right(
     left: Expr,
     right: Expr,
     ctype: &Type,
+    target: &TargetInfo,
     location: &Location,
 ) -> CompileResult<ExprType> {
-    let (left, right) = (left.const_fold()?, right.const_fold()?);
+    let (left, right) = (left.const_fold(target)?, right.const_fold(target)?);
     if let ExprType::Literal(token) = right.expr {
         let shift = match token.non_negative_int() {
             Ok(u) => u,
@@ -485,7 +555,7 @@ fn shift_right(
                 return Err(location.error(SemanticError::NegativeShift { is_left: false }));
             }
         };
-        let sizeof = ctype.sizeof().map_err(|err| Locatable {
+        let sizeof = ctype.sizeof(target).map_err(|err| Locatable {
             data: err.to_string(),
             location: *location,
         })?;
@@ -526,9 +596,10 @@ fn shift_left(
     left: Expr,
     right: Expr,
     ctype: &Type,
+    target: &TargetInfo,
     location: &Location,
 ) -> CompileResult<ExprType> {
-    let (left, right) = (left.const_fold()?, right.const_fold()?);
+    let (left, right) = (left.const_fold(target)?, right.const_fold(target)?);
     if let ExprType::Literal(token) = right.expr {
         let shift = match token.non_negative_int() {
             Ok(u) => u,
@@ -538,7 +609,7 @@ fn shift_left(
         };
 
         if left.ctype.is_signed() {
-            let size = match left.ctype.sizeof() {
+            let size = match left.ctype.sizeof(target) {
                 Ok(s) => s,
                 Err(err) => {
                     return Err(Locatable::new(
@@ -593,7 +664,9 @@ mod tests {
     use crate::data::*;
 
     fn test_const_fold(s: &str) -> CompileResult<Expr> {
-        analyze_expr(s).unwrap().const_fold()
+        analyze_expr(s)
+            .unwrap()
+            .const_fold(&crate::arch::TargetInfo::host())
     }
     fn assert_fold(original: &str, expected: &str) {
         let (folded_a, folded_b) = (
@@ -771,4 +844,16 @@ mod tests {
         assert_fold("(long)'0'", "48");
         assert_fold("(unsigned short)'0'", "48u");
     }
+    #[test]
+    fn test_long_double() {
+        // these hold for every `long double` format, including `double`
+        assert_fold("1.5L * 2", "3.0L");
+        assert_fold("-(1.0L / 4) == -0.25", "1");
+        assert_fold("1.0L + 0x1p-52L > 1.0L", "1");
+        assert_fold("1.0L + 0x1p-200L == 1.0L", "1");
+        assert_fold("1e4000L > 1e308", "1");
+        assert_fold("(int)(2.5L * 3)", "7");
+        assert_fold("(double)(1.0L / 8)", "0.125");
+        assert_fold("!0.0L", "1");
+    }
 }
@@ -0,0 +1,16 @@
+// http://port70.net/~nsz/c/c11/n1570.html#7.9
+
+#ifndef __STDC_ISO646_H
+#define __STDC_ISO646_H
+#define and &&
+#define and_eq &=
+#define bitand &
+#define bitor |
+#define compl ~
+#define not !
+#define not_eq !=
+#define or ||
+#define or_eq |=
+#define xor ^
+#define xor_eq ^=
+#endif
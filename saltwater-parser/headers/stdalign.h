@@ -0,0 +1,9 @@
+// http://port70.net/~nsz/c/c11/n1570.html#7.15
+
+#ifndef __STDC_STDALIGN_H
+#define __STDC_STDALIGN_H
+#define alignas _Alignas
+#define alignof _Alignof
+#define __alignas_is_defined 1
+#define __alignof_is_defined 1
+#endif
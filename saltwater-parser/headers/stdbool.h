@@ -0,0 +1,9 @@
+// http://port70.net/~nsz/c/c11/n1570.html#7.18
+
+#ifndef __STDC_STDBOOL_H
+#define __STDC_STDBOOL_H
+#define bool _Bool
+#define true 1
+#define false 0
+#define __bool_true_false_are_defined 1
+#endif
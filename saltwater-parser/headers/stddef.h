@@ -2,11 +2,11 @@
 
 #ifndef __STDC_STDDEF_H
 #define __STDC_STDDEF_H
-typedef long ptrdiff_t;
-typedef unsigned long size_t;
-typedef long max_align_t;
+typedef __PTRDIFF_TYPE__ ptrdiff_t;
+typedef __SIZE_TYPE__ size_t;
+typedef long double max_align_t;
 // Unicode is at most 32-bits per character
-typedef int wchar_t;
+typedef __WCHAR_TYPE__ wchar_t;
 
 #define NULL 0
 #define offsetof(type, member) (offsetof not_currently_supported)
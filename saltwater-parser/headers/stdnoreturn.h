@@ -0,0 +1,6 @@
+// http://port70.net/~nsz/c/c11/n1570.html#7.23
+
+#ifndef __STDC_STDNORETURN_H
+#define __STDC_STDNORETURN_H
+#define noreturn _Noreturn
+#endif
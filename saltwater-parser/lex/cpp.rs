@@ -31,11 +31,13 @@ use std::borrow::Cow;
 use std::collections::{HashMap, VecDeque};
 use std::convert::TryFrom;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
 use super::files::FileProcessor;
+use super::pragma::{destringize, string_contents, PackState, PragmaHandler, Pragmas};
 use super::replace::{replace, replace_iter, Definition, Definitions};
 use super::{Lexer, LiteralParser, Token};
-use crate::arch::TARGET;
+use crate::arch::{predefined_macros, TargetInfo};
 use crate::data::error::CppError;
 use crate::data::lex::{Keyword, LiteralToken};
 use crate::data::*;
@@ -64,6 +66,10 @@ pub struct PreProcessorBuilder<'a> {
     search_path: Vec<Cow<'a, Path>>,
     /// The user-defined macros that should be defined at startup
     definitions: Definitions,
+    /// Pragma handlers in addition to the built-in ones
+    pragmas: Vec<(InternedStr, PragmaHandler)>,
+    /// The platform being compiled for
+    target: TargetInfo,
 }
 
 impl<'a> PreProcessorBuilder<'a> {
@@ -74,6 +80,8 @@ impl<'a> PreProcessorBuilder<'a> {
             buf: buf.into(),
             search_path: Vec::new(),
             definitions: Definitions::new(),
+            pragmas: Vec::new(),
+            target: TargetInfo::host(),
         }
     }
     pub fn filename<P: Into<PathBuf>>(mut self, name: P) -> Self {
@@ -92,14 +100,32 @@ impl<'a> PreProcessorBuilder<'a> {
         self.definitions.insert(name, def.into());
         self
     }
+    /// Set the platform to predefine macros for. Defaults to the host.
+    pub fn target(mut self, target: TargetInfo) -> Self {
+        self.target = target;
+        self
+    }
+    /// Add a handler for `#pragma <name>`, replacing any built-in handler for `name`.
+    pub fn pragma<F>(mut self, name: &str, handler: F) -> Self
+    where
+        F: Fn(&mut PreProcessor<'_>, &[Locatable<Token>], Location) -> CompileResult<()> + 'static,
+    {
+        self.pragmas.push((name.into(), Rc::new(handler)));
+        self
+    }
     pub fn build(self) -> PreProcessor<'a> {
-        PreProcessor::new(
+        let mut cpp = PreProcessor::new(
             self.buf,
             self.filename,
             self.debug,
             self.search_path,
             self.definitions,
-        )
+            self.target,
+        );
+        for (name, handler) in self.pragmas {
+            cpp.pragmas.add_handler(name, handler);
+        }
+        cpp
     }
 }
 
@@ -122,9 +148,9 @@ impl<'a> PreProcessorBuilder<'a> {
 /// Examples:
 ///
 /// ```
-/// use saltwater_parser::PreProcessor;
+/// use saltwater_parser::{arch::TargetInfo, PreProcessor};
 ///
-/// let cpp = PreProcessor::new("int main(void) { char *hello = \"hi\"; }\n", "example.c", false, vec![], Default::default());
+/// let cpp = PreProcessor::new("int main(void) { char *hello = \"hi\"; }\n", "example.c", false, vec![], Default::default(), TargetInfo::host());
 /// for token in cpp {
 ///     assert!(token.is_ok());
 /// }
@@ -138,9 +164,15 @@ pub struct PreProcessor<'a> {
     /// The paths to search for `#include`d files
     search_path: Vec<Cow<'a, Path>>,
     /// The current macro definitions
-    definitions: Definitions,
+    pub(super) definitions: Definitions,
     /// Handles reading from files
     file_processor: FileProcessor,
+    /// The pragma handlers and the state they keep
+    pub(super) pragmas: Pragmas,
+    /// The value of the next `__COUNTER__`
+    counter: u32,
+    /// The platform being compiled for
+    target: TargetInfo,
 }
 
 enum PendingToken {
@@ -172,8 +204,14 @@ impl TryFrom<&str> for Definition {
                 Ok(loc) => Ok(loc.data),
                 Err(err) => Err(err.data),
             })
-            .collect::<Result<_, _>>()
-            .map(Definition::Object)
+            .collect::<Result<Vec<_>, _>>()
+            .map(|mut tokens| {
+                // the newline added above isn't part of the definition
+                if let Some(Token::Whitespace(_)) = tokens.last() {
+                    tokens.pop();
+                }
+                Definition::Object(tokens)
+            })
     }
 }
 
@@ -244,12 +282,31 @@ impl Iterator for PreProcessor<'_> {
                                 Ok(()) => continue,
                             }
                         }
+                        CppToken::LineMarker(number) => {
+                            match self.line_marker(Locatable::new(number, loc.location)) {
+                                Err(err) => return Some(Err(err)),
+                                Ok(()) => continue,
+                            }
+                        }
                         CppToken::Token(token) => {
                             self.handle_token(PendingToken::NeedsReplacement(token), loc.location)
                         }
                     },
                 }
             };
+            // _Pragma("once")
+            if let Some(Ok(Locatable {
+                data: Token::Id(id),
+                location,
+            })) = replacement
+            {
+                if get_str!(id) == "_Pragma" {
+                    match self.pragma_operator(location) {
+                        Err(err) => return Some(Err(err)),
+                        Ok(()) => continue,
+                    }
+                }
+            }
             if let Some(token) = replacement {
                 return Some(token);
             }
@@ -311,6 +368,9 @@ impl<'a> PreProcessor<'a> {
             if let Token::Id(name) = &data {
                 if let Some(keyword) = KEYWORDS.get(get_str!(name)) {
                     *data = Token::Keyword(*keyword);
+                } else if get_str!(name) == "__COUNTER__" {
+                    // this can't be a normal definition, since it changes every time it's used
+                    *data = counter_token(&mut self.counter);
                 }
             }
         }
@@ -323,24 +383,30 @@ impl<'a> PreProcessor<'a> {
     /// but will never delete a file.
     ///
     /// The `debug` parameter specifies whether to print out tokens before replacement.
+    /// `target` determines the predefined macros and the system include directories.
     pub fn new<'search: 'a, I: IntoIterator<Item = Cow<'search, Path>>, S: Into<ArcStr>>(
         chars: S,
         filename: impl Into<std::ffi::OsString>,
         debug: bool,
         user_search_path: I,
         user_definitions: HashMap<InternedStr, Definition>,
+        target: TargetInfo,
     ) -> Self {
         let system_path = format!(
             "{}-{}-{}",
-            TARGET.architecture, TARGET.operating_system, TARGET.environment
+            target.triple.architecture, target.triple.operating_system, target.triple.environment
         );
 
-        let now = time::OffsetDateTime::now_utc();
+        let (now, invalid_epoch) = match std::env::var("SOURCE_DATE_EPOCH") {
+            Ok(epoch) => match parse_source_date_epoch(&epoch) {
+                Some(time) => (time, None),
+                None => (time::OffsetDateTime::now_utc(), Some(epoch)),
+            },
+            Err(_) => (time::OffsetDateTime::now_utc(), None),
+        };
 
         #[allow(clippy::inconsistent_digit_grouping)]
         let mut definitions = map! {
-            format!("__{}__", TARGET.architecture).into() => int_def(1),
-            format!("__{}__", TARGET.operating_system).into() => int_def(1),
             "__STDC__".into() => int_def(1),
             "__STDC_HOSTED__".into() => int_def(1),
             "__STDC_VERSION__".into() => int_def(2011_12),
@@ -351,6 +417,11 @@ impl<'a> PreProcessor<'a> {
             "__DATE__".into() => str_def(&now.format(&DATE_FMT).expect("Failed to format __DATE__")),
             "__TIME__".into() => str_def(&now.format(&TIME_FMT).expect("Failed to format __TIME__")),
         };
+        definitions.extend(
+            predefined_macros(&target)
+                .iter()
+                .map(|(name, body)| predefined_def(name, body)),
+        );
         definitions.extend(user_definitions);
         let mut search_path = vec![
             PathBuf::from(format!("/usr/local/include/{}", system_path)).into(),
@@ -369,7 +440,18 @@ impl<'a> PreProcessor<'a> {
             search_path,
             definitions,
             file_processor,
+            pragmas: Pragmas::new(),
+            counter: 0,
+            target,
         };
+        if let Some(epoch) = invalid_epoch {
+            let warning = format!(
+                "SOURCE_DATE_EPOCH must be a non-negative number of seconds, got '{}'; using the current time",
+                epoch
+            );
+            let location = new_cpp.span(0);
+            new_cpp.warn(error::Warning::Generic(warning), location);
+        }
         new_cpp.update_builtin_definitions(); // So they are defined from the start
         new_cpp
     }
@@ -386,6 +468,36 @@ impl<'a> PreProcessor<'a> {
         warnings
     }
 
+    /// Return all regions set by `#pragma GCC diagnostic` so far.
+    ///
+    /// Like `warnings()`, these are consumed. Regions that have not been popped
+    /// last until the end of their file.
+    pub fn diagnostic_regions(&mut self) -> Vec<DiagnosticRegion> {
+        std::mem::take(&mut self.pragmas.diagnostic_regions)
+    }
+
+    /// Add a handler for `#pragma <name>`, replacing any existing handler for `name`.
+    ///
+    /// See `PragmaHandler` for the arguments the handler is called with.
+    pub fn add_pragma<F>(&mut self, name: &str, handler: F)
+    where
+        F: Fn(&mut PreProcessor<'_>, &[Locatable<Token>], Location) -> CompileResult<()> + 'static,
+    {
+        self.pragmas.add_handler(name.into(), Rc::new(handler));
+    }
+
+    /// Return the maximum struct alignment set by `#pragma pack`.
+    ///
+    /// The parser needs this to lay out structs; see `Parser::set_pack_state`.
+    pub fn pack_state(&self) -> PackState {
+        self.pragmas.pack_state()
+    }
+
+    /// Emit a warning. This is useful for pragma handlers.
+    pub fn warn(&mut self, warning: error::Warning, location: Location) {
+        self.error_handler.warn(warning, location);
+    }
+
     pub fn eof(&self) -> Location {
         self.file_processor.eof()
     }
@@ -399,6 +511,10 @@ impl<'a> PreProcessor<'a> {
         self.file_processor.span(start)
     }
 
+    pub(super) fn current_path(&self) -> &Path {
+        self.file_processor.path()
+    }
+
     fn lexer(&mut self) -> &Lexer {
         self.file_processor.lexer()
     }
@@ -452,6 +568,12 @@ impl<'a> PreProcessor<'a> {
                         Err(Locatable::new(CppError::InvalidDirective.into(), location))
                     }
                 }
+                Ok(Locatable {
+                    data: number @ Token::Literal(LiteralToken::Int(_)),
+                    location,
+                }) if self.file_processor.line() == line => {
+                    Ok(Locatable::new(CppToken::LineMarker(number), location))
+                }
                 Ok(other) => {
                     if self.file_processor.line() == line {
                         Err(other.map(|tok| CppError::UnexpectedToken("directive", tok).into()))
@@ -496,12 +618,12 @@ impl<'a> PreProcessor<'a> {
             IfNDef => {
                 self.consume_whitespace_oneline(start, CppError::ExpectedMacroId)?;
                 let name = self.expect_id()?;
-                self.if_directive(!self.definitions.contains_key(&name.data), start)
+                self.if_directive(!self.is_defined(name.data), start)
             }
             IfDef => {
                 self.consume_whitespace_oneline(start, CppError::ExpectedMacroId)?;
                 let name = self.expect_id()?;
-                self.if_directive(self.definitions.contains_key(&name.data), start)
+                self.if_directive(self.is_defined(name.data), start)
             }
             // No matter what happens here, we will not read the tokens from this `#elif`.
             // Either we have been reading an `#if` or an `#elif` or an `#else`;
@@ -552,10 +674,17 @@ impl<'a> PreProcessor<'a> {
                 Ok(())
             }
             Pragma => {
-                self.error_handler
-                    .warn(WarningDiagnostic::IgnoredPragma, self.span(start));
-                drop(self.tokens_until_newline(false));
-                Ok(())
+                let tokens: Result<Vec<_>, _> =
+                    self.tokens_until_newline(false).into_iter().collect();
+                let location = self.span(start);
+                match tokens {
+                    Ok(tokens) => self.pragma(&tokens, location),
+                    // pragmas we don't understand can have any tokens
+                    Err(_) => {
+                        self.warn(WarningDiagnostic::IgnoredPragma, location);
+                        Ok(())
+                    }
+                }
             }
             // NOTE: #warning is a non-standard extension, but is implemented
             // by most major compilers including clang and gcc.
@@ -580,12 +709,17 @@ impl<'a> PreProcessor<'a> {
                 Ok(())
             }
             Line => {
-                self.error_handler.warn(
-                    WarningDiagnostic::Generic("#line is not yet implemented".into()),
-                    self.span(start),
-                );
-                drop(self.tokens_until_newline(false));
-                Ok(())
+                let tokens: Vec<_> = self
+                    .tokens_until_newline(false)
+                    .into_iter()
+                    .collect::<Result<_, _>>()?;
+                // unlike linemarkers, `#line` is macro-replaced (6.10.4p5)
+                self.update_builtin_definitions();
+                let tokens: Vec<_> = replace_iter(tokens.into_iter().map(Ok), &self.definitions)
+                    .flatten()
+                    .filter(|token| !Self::is_whitespace(token))
+                    .collect::<Result<_, _>>()?;
+                self.line_directive(tokens, self.span(start), false)
             }
             Include => self.include(start),
         }
@@ -601,9 +735,10 @@ impl<'a> PreProcessor<'a> {
 
         self.update_builtin_definitions();
         // TODO: is this unwrap safe? there should only be scalar types in a cpp directive...
-        match Self::cpp_expr(&self.definitions, lex_tokens.into_iter(), location)?
+        match self
+            .cpp_expr(lex_tokens.into_iter(), location)?
             .truthy(&mut self.error_handler)
-            .constexpr()?
+            .constexpr(&self.target)?
             .data
         {
             (LiteralValue::Int(i), Type::Bool) => Ok(i != 0),
@@ -683,11 +818,9 @@ impl<'a> PreProcessor<'a> {
     ///
     /// Note that identifiers are replaced with a constant 0,
     /// as per [6.10.1](http://port70.net/~nsz/c/c11/n1570.html#6.10.1p4).
-    pub fn cpp_expr<L>(
-        definitions: &Definitions,
-        mut lex_tokens: L,
-        location: Location,
-    ) -> CompileResult<hir::Expr>
+    ///
+    /// As well as `defined`, this evaluates `__has_include`, `__has_include_next`, and `__has_builtin`.
+    pub fn cpp_expr<L>(&mut self, mut lex_tokens: L, location: Location) -> CompileResult<hir::Expr>
     where
         L: Iterator<Item = Locatable<Token>>,
     {
@@ -696,6 +829,10 @@ impl<'a> PreProcessor<'a> {
 
         let mut cpp_tokens = Vec::with_capacity(lex_tokens.size_hint().1.unwrap_or_default());
         let defined = "defined".into();
+        let has_include = "__has_include".into();
+        let has_include_next = "__has_include_next".into();
+        let has_builtin = "__has_builtin".into();
+        let literal = |condition: bool| if condition { ONE } else { ZERO };
 
         while let Some(token) = lex_tokens.next() {
             let token = match token {
@@ -705,31 +842,50 @@ impl<'a> PreProcessor<'a> {
                     location,
                 } if name == defined => {
                     let def = Self::defined(&mut lex_tokens, location)?;
-                    let literal = if definitions.contains_key(&def) {
-                        ONE
-                    } else {
-                        ZERO
-                    };
-                    location.with(Token::Literal(literal))
+                    location.with(Token::Literal(literal(self.is_defined(def))))
+                }
+                // #if __has_include(<stdatomic.h>)
+                Locatable {
+                    data: Token::Id(name),
+                    location,
+                } if name == has_include || name == has_include_next => {
+                    let next = name == has_include_next;
+                    let found = self.has_include(&mut lex_tokens, next, location)?;
+                    location.with(Token::Literal(literal(found)))
+                }
+                // #if __has_builtin(__builtin_va_arg)
+                Locatable {
+                    data: Token::Id(name),
+                    location,
+                } if name == has_builtin => {
+                    let builtin = Self::defined(&mut lex_tokens, location)?;
+                    let found = BUILTINS.contains(&get_str!(builtin));
+                    location.with(Token::Literal(literal(found)))
                 }
                 _ => token,
             };
             cpp_tokens.push(token);
         }
         let mut expr_location = None;
-        let cpp_tokens: Vec<_> = replace_iter(cpp_tokens.into_iter().map(Result::Ok), definitions)
-            .flatten()
-            .filter(PreProcessor::is_not_whitespace)
-            .map(|mut token| {
-                if let Ok(tok) = &mut token {
-                    expr_location = Some(location.maybe_merge(expr_location));
-                    if let Token::Id(_) = tok.data {
-                        tok.data = Token::Literal(ZERO);
+        let counter = &mut self.counter;
+        let cpp_tokens: Vec<_> =
+            replace_iter(cpp_tokens.into_iter().map(Result::Ok), &self.definitions)
+                .flatten()
+                .filter(PreProcessor::is_not_whitespace)
+                .map(|mut token| {
+                    if let Ok(tok) = &mut token {
+                        expr_location = Some(location.maybe_merge(expr_location));
+                        if let Token::Id(name) = tok.data {
+                            tok.data = if get_str!(name) == "__COUNTER__" {
+                                counter_token(counter)
+                            } else {
+                                Token::Literal(ZERO)
+                            };
+                        }
                     }
-                }
-                token
-            })
-            .collect();
+                    token
+                })
+                .collect();
         if cpp_tokens.is_empty() {
             return Err(CompileError::new(
                 CppError::EmptyExpression.into(),
@@ -749,7 +905,7 @@ impl<'a> PreProcessor<'a> {
         // TODO: catch expressions that aren't allowed
         // (see https://github.com/jyn514/rcc/issues/5#issuecomment-575339427)
         // TODO: can semantic errors happen here? should we check?
-        Ok(PureAnalyzer::new().expr(expr))
+        Ok(PureAnalyzer::new(self.target.clone()).expr(expr))
     }
     /// We saw an `#if`, `#ifdef`, or `#ifndef` token at the start of the line
     /// and want to either take the branch or ignore the tokens within the directive.
@@ -833,7 +989,9 @@ impl<'a> PreProcessor<'a> {
     // after:
     // #define f(a, b, c) a + b + c
     //                   ^
-    fn fn_args(&mut self, start: u32) -> Result<Vec<InternedStr>, Locatable<Error>> {
+    //
+    // Returns the named parameters and whether the macro is variadic.
+    fn fn_args(&mut self, start: u32) -> Result<(Vec<InternedStr>, bool), Locatable<Error>> {
         let mut arguments = Vec::new();
         loop {
             match self.file_processor.next_non_whitespace() {
@@ -849,18 +1007,38 @@ impl<'a> PreProcessor<'a> {
                     self.error_handler.push_back(err);
                     continue;
                 }
+                // `...` must be the last parameter
                 Some(Ok(Locatable {
                     data: Token::Ellipsis,
                     ..
                 })) => {
-                    let location = self.lexer().span(start);
-                    self.error_handler
-                        .warn(crate::data::error::Warning::IgnoredVariadic, location);
+                    self.consume_whitespace_oneline(
+                        self.file_processor.offset(),
+                        CppError::Expected("')'", "macro parameter list"),
+                    )?;
+                    if self.lexer_mut().match_next(')') {
+                        return Ok((arguments, true));
+                    }
+                    return match self.file_processor.next() {
+                        None => Err(CompileError::new(
+                            CppError::EndOfFile("')'").into(),
+                            self.lexer().span(start),
+                        )),
+                        Some(Err(err)) => Err(err),
+                        Some(Ok(other)) => Err(other
+                            .map(|tok| CppError::UnexpectedToken("')' after '...'", tok).into())),
+                    };
                 }
                 Some(Ok(Locatable {
                     data: Token::Id(id),
-                    ..
-                })) => arguments.push(id),
+                    location,
+                })) => {
+                    if id == "__VA_ARGS__".into() {
+                        self.error_handler
+                            .error(CppError::VaArgsOutsideVariadic(id), location);
+                    }
+                    arguments.push(id)
+                }
                 Some(Ok(Locatable {
                     data: other,
                     location,
@@ -875,7 +1053,7 @@ impl<'a> PreProcessor<'a> {
             )?;
             // either `,` or `)`
             if self.lexer_mut().match_next(')') {
-                return Ok(arguments);
+                return Ok((arguments, false));
             }
             if self.lexer_mut().match_next(',') {
                 continue;
@@ -922,23 +1100,44 @@ impl<'a> PreProcessor<'a> {
                 self.file_processor.offset(),
                 CppError::Expected(")", "macro parameter list"),
             )?;
-            let params = if !self.lexer_mut().match_next(')') {
+            let (params, variadic) = if !self.lexer_mut().match_next(')') {
                 self.fn_args(start)?
             } else {
-                Vec::new()
+                (Vec::new(), false)
             };
             let body = body(self)?;
-            self.define_macro(id.data, Definition::Function { params, body })
+            if !variadic {
+                Self::check_va_args(&body).map_err(|e| self.span(start).with(e))?;
+            }
+            let definition = Definition::Function {
+                params,
+                variadic,
+                body,
+            };
+            self.define_macro(id.data, definition)
                 .map_err(|e| self.span(start).with(e))?;
             Ok(())
         } else {
             // object macro
             let tokens = body(self)?;
+            Self::check_va_args(&tokens).map_err(|e| self.span(start).with(e))?;
             self.define_macro(id.data, Definition::Object(tokens))
                 .map_err(|e| self.span(start).with(e))?;
             Ok(())
         }
     }
+    // `__VA_ARGS__` and `__VA_OPT__` can only be used in the body of a variadic macro
+    // http://port70.net/~nsz/c/c11/n1570.html#6.10.3p5
+    fn check_va_args(body: &[Token]) -> Result<(), CppError> {
+        let (va_args, va_opt) = ("__VA_ARGS__".into(), "__VA_OPT__".into());
+        match body
+            .iter()
+            .find(|&token| *token == Token::Id(va_args) || *token == Token::Id(va_opt))
+        {
+            Some(&Token::Id(id)) => Err(CppError::VaArgsOutsideVariadic(id)),
+            _ => Ok(()),
+        }
+    }
     fn define_macro(&mut self, name: InternedStr, definition: Definition) -> Result<(), CppError> {
         use std::collections::hash_map::Entry;
         match self.definitions.entry(name) {
@@ -948,7 +1147,7 @@ impl<'a> PreProcessor<'a> {
             }
             Entry::Occupied(entry) => {
                 // This behavior is defined by the spec in section 6.10.3p1
-                if entry.get() != &definition {
+                if !entry.get().is_identical(&definition) {
                     Err(CppError::IncompatibleRedefinition(name))
                 } else {
                     Ok(())
@@ -959,8 +1158,8 @@ impl<'a> PreProcessor<'a> {
     // http://port70.net/~nsz/c/c11/n1570.html#6.10.2
     // `#include <file>` - system include
     // `#include "file"` - local include, but falls back to system include if `file` is not found.
+    // `#include MACRO` - computed include, the macro must expand to one of the above
     fn include(&mut self, start: u32) -> Result<(), Locatable<Error>> {
-        use crate::data::lex::ComparisonToken;
         self.consume_whitespace_oneline(start, CppError::EmptyInclude)?;
         let lexer = self.lexer_mut();
         let local = if lexer.match_next('"') {
@@ -968,71 +1167,159 @@ impl<'a> PreProcessor<'a> {
         } else if lexer.match_next('<') {
             false
         } else {
-            let (id, location) = match self.file_processor.next() {
-                Some(Ok(Locatable {
-                    data: Token::Id(id),
-                    location,
-                })) => (id, location),
-                Some(Err(err)) => return Err(err),
-                Some(Ok(other)) => {
-                    return Err(CompileError::new(
-                        CppError::UnexpectedToken("include file", other.data).into(),
-                        other.location,
-                    ))
-                }
-                None => {
-                    return Err(CompileError::new(
-                        CppError::EndOfFile("include file").into(),
-                        self.span(start),
-                    ))
-                }
-            };
-            self.update_builtin_definitions();
-            match replace(
-                &self.definitions,
-                Token::Id(id),
-                &mut self.file_processor,
-                location,
-            )
-            .into_iter()
-            .next()
-            {
-                // local
-                Some(Ok(Locatable {
-                    data: Token::Literal(LiteralToken::Str(_)),
-                    ..
-                })) => unimplemented!("#include for macros"), //return self.include_path(id, true, start),
-                // system
-                Some(Ok(Locatable {
-                    data: Token::Comparison(ComparisonToken::Less),
-                    ..
-                })) => unimplemented!("#include for macros"),
-                Some(Err(err)) => return Err(err),
-                Some(Ok(other)) => {
-                    return Err(CompileError::new(
-                        CppError::UnexpectedToken("include file", other.data).into(),
-                        other.location,
-                    ))
-                }
-                None => {
-                    return Err(CompileError::new(
-                        CppError::EndOfFile("include file").into(),
-                        self.span(start),
-                    ))
-                }
-            }
+            let (filename, local) = self.computed_include(start)?;
+            return self.include_path(filename, local, start);
         };
 
         let end = if local { '"' } else { '>' };
         let filename = PathBuf::from(self.chars_until(end).to_owned());
         self.include_path(filename, local, start)
     }
+    // `#include pp-tokens`: the tokens are macro-replaced,
+    // then must be either a single string literal or a sequence of tokens between `<` and `>`.
+    // http://port70.net/~nsz/c/c11/n1570.html#6.10.2p4
+    //
+    // Returns the filename and whether it was a local include.
+    fn computed_include(&mut self, start: u32) -> Result<(PathBuf, bool), Locatable<Error>> {
+        let tokens: Vec<_> = self
+            .tokens_until_newline(true)
+            .into_iter()
+            .collect::<Result<_, _>>()?;
+        self.update_builtin_definitions();
+        let tokens: Vec<_> = replace_iter(tokens.into_iter().map(Ok), &self.definitions)
+            .flatten()
+            .collect::<Result<_, _>>()?;
+        Self::header_name(tokens, self.span(start))
+    }
+    // Turn `"file"` or `<file>` back into a filename, after they've been lexed as tokens.
+    //
+    // Returns the filename and whether it was a local include.
+    fn header_name(
+        tokens: Vec<Locatable<Token>>,
+        location: Location,
+    ) -> Result<(PathBuf, bool), Locatable<Error>> {
+        use crate::data::lex::ComparisonToken;
+
+        let mut tokens = tokens
+            .into_iter()
+            .skip_while(|token| matches!(token.data, Token::Whitespace(_)));
+        let unexpected = |token: Locatable<Token>| {
+            Err(token.map(|tok| CppError::UnexpectedToken("include file", tok).into()))
+        };
+
+        let (filename, local) = match tokens.next() {
+            None => return Err(location.error(CppError::EmptyInclude)),
+            Some(token) => match &token.data {
+                Token::Literal(LiteralToken::Str(_)) => match string_contents(&token.data) {
+                    Some(filename) => (filename, true),
+                    None => return unexpected(token),
+                },
+                // 6.10.2p4: "The method by which a sequence of preprocessing tokens
+                // between a < and a > preprocessing token pair ... are combined into a single header name
+                // preprocessing token is implementation-defined."
+                // Like GCC, this keeps whitespace between the tokens.
+                Token::Comparison(ComparisonToken::Less) => {
+                    let mut filename = String::new();
+                    loop {
+                        match tokens.next() {
+                            Some(Locatable {
+                                data: Token::Comparison(ComparisonToken::Greater),
+                                ..
+                            }) => break,
+                            Some(Locatable {
+                                data: Token::Whitespace(_),
+                                ..
+                            }) => filename.push(' '),
+                            Some(token) => filename.push_str(&token.data.to_string()),
+                            None => {
+                                return Err(
+                                    token.map(|_| CppError::Expected(">", "#include").into())
+                                )
+                            }
+                        }
+                    }
+                    (filename.trim().to_owned(), false)
+                }
+                _ => return unexpected(token),
+            },
+        };
+        match tokens.find(|token| !matches!(token.data, Token::Whitespace(_))) {
+            None => Ok((PathBuf::from(filename), local)),
+            Some(token) => Err(token.map(|tok| CppError::UnexpectedToken("newline", tok).into())),
+        }
+    }
+    // `__has_include(<file>)`, `__has_include("file")`, or `__has_include(MACRO)`.
+    // This assumes `__has_include` or `__has_include_next` has already been consumed.
+    // https://gcc.gnu.org/onlinedocs/cpp/_005f_005fhas_005finclude.html
+    fn has_include(
+        &self,
+        lex_tokens: &mut impl Iterator<Item = Locatable<Token>>,
+        next: bool,
+        location: Location,
+    ) -> Result<bool, CompileError> {
+        use crate::data::lex::ComparisonToken;
+
+        match lex_tokens.next() {
+            Some(Locatable {
+                data: Token::LeftParen,
+                ..
+            }) => {}
+            Some(other) => {
+                return Err(other.map(|tok| CppError::UnexpectedToken("left paren", tok).into()))
+            }
+            None => return Err(location.error(CppError::EndOfFile("left paren"))),
+        }
+        let mut depth = 1;
+        let mut tokens = Vec::new();
+        loop {
+            let token = lex_tokens
+                .next()
+                .ok_or_else(|| location.error(CppError::Expected(")", "__has_include")))?;
+            match token.data {
+                Token::LeftParen => depth += 1,
+                Token::RightParen => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+            tokens.push(token);
+        }
+        // like `#include`, the tokens are only replaced if they aren't already a header name
+        let tokens = match tokens.first().map(|token| &token.data) {
+            Some(Token::Literal(LiteralToken::Str(_)))
+            | Some(Token::Comparison(ComparisonToken::Less)) => tokens,
+            _ => replace_iter(tokens.into_iter().map(Ok), &self.definitions)
+                .flatten()
+                .collect::<Result<_, _>>()?,
+        };
+        let (filename, local) = Self::header_name(tokens, location)?;
+        let builtin = filename
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(get_builtin_header)
+            .is_some();
+        Ok(self
+            .find_include_path(&filename, local, next, location.span.start)
+            .is_ok()
+            || builtin)
+    }
+    /// Whether `name` is a macro, including special macros which aren't in `definitions`.
+    fn is_defined(&self, name: InternedStr) -> bool {
+        self.definitions.contains_key(&name) || SPECIAL_MACROS.contains(&get_str!(name))
+    }
     // we've done the parsing for an `#include`,
     // now we want to figure what file on disk it corresponds to
+    //
+    // `next` is for `__has_include_next`: it skips the current directory and
+    // all of the search path up to and including the directory containing the current file.
     fn find_include_path(
-        &mut self,
+        &self,
         filename: &Path,
         local: bool,
+        next: bool,
         start: u32,
     ) -> Result<PathBuf, Locatable<Error>> {
         if filename.as_os_str().is_empty() {
@@ -1057,9 +1344,9 @@ impl<'a> PreProcessor<'a> {
                 not_found(self, filename)
             };
         }
+        let current_path = self.file_processor.path();
         // local include: #include "dict.h"
-        if local {
-            let current_path = self.file_processor.path();
+        if local && !next {
             let relative_path = &current_path
                 .parent()
                 .unwrap_or_else(|| std::path::Path::new(""));
@@ -1068,9 +1355,17 @@ impl<'a> PreProcessor<'a> {
                 return Ok(resolved);
             }
         }
+        let skip = if next {
+            self.search_path
+                .iter()
+                .position(|dir| current_path.starts_with(dir))
+                .map_or(0, |i| i + 1)
+        } else {
+            0
+        };
         // if we don't find it locally, we fall back to system headers
         // this is part of the spec! http://port70.net/~nsz/c/c11/n1570.html#6.10.2p3
-        for path in &self.search_path {
+        for path in &self.search_path[skip..] {
             let mut buf = path.clone().into_owned();
             buf.push(filename);
             if buf.exists() {
@@ -1088,7 +1383,7 @@ impl<'a> PreProcessor<'a> {
         local: bool,
         start: u32,
     ) -> Result<(), Locatable<Error>> {
-        let (path, src) = match self.find_include_path(&filename, local, start) {
+        let (path, src) = match self.find_include_path(&filename, local, false, start) {
             Ok(path) => {
                 let src = std::fs::read_to_string(&path)
                     .map_err(|err| Locatable {
@@ -1113,9 +1408,13 @@ impl<'a> PreProcessor<'a> {
                 }
             }
         };
+        if self.pragmas.is_once(&path) {
+            return Ok(());
+        }
         let source = crate::Source {
             path,
             code: ArcStr::clone(&src),
+            line_directives: Vec::new(),
         };
         self.file_processor.add_file(filename, source);
         Ok(())
@@ -1168,19 +1467,152 @@ impl<'a> PreProcessor<'a> {
         Ok(ret)
     }
 
+    /// Handle a GNU linemarker, `# 1 "file.c" 1 3`.
+    ///
+    /// This assumes the `#` and the line number have already been consumed.
+    fn line_marker(&mut self, number: Locatable<Token>) -> Result<(), CompileError> {
+        let location = number.location;
+        let mut tokens = vec![number];
+        for token in self.tokens_until_newline(false) {
+            tokens.push(token?);
+        }
+        self.line_directive(tokens, location, true)
+    }
+
+    /// `#line digit-sequence "s-char-sequence"`, or the same thing in a linemarker.
+    ///
+    /// Both change the line number (and optionally the filename) reported for all following lines.
+    /// Linemarkers may have flags after the filename, saying whether a header was entered or left;
+    /// these are ignored.
+    /// http://port70.net/~nsz/c/c11/n1570.html#6.10.4
+    /// https://gcc.gnu.org/onlinedocs/cpp/Preprocessor-Output.html
+    fn line_directive(
+        &mut self,
+        tokens: Vec<Locatable<Token>>,
+        location: Location,
+        line_marker: bool,
+    ) -> Result<(), CompileError> {
+        let mut tokens = tokens.into_iter();
+        let number = tokens
+            .next()
+            .ok_or_else(|| location.error(CppError::Expected("line number", "#line")))?;
+        // 6.10.4p3: the digit sequence is decimal even if it starts with 0,
+        // and must not be greater than 2147483647
+        let line = match &number.data {
+            Token::Literal(LiteralToken::Int(digits))
+                if digits.bytes().all(|byte| byte.is_ascii_digit()) =>
+            {
+                digits.parse::<i32>().ok()
+            }
+            _ => None,
+        };
+        let line = match line {
+            Some(line) => line as u32,
+            None => {
+                return Err(number.map(|tok| CppError::UnexpectedToken("line number", tok).into()))
+            }
+        };
+        let filename = match tokens.next() {
+            None => None,
+            Some(token) => match string_contents(&token.data) {
+                Some(filename) => Some(ArcStr::from(destringize(&filename))),
+                None => {
+                    return Err(token.map(|tok| CppError::UnexpectedToken("filename", tok).into()))
+                }
+            },
+        };
+        if !line_marker {
+            if let Some(token) = tokens.next() {
+                return Err(token.map(|tok| CppError::UnexpectedToken("newline", tok).into()));
+            }
+        }
+        self.file_processor.add_line_directive(line, filename);
+        Ok(())
+    }
+
     fn update_builtin_definitions(&mut self) {
+        let (line, filename) = self.file_processor.presumed_line();
+        let filename = match filename {
+            Some(filename) => filename.to_string(),
+            None => self.file_processor.path().to_string_lossy().into_owned(),
+        };
         self.definitions.extend(map! {
-            "__LINE__".into() => int_def((self.line() + 1) as i32),
-            "__FILE__".into() => str_def(self.file_processor.path().to_string_lossy()),
+            "__LINE__".into() => int_def(line as i32),
+            "__FILE__".into() => str_def(filename),
         })
     }
 }
 
+/// Macros which are defined, but can't be replaced using `definitions`.
+const SPECIAL_MACROS: [&str; 4] = [
+    "__COUNTER__",
+    "__has_include",
+    "__has_include_next",
+    "__has_builtin",
+];
+
+/// The builtin functions recognized by `__has_builtin`.
+const BUILTINS: [&str; 4] = [
+    "__builtin_va_start",
+    "__builtin_va_arg",
+    "__builtin_va_end",
+    "__builtin_va_copy",
+];
+
+/// Return the current value of `__COUNTER__` and increment it.
+fn counter_token(counter: &mut u32) -> Token {
+    let value = *counter;
+    *counter += 1;
+    LiteralToken::Int(Substr::from(value.to_string())).into()
+}
+
+/// The time `SOURCE_DATE_EPOCH` refers to, or `None` if it's invalid.
+///
+/// This is used instead of the current time for `__DATE__` and `__TIME__` so builds are reproducible.
+/// https://reproducible-builds.org/specs/source-date-epoch/
+fn parse_source_date_epoch(epoch: &str) -> Option<time::OffsetDateTime> {
+    if epoch.is_empty() || !epoch.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let seconds = epoch.parse().ok()?;
+    let time = time::OffsetDateTime::from_unix_timestamp(seconds).ok()?;
+    // `__DATE__` only has room for a 4-digit year
+    if time.year() > 9999 {
+        return None;
+    }
+    Some(time)
+}
+
 fn int_def(i: i32) -> Definition {
     Definition::Object(vec![LiteralToken::Int(Substr::from(i.to_string())).into()])
 }
+/// Turn a macro from `arch::predefined_macros` into a definition.
+///
+/// A name like `__INT64_C(c)` becomes a function-like macro.
+fn predefined_def(name: &str, body: &str) -> (InternedStr, Definition) {
+    let body = match Definition::try_from(body) {
+        Ok(Definition::Object(tokens)) => tokens,
+        _ => panic!("invalid predefined macro {}", name),
+    };
+    match name.find('(') {
+        Some(paren) => {
+            let params = name[paren + 1..name.len() - 1]
+                .split(',')
+                .map(|param| param.trim().into())
+                .collect();
+            let def = Definition::Function {
+                params,
+                variadic: false,
+                body,
+            };
+            (name[..paren].into(), def)
+        }
+        None => (name.into(), Definition::Object(body)),
+    }
+}
 fn str_def<S: Into<String>>(s: S) -> Definition {
-    let substr = Substr::from(arcstr::format!("\"{}\"", s.into().replace(r#"""#, r#"\""#)));
+    let escaped = s.into().replace('\\', r"\\").replace('"', r#"\""#);
+    let substr = Substr::from(arcstr::format!("\"{}\"", escaped));
     Definition::Object(vec![LiteralToken::Str(vec![substr]).into()])
 }
 
@@ -1196,9 +1628,16 @@ macro_rules! built_in_headers {
 // [(filename, contents)]
 // TODO: this could probably use a perfect-hashmap,
 // but it's so small that it's not worth it
-const PRECOMPILED_HEADERS: [(&str, &str); 2] = built_in_headers! {
+const PRECOMPILED_HEADERS: [(&str, &str); 9] = built_in_headers! {
+    "float.h",
+    "iso646.h",
+    "limits.h",
+    "stdalign.h",
     "stdarg.h",
+    "stdbool.h",
     "stddef.h",
+    "stdint.h",
+    "stdnoreturn.h",
 };
 
 fn get_builtin_header(expected: impl AsRef<str>) -> Option<&'static str> {
@@ -1229,6 +1668,8 @@ enum DirectiveKind {
 enum CppToken {
     Token(Token),
     Directive(DirectiveKind),
+    /// A GNU linemarker, `# 1 "file.c"`. This holds the line number.
+    LineMarker(Token),
 }
 
 impl From<Locatable<Token>> for Locatable<CppToken> {
@@ -1318,12 +1759,17 @@ lazy_static! {
         "_Alignas" => Keyword::Alignas,
         "_Generic" => Keyword::Generic,
         "_Static_assert" => Keyword::StaticAssert,
+        "__builtin_va_start" => Keyword::VaStart,
+        "__builtin_va_arg" => Keyword::VaArg,
+        "__builtin_va_end" => Keyword::VaEnd,
+        "__builtin_va_copy" => Keyword::VaCopy,
     };
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::data::error::Warning;
     use crate::data::lex::test::{cpp, cpp_no_newline};
 
     macro_rules! assert_err {
@@ -1575,6 +2021,54 @@ a(2)
 a(2)
 ";
         assert_same(src, "2+1");
+
+        // all whitespace separations are the same
+        let src = "#define a(b) b  ##\t1\n#define a(b) b ## 1\na(2)";
+        assert_same(src, "21");
+        let src = "#define a b + 1\n#define a b+1\na";
+        assert_err!(
+            src,
+            CppError::IncompatibleRedefinition(_),
+            "incompatible redefinition"
+        );
+    }
+    #[test]
+    fn builtin_headers() {
+        // these are used when there are no system headers, so they can't depend on any
+        for (name, contents) in &PRECOMPILED_HEADERS {
+            let errors: Vec<_> = crate::analyze::test::decls(contents)
+                .into_iter()
+                .filter_map(Result::err)
+                .collect();
+            assert!(errors.is_empty(), "errors in {}: {:?}", name, errors);
+        }
+        let headers = format!(
+            "{}{}",
+            get_builtin_header("limits.h").unwrap(),
+            get_builtin_header("stdint.h").unwrap()
+        );
+        assert_same(
+            &format!("{}UINT_MAX LLONG_MIN INT8_MIN UINT64_C(1)", headers),
+            &format!(
+                "{}(0x7fffffff * 2U + 1U) (-0x7fffffffffffffffLL - 1LL) (-0x7f - 1) 1UL",
+                headers
+            ),
+        );
+    }
+    #[test]
+    fn predefined() {
+        assert_same(
+            "#if __SIZEOF_POINTER__ == 8 && __INT_MAX__ == 2147483647 && __CHAR_BIT__ == 8\nyes\n#endif",
+            "yes",
+        );
+        assert_same(
+            "#if __BYTE_ORDER__ == __ORDER_LITTLE_ENDIAN__ && __STDC_VERSION__ >= 201112L\nyes\n#endif",
+            "yes",
+        );
+        assert_same("__INT64_C(5) __UINT8_C(1)", "5L 1");
+        assert_same("__SIZE_TYPE__ __INT32_TYPE__", "long unsigned int int");
+        // glibc redefines these with tabs
+        assert_same("#define __INT64_C(c)\tc ## L\n__INT64_C(5)", "5L");
     }
     #[test]
     fn undef() {
@@ -1639,11 +2133,150 @@ d
         assert!(cpp(src).next_non_whitespace().is_none());
     }
     #[test]
-    fn line() {
-        let src = "#line 1";
-        let mut cpp = cpp(src);
+    fn diagnostic_pragma() {
+        let src = "#pragma GCC diagnostic push
+#pragma GCC diagnostic ignored \"-Wimplicit-int\"
+#pragma GCC diagnostic ignored \"-Wnot-a-saltwater-warning\"
+#pragma clang diagnostic error \"-Wcpp\"
+#pragma GCC diagnostic pop
+#pragma GCC diagnostic warning \"-Wextra-semi\"
+";
+        let mut pragmas = cpp(src);
+        assert!(pragmas.next_non_whitespace().is_none());
+        assert!(pragmas.warnings().is_empty());
+        let regions = pragmas.diagnostic_regions();
+        let summary: Vec<_> = regions
+            .iter()
+            .map(|region| (region.flag, region.level, region.end == u32::MAX))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("implicit-int", WarningLevel::Ignored, false),
+                ("cpp", WarningLevel::Error, false),
+                ("extra-semi", WarningLevel::Warning, true),
+            ]
+        );
+        // the region starts after the pragma and ends at the `pop` (just after the `#`)
+        let ignored = "#pragma GCC diagnostic ignored \"-Wimplicit-int\"";
+        let start = src.find(ignored).unwrap() + ignored.len();
+        assert_eq!(regions[0].start, start as u32);
+        assert_eq!(
+            regions[0].end,
+            src.find("pragma GCC diagnostic pop").unwrap() as u32
+        );
+
+        let mut cpp = cpp("#pragma GCC diagnostic pop\n#pragma GCC diagnostic ignored\n");
         assert!(cpp.next_non_whitespace().is_none());
-        assert!(cpp.warnings().pop_front().is_some());
+        assert_eq!(cpp.warnings().len(), 2);
+        assert!(cpp.diagnostic_regions().is_empty());
+    }
+    #[test]
+    fn pragma_handlers() {
+        use std::cell::RefCell;
+
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let handler_seen = Rc::clone(&seen);
+        let mut cpp = PreProcessorBuilder::new("#pragma custom a b\n#pragma unknown\n")
+            .pragma("custom", move |_, tokens, _| {
+                let tokens = tokens.iter().map(|token| token.data.to_string());
+                handler_seen.borrow_mut().extend(tokens);
+                Ok(())
+            })
+            .build();
+        assert!(cpp.next_non_whitespace().is_none());
+        assert_eq!(*seen.borrow(), ["a", "b"]);
+        assert!(matches!(
+            cpp.warnings().pop_front().unwrap().data,
+            Warning::IgnoredPragma
+        ));
+
+        // handlers can be replaced and can return errors
+        let mut cpp = cpp_no_newline("#pragma once\n");
+        cpp.add_pragma("once", |_, _, location| {
+            Err(location.error(CppError::InvalidDirective))
+        });
+        assert!(cpp.next_non_whitespace().unwrap().is_err());
+    }
+    #[test]
+    fn push_pop_macro() {
+        assert_same(
+            "#define x 1
+#pragma push_macro(\"x\")
+#undef x
+#define x 2
+x
+#pragma pop_macro(\"x\")
+x",
+            "2 1",
+        );
+        // a macro that wasn't defined is undefined again
+        assert_same(
+            "#pragma push_macro(\"y\")
+#define y 1
+#pragma pop_macro(\"y\")
+y",
+            "y",
+        );
+    }
+    #[test]
+    fn pragma_operator() {
+        assert_same(
+            "#define x 1
+#define PRAGMA(p) _Pragma(#p)
+PRAGMA(push_macro(\"x\"))
+#undef x
+_Pragma(\"pop_macro(\\\"x\\\")\") x",
+            "1",
+        );
+        let mut cpp = cpp("_Pragma(\"message(\\\"hi\\\")\")");
+        assert!(cpp.next_non_whitespace().is_none());
+        match cpp.warnings().pop_front().unwrap().data {
+            Warning::PragmaMessage(message) => assert_eq!(message, "hi"),
+            other => panic!("expected a #pragma message, got {}", other),
+        }
+        assert_err!(
+            "_Pragma(1)",
+            CppError::UnexpectedToken(_, _),
+            "unexpected token"
+        );
+    }
+    #[test]
+    fn line() {
+        assert_same("#line 10\n__LINE__ __LINE__\n__LINE__", "10 10 11");
+        assert_same(
+            "#line 5 \"grammar.y\"\n__FILE__ __LINE__\n#line 20\n__FILE__ __LINE__",
+            "\"grammar.y\" 5 \"grammar.y\" 20",
+        );
+        // `#line` is macro-replaced
+        assert_same(
+            "#define L 7\n#define F \"f.c\"\n#line L F\n__LINE__ __FILE__",
+            "7 \"f.c\"",
+        );
+        // but linemarkers are not, and can have flags
+        assert_same(
+            "# 33 \"/usr/include/stdio.h\" 1 3 4\n__LINE__ __FILE__",
+            "33 \"/usr/include/stdio.h\"",
+        );
+        assert_same(
+            "# 1 \"C:\\\\dir\\\\\\\"q\\\".c\"\n__FILE__",
+            r#""C:\\dir\\\"q\".c""#,
+        );
+        // skipped linemarkers are ignored
+        assert_same("#if 0\n# 50 \"a.c\"\n#endif\n__LINE__", "4");
+
+        for src in &[
+            "#line",
+            "#line x",
+            "#line 0x10",
+            "#line 2147483648",
+            "#line 1 2",
+            "#line 1 \"a.c\" 3",
+            "#line 1 L\"a.c\"",
+            "# 1 x",
+        ] {
+            assert!(cpp(src).next_non_whitespace().unwrap().is_err(), "{}", src);
+        }
     }
     #[test]
     fn warning() {
@@ -1657,9 +2290,37 @@ d
         assert_err!("#error cannot drink and drive", CppError::User(_), "#error",);
     }
     #[test]
+    fn computed_include() {
+        let err = |src| match cpp(src).next_non_whitespace().unwrap().unwrap_err().data {
+            Error::PreProcessor(err) => err,
+            other => panic!("expected cpp error, got {}", other),
+        };
+        assert_eq!(
+            err("#define H <no/such file . h >\n#include H"),
+            CppError::FileNotFound("no/such file . h".into())
+        );
+        assert_eq!(
+            err("#define H(x) #x\n#include H(no_such_file.h)"),
+            CppError::FileNotFound("no_such_file.h".into())
+        );
+        assert_eq!(err("#define H\n#include H"), CppError::EmptyInclude);
+        assert_eq!(
+            err("#define H <stddef.h\n#include H"),
+            CppError::Expected(">", "#include")
+        );
+        for src in &[
+            "#define H 1\n#include H",
+            "#define H L\"stddef.h\"\n#include H",
+            "#define H \"stddef.h\" x\n#include H",
+            "#define H <stddef.h> x\n#include H",
+        ] {
+            assert!(matches!(err(src), CppError::UnexpectedToken(..)), "{}", src);
+        }
+    }
+    #[test]
     fn invalid_directive() {
         assert_err!("#wrong", CppError::InvalidDirective, "invalid directive",);
-        assert_err!("#1", CppError::UnexpectedToken(_, _), "unexpected token",);
+        assert_err!("#+", CppError::UnexpectedToken(_, _), "unexpected token",);
         assert_err!("#include", CppError::EmptyInclude, "empty include");
         assert_err!("#if defined", CppError::EndOfFile(_), "unexpected eof");
         for s in &[
@@ -1952,6 +2613,70 @@ h",
         assert_is_str("__DATE__");
         assert_is_str("__TIME__");
     }
+    #[test]
+    fn source_date_epoch() {
+        use time::OffsetDateTime;
+        let epoch = |s| parse_source_date_epoch(s).map(OffsetDateTime::unix_timestamp);
+        assert_eq!(epoch("0"), Some(0));
+        assert_eq!(epoch("1597589916"), Some(1_597_589_916));
+        assert_eq!(epoch("253402300799"), Some(253_402_300_799));
+        for invalid in &["", "-1", "+1", "1.5", " 1", "abc", "253402300800"] {
+            assert_eq!(epoch(invalid), None, "{}", invalid);
+        }
+    }
+    #[test]
+    fn counter() {
+        assert_same("__COUNTER__ __COUNTER__ __COUNTER__", "0 1 2");
+        assert_same("#define C __COUNTER__ __COUNTER__\nC C", "0 1 2 3");
+        assert_same(
+            "#if __COUNTER__ == 0 && __COUNTER__ == 1\n__COUNTER__\n#endif",
+            "2",
+        );
+        assert_same(
+            "#define S(x) #x\nS(__COUNTER__) __COUNTER__",
+            "\"__COUNTER__\" 0",
+        );
+    }
+    #[test]
+    fn has_include() {
+        assert_same(
+            "#if __has_include(<stddef.h>) && __has_include(\"stdarg.h\")\nyes\n#endif",
+            "yes",
+        );
+        assert_same(
+            "#if __has_include(<no/such/file.h>) || __has_include_next(\"no_such_file.h\")\nyes\n#endif",
+            "",
+        );
+        assert_same(
+            "#define H <stddef.h>\n#if __has_include(H)\nyes\n#endif",
+            "yes",
+        );
+        assert_same(
+            "#if defined(__has_include) && defined __has_include_next\nyes\n#endif",
+            "yes",
+        );
+        for src in &[
+            "#if __has_include\n#endif",
+            "#if __has_include(\n#endif",
+            "#if __has_include <stddef.h>\n#endif",
+            "#if __has_include(stddef.h)\n#endif",
+            "#if __has_include(<stddef.h)\n#endif",
+        ] {
+            assert!(cpp(src).next_non_whitespace().unwrap().is_err(), "{}", src);
+        }
+    }
+    #[test]
+    fn has_builtin() {
+        assert_same(
+            "#if __has_builtin(__builtin_va_arg) && !__has_builtin(__builtin_trap)\nyes\n#endif",
+            "yes",
+        );
+        assert_same("#ifdef __has_builtin\nyes\n#endif", "yes");
+        assert!(cpp("#if __has_builtin(1)\n#endif")
+            .next_non_whitespace()
+            .unwrap()
+            .is_err());
+    }
 
     #[test]
     fn hashhash() {
@@ -2000,6 +2725,70 @@ h",
         );
     }
 
+    #[test]
+    fn variadic_macros() {
+        assert_same(
+            "#define LOG(fmt, ...) printf(fmt, __VA_ARGS__)\nLOG(\"%d %d\", 1, (2, 3))",
+            "printf(\"%d %d\", 1, (2, 3))",
+        );
+        assert_same("#define f(...) g(__VA_ARGS__)\nf()", "g()");
+        assert_same("#define f(...) g(__VA_ARGS__)\nf(a,b,c)", "g(a,b,c)");
+        assert_same("#define f(a, ...) a __VA_ARGS__\nf(1)", "1");
+        assert_same_exact(
+            "#define str(...) #__VA_ARGS__\nstr(a,  b ,c)",
+            "\n\"a, b ,c\"",
+        );
+        assert_same("#define cat(a, ...) a ## __VA_ARGS__\ncat(x, y)", "xy");
+        assert!(cpp("#define f(a, ...) a\nf()").all(|x| x.is_ok()));
+        assert!(cpp("#define f(a, b, ...) a\nf(1)").any(|x| x.is_err()));
+    }
+    #[test]
+    fn gnu_comma_paste() {
+        let def = "#define LOG(fmt, ...) printf(fmt, ## __VA_ARGS__)\n";
+        assert_same(&format!("{}LOG(\"hi\")", def), "printf(\"hi\")");
+        assert_same(&format!("{}LOG(\"hi\",)", def), "printf(\"hi\")");
+        assert_same(&format!("{}LOG(\"%d\", 1, 2)", def), "printf(\"%d\", 1, 2)");
+    }
+    #[test]
+    fn va_opt() {
+        let def = "#define F(a, ...) f(a __VA_OPT__(,) __VA_ARGS__)\n";
+        assert_same(&format!("{}F(1)", def), "f(1)");
+        assert_same(&format!("{}F(1, 2, 3)", def), "f(1, 2, 3)");
+        assert_same(
+            "#define G(x, ...) __VA_OPT__((x + (__VA_ARGS__)))\nG(1) G(1, 2)",
+            "(1 + (2))",
+        );
+        assert_same_exact(
+            "#define S(...) #__VA_OPT__(a __VA_ARGS__)\nS() S(b)",
+            "\n\"\" \"a b\"",
+        );
+        assert!(cpp("#define F(...) __VA_OPT__\nF(1)").any(|x| x.is_err()));
+        assert!(cpp("#define F(...) __VA_OPT__(\nF(1)").any(|x| x.is_err()));
+    }
+    #[test]
+    fn invalid_variadic() {
+        assert_err!(
+            "#define f(a) __VA_ARGS__",
+            CppError::VaArgsOutsideVariadic(_),
+            "__VA_ARGS__ outside variadic macro",
+        );
+        assert_err!(
+            "#define f __VA_OPT__(a)",
+            CppError::VaArgsOutsideVariadic(_),
+            "__VA_OPT__ outside variadic macro",
+        );
+        assert_err!(
+            "#define f(__VA_ARGS__) 1",
+            CppError::VaArgsOutsideVariadic(_),
+            "__VA_ARGS__ as a parameter",
+        );
+        assert_err!(
+            "#define f(..., a) 1",
+            CppError::UnexpectedToken(_, _),
+            "parameter after ...",
+        );
+    }
+
     #[test]
     fn gnu_weirdness() {
         // #506
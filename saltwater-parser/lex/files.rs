@@ -3,8 +3,10 @@ use crate::{
     data::{CompileResult, Locatable, Token},
     ErrorHandler, Location,
 };
-use crate::{Files, Source};
+use crate::{Files, LineDirective, Source};
 use arcstr::ArcStr;
+use codespan::FileId;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 // TODO: this API is absolutely terrible, there's _no_ encapsulation
@@ -16,6 +18,10 @@ pub(super) struct FileProcessor {
     includes: Vec<Lexer>,
     /// All known files, including files which have already been read.
     files: Files,
+    /// The `#line` directives seen so far in each file.
+    ///
+    /// These are moved into `files` by `into_files`.
+    line_directives: HashMap<FileId, Vec<LineDirective>>,
     pub(super) error_handler: ErrorHandler,
     current: Option<CompileResult<Locatable<Token>>>,
 }
@@ -59,12 +65,14 @@ impl FileProcessor {
         let source = crate::Source {
             code: ArcStr::clone(&chars),
             path: filename.clone().into(),
+            line_directives: Vec::new(),
         };
         let file = files.add(filename, source);
         Self {
             error_handler: ErrorHandler::default(),
             first_lexer: Lexer::new(file, chars, debug),
             files,
+            line_directives: HashMap::new(),
             includes: Default::default(),
             current: None,
         }
@@ -105,8 +113,57 @@ impl FileProcessor {
     /// Return all files loaded by the preprocessor, consuming it in the process.
     ///
     /// Files can be loaded by C source using `#include` directives.
-    pub(super) fn into_files(self) -> Files {
+    pub(super) fn into_files(mut self) -> Files {
+        for (file, line_directives) in self.line_directives {
+            let source = Source {
+                line_directives,
+                ..self.files.source(file).clone()
+            };
+            self.files.update(file, source);
+        }
+        self.files
+    }
+
+    /// Record a `#line` directive on the current line, which applies to all following lines.
+    ///
+    /// If `filename` is `None`, the filename from the last directive in the file is kept.
+    pub(super) fn add_line_directive(&mut self, line: u32, filename: Option<ArcStr>) {
+        let (file, offset) = (self.lexer().location.file, self.offset());
+        let physical_line = self.physical_line(file, offset) + 1;
+        let directives = self.line_directives.entry(file).or_default();
+        let filename = filename.or_else(|| directives.last()?.filename.clone());
+        directives.push(LineDirective {
+            physical_line,
+            line,
+            filename,
+        });
+    }
+
+    /// The line number of the current line and the filename set by `#line`, if any.
+    ///
+    /// Without a `#line` directive, this is the same as `line() + 1`.
+    pub(super) fn presumed_line(&self) -> (usize, Option<&ArcStr>) {
+        let (file, offset) = (self.lexer().location.file, self.offset());
+        match self
+            .line_directives
+            .get(&file)
+            .and_then(|directives| directives.last())
+        {
+            Some(directive) => {
+                let physical_line = self.physical_line(file, offset);
+                let offset = physical_line.saturating_sub(directive.physical_line);
+                let line = directive.line.saturating_add(offset);
+                (line as usize, directive.filename.as_ref())
+            }
+            None => (self.line() + 1, None),
+        }
+    }
+
+    /// Unlike `line()`, this counts lines ending in a backslash.
+    fn physical_line(&self, file: FileId, offset: u32) -> u32 {
         self.files
+            .location(file, offset)
+            .map_or(0, |location| location.line.0)
     }
 
     /* Convenience functions */
@@ -2,6 +2,7 @@ use std::convert::{TryFrom, TryInto};
 
 use codespan::FileId;
 
+use super::arch::LongDoubleFormat;
 use super::data::{
     error::{LexError, Warning},
     lex::*,
@@ -12,6 +13,7 @@ use arcstr::{ArcStr, Substr};
 
 mod cpp;
 mod files;
+mod pragma;
 pub mod replace;
 #[cfg(test)]
 mod tests;
@@ -19,6 +21,8 @@ mod tests;
 #[allow(unreachable_pub)]
 pub use cpp::{PreProcessor, PreProcessorBuilder};
 #[allow(unreachable_pub)]
+pub use pragma::{PackState, PragmaHandler};
+#[allow(unreachable_pub)]
 pub use replace::{Definition, Peekable};
 
 type LexResult<T = Token> = Result<T, Locatable<LexError>>;
@@ -1025,7 +1029,9 @@ impl LiteralToken {
             LiteralToken::Float(rcstr) => {
                 let buf = rcstr.as_str();
                 let hex = buf.starts_with("0x");
+                let long_double = buf.ends_with(['l', 'L']);
                 let buf = buf.trim_end_matches(|c| "fFlL".contains(c));
+                // this also checks the syntax for `long double`s, which are parsed separately
                 let float: f64 = if hex {
                     let float_literal: hexponent::FloatLiteral = buf.parse()?;
                     float_literal.into()
@@ -1036,7 +1042,15 @@ impl LiteralToken {
                     '.' | '+' | '-' | 'e' | 'p' | '0' => true,
                     _ => false,
                 });
-                if float == 0.0 && !should_be_zero {
+                if long_double {
+                    let value = LongDoubleValue::parse(buf);
+                    // binary128 has the smallest subnormals of any format
+                    if value.round(LongDoubleFormat::Binary128).is_zero() && !should_be_zero {
+                        Err(SyntaxError::FloatUnderflow)
+                    } else {
+                        Ok(LiteralValue::LongDouble(value))
+                    }
+                } else if float == 0.0 && !should_be_zero {
                     Err(SyntaxError::FloatUnderflow)
                 } else {
                     Ok(LiteralValue::Float(float))
@@ -0,0 +1,443 @@
+//! `#pragma` directives and the `_Pragma` operator.
+//!
+//! Pragmas are dispatched on their first identifier (e.g. `once` in `#pragma once`)
+//! to a `PragmaHandler`. The built-in handlers are in this file;
+//! library users can add their own with `PreProcessorBuilder::pragma` or `PreProcessor::add_pragma`.
+//! Pragmas with no handler are ignored with a warning.
+
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use arcstr::ArcStr;
+
+use super::cpp::PreProcessor;
+use super::replace::Definition;
+use super::Lexer;
+use crate::data::error::{CppError, DiagnosticRegion, Warning, WarningLevel};
+use crate::data::lex::LiteralToken;
+use crate::data::*;
+
+/// A handler for `#pragma <name> ...`.
+///
+/// The handler is called with the tokens after `<name>`, not including whitespace,
+/// and the location of the whole pragma. The tokens are not macro-replaced.
+pub type PragmaHandler =
+    Rc<dyn Fn(&mut PreProcessor<'_>, &[Locatable<Token>], Location) -> CompileResult<()>>;
+
+/// The maximum alignment of struct members set by `#pragma pack`.
+///
+/// This is shared between the preprocessor, which sees the pragma,
+/// and the parser, which records it for each struct it parses.
+/// `#pragma pack` applies to all structs after it in the token stream,
+/// even ones in other files, so it can't be looked up by location.
+#[derive(Clone, Debug, Default)]
+pub struct PackState(Rc<Cell<Option<u64>>>);
+
+impl PackState {
+    /// The current maximum alignment in bytes, or `None` if structs are not packed.
+    pub fn get(&self) -> Option<u64> {
+        self.0.get()
+    }
+    fn set(&self, pack: Option<u64>) {
+        self.0.set(pack);
+    }
+}
+
+/// The state kept by pragmas between directives.
+pub(super) struct Pragmas {
+    handlers: HashMap<InternedStr, PragmaHandler>,
+    /// The canonical paths of files containing `#pragma once`
+    once: HashSet<PathBuf>,
+    pack: PackState,
+    /// The packing at each `#pragma pack(push)`
+    pack_stack: Vec<Option<u64>>,
+    /// The definitions saved by `#pragma push_macro`, or `None` if the macro was not defined
+    macro_stack: HashMap<InternedStr, Vec<Option<Definition>>>,
+    /// The regions set by `#pragma GCC diagnostic`
+    pub(super) diagnostic_regions: Vec<DiagnosticRegion>,
+    /// The number of regions at each `#pragma GCC diagnostic push`
+    diagnostic_stack: Vec<usize>,
+}
+
+impl Pragmas {
+    pub(super) fn new() -> Self {
+        let builtins: [(&str, PragmaHandler); 7] = [
+            ("once", Rc::new(once)),
+            ("pack", Rc::new(pack)),
+            ("push_macro", Rc::new(push_macro)),
+            ("pop_macro", Rc::new(pop_macro)),
+            ("message", Rc::new(message)),
+            ("GCC", Rc::new(gcc)),
+            ("clang", Rc::new(gcc)),
+        ];
+        Pragmas {
+            handlers: builtins
+                .iter()
+                .map(|(name, handler)| ((*name).into(), Rc::clone(handler)))
+                .collect(),
+            once: HashSet::new(),
+            pack: PackState::default(),
+            pack_stack: Vec::new(),
+            macro_stack: HashMap::new(),
+            diagnostic_regions: Vec::new(),
+            diagnostic_stack: Vec::new(),
+        }
+    }
+    pub(super) fn add_handler(&mut self, name: InternedStr, handler: PragmaHandler) {
+        self.handlers.insert(name, handler);
+    }
+    pub(super) fn pack_state(&self) -> PackState {
+        self.pack.clone()
+    }
+    /// Whether `path` contained `#pragma once`.
+    pub(super) fn is_once(&self, path: &Path) -> bool {
+        self.once.contains(&canonicalize(path))
+    }
+}
+
+impl PreProcessor<'_> {
+    /// Run the handler for a pragma.
+    ///
+    /// `tokens` is everything after `#pragma`, not including whitespace.
+    pub(super) fn pragma(
+        &mut self,
+        tokens: &[Locatable<Token>],
+        location: Location,
+    ) -> CompileResult<()> {
+        let handler = match tokens.first() {
+            Some(Locatable {
+                data: Token::Id(name),
+                ..
+            }) => self.pragmas.handlers.get(name).cloned(),
+            _ => None,
+        };
+        match handler {
+            Some(handler) => handler(self, &tokens[1..], location),
+            None => {
+                self.warn(Warning::IgnoredPragma, location);
+                Ok(())
+            }
+        }
+    }
+
+    /// `_Pragma("...")`, which behaves the same as `#pragma ...`.
+    ///
+    /// This assumes `_Pragma` has already been consumed; `start` is its location.
+    /// http://port70.net/~nsz/c/c11/n1570.html#6.10.9
+    pub(super) fn pragma_operator(&mut self, start: Location) -> CompileResult<()> {
+        let expect = |cpp: &mut Self, expected: &'static str| match cpp.next_non_whitespace() {
+            Some(token) => token,
+            None => Err(CompileError::new(
+                CppError::EndOfFile(expected).into(),
+                start,
+            )),
+        };
+        let unexpected = |expected, token: Locatable<Token>| {
+            Err(token.map(|token| CppError::UnexpectedToken(expected, token).into()))
+        };
+
+        let paren = expect(self, "(")?;
+        if paren.data != Token::LeftParen {
+            return unexpected("(", paren);
+        }
+        let literal = expect(self, "string literal")?;
+        let contents = match string_contents(&literal.data) {
+            Some(contents) => contents,
+            None => return unexpected("string literal", literal),
+        };
+        let paren = expect(self, ")")?;
+        if paren.data != Token::RightParen {
+            return unexpected(")", paren);
+        }
+        let location = start.merge(paren.location);
+
+        let source = ArcStr::from(format!("{}\n", destringize(&contents)));
+        let mut files = codespan::Files::new();
+        let file = files.add("<_Pragma>", ArcStr::clone(&source));
+        let tokens = Lexer::new(file, source, false)
+            .filter(|token| {
+                !matches!(
+                    token,
+                    Ok(Locatable {
+                        data: Token::Whitespace(_),
+                        ..
+                    })
+                )
+            })
+            // the tokens have no location of their own, so use the location of `_Pragma`
+            .map(|token| match token {
+                Ok(token) => Ok(Locatable::new(token.data, location)),
+                Err(err) => Err(CompileError::new(err.data.into(), location)),
+            })
+            .collect::<CompileResult<Vec<_>>>()?;
+        self.pragma(&tokens, location)
+    }
+
+    fn malformed_pragma(&mut self, name: &str, location: Location) -> CompileResult<()> {
+        self.warn(
+            Warning::Generic(format!("ignoring malformed #pragma {}", name)),
+            location,
+        );
+        Ok(())
+    }
+}
+
+/// The tokens between the outermost parentheses, or `None` if `args` is not parenthesized.
+fn parenthesized(args: &[Locatable<Token>]) -> Option<&[Locatable<Token>]> {
+    match args {
+        [first, inner @ .., last]
+            if first.data == Token::LeftParen && last.data == Token::RightParen =>
+        {
+            Some(inner)
+        }
+        _ => None,
+    }
+}
+
+/// The contents of a string literal without the quotes. Escape sequences are not processed.
+///
+/// Returns `None` if `token` is not a string literal, or has an encoding prefix.
+pub(super) fn string_contents(token: &Token) -> Option<String> {
+    match token {
+        Token::Literal(LiteralToken::Str(parts)) => parts
+            .iter()
+            .map(|part| part.strip_prefix('"')?.strip_suffix('"'))
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Replace each `\"` in `contents` with a double-quote, and each `\\` with a single backslash.
+///
+/// This is how the string literal in `_Pragma` is destringized (6.10.9p1).
+/// It is also how linemarkers escape filenames.
+pub(super) fn destringize(contents: &str) -> String {
+    contents.replace("\\\"", "\"").replace("\\\\", "\\")
+}
+
+/// The path used to decide if two includes are the same file for `#pragma once`.
+fn canonicalize(path: &Path) -> PathBuf {
+    // builtin headers aren't on disk
+    path.canonicalize().unwrap_or_else(|_| path.to_owned())
+}
+
+/// `#pragma once`: ignore any future `#include`s of the current file.
+fn once(
+    cpp: &mut PreProcessor<'_>,
+    args: &[Locatable<Token>],
+    location: Location,
+) -> CompileResult<()> {
+    if !args.is_empty() {
+        return cpp.malformed_pragma("once", location);
+    }
+    let path = canonicalize(cpp.current_path());
+    cpp.pragmas.once.insert(path);
+    Ok(())
+}
+
+/// `#pragma pack(n)`, `#pragma pack()`, `#pragma pack(push[, n])`, and `#pragma pack(pop)`.
+///
+/// `n` is the maximum alignment of struct members, and must be 1, 2, 4, 8, or 16.
+/// `#pragma pack()` removes the limit.
+fn pack(
+    cpp: &mut PreProcessor<'_>,
+    args: &[Locatable<Token>],
+    location: Location,
+) -> CompileResult<()> {
+    let alignment = |token: &Token| match token {
+        Token::Literal(LiteralToken::Int(n)) => n
+            .parse::<u64>()
+            .ok()
+            .filter(|&n| n.is_power_of_two() && n <= 16),
+        _ => None,
+    };
+    let args: Vec<_> = match parenthesized(args) {
+        Some(args) => args.iter().map(|token| &token.data).collect(),
+        None => return cpp.malformed_pragma("pack", location),
+    };
+    let (push, pop) = ("push".into(), "pop".into());
+    let pragmas = &mut cpp.pragmas;
+    let new = match args.as_slice() {
+        [] => None,
+        [Token::Id(id)] if *id == push => {
+            pragmas.pack_stack.push(pragmas.pack.get());
+            return Ok(());
+        }
+        [Token::Id(id)] if *id == pop => {
+            // like GCC, an unmatched pop is ignored
+            if let Some(previous) = pragmas.pack_stack.pop() {
+                pragmas.pack.set(previous);
+            }
+            return Ok(());
+        }
+        [Token::Id(id), Token::Comma, n] if *id == push && alignment(n).is_some() => {
+            pragmas.pack_stack.push(pragmas.pack.get());
+            alignment(n)
+        }
+        [n] if alignment(n).is_some() => alignment(n),
+        _ => return cpp.malformed_pragma("pack", location),
+    };
+    pragmas.pack.set(new);
+    Ok(())
+}
+
+/// The name in `#pragma push_macro("name")` or `#pragma pop_macro("name")`.
+fn macro_name(args: &[Locatable<Token>]) -> Option<InternedStr> {
+    match parenthesized(args)? {
+        [name] => string_contents(&name.data).map(InternedStr::get_or_intern),
+        _ => None,
+    }
+}
+
+/// `#pragma push_macro("name")`: save the current definition of `name`, if any.
+fn push_macro(
+    cpp: &mut PreProcessor<'_>,
+    args: &[Locatable<Token>],
+    location: Location,
+) -> CompileResult<()> {
+    let name = match macro_name(args) {
+        Some(name) => name,
+        None => return cpp.malformed_pragma("push_macro", location),
+    };
+    let definition = cpp.definitions.get(&name).cloned();
+    cpp.pragmas
+        .macro_stack
+        .entry(name)
+        .or_default()
+        .push(definition);
+    Ok(())
+}
+
+/// `#pragma pop_macro("name")`: restore the definition saved by the last `push_macro`.
+fn pop_macro(
+    cpp: &mut PreProcessor<'_>,
+    args: &[Locatable<Token>],
+    location: Location,
+) -> CompileResult<()> {
+    let name = match macro_name(args) {
+        Some(name) => name,
+        None => return cpp.malformed_pragma("pop_macro", location),
+    };
+    // like GCC, an unmatched pop is ignored
+    let saved = cpp
+        .pragmas
+        .macro_stack
+        .get_mut(&name)
+        .and_then(|stack| stack.pop());
+    match saved {
+        Some(Some(definition)) => {
+            cpp.definitions.insert(name, definition);
+        }
+        Some(None) => {
+            cpp.definitions.remove(&name);
+        }
+        None => {}
+    }
+    Ok(())
+}
+
+/// `#pragma message("text")` or `#pragma message "text"`
+fn message(
+    cpp: &mut PreProcessor<'_>,
+    args: &[Locatable<Token>],
+    location: Location,
+) -> CompileResult<()> {
+    let args = parenthesized(args).unwrap_or(args);
+    let text: Option<String> = args
+        .iter()
+        .map(|token| string_contents(&token.data))
+        .collect();
+    match text {
+        Some(text) if !args.is_empty() => {
+            cpp.warn(Warning::PragmaMessage(text), location);
+            Ok(())
+        }
+        _ => cpp.malformed_pragma("message", location),
+    }
+}
+
+/// `#pragma GCC ...` and `#pragma clang ...`. Only `diagnostic` is supported.
+fn gcc(
+    cpp: &mut PreProcessor<'_>,
+    args: &[Locatable<Token>],
+    location: Location,
+) -> CompileResult<()> {
+    let args: Vec<_> = args.iter().map(|token| &token.data).collect();
+    match args.as_slice() {
+        [Token::Id(kind), args @ ..] if *kind == "diagnostic".into() => {
+            diagnostic(cpp, args, location);
+        }
+        _ => cpp.warn(Warning::IgnoredPragma, location),
+    }
+    Ok(())
+}
+
+/// `#pragma GCC diagnostic {push | pop | ignored | warning | error} ["-W<name>"]`
+///
+/// Unknown warning names are silently ignored, since headers often name
+/// warnings that only exist in other compilers.
+fn diagnostic(cpp: &mut PreProcessor<'_>, args: &[&Token], location: Location) {
+    let file = location.file;
+    let (kind, option) = match args {
+        [Token::Id(kind)] => (kind.resolve_and_clone(), None),
+        [Token::Id(kind), option] => (kind.resolve_and_clone(), Some(*option)),
+        _ => (String::new(), None),
+    };
+    let pragmas = &mut cpp.pragmas;
+    let level = match (kind.as_str(), option) {
+        ("push", None) => {
+            pragmas
+                .diagnostic_stack
+                .push(pragmas.diagnostic_regions.len());
+            return;
+        }
+        ("pop", None) => {
+            if let Some(pushed) = pragmas.diagnostic_stack.pop() {
+                for region in &mut pragmas.diagnostic_regions[pushed..] {
+                    if region.file == file && region.end == u32::MAX {
+                        region.end = location.span.start;
+                    }
+                }
+            } else {
+                cpp.warn(
+                    Warning::Generic("#pragma GCC diagnostic pop without a matching push".into()),
+                    location,
+                );
+            }
+            return;
+        }
+        ("ignored", Some(_)) => WarningLevel::Ignored,
+        ("warning", Some(_)) => WarningLevel::Warning,
+        ("error", Some(_)) => WarningLevel::Error,
+        _ => {
+            cpp.warn(Warning::IgnoredPragma, location);
+            return;
+        }
+    };
+    let option = option
+        .and_then(string_contents)
+        .and_then(|option| option.strip_prefix("-W").map(str::to_owned));
+    let option = match option {
+        Some(option) => option,
+        None => {
+            cpp.warn(
+                Warning::Generic(
+                    "expected a string like \"-W<name>\" after #pragma GCC diagnostic".into(),
+                ),
+                location,
+            );
+            return;
+        }
+    };
+    if let Some(flag) = Warning::find_flag(&option) {
+        cpp.pragmas.diagnostic_regions.push(DiagnosticRegion {
+            file,
+            start: location.span.end,
+            end: u32::MAX,
+            flag,
+            level,
+        });
+    }
+}
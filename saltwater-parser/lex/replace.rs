@@ -61,12 +61,17 @@ pub enum Definition {
         ///
         /// In the example above, `a` is a function parameter.
         /// A macro may have 0 or more parameters.
-        /// Variadic macros (`__VA_ARGS__`) are not yet implemented.
+        /// If the macro is variadic, the `...` is not included in `params`.
         ///
         /// Note that function macros may be called with an empty replacement list for any parameter.
         /// For example, `f()` is valid and exapands to `+ 1`.
         /// Similarly, for `#define g(a, b) a + b`, `g(,)` is valid and expands to `+`.
         params: Vec<InternedStr>,
+        /// Whether the macro takes variable arguments: `#define f(a, ...) a + __VA_ARGS__`
+        ///
+        /// All arguments after the last parameter, including their separating commas,
+        /// are substituted for `__VA_ARGS__`.
+        variadic: bool,
         /// The body for a function macro.
         ///
         /// The function body itself undergoes recursive macro replacement.
@@ -74,6 +79,40 @@ pub enum Definition {
     },
 }
 
+impl Definition {
+    /// Whether a redefinition of this macro is allowed.
+    ///
+    /// All whitespace separations are considered identical, so `a  +b` matches `a +b`, but not `a+b`.
+    /// http://port70.net/~nsz/c/c11/n1570.html#6.10.3p1
+    pub(crate) fn is_identical(&self, other: &Definition) -> bool {
+        fn same_body(a: &[Token], b: &[Token]) -> bool {
+            a.len() == b.len()
+                && a.iter().zip(b).all(|pair| match pair {
+                    (Token::Whitespace(_), Token::Whitespace(_)) => true,
+                    (a, b) => a == b,
+                })
+        }
+        match (self, other) {
+            (Definition::Object(a), Definition::Object(b)) => same_body(a, b),
+            (
+                Definition::Function {
+                    params,
+                    variadic,
+                    body,
+                },
+                Definition::Function {
+                    params: other_params,
+                    variadic: other_variadic,
+                    body: other_body,
+                },
+            ) => {
+                params == other_params && variadic == other_variadic && same_body(body, other_body)
+            }
+            _ => false,
+        }
+    }
+}
+
 pub struct Replace<'a, I: Iterator> {
     iter: std::iter::Peekable<I>,
     definitions: &'a Definitions,
@@ -346,6 +385,16 @@ fn replace_function(
         }
     }
 
+    let (params, body, variadic) = match definitions.get(&id) {
+        Some(Definition::Function {
+            params,
+            body,
+            variadic,
+        }) => (params, body, *variadic),
+        // TODO: it would be nice to pass in `params` and `body` directly, but that runs into borrow errors
+        _ => unreachable!("checked above"),
+    };
+
     // now, expand all arguments
     let mut args = Vec::new();
     let mut current_arg = Vec::new();
@@ -380,7 +429,8 @@ fn replace_function(
             // f ( a,
             // NOTE: `f(,)` is _legal_ and means to replace f with two arguments, each an empty token lists
             // on the bright side, we don't have to check if `current_arg` is empty or not
-            Token::Comma if nested_parens == 1 => {
+            // NOTE: for variadic macros, commas in the variable arguments are part of `__VA_ARGS__`
+            Token::Comma if nested_parens == 1 && !(variadic && args.len() == params.len()) => {
                 args.push(strip_whitespace(mem::take(&mut current_arg)));
                 continue;
             }
@@ -404,31 +454,90 @@ fn replace_function(
         current_arg.push(next.data);
     }
 
-    let (params, body) = match definitions.get(&id) {
-        Some(Definition::Function { params, body }) => (params, body),
-        // TODO: it would be nice to pass in `params` and `body` directly, but that runs into borrow errors
-        _ => unreachable!("checked above"),
-    };
-
-    let mut replacements = Vec::new();
-    if args.len() != params.len() {
+    // `#define f(a, ...)` can be called as `f(1)`, leaving `__VA_ARGS__` empty
+    if variadic && args.len() == params.len() {
+        args.push(Vec::new());
+    }
+    let expected = params.len() + usize::from(variadic);
+    if args.len() != expected {
         // There is no way to distinguish between a macro-function taking one empty argument
         // and taking no arguments other than knowing the number of parameters.
-        if !(args.len() == 1 && params.is_empty() && args[0].is_empty()) {
+        if !(args.len() == 1 && expected == 0 && args[0].is_empty()) {
             // booo, this is the _only_ error in the whole replacer
-            return wrap_error(
-                &location,
-                CppError::TooFewArguments(params.len(), args.len()),
-            );
+            return wrap_error(&location, CppError::TooFewArguments(expected, args.len()));
         }
     }
 
+    let replacements = match substitute(body, params, &args, variadic) {
+        Ok(replacements) => replacements,
+        Err(err) => return wrap_error(&location, err),
+    };
+    // TODO: this collect is useless
+    errors
+        .into_iter()
+        .chain(replacements.into_iter().map(|t| Ok(location.with(t))))
+        .collect()
+}
+
+/// Replace each parameter in `body` with the corresponding argument.
+///
+/// If the macro is variadic, `args` has one more element than `params`,
+/// which is used for `__VA_ARGS__`.
+fn substitute(
+    body: &[Token],
+    params: &[InternedStr],
+    args: &[Vec<Token>],
+    variadic: bool,
+) -> Result<Vec<Token>, CppError> {
+    let va_args = InternedStr::get_or_intern("__VA_ARGS__");
+    let va_opt = InternedStr::get_or_intern("__VA_OPT__");
+    let va_args_empty = variadic && args[params.len()].is_empty();
+    let arg_index = |id: InternedStr| {
+        if variadic && id == va_args {
+            Some(params.len())
+        } else {
+            params.iter().position(|&param| param == id)
+        }
+    };
+
+    let mut replacements = Vec::new();
     let mut pending_hash = false; // Seen a hash?
-    for token in body {
+    let mut i = 0;
+    while let Some(token) = body.get(i) {
+        i += 1;
         match token {
+            // GNU extension: `, ## __VA_ARGS__` deletes the comma if `__VA_ARGS__` is empty
+            Token::Comma if variadic && !pending_hash => {
+                match gnu_comma_paste(&body[i..], va_args) {
+                    Some(len) => {
+                        i += len;
+                        if !va_args_empty {
+                            replacements.push(Token::Comma);
+                            replacements.extend(args[params.len()].iter().cloned());
+                        }
+                    }
+                    None => replacements.push(Token::Comma),
+                }
+            }
+            // C23: `__VA_OPT__(tokens)` expands to `tokens` only if `__VA_ARGS__` is non-empty
+            &Token::Id(id) if variadic && id == va_opt => {
+                let (contents, len) = va_opt_contents(&body[i..])?;
+                i += len;
+                let contents = if va_args_empty {
+                    Vec::new()
+                } else {
+                    substitute(contents, params, args, variadic)?
+                };
+                if !pending_hash {
+                    replacements.extend(contents);
+                } else {
+                    replacements.push(stringify(contents));
+                }
+                pending_hash = false;
+            }
             &Token::Id(id) => {
                 // #define f(a) { a + 1 } \n f(b) => b + 1
-                if let Some(index) = params.iter().position(|&param| param == id) {
+                if let Some(index) = arg_index(id) {
                     let replacement = args[index].clone();
                     if !pending_hash {
                         replacements.extend(replacement);
@@ -437,7 +546,7 @@ fn replace_function(
                         replacements.push(stringify(replacement));
                     }
                 } else if pending_hash {
-                    return wrap_error(&location, CppError::HashMissingParameter);
+                    return Err(CppError::HashMissingParameter);
                 } else {
                     replacements.push(Token::Id(id));
                 }
@@ -453,18 +562,63 @@ fn replace_function(
             }
             _ => {
                 if pending_hash {
-                    return wrap_error(&location, CppError::HashMissingParameter);
+                    return Err(CppError::HashMissingParameter);
                 } else {
                     replacements.push(token.clone());
                 }
             }
         }
     }
-    // TODO: this collect is useless
-    errors
-        .into_iter()
-        .chain(replacements.into_iter().map(|t| Ok(location.with(t))))
-        .collect()
+    Ok(replacements)
+}
+
+/// If `rest` (the tokens after a comma) is `## __VA_ARGS__`,
+/// return the number of tokens up to and including `__VA_ARGS__`.
+fn gnu_comma_paste(rest: &[Token], va_args: InternedStr) -> Option<usize> {
+    let mut tokens = rest
+        .iter()
+        .enumerate()
+        .filter(|(_, token)| !matches!(token, Token::Whitespace(_)));
+    match (tokens.next(), tokens.next()) {
+        (Some((_, Token::HashHash { operator: true })), Some((i, &Token::Id(id))))
+            if id == va_args =>
+        {
+            Some(i + 1)
+        }
+        _ => None,
+    }
+}
+
+/// Given the tokens after `__VA_OPT__`, return the tokens between the parentheses
+/// and the number of tokens up to and including the closing `)`.
+fn va_opt_contents(rest: &[Token]) -> Result<(&[Token], usize), CppError> {
+    let start = match rest
+        .iter()
+        .position(|token| !matches!(token, Token::Whitespace(_)))
+    {
+        Some(i) if rest[i] == Token::LeftParen => i + 1,
+        Some(i) => {
+            return Err(CppError::UnexpectedToken(
+                "'(' after __VA_OPT__",
+                rest[i].clone(),
+            ))
+        }
+        None => return Err(CppError::Expected("'('", "__VA_OPT__")),
+    };
+    let mut nested_parens = 1;
+    for (i, token) in rest.iter().enumerate().skip(start) {
+        match token {
+            Token::LeftParen => nested_parens += 1,
+            Token::RightParen => {
+                nested_parens -= 1;
+                if nested_parens == 0 {
+                    return Ok((&rest[start..i], i + 1));
+                }
+            }
+            _ => {}
+        }
+    }
+    Err(CppError::Expected("')'", "__VA_OPT__"))
 }
 
 fn stringify(args: Vec<Token>) -> Token {
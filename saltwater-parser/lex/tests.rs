@@ -1,4 +1,5 @@
 use super::{CompileResult, LiteralToken, Locatable, Token};
+use crate::arch::LongDoubleFormat;
 use crate::data::hir::LiteralValue;
 use crate::data::lex::test::{cpp, cpp_no_newline};
 use crate::intern::InternedStr;
@@ -90,8 +91,14 @@ fn assert_float(s: &str, expected: f64) {
     let lexed = lex(s);
     assert!(
         match_data_ref(&lexed, |lexed| match lexed {
-            Ok(Token::Literal(lit @ LiteralToken::Float(_))) =>
-                lit.clone().parse() == Ok(LiteralValue::Float(expected)),
+            Ok(Token::Literal(lit @ LiteralToken::Float(_))) => {
+                let value = lit.clone().parse().map(|value| match value {
+                    // `long double`s are compared after rounding them to `double`
+                    LiteralValue::LongDouble(f) => LiteralValue::Float(f.to_f64()),
+                    value => value,
+                });
+                value == Ok(LiteralValue::Float(expected))
+            }
             _ => false,
         }),
         "({}) {:?} != {}",
@@ -207,6 +214,24 @@ fn test_float_literals() {
     // DBL_MIN is actually 2.22507385850720138309023271733240406e-308L
     assert_float("2.225073858507201383e-308L", 2.225_073_858_507_201_4e-308);
 }
+#[test]
+fn test_long_double_literals() {
+    let parse = |s: &str| match lex(s).unwrap().unwrap().data {
+        Token::Literal(lit) => lit.parse(),
+        other => panic!("{} is not a literal: {}", s, other),
+    };
+    let long_double = |s: &str| match parse(s) {
+        Ok(LiteralValue::LongDouble(f)) => f,
+        other => panic!("{} is not a long double: {:?}", s, other),
+    };
+    // too large and too precise for a double
+    let big = long_double("1e4000L");
+    assert!(big > long_double("1e308L") && big < big.add(&big, LongDoubleFormat::X87));
+    assert!(long_double("0.1L") != long_double("0.1000000000000000055511151231257827L"));
+    assert_eq!(long_double("0x1.8p1L"), long_double("3.0l"));
+    assert_eq!(parse("1.0"), Ok(LiteralValue::Float(1.0)));
+    assert!(parse("1e-6000L").is_err());
+}
 
 #[test]
 fn test_num_errors() {
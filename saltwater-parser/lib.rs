@@ -7,6 +7,7 @@
 #![deny(unused_extern_crates)]
 
 use std::collections::{HashMap, VecDeque};
+use std::ffi::OsStr;
 use std::io;
 use std::path::PathBuf;
 use std::rc::Rc;
@@ -26,6 +27,10 @@ pub use codespan;
 pub struct Source {
     pub code: ArcStr,
     pub path: PathBuf,
+    /// The `#line` directives and linemarkers in this file, in the order they appeared.
+    ///
+    /// These are filled in by the preprocessor. Use `presumed_location` to apply them.
+    pub line_directives: Vec<LineDirective>,
 }
 
 impl AsRef<str> for Source {
@@ -35,6 +40,65 @@ impl AsRef<str> for Source {
 }
 
 pub type Files = codespan::Files<Source>;
+
+/// A `#line` directive or linemarker, which changes the line number and filename
+/// reported for the rest of the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineDirective {
+    /// The index of the first line the directive applies to, starting from 0.
+    ///
+    /// This is the line after the directive.
+    pub physical_line: u32,
+    /// The line number reported for `physical_line`, starting from 1.
+    pub line: u32,
+    /// The filename reported for the rest of the file, or `None` to use the real filename.
+    pub filename: Option<ArcStr>,
+}
+
+/// A position in a file as set by `#line`, rather than where it actually is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PresumedLocation<'a> {
+    pub filename: &'a OsStr,
+    /// The line number, starting from 1
+    pub line: u32,
+    /// The column, starting from 1
+    pub column: u32,
+}
+
+/// Get the filename, line, and column of byte `index` in `file`, after applying `#line` directives.
+///
+/// Returns `None` if `index` is out of bounds.
+pub fn presumed_location(
+    files: &Files,
+    file: codespan::FileId,
+    index: u32,
+) -> Option<PresumedLocation<'_>> {
+    let location = files.location(file, index).ok()?;
+    let physical_line = location.line.0;
+    let directive = files
+        .source(file)
+        .line_directives
+        .iter()
+        .rev()
+        .find(|directive| directive.physical_line <= physical_line);
+    let (filename, line) = match directive {
+        Some(directive) => (
+            directive
+                .filename
+                .as_ref()
+                .map_or_else(|| files.name(file), |name| OsStr::new(name.as_str())),
+            directive
+                .line
+                .saturating_add(physical_line - directive.physical_line),
+        ),
+        None => (files.name(file), physical_line + 1),
+    };
+    Some(PresumedLocation {
+        filename,
+        line,
+        column: location.column.0 + 1,
+    })
+}
 /// A result which includes all warnings, even for `Err` variants.
 ///
 /// If successful, this returns an `Ok(T)`.
@@ -59,11 +123,34 @@ impl<T, E> Program<T, E> {
     }
 }
 
+impl<T> Program<T> {
+    /// Remove disabled warnings, and turn warnings promoted with `-Werror` into errors.
+    ///
+    /// Applying the same options more than once has no further effect.
+    pub fn apply_warning_options(mut self, options: &WarningOptions) -> Self {
+        let mut promoted = VecDeque::new();
+        for warning in std::mem::take(&mut self.warnings) {
+            match options.level(&warning) {
+                WarningLevel::Error => promoted.push_back(warning.map(data::Error::Warning)),
+                WarningLevel::Warning => self.warnings.push_back(warning),
+                WarningLevel::Ignored => {}
+            }
+        }
+        if !promoted.is_empty() {
+            match &mut self.result {
+                Ok(_) => self.result = Err(promoted),
+                Err(errs) => errs.extend(promoted),
+            }
+        }
+        self
+    }
+}
+
 pub use analyze::{Analyzer, PureAnalyzer};
 pub use data::*;
 // https://github.com/rust-lang/rust/issues/64762
 #[allow(unreachable_pub)]
-pub use lex::{Definition, Lexer, PreProcessor, PreProcessorBuilder};
+pub use lex::{Definition, Lexer, PackState, PragmaHandler, PreProcessor, PreProcessorBuilder};
 pub use parse::Parser;
 
 #[macro_use]
@@ -168,6 +255,11 @@ pub struct Opt {
     /// If set, compile and assemble but do not link. Object file is machine-dependent.
     pub no_link: bool,
 
+    /// If set, emit DWARF debug info describing functions, variables, types and line numbers.
+    ///
+    /// This only has an effect when compiling to an object file.
+    pub debug_info: bool,
+
     #[cfg(feature = "jit")]
     /// If set, compile and emit JIT code, and do not emit object files and binaries.
     pub jit: bool,
@@ -188,12 +280,54 @@ pub struct Opt {
     /// An empty path is allowed but not recommended; it will cause the preprocessor
     /// to look for includes relative to the current directory of the process.
     pub filename: PathBuf,
+
+    /// Which warnings to emit, and which to treat as errors.
+    pub warning_options: WarningOptions,
+
+    /// The platform to compile for, including the sizes of `int`, `long`, and pointers.
+    ///
+    /// Defaults to the host.
+    pub target: arch::TargetInfo,
+
+    /// How much effort to spend optimizing the generated code.
+    pub opt_level: OptLevel,
+}
+
+/// How much effort to spend optimizing the generated code, as in `-O0`, `-O1`, and `-O2`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum OptLevel {
+    /// Compile as quickly as possible and verify the generated IR.
+    #[default]
+    O0,
+    /// Optimize for speed, and keep local variables in registers when their address is never taken.
+    O1,
+    /// Optimize for both speed and code size.
+    O2,
+}
+
+impl std::str::FromStr for OptLevel {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "0" => Ok(OptLevel::O0),
+            "1" => Ok(OptLevel::O1),
+            "2" => Ok(OptLevel::O2),
+            _ => Err("optimization level must be 0, 1, or 2".into()),
+        }
+    }
 }
 
 /// Preprocess the source and return the tokens.
 pub fn preprocess(buf: &str, opt: Opt) -> Program<VecDeque<Locatable<Token>>> {
     let path = opt.search_path.iter().map(|p| p.into());
-    let mut cpp = PreProcessor::new(buf, opt.filename, opt.debug_lex, path, opt.definitions);
+    let mut cpp = PreProcessor::new(
+        buf,
+        opt.filename,
+        opt.debug_lex,
+        path,
+        opt.definitions,
+        opt.target.clone(),
+    );
 
     let mut tokens = VecDeque::new();
     let mut errs = VecDeque::new();
@@ -208,22 +342,36 @@ pub fn preprocess(buf: &str, opt: Opt) -> Program<VecDeque<Locatable<Token>>> {
     } else {
         Err(errs)
     };
+    let mut warning_options = opt.warning_options;
+    warning_options.add_regions(cpp.diagnostic_regions());
     Program {
         result,
         warnings: cpp.warnings(),
         files: cpp.into_files(),
     }
+    .apply_warning_options(&warning_options)
 }
 
 /// Perform semantic analysis, including type checking and constant folding.
 pub fn check_semantics(buf: &str, opt: Opt) -> Program<Vec<Locatable<hir::Declaration>>> {
     let path = opt.search_path.iter().map(|p| p.into());
-    let mut cpp = PreProcessor::new(buf, opt.filename, opt.debug_lex, path, opt.definitions);
+    let mut cpp = PreProcessor::new(
+        buf,
+        opt.filename,
+        opt.debug_lex,
+        path,
+        opt.definitions,
+        opt.target.clone(),
+    );
 
     let mut errs = VecDeque::new();
+    let mut warning_options = opt.warning_options;
 
     let mut hir = vec![];
-    let mut parser = Analyzer::new(Parser::new(&mut cpp, opt.debug_ast), opt.debug_hir);
+    let pack = cpp.pack_state();
+    let mut parser = Parser::new(&mut cpp, opt.debug_ast);
+    parser.set_pack_state(pack);
+    let mut parser = Analyzer::new(parser, opt.debug_hir, opt.target);
     for res in &mut parser {
         match res {
             Ok(decl) => hir.push(decl),
@@ -231,7 +379,9 @@ pub fn check_semantics(buf: &str, opt: Opt) -> Program<Vec<Locatable<hir::Declar
                 errs.push_back(err);
                 if let Some(max) = opt.max_errors {
                     if errs.len() >= max.into() {
-                        return Program::from_cpp(cpp, Err(errs));
+                        warning_options.add_regions(cpp.diagnostic_regions());
+                        return Program::from_cpp(cpp, Err(errs))
+                            .apply_warning_options(&warning_options);
                     }
                 }
             }
@@ -244,11 +394,13 @@ pub fn check_semantics(buf: &str, opt: Opt) -> Program<Vec<Locatable<hir::Declar
         errs.push_back(cpp.eof().error(SemanticError::EmptyProgram));
     }
     let result = if !errs.is_empty() { Err(errs) } else { Ok(hir) };
+    warning_options.add_regions(cpp.diagnostic_regions());
     Program {
         result,
         warnings,
         files: cpp.into_files(),
     }
+    .apply_warning_options(&warning_options)
 }
 
 impl<T: Into<ArcStr>> From<T> for Source {
@@ -256,6 +408,7 @@ impl<T: Into<ArcStr>> From<T> for Source {
         Self {
             code: src.into(),
             path: PathBuf::new(),
+            line_directives: Vec::new(),
         }
     }
 }
@@ -294,4 +447,145 @@ mod tests {
         assert!(parse_err.is_empty());
         assert!(err.unwrap().data.is_syntax_err());
     }
+    #[test]
+    fn presumed_location() {
+        let src = "int a;\n#line 10 \"parse.y\"\nint b;\\\n;\n#line 20\nint c;\n";
+        let opt = Opt {
+            filename: "parse.c".into(),
+            ..Opt::default()
+        };
+        let Program { result, files, .. } = check_semantics(src, opt);
+        let decls = result.unwrap();
+        let presumed = |decl: &Locatable<hir::Declaration>| {
+            let location =
+                super::presumed_location(&files, decl.location.file, decl.location.span.start)
+                    .unwrap();
+            (location.filename.to_str().unwrap(), location.line)
+        };
+        let lines: Vec<_> = decls.iter().map(presumed).collect();
+        assert_eq!(lines, [("parse.c", 1), ("parse.y", 10), ("parse.y", 20)]);
+    }
+    #[test]
+    fn data_model() {
+        use arch::{DataModel, TargetInfo};
+        let sizes = |data_model| {
+            let opt = Opt {
+                target: TargetInfo {
+                    data_model,
+                    ..TargetInfo::host()
+                },
+                ..Opt::default()
+            };
+            let src =
+                "char i[sizeof(int)], l[sizeof(long)], p[sizeof(char *)], m[__SIZEOF_LONG__];";
+            let decls = check_semantics(src, opt).result.unwrap();
+            decls
+                .iter()
+                .map(|decl| {
+                    decl.data
+                        .symbol
+                        .get()
+                        .ctype
+                        .sizeof(&TargetInfo::host())
+                        .unwrap()
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(sizes(DataModel::LP64), vec![4, 8, 8, 8]);
+        assert_eq!(sizes(DataModel::LLP64), vec![4, 4, 8, 4]);
+        assert_eq!(sizes(DataModel::ILP32), vec![4, 4, 4, 4]);
+    }
+    #[test]
+    fn target_macros() {
+        use arch::TargetInfo;
+        let expand = |triple: &str| {
+            let opt = Opt {
+                target: TargetInfo::new(triple.parse().unwrap()),
+                ..Opt::default()
+            };
+            let src = "__aarch64__ __riscv __riscv_xlen __x86_64__ __linux__ __APPLE__ _WIN64 __SIZEOF_LONG__";
+            let tokens = preprocess(src, opt).result.unwrap();
+            tokens
+                .iter()
+                .map(|token| token.data.to_string())
+                .collect::<String>()
+        };
+        assert_eq!(
+            expand("aarch64-unknown-linux-gnu"),
+            "1 __riscv __riscv_xlen __x86_64__ 1 __APPLE__ _WIN64 8"
+        );
+        assert_eq!(
+            expand("riscv64gc-unknown-linux-gnu"),
+            "__aarch64__ 1 64 __x86_64__ 1 __APPLE__ _WIN64 8"
+        );
+        assert_eq!(
+            expand("aarch64-apple-darwin"),
+            "1 __riscv __riscv_xlen __x86_64__ __linux__ 1 _WIN64 8"
+        );
+        assert_eq!(
+            expand("x86_64-pc-windows-msvc"),
+            "__aarch64__ __riscv __riscv_xlen 1 __linux__ __APPLE__ 1 4"
+        );
+    }
+    #[test]
+    fn warning_options() {
+        let compile = |src: &str, flags: &[&str]| {
+            let mut opt = Opt::default();
+            for flag in flags {
+                opt.warning_options.apply_flag(flag).unwrap();
+            }
+            check_semantics(src, opt)
+        };
+        let src = "static i = 1;\n";
+        let program = compile(src, &[]);
+        assert!(program.result.is_ok());
+        assert_eq!(program.warnings.len(), 1);
+
+        let program = compile(src, &["no-implicit-int"]);
+        assert!(program.result.is_ok());
+        assert!(program.warnings.is_empty());
+
+        let program = compile(src, &["error=implicit-int"]);
+        assert!(program.warnings.is_empty());
+        let errs = program.result.unwrap_err();
+        assert_eq!(errs.len(), 1);
+        assert_eq!(
+            errs[0].data,
+            data::Error::Warning(error::Warning::ImplicitInt)
+        );
+        assert_eq!(
+            errs[0].data.to_string(),
+            "implicit int is deprecated and may be removed in a future release [-Werror=implicit-int]"
+        );
+    }
+    #[test]
+    fn diagnostic_pragma() {
+        let src = "static a = 1;
+#pragma GCC diagnostic push
+#pragma GCC diagnostic ignored \"-Wimplicit-int\"
+static b = 2;
+#pragma GCC diagnostic pop
+static c = 3;
+";
+        let program = check_semantics(src, Opt::default());
+        assert!(program.result.is_ok());
+        let lines: Vec<_> = program
+            .warnings
+            .iter()
+            .map(|warning| {
+                src[..warning.location.span.start as usize]
+                    .matches('\n')
+                    .count()
+                    + 1
+            })
+            .collect();
+        assert_eq!(lines, [1, 6]);
+
+        // pragmas override the command line
+        let mut opt = Opt::default();
+        opt.warning_options.apply_flag("error").unwrap();
+        let program = check_semantics(src, opt);
+        assert_eq!(program.result.unwrap_err().len(), 2);
+        assert!(program.warnings.is_empty());
+    }
 }
@@ -1,7 +1,7 @@
 use super::*;
 use crate::data::ast::{
-    self, Declaration, DeclarationSpecifier, Declarator, Expr, ExternalDeclaration, Initializer,
-    TypeName,
+    self, Declaration, DeclarationSpecifier, Declarator, Designator, Expr, ExternalDeclaration,
+    Initializer, StaticAssert, StructDeclaration, TypeName,
 };
 use crate::data::error::Warning;
 use crate::data::*;
@@ -42,6 +42,10 @@ impl<I: Lexer> Parser<I> {
     /// ```
     /// <http://www.quut.com/c/ANSI-C-grammar-y.html#external_declaration>
     pub fn external_declaration(&mut self) -> SyntaxResult<Locatable<ExternalDeclaration>> {
+        if let Some(keyword) = self.match_keywords(&[Keyword::StaticAssert]) {
+            let assert = self.static_assert(keyword.location)?;
+            return Ok(assert.map(ExternalDeclaration::StaticAssert));
+        }
         let (specifiers, specifier_locations) = self.specifiers()?;
 
         // allow `int;`
@@ -149,6 +153,7 @@ impl<I: Lexer> Parser<I> {
                 Keyword::Struct => self.struct_specifier(true, location)?,
                 Keyword::Union => self.struct_specifier(false, location)?,
                 Keyword::Enum => self.enum_specifier(location)?,
+                Keyword::Alignas => self.alignment_specifier(location)?,
                 Keyword::UserTypedef(name) => {
                     // absolute hack: allow awful code like `typedef int I; { I I; }`
                     if !seen_typedef {
@@ -191,8 +196,10 @@ impl<I: Lexer> Parser<I> {
             start = start.merge(id.location);
             id.data
         });
+        let mut packed = None;
         let members = if let Some(token) = self.match_next(&Token::LeftBrace) {
             start = start.merge(token.location);
+            packed = self.pack.get().map(|pack| pack as u16);
             let mut members = Vec::new();
             loop {
                 if let Some(token) = self.match_next(&Token::RightBrace) {
@@ -206,15 +213,25 @@ impl<I: Lexer> Parser<I> {
                     );
                     continue;
                 }
+                if let Some(keyword) = self.match_keywords(&[Keyword::StaticAssert]) {
+                    let assert = self.static_assert(keyword.location)?;
+                    start = start.merge(assert.location);
+                    members.push(StructDeclaration::StaticAssert(assert.data));
+                    continue;
+                }
                 let decl = self.struct_declaration_list()?;
                 start = start.merge(decl.location);
-                members.push(decl.data);
+                members.push(StructDeclaration::Members(decl.data));
             }
             Some(members)
         } else {
             None
         };
-        let spec = StructSpecifier { name, members };
+        let spec = StructSpecifier {
+            name,
+            members,
+            packed,
+        };
         let spec = if is_struct {
             DeclarationSpecifier::Struct(spec)
         } else {
@@ -223,6 +240,60 @@ impl<I: Lexer> Parser<I> {
         Ok(Locatable::new(spec, start))
     }
 
+    /// ```yacc
+    /// alignment_specifier
+    /// : '_Alignas' '(' type_name ')'
+    /// | '_Alignas' '(' constant_expr ')'
+    /// ;
+    /// ```
+    // we've already seen an `_Alignas` token, `start` is where we saw it
+    fn alignment_specifier(
+        &mut self,
+        start: Location,
+    ) -> SyntaxResult<Locatable<DeclarationSpecifier>> {
+        self.expect(Token::LeftParen)?;
+        let spec = match self.peek_token() {
+            Some(token) if token.is_decl_specifier() => {
+                DeclarationSpecifier::AlignasType(Box::new(self.type_name()?.data))
+            }
+            _ => DeclarationSpecifier::AlignasExpr(Box::new(self.ternary_expr()?)),
+        };
+        let end = self.expect(Token::RightParen)?.location;
+        Ok(Locatable::new(spec, start.merge(end)))
+    }
+
+    /// ```yacc
+    /// static_assert_declaration
+    /// : '_Static_assert' '(' constant_expr ',' STRING_LITERAL ')' ';'
+    /// ;
+    /// ```
+    // we've already seen a `_Static_assert` token, `start` is where we saw it
+    fn static_assert(&mut self, start: Location) -> SyntaxResult<Locatable<StaticAssert>> {
+        use crate::data::hir::LiteralValue;
+
+        self.expect(Token::LeftParen)?;
+        let condition = self.ternary_expr()?;
+        self.expect(Token::Comma)?;
+        let message = match self.match_literal() {
+            Some(Locatable {
+                data: literal @ LiteralToken::Str(_),
+                location,
+            }) => match literal.parse() {
+                Ok(LiteralValue::Str(message)) => message,
+                Ok(_) => unreachable!("string tokens should always parse to strings"),
+                Err(err) => return Err(location.with(err)),
+            },
+            _ => {
+                let err = SyntaxError::ExpectedStaticAssertMessage;
+                return Err(self.next_location().with(err));
+            }
+        };
+        self.expect(Token::RightParen)?;
+        let end = self.expect(Token::Semicolon)?.location;
+        let assert = StaticAssert { condition, message };
+        Ok(Locatable::new(assert, start.merge(end)))
+    }
+
     /// ```yacc
     /// struct_declaration: (type_specifier | type_qualifier)+ struct_declarator_list ';'
     ///
@@ -658,17 +729,18 @@ impl<I: Lexer> Parser<I> {
 
     // handle char[][3] = {{1,2,3}}, but also = {1,2,3} and {{1}, 2, 3}
     // NOTE: this does NOT consume {} except for sub-elements
-    fn aggregate_initializer(&mut self) -> SyntaxResult<Initializer> {
+    pub(super) fn aggregate_initializer(&mut self) -> SyntaxResult<Initializer> {
         let _guard = self.recursion_check();
         let mut elems = vec![];
         while self.match_next(&Token::RightBrace).is_none() {
+            let designators = self.designation()?;
             let next = if self.match_next(&Token::LeftBrace).is_some() {
                 self.aggregate_initializer()?
             } else {
                 // scalar
                 self.initializer()?
             };
-            elems.push(next);
+            elems.push((designators, next));
             // NOTE: this allows trailing commas
             if self.match_next(&Token::Comma).is_none() {
                 self.expect(Token::RightBrace)?;
@@ -677,6 +749,28 @@ impl<I: Lexer> Parser<I> {
         }
         Ok(Initializer::Aggregate(elems))
     }
+
+    // designation: designator+ '='
+    // designator: '[' constant_expr ']' | '.' ID
+    fn designation(&mut self) -> SyntaxResult<Vec<Designator>> {
+        let mut designators = vec![];
+        loop {
+            if self.match_next(&Token::LeftBracket).is_some() {
+                let index = self.ternary_expr()?;
+                self.expect(Token::RightBracket)?;
+                designators.push(Designator::Index(index));
+            } else if self.match_next(&Token::Dot).is_some() {
+                let id = self.expect_id()?.data;
+                designators.push(Designator::Member(id));
+            } else {
+                break;
+            }
+        }
+        if !designators.is_empty() {
+            self.expect(Token::EQUAL)?;
+        }
+        Ok(designators)
+    }
 }
 
 impl InternalDeclarator {
@@ -765,7 +859,9 @@ impl Keyword {
             // qualifier
             | Const | Volatile | Restrict | Atomic | ThreadLocal
             // function qualifier
-            | Inline | NoReturn => true,
+            | Inline | NoReturn
+            // alignment specifier
+            | Alignas => true,
             _ => false,
         }
     }
@@ -832,6 +928,25 @@ pub(crate) mod test {
         assert!(decl("struct s { int *; };").is_err());
     }
     #[test]
+    fn test_static_assert() {
+        assert_no_change("_Static_assert(1, \"message\");");
+        assert!(display("struct { int i; _Static_assert(1, \"m\"); } s;")
+            .contains("_Static_assert(1, \"m\");"));
+        assert!(decl("_Static_assert(1);").is_err());
+        assert!(decl("_Static_assert(1, 2);").is_err());
+    }
+    #[test]
+    fn test_alignas() {
+        assert_no_change("_Alignas(16) int i;");
+        assert_no_change("_Alignas(double) char c;");
+        assert_no_change("int _Alignas(8) i;");
+    }
+    #[test]
+    fn test_generic() {
+        assert_no_change("int i = _Generic(i, int: 1, char (*): 2, default: 3);");
+        assert!(decl("int i = _Generic(i);").is_err());
+    }
+    #[test]
     fn test_cursed_function_declarator() {
         let decl = parser("f(())")
             .declarator(false)
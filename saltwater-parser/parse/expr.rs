@@ -197,10 +197,20 @@ impl<I: Lexer> Parser<I> {
     // this takes the place of `unary_expr` in the yacc grammar
     fn unary_expr(&mut self) -> SyntaxResult<Expr> {
         // prefix expressions
-        let mut prefixes = Vec::new();
+        let mut prefixes: Vec<(Box<dyn UnaryExprFn>, _)> = Vec::new();
         // hack: `sizeof` can be either a unary or primary expression, so we special-case it
         let mut inner = loop {
-            if let Some(Locatable {
+            if let Some(ctype) = self.parenthesized_type()? {
+                // `(int[]){ 1, 2 }` is a compound literal, not a cast
+                if self.match_next(&Token::LeftBrace).is_some() {
+                    break self.compound_literal(ctype)?;
+                }
+                let Locatable { data, location } = ctype;
+                prefixes.push((
+                    Box::new(move |expr| ExprType::Cast(data, Box::new(expr))),
+                    location,
+                ));
+            } else if let Some(Locatable {
                 data: constructor,
                 location,
             }) = self.match_prefix_operator()
@@ -211,6 +221,16 @@ impl<I: Lexer> Parser<I> {
             {
                 // `sizeof(int)` is a primary expr
                 if let Some(mut ctype) = self.parenthesized_type()? {
+                    // `sizeof (int[]){ 1, 2 }` is a unary expr
+                    if self.match_next(&Token::LeftBrace).is_some() {
+                        let location = keyword.location.merge(ctype.location);
+                        let literal = self.compound_literal(ctype)?;
+                        break if keyword.data == Keyword::Sizeof {
+                            location.with(ExprType::SizeofExpr(Box::new(literal)))
+                        } else {
+                            location.with(ExprType::AlignofExpr(Box::new(literal)))
+                        };
+                    }
                     ctype.location = keyword.location.merge(ctype.location);
                     let constructor = if keyword.data == Keyword::Sizeof {
                         ExprType::SizeofType
@@ -257,6 +277,15 @@ impl<I: Lexer> Parser<I> {
             let end_loc = self.expect(Token::RightParen)?.location;
             inner.location = paren.location.merge(&end_loc);
             inner
+        } else if let Some(keyword) = self.match_keywords(&[
+            Keyword::VaStart,
+            Keyword::VaArg,
+            Keyword::VaEnd,
+            Keyword::VaCopy,
+        ]) {
+            self.va_intrinsic(keyword)?
+        } else if let Some(keyword) = self.match_keywords(&[Keyword::Generic]) {
+            self.generic_selection(keyword.location)?
         } else if let Some(loc) = self.match_id() {
             loc.map(ExprType::Id)
         } else if let Some(literal) = self.match_literal() {
@@ -271,6 +300,71 @@ impl<I: Lexer> Parser<I> {
         self.postfix_expr(primary)
     }
 
+    // '(' type_name ')' '{' initializer_list '}' postfix_op*
+    //
+    // the type name and opening brace should already have been consumed
+    fn compound_literal(&mut self, ctype: Locatable<TypeName>) -> SyntaxResult<Expr> {
+        let init = self.aggregate_initializer()?;
+        let literal = ctype
+            .location
+            .with(ExprType::CompoundLiteral(ctype.data, Box::new(init)));
+        self.postfix_expr(literal)
+    }
+
+    // '_Generic' '(' assignment_expr (',' (type_name | 'default') ':' assignment_expr)+ ')'
+    //
+    // the `_Generic` keyword should already have been consumed
+    fn generic_selection(&mut self, start: Location) -> SyntaxResult<Expr> {
+        self.expect(Token::LeftParen)?;
+        let controlling = Box::new(self.assignment_expr()?);
+        let mut associations = Vec::new();
+        while self.match_next(&Token::Comma).is_some() {
+            let ctype = if self.match_keywords(&[Keyword::Default]).is_some() {
+                None
+            } else {
+                Some(self.type_name()?.data)
+            };
+            self.expect(Token::Colon)?;
+            associations.push((ctype, self.assignment_expr()?));
+        }
+        let end = self.expect(Token::RightParen)?.location;
+        if associations.is_empty() {
+            return Err(end.with(SyntaxError::EmptyGeneric));
+        }
+        let generic = ExprType::Generic(controlling, associations);
+        Ok(start.merge(end).with(generic))
+    }
+
+    // '__builtin_va_start' '(' expr ',' expr ')'
+    // | '__builtin_va_arg' '(' expr ',' type_name ')'
+    // | '__builtin_va_end' '(' expr ')'
+    // | '__builtin_va_copy' '(' expr ',' expr ')'
+    //
+    // these look like function calls, but `va_arg` takes a type, so they have to be parsed specially
+    fn va_intrinsic(&mut self, keyword: Locatable<Keyword>) -> SyntaxResult<Expr> {
+        self.expect(Token::LeftParen)?;
+        let ap = Box::new(self.assignment_expr()?);
+        let expr = match keyword.data {
+            Keyword::VaEnd => ExprType::VaEnd(ap),
+            Keyword::VaArg => {
+                self.expect(Token::Comma)?;
+                ExprType::VaArg(ap, self.type_name()?.data)
+            }
+            Keyword::VaStart | Keyword::VaCopy => {
+                self.expect(Token::Comma)?;
+                let other = Box::new(self.assignment_expr()?);
+                if keyword.data == Keyword::VaStart {
+                    ExprType::VaStart(ap, other)
+                } else {
+                    ExprType::VaCopy(ap, other)
+                }
+            }
+            _ => unreachable!("only va_* keywords should be passed to va_intrinsic"),
+        };
+        let end = self.expect(Token::RightParen)?.location;
+        Ok(keyword.location.merge(end).with(expr))
+    }
+
     // `expr` should be a primary expression
     fn postfix_expr(&mut self, mut expr: Expr) -> SyntaxResult<Expr> {
         // fortunately, all postfix expressions have the same precedence
@@ -285,19 +379,10 @@ impl<I: Lexer> Parser<I> {
         Ok(expr)
     }
 
-    // '(' TYPE_NAME ')' | '*' | '~' | '!' | '+' | '-' | '&' | '++' | '--'
+    // '*' | '~' | '!' | '+' | '-' | '&' | '++' | '--'
+    //
+    // casts are handled by `unary_expr`, since they could be the start of a compound literal
     fn match_prefix_operator(&mut self) -> Option<Locatable<Box<dyn UnaryExprFn>>> {
-        let maybe_type = self.parenthesized_type().unwrap_or_else(|err| {
-            self.error_handler.push_back(err);
-            None
-        });
-        if let Some(cast) = maybe_type {
-            let loc = cast.location;
-            return Some(Locatable::new(
-                Box::new(move |expr| ExprType::Cast(cast.data, Box::new(expr))),
-                loc,
-            ));
-        }
         // prefix operator
         let func = match self.peek_token()? {
             Token::Star => ExprType::Deref,
@@ -440,6 +525,18 @@ pub(crate) mod test {
         assert_expr_display("1()()()", "(((1)())())()");
     }
     #[test]
+    fn parse_va_intrinsics() {
+        assert_expr_display("__builtin_va_start(ap, n)", "__builtin_va_start(ap, n)");
+        assert_expr_display("__builtin_va_arg(ap, long)", "__builtin_va_arg(ap, long)");
+        assert_expr_display(
+            "__builtin_va_arg(ap, int) + 1",
+            "(__builtin_va_arg(ap, int)) + (1)",
+        );
+        assert_expr_display("__builtin_va_copy(*a, b)", "__builtin_va_copy(*(a), b)");
+        assert!(expr("__builtin_va_arg(ap)").is_err());
+        assert!(expr("__builtin_va_end").is_err());
+    }
+    #[test]
     fn parse_binary() {
         assert_eq!(
             expr("1 = 2 = 3 + 4*5 + 6 + 7").unwrap().to_string(),
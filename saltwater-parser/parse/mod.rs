@@ -8,7 +8,7 @@ use std::mem;
 
 use crate::data::*;
 use crate::data::{ast::ExternalDeclaration, hir::Scope, lex::Keyword};
-use crate::RecursionGuard;
+use crate::{PackState, RecursionGuard};
 
 type Lexeme = CompileResult<Locatable<Token>>;
 type SyntaxResult<T> = Result<T, Locatable<SyntaxError>>;
@@ -43,6 +43,8 @@ pub struct Parser<I: Lexer> {
     error_handler: ErrorHandler,
     /// Internal API which prevents segfaults due to stack overflow
     recursion_guard: RecursionGuard,
+    /// The current `#pragma pack`, recorded for each struct definition
+    pack: PackState,
 }
 
 impl<I: Lexer> Parser<I> {
@@ -61,8 +63,15 @@ impl<I: Lexer> Parser<I> {
             debug,
             error_handler: ErrorHandler::new(),
             recursion_guard: Default::default(),
+            pack: Default::default(),
         }
     }
+    /// Lay out structs using the `#pragma pack` seen by a preprocessor.
+    ///
+    /// Without this, structs are never packed.
+    pub fn set_pack_state(&mut self, pack: PackState) {
+        self.pack = pack;
+    }
     /// Return whether this parser has fully finished parsing.
     ///
     /// This can be used if, for example, you call `parser.expr()`
@@ -47,6 +47,9 @@ impl<I: Lexer> Parser<I> {
     }
     fn declaration(&mut self) -> SyntaxResult<Stmt> {
         let decl = self.external_declaration()?;
+        if let ExternalDeclaration::StaticAssert(assert) = decl.data {
+            return Ok(Stmt::new(StmtType::StaticAssert(assert), decl.location));
+        }
         match decl.data.into_declaration() {
             Err(err) => Err(decl.location.with(err)),
             Ok(declaration) => Ok(Stmt::new(StmtType::Decl(declaration), decl.location)),
@@ -127,10 +130,13 @@ impl<I: Lexer> Parser<I> {
 
                 // start of an expression statement
                 Keyword::Sizeof
-                | Keyword::StaticAssert
-                | Keyword::Alignas
                 | Keyword::Alignof
-                | Keyword::Generic => self.expression_statement(),
+                | Keyword::Generic
+                | Keyword::VaStart
+                | Keyword::VaArg
+                | Keyword::VaEnd
+                | Keyword::VaCopy => self.expression_statement(),
+                Keyword::StaticAssert => self.declaration(),
                 decl if decl.is_decl_specifier() => self.declaration(),
                 other => {
                     let err = SyntaxError::NotAStatement(*other);
@@ -352,6 +358,9 @@ impl ExternalDeclaration {
         match self {
             ExternalDeclaration::Function(def) => Err(SyntaxError::FunctionNotAllowed(def)),
             ExternalDeclaration::Declaration(decl) => Ok(decl),
+            ExternalDeclaration::StaticAssert(_) => {
+                unreachable!("static assertions should be handled by the caller")
+            }
         }
     }
 }
@@ -0,0 +1,280 @@
+//! Machine-readable diagnostics, selected with `--error-format`.
+//!
+//! `json` prints each diagnostic as a single-line JSON object as soon as it is found.
+//! `sarif` collects all diagnostics and prints one SARIF 2.1.0 log just before swcc exits.
+use std::sync::Mutex;
+
+use saltwater_parser::{data::Location, presumed_location, Files};
+use serde_json::{json, Value};
+
+/// The SARIF results found so far, if `--error-format=sarif` was given.
+static SARIF_RESULTS: Mutex<Option<Vec<Value>>> = Mutex::new(None);
+
+/// The category of each `Error`, along with a description for the SARIF rules.
+const CATEGORIES: &[(&str, &str)] = &[
+    ("lex", "invalid token"),
+    ("cpp", "invalid macro"),
+    ("syntax", "invalid syntax"),
+    ("semantic", "invalid program"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ErrorFormat {
+    Human,
+    Json,
+    Sarif,
+}
+
+impl std::str::FromStr for ErrorFormat {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<ErrorFormat, &'static str> {
+        match s {
+            "human" => Ok(ErrorFormat::Human),
+            "json" => Ok(ErrorFormat::Json),
+            "sarif" => Ok(ErrorFormat::Sarif),
+            _ => Err("Invalid error format"),
+        }
+    }
+}
+
+impl ErrorFormat {
+    /// Prepare to emit diagnostics. This must be called before `emit`.
+    pub(crate) fn start(self) {
+        if self == ErrorFormat::Sarif {
+            *SARIF_RESULTS.lock().unwrap() = Some(Vec::new());
+        }
+    }
+
+    /// Emit a single error or warning in a machine-readable format.
+    ///
+    /// `flag` is the `-W` flag that controls the diagnostic, if any.
+    /// `Human` diagnostics are printed by `pretty_print` instead.
+    pub(crate) fn emit<T: std::fmt::Display>(
+        self,
+        level: &'static str,
+        category: &'static str,
+        flag: Option<&'static str>,
+        message: T,
+        location: Location,
+        files: &Files,
+    ) {
+        let mut diagnostic = Diagnostic::new(level, category, message.to_string(), location, files);
+        diagnostic.flag = flag;
+        match self {
+            ErrorFormat::Human => unreachable!("human diagnostics should be pretty printed"),
+            ErrorFormat::Json => println!("{}", diagnostic.to_json()),
+            ErrorFormat::Sarif => {
+                if let Some(results) = SARIF_RESULTS.lock().unwrap().as_mut() {
+                    results.push(diagnostic.to_sarif());
+                }
+            }
+        }
+    }
+}
+
+/// Print the SARIF log, if one is being collected.
+///
+/// This should be called before exiting; calling it more than once prints nothing.
+pub(crate) fn finish() {
+    if let Some(results) = SARIF_RESULTS.lock().unwrap().take() {
+        println!("{}", sarif_log(results));
+    }
+}
+
+/// A line and column, both starting from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Position {
+    line: u32,
+    column: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Diagnostic {
+    level: &'static str,
+    category: &'static str,
+    /// The `-W` flag that controls this diagnostic, without the leading `-W`
+    flag: Option<&'static str>,
+    message: String,
+    file: String,
+    /// The byte offsets of the start and end of the span
+    span: (u32, u32),
+    start: Option<Position>,
+    /// `None` if the diagnostic is at the end of the file
+    end: Option<Position>,
+    /// Extra information about the diagnostic.
+    ///
+    /// No diagnostics have notes yet; the field is always present so tools can rely on it.
+    notes: Vec<String>,
+}
+
+impl Diagnostic {
+    fn new(
+        level: &'static str,
+        category: &'static str,
+        message: String,
+        location: Location,
+        files: &Files,
+    ) -> Self {
+        // lines and filenames are the ones set by `#line`, but the span is into the real file
+        let position = |index: u32| {
+            presumed_location(files, location.file, index).map(|presumed| Position {
+                line: presumed.line,
+                column: presumed.column,
+            })
+        };
+        let span = location.span;
+        let file = presumed_location(files, location.file, span.start)
+            .map_or_else(|| files.name(location.file), |presumed| presumed.filename);
+        Diagnostic {
+            level,
+            category,
+            flag: None,
+            message,
+            file: file.to_string_lossy().into_owned(),
+            span: (span.start, span.end),
+            start: position(span.start),
+            // avoid pointing past the end of the file, as `pretty_print` does
+            end: if span.end == 0 {
+                None
+            } else {
+                position(span.end)
+            },
+            notes: Vec::new(),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        let position = |position: Option<Position>| {
+            position.map(|p| json!({ "line": p.line, "column": p.column }))
+        };
+        json!({
+            "level": self.level,
+            "category": self.category,
+            "flag": self.flag,
+            "message": self.message,
+            "file": self.file,
+            "span": { "start": self.span.0, "end": self.span.1 },
+            "start": position(self.start),
+            "end": position(self.end),
+            "notes": self.notes,
+        })
+    }
+
+    fn to_sarif(&self) -> Value {
+        let mut region = json!({
+            "byteOffset": self.span.0,
+            "byteLength": self.span.1.saturating_sub(self.span.0),
+        });
+        if let Some(start) = self.start {
+            region["startLine"] = json!(start.line);
+            region["startColumn"] = json!(start.column);
+        }
+        if let Some(end) = self.end {
+            region["endLine"] = json!(end.line);
+            region["endColumn"] = json!(end.column);
+        }
+        let mut result = json!({
+            "ruleId": self.category,
+            "level": self.level,
+            "message": { "text": self.message },
+            "locations": [{
+                "physicalLocation": {
+                    "artifactLocation": { "uri": self.file },
+                    "region": region,
+                }
+            }],
+        });
+        if let Some(flag) = self.flag {
+            result["properties"] = json!({ "flag": flag });
+        }
+        if !self.notes.is_empty() {
+            let notes: Vec<_> = self
+                .notes
+                .iter()
+                .map(|note| json!({ "message": { "text": note } }))
+                .collect();
+            result["relatedLocations"] = json!(notes);
+        }
+        result
+    }
+}
+
+fn sarif_log(results: Vec<Value>) -> Value {
+    let rules: Vec<_> = CATEGORIES
+        .iter()
+        .map(|(id, description)| json!({ "id": id, "shortDescription": { "text": description } }))
+        .collect();
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "swcc",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use saltwater_parser::data::lex::Span;
+
+    fn diagnostic<S: Into<Span>>(span: S, source: &str) -> Diagnostic {
+        let mut files = Files::new();
+        let file = files.add("<test-suite>", String::from(source).into());
+        let location = Location {
+            file,
+            span: span.into(),
+        };
+        Diagnostic::new("error", "semantic", "oops".into(), location, &files)
+    }
+
+    #[test]
+    fn json() {
+        let json = diagnostic(13..20, "int i;\nint j = \"hello\";\n").to_json();
+        assert_eq!(
+            json,
+            json!({
+                "level": "error",
+                "category": "semantic",
+                "flag": null,
+                "message": "oops",
+                "file": "<test-suite>",
+                "span": { "start": 13, "end": 20 },
+                "start": { "line": 2, "column": 7 },
+                "end": { "line": 2, "column": 14 },
+                "notes": [],
+            })
+        );
+        // end of file
+        assert_eq!(diagnostic(0..0, "").to_json()["end"], Value::Null);
+    }
+
+    #[test]
+    fn sarif() {
+        let result = diagnostic(13..20, "int i;\nint j = \"hello\";\n").to_sarif();
+        assert_eq!(result["ruleId"], "semantic");
+        assert_eq!(result["message"]["text"], "oops");
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "<test-suite>");
+        assert_eq!(location["region"]["startLine"], 2);
+        assert_eq!(location["region"]["byteLength"], 7);
+        assert_eq!(result.get("properties"), None);
+
+        let mut warning = diagnostic(0..3, "int i;\n");
+        warning.flag = Some("implicit-int");
+        assert_eq!(warning.to_json()["flag"], "implicit-int");
+        assert_eq!(warning.to_sarif()["properties"]["flag"], "implicit-int");
+
+        let log = sarif_log(vec![result]);
+        assert_eq!(log["version"], "2.1.0");
+        assert_eq!(log["runs"][0]["results"].as_array().unwrap().len(), 1);
+    }
+}
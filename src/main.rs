@@ -8,12 +8,19 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 
 use ansi_term::{ANSIString, Colour};
 use arcstr::ArcStr;
+use diagnostics::ErrorFormat;
 use pico_args::Arguments;
-use saltwater_codegen::{assemble, compile, link};
-use saltwater_parser::data::{error::CompileWarning, Location};
-use saltwater_parser::{preprocess, Error, Files, Opt, Program};
+use saltwater_codegen::{assemble, compile_object, link};
+use saltwater_parser::arch::TargetInfo;
+use saltwater_parser::data::{error::CompileWarning, Location, WarningOptions};
+use saltwater_parser::{
+    check_semantics, preprocess, presumed_location, Error, Files, Opt, Program,
+};
+use target_lexicon::Triple;
 use tempfile::NamedTempFile;
 
+mod diagnostics;
+
 static ERRORS: AtomicUsize = AtomicUsize::new(0);
 static WARNINGS: AtomicUsize = AtomicUsize::new(0);
 
@@ -23,7 +30,7 @@ const HELP: &str = concat!(
     env!("CARGO_PKG_DESCRIPTION"), "\n",
     "Homepage: ", env!("CARGO_PKG_REPOSITORY"), "\n",
     "\n",
-    "usage: swcc [FLAGS] [OPTIONS] [<file>]
+    "usage: swcc [FLAGS] [OPTIONS] [<file>...]
 
 FLAGS:
         --debug-ast        If set, print the parsed abstract syntax tree (AST) in addition to compiling.
@@ -34,16 +41,28 @@ FLAGS:
         --debug-lex        If set, print all tokens found by the lexer in addition to compiling.
         --jit              If set, will use JIT compilation for C code and instantly run compiled code (No files produced).
                             NOTE: this option only works if saltwater was compiled with the `jit` feature.
+    -g, --debug-info       If set, emit DWARF debug info for use with a debugger like gdb.
     -h, --help             Prints help information
     -c, --no-link          If set, compile and assemble but do not link. Object file is machine-dependent.
+                            If multiple files are given, each object is written to `<file stem>.o`.
     -E, --preprocess-only  If set, preprocess only, but do not do anything else.
                             Note that preprocessing discards whitespace and comments.
                             There is not currently a way to disable this behavior.
+        --syntax-only      If set, check the program for errors, but do not generate code.
+                            Unlike compiling, this works for any target, such as `i686-unknown-linux-gnu`.
     -V, --version          Prints version information
 
 OPTIONS:
         --color <when>       When to use color. May be \"never\", \"auto\", or \"always\". [default: auto]
+        --error-format <fmt> How to print errors and warnings. May be \"human\", \"json\", or \"sarif\".
+                              `json` prints one JSON object per line; `sarif` prints a SARIF 2.1.0 log at exit.
+                              [default: human]
     -o, --output <output>    The output file to use. [default: a.out]
+    -O <level>               How much to optimize the generated code. May be `0`, `1`, or `2`. [default: 0]
+                              `-O0` compiles quickly and verifies the generated IR, `-O1` optimizes for speed,
+                              and `-O2` optimizes for both speed and code size.
+                              Above `-O0`, local variables are kept in registers when possible,
+                              unless `-g` is given.
         --max-errors <max>   The maximum number of errors to allow before giving up.
                              Use 0 to allow unlimited errors. [default: 10]
     -I, --include <dir>      Add a directory to the local include path (`#include \"file.h\"`).
@@ -51,27 +70,48 @@ OPTIONS:
     -D, --define <id[=val]>  Define an object-like macro.
                               Can be specified multiple times to add multiple macros.
                               `val` defaults to `1`.
+        --target <triple>    The platform to compile for, such as `aarch64-unknown-linux-gnu`. [default: the host]
+                              Cross-compiling requires --no-link, since saltwater has no linker for other platforms.
+                              Supported architectures are x86_64, aarch64, and riscv64, except on Windows.
+                              Other targets can only be used with --preprocess-only or --syntax-only.
+    -W <warning>             Control warnings. May be `all`, `<name>`, `no-<name>`, `error`, or `error=<name>`.
+                              All warnings are enabled by default; the name of each warning is shown after it.
+                              Can be specified multiple times; later flags override earlier ones.
 
 ARGS:
-    <file>    The file to read C source from. \"-\" means stdin (use ./- to read a file called '-').
-              Only one file at a time is currently accepted. [default: -]"
+    <file>... The files to read C source from. \"-\" means stdin (use ./- to read a file called '-').
+              Each C file is compiled separately and all of them are linked together.
+              Files ending in `.o`, `.a`, or `.so` are passed directly to the linker. [default: -]"
 );
 
 const USAGE: &str = "\
 usage: swcc [--help | -h] [--version | -V] [--debug-ir] [--debug-ast] [--debug-lex]
-            [--debug-hir] [--jit] [--no-link | -c] [--preprocess-only | -E]
-            [-I <dir>] [-D <id[=val]>] [<file>]";
+            [--debug-hir] [--jit] [--no-link | -c] [--preprocess-only | -E] [--syntax-only] [-g]
+            [--error-format <fmt>] [--target <triple>] [-O <level>] [-I <dir>]
+            [-D <id[=val]>] [-W <warning>] [<file>...]";
 
 struct BinOpt {
     /// The options that will be passed to `compile()`
+    ///
+    /// `opt.filename` is overwritten for each translation unit.
     opt: Opt,
+    /// The files given on the command line, in order.
+    ///
+    /// This includes both C source files and files that are only passed to the linker.
+    files: Vec<PathBuf>,
     /// If set, preprocess only, but do not do anything else.
     ///
     /// Note that preprocessing discards whitespace and comments.
     /// There is not currently a way to disable this behavior.
     preprocess_only: bool,
+    /// If set, check the program for errors, but do not generate code.
+    ///
+    /// This is the only way to analyze a program for a target that codegen doesn't support.
+    syntax_only: bool,
     /// Whether or not to use color
     color: ColorChoice,
+    /// How to print errors and warnings
+    error_format: ErrorFormat,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -114,21 +154,45 @@ macro_rules! sw_try {
 
 // TODO: when std::process::termination is stable, make err_exit an impl for CompileError
 // TODO: then we can move this into `main` and have main return `Result<(), Error>`
-fn real_main(buf: ArcStr, bin_opt: BinOpt, output: &Path) -> Result<(), (Error, Files)> {
+fn real_main(bin_opt: BinOpt, output: &Path) -> Result<(), (Error, Files)> {
     let opt = if bin_opt.preprocess_only {
         use std::io::{BufWriter, Write};
 
-        let Program {
-            result: tokens,
-            warnings,
-            files,
-        } = preprocess(&buf, bin_opt.opt);
-        handle_warnings(warnings, &files, bin_opt.color);
-
         let stdout = io::stdout();
         let mut stdout_buf = BufWriter::new(stdout.lock());
-        for token in sw_try!(tokens, files) {
-            write!(stdout_buf, "{}", token.data).expect("failed to write to stdout");
+        for file in &bin_opt.files {
+            let (buf, filename) = read_source(file);
+            let opt = Opt {
+                filename,
+                ..bin_opt.opt.clone()
+            };
+            let Program {
+                result: tokens,
+                warnings,
+                files,
+            } = preprocess(&buf, opt);
+            handle_warnings(warnings, &files, bin_opt.color, bin_opt.error_format);
+
+            for token in sw_try!(tokens, files) {
+                write!(stdout_buf, "{}", token.data).expect("failed to write to stdout");
+            }
+        }
+
+        return Ok(());
+    } else if bin_opt.syntax_only {
+        for file in bin_opt.files.iter().filter(|f| !is_linker_input(f)) {
+            let (buf, filename) = read_source(file);
+            let opt = Opt {
+                filename,
+                ..bin_opt.opt.clone()
+            };
+            let Program {
+                result,
+                warnings,
+                files,
+            } = check_semantics(&buf, opt);
+            handle_warnings(warnings, &files, bin_opt.color, bin_opt.error_format);
+            sw_try!(result, files);
         }
 
         return Ok(());
@@ -138,16 +202,29 @@ fn real_main(buf: ArcStr, bin_opt: BinOpt, output: &Path) -> Result<(), (Error,
     #[cfg(feature = "jit")]
     {
         if !opt.jit {
-            aot_main(&buf, opt, output, bin_opt.color)
+            aot_main(
+                &bin_opt.files,
+                opt,
+                output,
+                bin_opt.color,
+                bin_opt.error_format,
+            )
         } else {
-            let module = saltwater_codegen::initialize_jit_module();
+            let (buf, filename) = match bin_opt.files.as_slice() {
+                [file] if !is_linker_input(file) => read_source(file),
+                _ => fatal("--jit requires exactly one C source file", 1, bin_opt.color),
+            };
+            let opt = Opt { filename, ..opt };
+            let module = saltwater_codegen::initialize_jit_module(opt.opt_level);
             let Program {
                 result,
                 warnings,
                 files,
-            } = compile(module, &buf, opt);
-            handle_warnings(warnings, &files, bin_opt.color);
+            } = saltwater_codegen::compile(module, &buf, opt);
+            handle_warnings(warnings, &files, bin_opt.color, bin_opt.error_format);
             let mut jit = saltwater_codegen::JIT::from(sw_try!(result, files));
+            // the program may exit without returning
+            diagnostics::finish();
             if let Some(exit_code) = unsafe { jit.run_main() } {
                 std::process::exit(exit_code);
             }
@@ -155,33 +232,134 @@ fn real_main(buf: ArcStr, bin_opt: BinOpt, output: &Path) -> Result<(), (Error,
         }
     }
     #[cfg(not(feature = "jit"))]
-    aot_main(&buf, opt, output, bin_opt.color)
+    aot_main(
+        &bin_opt.files,
+        opt,
+        output,
+        bin_opt.color,
+        bin_opt.error_format,
+    )
 }
 
+/// Compile each C file to its own object, then link all the objects together.
 #[inline]
-fn aot_main(buf: &str, opt: Opt, output: &Path, color: ColorChoice) -> Result<(), (Error, Files)> {
+fn aot_main(
+    inputs: &[PathBuf],
+    opt: Opt,
+    output: &Path,
+    color: ColorChoice,
+    error_format: ErrorFormat,
+) -> Result<(), (Error, Files)> {
     let no_link = opt.no_link;
-    let module = saltwater_codegen::initialize_aot_module("saltwater_main".to_owned());
-    let Program {
-        result,
-        warnings,
-        files,
-    } = compile(module, buf, opt);
-    handle_warnings(warnings, &files, color);
-
-    let product = sw_try!(result.map(|x| x.finish()), files);
+    let sources = inputs.iter().filter(|f| !is_linker_input(f)).count();
+    // the temporary files are deleted when dropped, so keep them alive until after linking
+    let mut tmp_files = Vec::new();
+    let mut objects = Vec::new();
+    for input in inputs {
+        if is_linker_input(input) {
+            objects.push(input.clone());
+            continue;
+        }
+        let (buf, filename) = read_source(input);
+        let module = saltwater_codegen::initialize_aot_module(
+            filename.to_string_lossy().into_owned(),
+            &opt.target,
+            opt.opt_level,
+        );
+        let opt = Opt {
+            filename,
+            ..opt.clone()
+        };
+        let Program {
+            result,
+            warnings,
+            files,
+        } = compile_object(module, &buf, opt);
+        handle_warnings(warnings, &files, color, error_format);
+
+        let product = sw_try!(result, files);
+        if no_link {
+            let object = if sources == 1 {
+                output.to_owned()
+            } else {
+                object_path(input)
+            };
+            sw_try!(assemble(product, &object), files);
+            continue;
+        }
+        let tmp_file = sw_try!(NamedTempFile::new(), files);
+        sw_try!(assemble(product, tmp_file.as_ref()), files);
+        objects.push(tmp_file.path().to_owned());
+        tmp_files.push(tmp_file);
+    }
     if no_link {
-        sw_try!(assemble(product, output), files);
         return Ok(());
     }
-    let tmp_file = sw_try!(NamedTempFile::new(), files);
-    sw_try!(assemble(product, tmp_file.as_ref()), files);
-    sw_try!(link(tmp_file.as_ref(), output), files);
-    Ok(())
+    link(&objects, output).map_err(|err| (err.into(), Files::new()))
+}
+
+/// Whether `path` should be passed directly to the linker instead of being compiled.
+fn is_linker_input(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("o") | Some("a") | Some("so")
+    )
 }
 
-fn handle_warnings(warnings: VecDeque<CompileWarning>, file_db: &Files, color: ColorChoice) {
+/// The object file to write for `source` when compiling multiple files with `--no-link`.
+fn object_path(source: &Path) -> PathBuf {
+    let stem = match source.file_stem() {
+        Some(stem) if source != Path::new("-") => stem,
+        _ => "stdin".as_ref(),
+    };
+    Path::new(stem).with_extension("o")
+}
+
+/// Read the C source at `path`, returning the source and the filename to report in errors.
+///
+/// Exits the process if the file can't be read.
+fn read_source(path: &Path) -> (ArcStr, PathBuf) {
+    // NOTE: only holds valid UTF-8; will panic otherwise
+    let mut buf = String::new();
+    let filename = if path == Path::new("-") {
+        io::stdin().read_to_string(&mut buf).unwrap_or_else(|err| {
+            eprintln!("Failed to read stdin: {}", err);
+            process::exit(1);
+        });
+        PathBuf::from("<stdin>")
+    } else {
+        File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut buf))
+            .unwrap_or_else(|err| {
+                eprintln!("Failed to read {}: {}", path.to_string_lossy(), err);
+                process::exit(1);
+            });
+        path.to_owned()
+    };
+    (buf.into(), filename)
+}
+
+fn handle_warnings(
+    warnings: VecDeque<CompileWarning>,
+    file_db: &Files,
+    color: ColorChoice,
+    error_format: ErrorFormat,
+) {
     WARNINGS.fetch_add(warnings.len(), Ordering::Relaxed);
+    if error_format != ErrorFormat::Human {
+        for warning in warnings {
+            let (category, flag) = (warning.data.category(), warning.data.flag());
+            error_format.emit(
+                "warning",
+                category,
+                Some(flag),
+                warning.data,
+                warning.location,
+                file_db,
+            );
+        }
+        return;
+    }
     let warn = "warning";
 
     let tag = if color.use_color_for(atty::Stream::Stdout) {
@@ -190,15 +368,16 @@ fn handle_warnings(warnings: VecDeque<CompileWarning>, file_db: &Files, color: C
         ANSIString::from(warn)
     };
     for warning in warnings {
+        let message = format!("{} [-W{}]", warning.data, warning.data.flag());
         print!(
             "{}",
-            pretty_print(tag.clone(), warning.data, warning.location, file_db)
+            pretty_print(tag.clone(), message, warning.location, file_db)
         );
     }
 }
 
 fn main() {
-    let (mut opt, output) = match parse_args() {
+    let (opt, output) = match parse_args() {
         Ok(opt) => opt,
         Err(err) => {
             println!(
@@ -216,38 +395,27 @@ fn main() {
     #[cfg(feature = "color-backtrace")]
     backtrace::install(opt.color);
 
-    // NOTE: only holds valid UTF-8; will panic otherwise
-    let mut buf = String::new();
-    opt.opt.filename = if opt.opt.filename == PathBuf::from("-") {
-        io::stdin().read_to_string(&mut buf).unwrap_or_else(|err| {
-            eprintln!("Failed to read stdin: {}", err);
-            process::exit(1);
-        });
-        PathBuf::from("<stdin>")
-    } else {
-        File::open(opt.opt.filename.as_path())
-            .and_then(|mut file| file.read_to_string(&mut buf))
-            .unwrap_or_else(|err| {
-                eprintln!(
-                    "Failed to read {}: {}",
-                    opt.opt.filename.to_string_lossy(),
-                    err
-                );
-                process::exit(1);
-            });
-        opt.opt.filename
-    };
-    let buf: ArcStr = buf.into();
     let max_errors = opt.opt.max_errors;
     let color_choice = opt.color;
-    real_main(buf, opt, &output)
-        .unwrap_or_else(|(err, files)| err_exit(err, max_errors, color_choice, &files));
+    let error_format = opt.error_format;
+    error_format.start();
+    real_main(opt, &output).unwrap_or_else(|(err, files)| {
+        err_exit(err, max_errors, color_choice, error_format, &files)
+    });
+    diagnostics::finish();
 }
 
 fn str_to_path_buf(s: &str) -> Result<PathBuf, bool> {
     Ok(s.into())
 }
 
+fn parse_target(s: &str) -> Result<TargetInfo, String> {
+    let triple: Triple = s
+        .parse()
+        .map_err(|err| format!("invalid target '{}': {}", s, err))?;
+    Ok(TargetInfo::new(triple))
+}
+
 macro_rules! type_sizes {
     ($($type: ty),* $(,)?) => {
         $(println!("{}: {}", stringify!($type), std::mem::size_of::<$type>());)*
@@ -290,9 +458,9 @@ fn parse_args() -> Result<(BinOpt, PathBuf), pico_args::Error> {
             Token,
         );
     }
-    let output = input
-        .opt_value_from_fn(["-o", "--output"], str_to_path_buf)?
-        .unwrap_or_else(|| "a.out".into());
+    let output = input.opt_value_from_fn(["-o", "--output"], str_to_path_buf)?;
+    let explicit_output = output.is_some();
+    let output = output.unwrap_or_else(|| "a.out".into());
     let max_errors = input
         .opt_value_from_fn("--max-errors", |s| {
             usize::from_str_radix(s, 10).map(NonZeroUsize::new)
@@ -301,10 +469,23 @@ fn parse_args() -> Result<(BinOpt, PathBuf), pico_args::Error> {
     let color_choice = input
         .opt_value_from_str("--color")?
         .unwrap_or(ColorChoice::Auto);
+    let error_format = input
+        .opt_value_from_str("--error-format")?
+        .unwrap_or(ErrorFormat::Human);
+    let target = input
+        .opt_value_from_fn("--target", parse_target)?
+        .unwrap_or_else(TargetInfo::host);
+    let opt_level = input.opt_value_from_str("-O")?.unwrap_or_default();
     let mut search_path = Vec::new();
     while let Some(include) = input.opt_value_from_fn(["-I", "--include"], str_to_path_buf)? {
         search_path.push(include);
     }
+    let mut warning_options = WarningOptions::default();
+    while let Some(flag) = input.opt_value_from_str::<_, String>("-W")? {
+        warning_options
+            .apply_flag(&flag)
+            .map_err(|cause| pico_args::Error::ArgumentParsingFailed { cause })?;
+    }
     let mut definitions = HashMap::new();
     while let Some(arg) = input.opt_value_from_str::<_, String>(["-D", "--define"])? {
         use pico_args::Error::ArgumentParsingFailed;
@@ -322,45 +503,124 @@ fn parse_args() -> Result<(BinOpt, PathBuf), pico_args::Error> {
             })?;
         definitions.insert(key.into(), def);
     }
+    let preprocess_only = input.contains(["-E", "--preprocess-only"]);
+    let syntax_only = input.contains("--syntax-only");
+    let opt = Opt {
+        debug_lex: input.contains("--debug-lex"),
+        debug_asm: input.contains("--debug-ir"),
+        debug_ast: input.contains("--debug-ast"),
+        debug_hir: input.contains("--debug-hir"),
+        no_link: input.contains(["-c", "--no-link"]),
+        debug_info: input.contains(["-g", "--debug-info"]),
+        #[cfg(feature = "jit")]
+        jit: input.contains("--jit"),
+        max_errors,
+        definitions,
+        search_path,
+        warning_options,
+        target,
+        opt_level,
+        // set separately for each file
+        filename: PathBuf::new(),
+    };
+    // This is a little odd because `free` expects no arguments to be left,
+    // so we have to parse it last.
+    let mut files = Vec::new();
+    while let Some(file) = input.opt_free_from_fn(str_to_path_buf)? {
+        files.push(file);
+    }
+    if files.is_empty() {
+        files.push("-".into());
+    }
+    if files
+        .iter()
+        .filter(|f| f.as_path() == Path::new("-"))
+        .count()
+        > 1
+    {
+        return Err(pico_args::Error::ArgumentParsingFailed {
+            cause: "stdin can only be read once".into(),
+        });
+    }
+    let sources = files.iter().filter(|f| !is_linker_input(f)).count();
+    if opt.no_link && sources > 1 && explicit_output {
+        return Err(pico_args::Error::ArgumentParsingFailed {
+            cause: "cannot specify --output with --no-link and multiple files".into(),
+        });
+    }
+    // the data model and macros are known for every target, but the calling convention isn't
+    let generates_code = !preprocess_only && !syntax_only;
+    if generates_code && !saltwater_codegen::is_supported(&opt.target.triple) {
+        return Err(pico_args::Error::ArgumentParsingFailed {
+            cause: format!(
+                "unsupported target '{}'; use --preprocess-only or --syntax-only to check programs for it",
+                opt.target.triple
+            ),
+        });
+    }
+    if opt.target.triple != Triple::host() {
+        #[cfg(feature = "jit")]
+        if opt.jit {
+            return Err(pico_args::Error::ArgumentParsingFailed {
+                cause: "cannot use --jit with --target".into(),
+            });
+        }
+        if !opt.no_link && generates_code {
+            return Err(pico_args::Error::ArgumentParsingFailed {
+                cause: format!(
+                    "cannot link for target '{}'; use --no-link to only produce object files",
+                    opt.target.triple
+                ),
+            });
+        }
+    }
     let bin_opt = BinOpt {
-        preprocess_only: input.contains(["-E", "--preprocess-only"]),
-        opt: Opt {
-            debug_lex: input.contains("--debug-lex"),
-            debug_asm: input.contains("--debug-ir"),
-            debug_ast: input.contains("--debug-ast"),
-            debug_hir: input.contains("--debug-hir"),
-            no_link: input.contains(["-c", "--no-link"]),
-            #[cfg(feature = "jit")]
-            jit: input.contains("--jit"),
-            max_errors,
-            definitions,
-            search_path,
-            // This is a little odd because `free` expects no arguments to be left,
-            // so we have to parse it last.
-            filename: input.free_from_fn(str_to_path_buf)?,
-        },
+        preprocess_only,
+        syntax_only,
+        opt,
+        files,
         color: color_choice,
+        error_format,
     };
     Ok((bin_opt, output))
 }
 
-fn err_exit(err: Error, max_errors: Option<NonZeroUsize>, color: ColorChoice, files: &Files) -> ! {
+fn err_exit(
+    err: Error,
+    max_errors: Option<NonZeroUsize>,
+    color: ColorChoice,
+    error_format: ErrorFormat,
+    files: &Files,
+) -> ! {
     use Error::*;
     match err {
         Source(errs) => {
             for err in &errs {
-                error(&err.data, err.location(), files, color);
+                if error_format == ErrorFormat::Human {
+                    error(&err.data, err.location(), files, color);
+                } else {
+                    ERRORS.fetch_add(1, Ordering::Relaxed);
+                    let (category, flag) = (err.data.category(), err.data.flag());
+                    error_format.emit("error", category, flag, &err.data, err.location(), files);
+                }
             }
             if let Some(max) = max_errors {
                 if usize::from(max) <= errs.len() {
-                    println!(
+                    let msg = format!(
                         "fatal: too many errors (--max-errors {}), stopping now",
                         max
                     );
+                    // keep stdout machine-readable
+                    if error_format == ErrorFormat::Human {
+                        println!("{}", msg);
+                    } else {
+                        eprintln!("{}", msg);
+                    }
                 }
             }
             let (num_warnings, num_errors) = (get_warnings(), get_errors());
             print_issues(num_warnings, num_errors);
+            diagnostics::finish();
             process::exit(2);
         }
         IO(err) => fatal(&err, 3, color),
@@ -404,11 +664,14 @@ fn pretty_print<T: std::fmt::Display>(
     let start = file_db
         .location(file, location.span.start)
         .expect("start location should be in bounds");
+    // the header uses `#line` directives, but the snippet is from the real file
+    let presumed = presumed_location(file_db, file, location.span.start)
+        .expect("start location should be in bounds");
     let buf = format!(
         "{}:{}:{} {}: {}\n",
-        file_db.name(file).to_string_lossy(),
-        start.line.number(),
-        start.column.number(),
+        presumed.filename.to_string_lossy(),
+        presumed.line,
+        presumed.column,
         prefix,
         msg
     );
@@ -446,6 +709,7 @@ fn get_errors() -> usize {
 }
 
 fn fatal<T: std::fmt::Display>(msg: T, code: i32, color: ColorChoice) -> ! {
+    diagnostics::finish();
     if color.use_color_for(atty::Stream::Stderr) {
         eprintln!("{}: {}", Colour::Black.bold().paint("fatal"), msg);
     } else {
@@ -0,0 +1,3 @@
+
+extern int *__errno_location (void) ;
+
@@ -0,0 +1,536 @@
+typedef long unsigned int size_t;
+typedef int wchar_t;
+
+typedef float _Float32;
+typedef double _Float64;
+typedef double _Float32x;
+typedef long double _Float64x;
+typedef struct
+  {
+    int quot;
+    int rem;
+  } div_t;
+typedef struct
+  {
+    long int quot;
+    long int rem;
+  } ldiv_t;
+ typedef struct
+  {
+    long long int quot;
+    long long int rem;
+  } lldiv_t;
+extern size_t __ctype_get_mb_cur_max (void) ;
+extern double atof (const char *__nptr)
+     ;
+extern int atoi (const char *__nptr)
+     ;
+extern long int atol (const char *__nptr)
+     ;
+ extern long long int atoll (const char *__nptr)
+     ;
+extern double strtod (const char *restrict __nptr,
+        char **restrict __endptr)
+     ;
+extern float strtof (const char *restrict __nptr,
+       char **restrict __endptr) ;
+extern long double strtold (const char *restrict __nptr,
+       char **restrict __endptr)
+     ;
+extern long int strtol (const char *restrict __nptr,
+   char **restrict __endptr, int __base)
+     ;
+extern unsigned long int strtoul (const char *restrict __nptr,
+      char **restrict __endptr, int __base)
+     ;
+
+extern long long int strtoq (const char *restrict __nptr,
+        char **restrict __endptr, int __base)
+     ;
+
+extern unsigned long long int strtouq (const char *restrict __nptr,
+           char **restrict __endptr, int __base)
+     ;
+
+extern long long int strtoll (const char *restrict __nptr,
+         char **restrict __endptr, int __base)
+     ;
+
+extern unsigned long long int strtoull (const char *restrict __nptr,
+     char **restrict __endptr, int __base)
+     ;
+extern char *l64a (long int __n) ;
+extern long int a64l (const char *__s)
+     ;
+
+typedef unsigned char __u_char;
+typedef unsigned short int __u_short;
+typedef unsigned int __u_int;
+typedef unsigned long int __u_long;
+typedef signed char __int8_t;
+typedef unsigned char __uint8_t;
+typedef signed short int __int16_t;
+typedef unsigned short int __uint16_t;
+typedef signed int __int32_t;
+typedef unsigned int __uint32_t;
+typedef signed long int __int64_t;
+typedef unsigned long int __uint64_t;
+typedef __int8_t __int_least8_t;
+typedef __uint8_t __uint_least8_t;
+typedef __int16_t __int_least16_t;
+typedef __uint16_t __uint_least16_t;
+typedef __int32_t __int_least32_t;
+typedef __uint32_t __uint_least32_t;
+typedef __int64_t __int_least64_t;
+typedef __uint64_t __uint_least64_t;
+typedef long int __quad_t;
+typedef unsigned long int __u_quad_t;
+typedef long int __intmax_t;
+typedef unsigned long int __uintmax_t;
+typedef unsigned long int __dev_t;
+typedef unsigned int __uid_t;
+typedef unsigned int __gid_t;
+typedef unsigned long int __ino_t;
+typedef unsigned long int __ino64_t;
+typedef unsigned int __mode_t;
+typedef unsigned long int __nlink_t;
+typedef long int __off_t;
+typedef long int __off64_t;
+typedef int __pid_t;
+typedef struct { int __val[2]; } __fsid_t;
+typedef long int __clock_t;
+typedef unsigned long int __rlim_t;
+typedef unsigned long int __rlim64_t;
+typedef unsigned int __id_t;
+typedef long int __time_t;
+typedef unsigned int __useconds_t;
+typedef long int __suseconds_t;
+typedef long int __suseconds64_t;
+typedef int __daddr_t;
+typedef int __key_t;
+typedef int __clockid_t;
+typedef void * __timer_t;
+typedef long int __blksize_t;
+typedef long int __blkcnt_t;
+typedef long int __blkcnt64_t;
+typedef unsigned long int __fsblkcnt_t;
+typedef unsigned long int __fsblkcnt64_t;
+typedef unsigned long int __fsfilcnt_t;
+typedef unsigned long int __fsfilcnt64_t;
+typedef long int __fsword_t;
+typedef long int __ssize_t;
+typedef long int __syscall_slong_t;
+typedef unsigned long int __syscall_ulong_t;
+typedef __off64_t __loff_t;
+typedef char *__caddr_t;
+typedef long int __intptr_t;
+typedef unsigned int __socklen_t;
+typedef int __sig_atomic_t;
+typedef __u_char u_char;
+typedef __u_short u_short;
+typedef __u_int u_int;
+typedef __u_long u_long;
+typedef __quad_t quad_t;
+typedef __u_quad_t u_quad_t;
+typedef __fsid_t fsid_t;
+typedef __loff_t loff_t;
+typedef __ino_t ino_t;
+typedef __dev_t dev_t;
+typedef __gid_t gid_t;
+typedef __mode_t mode_t;
+typedef __nlink_t nlink_t;
+typedef __uid_t uid_t;
+typedef __off_t off_t;
+typedef __pid_t pid_t;
+typedef __id_t id_t;
+typedef __ssize_t ssize_t;
+typedef __daddr_t daddr_t;
+typedef __caddr_t caddr_t;
+typedef __key_t key_t;
+typedef __clock_t clock_t;
+typedef __clockid_t clockid_t;
+typedef __time_t time_t;
+typedef __timer_t timer_t;
+typedef unsigned long int ulong;
+typedef unsigned short int ushort;
+typedef unsigned int uint;
+typedef __int8_t int8_t;
+typedef __int16_t int16_t;
+typedef __int32_t int32_t;
+typedef __int64_t int64_t;
+typedef __uint8_t u_int8_t;
+typedef __uint16_t u_int16_t;
+typedef __uint32_t u_int32_t;
+typedef __uint64_t u_int64_t;
+typedef int register_t;
+static inline __uint16_t
+__bswap_16 (__uint16_t __bsx)
+{
+  return ((__uint16_t) ((((__bsx) >> 8) & 0xff) | (((__bsx) & 0xff) << 8)));
+}
+static inline __uint32_t
+__bswap_32 (__uint32_t __bsx)
+{
+  return ((((__bsx) & 0xff000000u) >> 24) | (((__bsx) & 0x00ff0000u) >> 8) | (((__bsx) & 0x0000ff00u) << 8) | (((__bsx) & 0x000000ffu) << 24));
+}
+ static inline __uint64_t
+__bswap_64 (__uint64_t __bsx)
+{
+  return ((((__bsx) & 0xff00000000000000ull) >> 56) | (((__bsx) & 0x00ff000000000000ull) >> 40) | (((__bsx) & 0x0000ff0000000000ull) >> 24) | (((__bsx) & 0x000000ff00000000ull) >> 8) | (((__bsx) & 0x00000000ff000000ull) << 8) | (((__bsx) & 0x0000000000ff0000ull) << 24) | (((__bsx) & 0x000000000000ff00ull) << 40) | (((__bsx) & 0x00000000000000ffull) << 56));
+}
+static inline __uint16_t
+__uint16_identity (__uint16_t __x)
+{
+  return __x;
+}
+static inline __uint32_t
+__uint32_identity (__uint32_t __x)
+{
+  return __x;
+}
+static inline __uint64_t
+__uint64_identity (__uint64_t __x)
+{
+  return __x;
+}
+typedef struct
+{
+  unsigned long int __val[(1024 / (8 * sizeof (unsigned long int)))];
+} __sigset_t;
+typedef __sigset_t sigset_t;
+struct timeval
+{
+  __time_t tv_sec;
+  __suseconds_t tv_usec;
+};
+struct timespec
+{
+  __time_t tv_sec;
+  __syscall_slong_t tv_nsec;
+};
+typedef __suseconds_t suseconds_t;
+typedef long int __fd_mask;
+typedef struct
+  {
+    __fd_mask __fds_bits[1024 / (8 * (int) sizeof (__fd_mask))];
+  } fd_set;
+typedef __fd_mask fd_mask;
+
+extern int select (int __nfds, fd_set *restrict __readfds,
+     fd_set *restrict __writefds,
+     fd_set *restrict __exceptfds,
+     struct timeval *restrict __timeout);
+extern int pselect (int __nfds, fd_set *restrict __readfds,
+      fd_set *restrict __writefds,
+      fd_set *restrict __exceptfds,
+      const struct timespec *restrict __timeout,
+      const __sigset_t *restrict __sigmask);
+
+typedef __blksize_t blksize_t;
+typedef __blkcnt_t blkcnt_t;
+typedef __fsblkcnt_t fsblkcnt_t;
+typedef __fsfilcnt_t fsfilcnt_t;
+typedef union
+{
+  unsigned long long int __value64;
+  struct
+  {
+    unsigned int __low;
+    unsigned int __high;
+  } __value32;
+} __atomic_wide_counter;
+typedef struct __pthread_internal_list
+{
+  struct __pthread_internal_list *__prev;
+  struct __pthread_internal_list *__next;
+} __pthread_list_t;
+typedef struct __pthread_internal_slist
+{
+  struct __pthread_internal_slist *__next;
+} __pthread_slist_t;
+struct __pthread_mutex_s
+{
+  int __lock;
+  unsigned int __count;
+  int __owner;
+  unsigned int __nusers;
+  int __kind;
+  short __spins;
+  short __elision;
+  __pthread_list_t __list;
+};
+struct __pthread_rwlock_arch_t
+{
+  unsigned int __readers;
+  unsigned int __writers;
+  unsigned int __wrphase_futex;
+  unsigned int __writers_futex;
+  unsigned int __pad3;
+  unsigned int __pad4;
+  int __cur_writer;
+  int __shared;
+  signed char __rwelision;
+  unsigned char __pad1[7];
+  unsigned long int __pad2;
+  unsigned int __flags;
+};
+struct __pthread_cond_s
+{
+  __atomic_wide_counter __wseq;
+  __atomic_wide_counter __g1_start;
+  unsigned int __g_refs[2] ;
+  unsigned int __g_size[2];
+  unsigned int __g1_orig_size;
+  unsigned int __wrefs;
+  unsigned int __g_signals[2];
+};
+typedef unsigned int __tss_t;
+typedef unsigned long int __thrd_t;
+typedef struct
+{
+  int __data ;
+} __once_flag;
+typedef unsigned long int pthread_t;
+typedef union
+{
+  char __size[4];
+  int __align;
+} pthread_mutexattr_t;
+typedef union
+{
+  char __size[4];
+  int __align;
+} pthread_condattr_t;
+typedef unsigned int pthread_key_t;
+typedef int pthread_once_t;
+union pthread_attr_t
+{
+  char __size[56];
+  long int __align;
+};
+typedef union pthread_attr_t pthread_attr_t;
+typedef union
+{
+  struct __pthread_mutex_s __data;
+  char __size[40];
+  long int __align;
+} pthread_mutex_t;
+typedef union
+{
+  struct __pthread_cond_s __data;
+  char __size[48];
+  long long int __align;
+} pthread_cond_t;
+typedef union
+{
+  struct __pthread_rwlock_arch_t __data;
+  char __size[56];
+  long int __align;
+} pthread_rwlock_t;
+typedef union
+{
+  char __size[8];
+  long int __align;
+} pthread_rwlockattr_t;
+typedef volatile int pthread_spinlock_t;
+typedef union
+{
+  char __size[32];
+  long int __align;
+} pthread_barrier_t;
+typedef union
+{
+  char __size[4];
+  int __align;
+} pthread_barrierattr_t;
+
+extern long int random (void) ;
+extern void srandom (unsigned int __seed) ;
+extern char *initstate (unsigned int __seed, char *__statebuf,
+   size_t __statelen) ;
+extern char *setstate (char *__statebuf) ;
+struct random_data
+  {
+    int32_t *fptr;
+    int32_t *rptr;
+    int32_t *state;
+    int rand_type;
+    int rand_deg;
+    int rand_sep;
+    int32_t *end_ptr;
+  };
+extern int random_r (struct random_data *restrict __buf,
+       int32_t *restrict __result) ;
+extern int srandom_r (unsigned int __seed, struct random_data *__buf)
+     ;
+extern int initstate_r (unsigned int __seed, char *restrict __statebuf,
+   size_t __statelen,
+   struct random_data *restrict __buf)
+     ;
+extern int setstate_r (char *restrict __statebuf,
+         struct random_data *restrict __buf)
+     ;
+extern int rand (void) ;
+extern void srand (unsigned int __seed) ;
+extern int rand_r (unsigned int *__seed) ;
+extern double drand48 (void) ;
+extern double erand48 (unsigned short int __xsubi[3]) ;
+extern long int lrand48 (void) ;
+extern long int nrand48 (unsigned short int __xsubi[3])
+     ;
+extern long int mrand48 (void) ;
+extern long int jrand48 (unsigned short int __xsubi[3])
+     ;
+extern void srand48 (long int __seedval) ;
+extern unsigned short int *seed48 (unsigned short int __seed16v[3])
+     ;
+extern void lcong48 (unsigned short int __param[7]) ;
+struct drand48_data
+  {
+    unsigned short int __x[3];
+    unsigned short int __old_x[3];
+    unsigned short int __c;
+    unsigned short int __init;
+    unsigned long long int __a;
+  };
+extern int drand48_r (struct drand48_data *restrict __buffer,
+        double *restrict __result) ;
+extern int erand48_r (unsigned short int __xsubi[3],
+        struct drand48_data *restrict __buffer,
+        double *restrict __result) ;
+extern int lrand48_r (struct drand48_data *restrict __buffer,
+        long int *restrict __result)
+     ;
+extern int nrand48_r (unsigned short int __xsubi[3],
+        struct drand48_data *restrict __buffer,
+        long int *restrict __result)
+     ;
+extern int mrand48_r (struct drand48_data *restrict __buffer,
+        long int *restrict __result)
+     ;
+extern int jrand48_r (unsigned short int __xsubi[3],
+        struct drand48_data *restrict __buffer,
+        long int *restrict __result)
+     ;
+extern int srand48_r (long int __seedval, struct drand48_data *__buffer)
+     ;
+extern int seed48_r (unsigned short int __seed16v[3],
+       struct drand48_data *__buffer) ;
+extern int lcong48_r (unsigned short int __param[7],
+        struct drand48_data *__buffer)
+     ;
+extern __uint32_t arc4random (void)
+     ;
+extern void arc4random_buf (void *__buf, size_t __size)
+     ;
+extern __uint32_t arc4random_uniform (__uint32_t __upper_bound)
+     ;
+extern void *malloc (size_t __size)
+     ;
+extern void *calloc (size_t __nmemb, size_t __size)
+     ;
+extern void *realloc (void *__ptr, size_t __size)
+     ;
+extern void free (void *__ptr) ;
+extern void *reallocarray (void *__ptr, size_t __nmemb, size_t __size)
+    
+    
+    ;
+extern void *reallocarray (void *__ptr, size_t __nmemb, size_t __size)
+     ;
+
+extern void *alloca (size_t __size) ;
+
+extern void *valloc (size_t __size)
+     ;
+extern int posix_memalign (void **__memptr, size_t __alignment, size_t __size)
+     ;
+extern void *aligned_alloc (size_t __alignment, size_t __size)
+    
+     ;
+extern void abort (void) ;
+extern int atexit (void (*__func) (void)) ;
+extern int at_quick_exit (void (*__func) (void)) ;
+extern int on_exit (void (*__func) (int __status, void *__arg), void *__arg)
+     ;
+extern void exit (int __status) ;
+extern void quick_exit (int __status) ;
+extern void _Exit (int __status) ;
+extern char *getenv (const char *__name) ;
+extern int putenv (char *__string) ;
+extern int setenv (const char *__name, const char *__value, int __replace)
+     ;
+extern int unsetenv (const char *__name) ;
+extern int clearenv (void) ;
+extern char *mktemp (char *__template) ;
+extern int mkstemp (char *__template) ;
+extern int mkstemps (char *__template, int __suffixlen) ;
+extern char *mkdtemp (char *__template) ;
+extern int system (const char *__command) ;
+extern char *realpath (const char *restrict __name,
+         char *restrict __resolved) ;
+typedef int (*__compar_fn_t) (const void *, const void *);
+extern void *bsearch (const void *__key, const void *__base,
+        size_t __nmemb, size_t __size, __compar_fn_t __compar)
+     ;
+extern void qsort (void *__base, size_t __nmemb, size_t __size,
+     __compar_fn_t __compar) ;
+extern int abs (int __x) ;
+extern long int labs (long int __x) ;
+ extern long long int llabs (long long int __x)
+     ;
+extern div_t div (int __numer, int __denom)
+     ;
+extern ldiv_t ldiv (long int __numer, long int __denom)
+     ;
+ extern lldiv_t lldiv (long long int __numer,
+        long long int __denom)
+     ;
+extern char *ecvt (double __value, int __ndigit, int *restrict __decpt,
+     int *restrict __sign) ;
+extern char *fcvt (double __value, int __ndigit, int *restrict __decpt,
+     int *restrict __sign) ;
+extern char *gcvt (double __value, int __ndigit, char *__buf)
+     ;
+extern char *qecvt (long double __value, int __ndigit,
+      int *restrict __decpt, int *restrict __sign)
+     ;
+extern char *qfcvt (long double __value, int __ndigit,
+      int *restrict __decpt, int *restrict __sign)
+     ;
+extern char *qgcvt (long double __value, int __ndigit, char *__buf)
+     ;
+extern int ecvt_r (double __value, int __ndigit, int *restrict __decpt,
+     int *restrict __sign, char *restrict __buf,
+     size_t __len) ;
+extern int fcvt_r (double __value, int __ndigit, int *restrict __decpt,
+     int *restrict __sign, char *restrict __buf,
+     size_t __len) ;
+extern int qecvt_r (long double __value, int __ndigit,
+      int *restrict __decpt, int *restrict __sign,
+      char *restrict __buf, size_t __len)
+     ;
+extern int qfcvt_r (long double __value, int __ndigit,
+      int *restrict __decpt, int *restrict __sign,
+      char *restrict __buf, size_t __len)
+     ;
+extern int mblen (const char *__s, size_t __n) ;
+extern int mbtowc (wchar_t *restrict __pwc,
+     const char *restrict __s, size_t __n) ;
+extern int wctomb (char *__s, wchar_t __wchar) ;
+extern size_t mbstowcs (wchar_t *restrict __pwcs,
+   const char *restrict __s, size_t __n)
+    ;
+extern size_t wcstombs (char *restrict __s,
+   const wchar_t *restrict __pwcs, size_t __n)
+    
+ 
+  ;
+extern int rpmatch (const char *__response) ;
+extern int getsubopt (char **restrict __optionp,
+        char *const *restrict __tokens,
+        char **restrict __valuep)
+     ;
+extern int getloadavg (double __loadavg[], int __nelem)
+     ;
+
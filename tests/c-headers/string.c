@@ -0,0 +1,123 @@
+
+typedef long unsigned int size_t;
+extern void *memcpy (void *restrict __dest, const void *restrict __src,
+       size_t __n) ;
+extern void *memmove (void *__dest, const void *__src, size_t __n)
+     ;
+extern void *memccpy (void *restrict __dest, const void *restrict __src,
+        int __c, size_t __n)
+    ;
+extern void *memset (void *__s, int __c, size_t __n) ;
+extern int memcmp (const void *__s1, const void *__s2, size_t __n)
+     ;
+extern int __memcmpeq (const void *__s1, const void *__s2, size_t __n)
+     ;
+extern void *memchr (const void *__s, int __c, size_t __n)
+      ;
+extern char *strcpy (char *restrict __dest, const char *restrict __src)
+     ;
+extern char *strncpy (char *restrict __dest,
+        const char *restrict __src, size_t __n)
+     ;
+extern char *strcat (char *restrict __dest, const char *restrict __src)
+     ;
+extern char *strncat (char *restrict __dest, const char *restrict __src,
+        size_t __n) ;
+extern int strcmp (const char *__s1, const char *__s2)
+     ;
+extern int strncmp (const char *__s1, const char *__s2, size_t __n)
+     ;
+extern int strcoll (const char *__s1, const char *__s2)
+     ;
+extern size_t strxfrm (char *restrict __dest,
+         const char *restrict __src, size_t __n)
+    ;
+struct __locale_struct
+{
+  struct __locale_data *__locales[13];
+  const unsigned short int *__ctype_b;
+  const int *__ctype_tolower;
+  const int *__ctype_toupper;
+  const char *__names[13];
+};
+typedef struct __locale_struct *__locale_t;
+typedef __locale_t locale_t;
+extern int strcoll_l (const char *__s1, const char *__s2, locale_t __l)
+     ;
+extern size_t strxfrm_l (char *__dest, const char *__src, size_t __n,
+    locale_t __l)
+     ;
+extern char *strdup (const char *__s)
+     ;
+extern char *strndup (const char *__string, size_t __n)
+     ;
+extern char *strchr (const char *__s, int __c)
+     ;
+extern char *strrchr (const char *__s, int __c)
+     ;
+extern size_t strcspn (const char *__s, const char *__reject)
+     ;
+extern size_t strspn (const char *__s, const char *__accept)
+     ;
+extern char *strpbrk (const char *__s, const char *__accept)
+     ;
+extern char *strstr (const char *__haystack, const char *__needle)
+     ;
+extern char *strtok (char *restrict __s, const char *restrict __delim)
+     ;
+extern char *__strtok_r (char *restrict __s,
+    const char *restrict __delim,
+    char **restrict __save_ptr)
+     ;
+extern char *strtok_r (char *restrict __s, const char *restrict __delim,
+         char **restrict __save_ptr)
+     ;
+extern size_t strlen (const char *__s)
+     ;
+extern size_t strnlen (const char *__string, size_t __maxlen)
+     ;
+extern char *strerror (int __errnum) ;
+extern int __xpg_strerror_r (int __errnum, char *__buf, size_t __buflen)
+     ;
+extern char *strerror_l (int __errnum, locale_t __l) ;
+
+extern int bcmp (const void *__s1, const void *__s2, size_t __n)
+     ;
+extern void bcopy (const void *__src, void *__dest, size_t __n)
+  ;
+extern void bzero (void *__s, size_t __n) ;
+extern char *index (const char *__s, int __c)
+     ;
+extern char *rindex (const char *__s, int __c)
+     ;
+extern int ffs (int __i) ;
+extern int ffsl (long int __l) ;
+ extern int ffsll (long long int __ll)
+     ;
+extern int strcasecmp (const char *__s1, const char *__s2)
+     ;
+extern int strncasecmp (const char *__s1, const char *__s2, size_t __n)
+     ;
+extern int strcasecmp_l (const char *__s1, const char *__s2, locale_t __loc)
+     ;
+extern int strncasecmp_l (const char *__s1, const char *__s2,
+     size_t __n, locale_t __loc)
+     ;
+
+extern void explicit_bzero (void *__s, size_t __n)
+    ;
+extern char *strsep (char **restrict __stringp,
+       const char *restrict __delim)
+     ;
+extern char *strsignal (int __sig) ;
+extern char *__stpcpy (char *restrict __dest, const char *restrict __src)
+     ;
+extern char *stpcpy (char *restrict __dest, const char *restrict __src)
+     ;
+extern char *__stpncpy (char *restrict __dest,
+   const char *restrict __src, size_t __n)
+     ;
+extern char *stpncpy (char *restrict __dest,
+        const char *restrict __src, size_t __n)
+     ;
+
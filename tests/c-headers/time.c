@@ -0,0 +1,163 @@
+typedef long unsigned int size_t;
+typedef unsigned char __u_char;
+typedef unsigned short int __u_short;
+typedef unsigned int __u_int;
+typedef unsigned long int __u_long;
+typedef signed char __int8_t;
+typedef unsigned char __uint8_t;
+typedef signed short int __int16_t;
+typedef unsigned short int __uint16_t;
+typedef signed int __int32_t;
+typedef unsigned int __uint32_t;
+typedef signed long int __int64_t;
+typedef unsigned long int __uint64_t;
+typedef __int8_t __int_least8_t;
+typedef __uint8_t __uint_least8_t;
+typedef __int16_t __int_least16_t;
+typedef __uint16_t __uint_least16_t;
+typedef __int32_t __int_least32_t;
+typedef __uint32_t __uint_least32_t;
+typedef __int64_t __int_least64_t;
+typedef __uint64_t __uint_least64_t;
+typedef long int __quad_t;
+typedef unsigned long int __u_quad_t;
+typedef long int __intmax_t;
+typedef unsigned long int __uintmax_t;
+typedef unsigned long int __dev_t;
+typedef unsigned int __uid_t;
+typedef unsigned int __gid_t;
+typedef unsigned long int __ino_t;
+typedef unsigned long int __ino64_t;
+typedef unsigned int __mode_t;
+typedef unsigned long int __nlink_t;
+typedef long int __off_t;
+typedef long int __off64_t;
+typedef int __pid_t;
+typedef struct { int __val[2]; } __fsid_t;
+typedef long int __clock_t;
+typedef unsigned long int __rlim_t;
+typedef unsigned long int __rlim64_t;
+typedef unsigned int __id_t;
+typedef long int __time_t;
+typedef unsigned int __useconds_t;
+typedef long int __suseconds_t;
+typedef long int __suseconds64_t;
+typedef int __daddr_t;
+typedef int __key_t;
+typedef int __clockid_t;
+typedef void * __timer_t;
+typedef long int __blksize_t;
+typedef long int __blkcnt_t;
+typedef long int __blkcnt64_t;
+typedef unsigned long int __fsblkcnt_t;
+typedef unsigned long int __fsblkcnt64_t;
+typedef unsigned long int __fsfilcnt_t;
+typedef unsigned long int __fsfilcnt64_t;
+typedef long int __fsword_t;
+typedef long int __ssize_t;
+typedef long int __syscall_slong_t;
+typedef unsigned long int __syscall_ulong_t;
+typedef __off64_t __loff_t;
+typedef char *__caddr_t;
+typedef long int __intptr_t;
+typedef unsigned int __socklen_t;
+typedef int __sig_atomic_t;
+typedef __clock_t clock_t;
+typedef __time_t time_t;
+struct tm
+{
+  int tm_sec;
+  int tm_min;
+  int tm_hour;
+  int tm_mday;
+  int tm_mon;
+  int tm_year;
+  int tm_wday;
+  int tm_yday;
+  int tm_isdst;
+  long int tm_gmtoff;
+  const char *tm_zone;
+};
+struct timespec
+{
+  __time_t tv_sec;
+  __syscall_slong_t tv_nsec;
+};
+typedef __clockid_t clockid_t;
+typedef __timer_t timer_t;
+struct itimerspec
+  {
+    struct timespec it_interval;
+    struct timespec it_value;
+  };
+struct sigevent;
+typedef __pid_t pid_t;
+struct __locale_struct
+{
+  struct __locale_data *__locales[13];
+  const unsigned short int *__ctype_b;
+  const int *__ctype_tolower;
+  const int *__ctype_toupper;
+  const char *__names[13];
+};
+typedef struct __locale_struct *__locale_t;
+typedef __locale_t locale_t;
+
+extern clock_t clock (void) ;
+extern time_t time (time_t *__timer) ;
+extern double difftime (time_t __time1, time_t __time0)
+     ;
+extern time_t mktime (struct tm *__tp) ;
+extern size_t strftime (char *restrict __s, size_t __maxsize,
+   const char *restrict __format,
+   const struct tm *restrict __tp) ;
+extern size_t strftime_l (char *restrict __s, size_t __maxsize,
+     const char *restrict __format,
+     const struct tm *restrict __tp,
+     locale_t __loc) ;
+extern struct tm *gmtime (const time_t *__timer) ;
+extern struct tm *localtime (const time_t *__timer) ;
+extern struct tm *gmtime_r (const time_t *restrict __timer,
+       struct tm *restrict __tp) ;
+extern struct tm *localtime_r (const time_t *restrict __timer,
+          struct tm *restrict __tp) ;
+extern char *asctime (const struct tm *__tp) ;
+extern char *ctime (const time_t *__timer) ;
+extern char *asctime_r (const struct tm *restrict __tp,
+   char *restrict __buf) ;
+extern char *ctime_r (const time_t *restrict __timer,
+        char *restrict __buf) ;
+extern char *__tzname[2];
+extern int __daylight;
+extern long int __timezone;
+extern char *tzname[2];
+extern void tzset (void) ;
+extern int daylight;
+extern long int timezone;
+extern time_t timegm (struct tm *__tp) ;
+extern time_t timelocal (struct tm *__tp) ;
+extern int dysize (int __year) ;
+extern int nanosleep (const struct timespec *__requested_time,
+        struct timespec *__remaining);
+extern int clock_getres (clockid_t __clock_id, struct timespec *__res) ;
+extern int clock_gettime (clockid_t __clock_id, struct timespec *__tp)
+     ;
+extern int clock_settime (clockid_t __clock_id, const struct timespec *__tp)
+     ;
+extern int clock_nanosleep (clockid_t __clock_id, int __flags,
+       const struct timespec *__req,
+       struct timespec *__rem);
+extern int clock_getcpuclockid (pid_t __pid, clockid_t *__clock_id) ;
+extern int timer_create (clockid_t __clock_id,
+    struct sigevent *restrict __evp,
+    timer_t *restrict __timerid) ;
+extern int timer_delete (timer_t __timerid) ;
+extern int timer_settime (timer_t __timerid, int __flags,
+     const struct itimerspec *restrict __value,
+     struct itimerspec *restrict __ovalue) ;
+extern int timer_gettime (timer_t __timerid, struct itimerspec *__value)
+     ;
+extern int timer_getoverrun (timer_t __timerid) ;
+extern int timespec_get (struct timespec *__ts, int __base)
+     ;
+
@@ -0,0 +1,504 @@
+//! Cross-compiled objects can't be run, so these tests only inspect the object file.
+
+use gimli::{EndianSlice, LittleEndian};
+use object::{
+    Architecture, BinaryFormat, FileFlags, Object, ObjectKind, ObjectSection, ObjectSymbol,
+    RelocationTarget,
+};
+use saltwater_codegen::{compile_object, initialize_aot_module, is_supported};
+use saltwater_parser::{arch::TargetInfo, Opt, OptLevel};
+
+/// Compile `program` for `triple`, returning the contents of the relocatable object.
+fn compile_for(triple: &str, program: &str, debug_info: bool) -> Vec<u8> {
+    let opt = Opt {
+        debug_info,
+        target: TargetInfo::new(triple.parse().unwrap()),
+        ..Default::default()
+    };
+    compile_with(opt, program)
+}
+
+/// Compile `program` with the target and optimization level given by `opt`.
+fn compile_with(opt: Opt, program: &str) -> Vec<u8> {
+    let module = initialize_aot_module("cross.c".into(), &opt.target, opt.opt_level);
+    let triple = opt.target.triple.clone();
+    let opt = Opt {
+        filename: "cross.c".into(),
+        ..opt
+    };
+    compile_object(module, program, opt)
+        .result
+        .unwrap_or_else(|err| panic!("failed to compile for {}: {:?}", triple, err))
+        .emit()
+        .expect("failed to emit object")
+}
+
+/// The names of the symbols that relocations in `.text` refer to.
+fn text_relocations(file: &object::File) -> Vec<String> {
+    let text = file
+        .section_by_name(".text")
+        .or_else(|| file.section_by_name("__text"))
+        .expect("no .text section");
+    text.relocations()
+        .filter_map(|(_, relocation)| match relocation.target() {
+            RelocationTarget::Symbol(index) => {
+                let symbol = file.symbol_by_index(index).unwrap();
+                Some(symbol.name().unwrap().to_owned())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The value of the symbol `name`, which must be an `int` in `.data`.
+fn int_global(file: &object::File, name: &str) -> i32 {
+    i32::from_le_bytes(global_bytes(file, name, 4).try_into().unwrap())
+}
+
+/// The first `size` bytes of the global variable `name`.
+fn global_bytes<'a>(file: &'a object::File, name: &str, size: usize) -> &'a [u8] {
+    let symbol = file
+        .symbols()
+        .find(|symbol| symbol.name() == Ok(name))
+        .unwrap_or_else(|| panic!("no symbol named {}", name));
+    let section = file
+        .section_by_index(symbol.section_index().unwrap())
+        .unwrap();
+    let offset = (symbol.address() - section.address()) as usize;
+    &section.data().unwrap()[offset..offset + size]
+}
+
+const CALLS_PUTS: &str = "
+int puts(const char *);
+int main(void) {
+    return puts(\"hello\");
+}
+";
+
+#[test]
+fn architecture_and_relocations() {
+    for &(triple, arch) in &[
+        ("aarch64-unknown-linux-gnu", Architecture::Aarch64),
+        ("x86_64-unknown-linux-gnu", Architecture::X86_64),
+        ("riscv64gc-unknown-linux-gnu", Architecture::Riscv64),
+    ] {
+        let bytes = compile_for(triple, CALLS_PUTS, false);
+        let file = object::File::parse(&*bytes).unwrap();
+        assert_eq!(file.format(), BinaryFormat::Elf, "{}", triple);
+        assert_eq!(file.architecture(), arch, "{}", triple);
+        assert_eq!(file.kind(), ObjectKind::Relocatable, "{}", triple);
+        let targets = text_relocations(&file);
+        assert!(
+            targets.iter().any(|target| target == "puts"),
+            "no relocation for puts on {}: {:?}",
+            triple,
+            targets
+        );
+    }
+}
+
+#[test]
+fn object_format() {
+    for &(triple, format, arch) in &[
+        (
+            "aarch64-apple-darwin",
+            BinaryFormat::MachO,
+            Architecture::Aarch64,
+        ),
+        (
+            "x86_64-apple-darwin",
+            BinaryFormat::MachO,
+            Architecture::X86_64,
+        ),
+    ] {
+        let bytes = compile_for(triple, CALLS_PUTS, false);
+        let file = object::File::parse(&*bytes).unwrap();
+        assert_eq!(file.format(), format, "{}", triple);
+        assert_eq!(file.architecture(), arch, "{}", triple);
+    }
+}
+
+#[test]
+fn type_sizes_and_macros() {
+    let program = "
+int long_size = sizeof(long);
+int pointer_size = __SIZEOF_POINTER__;
+#if defined(__aarch64__)
+int arch = 1;
+#elif defined(__x86_64__)
+int arch = 2;
+#elif defined(__riscv)
+int arch = 4;
+#else
+int arch = 3;
+#endif
+";
+    for &(triple, long_size, arch) in &[
+        ("aarch64-unknown-linux-gnu", 8, 1),
+        ("x86_64-unknown-linux-gnu", 8, 2),
+        ("riscv64gc-unknown-linux-gnu", 8, 4),
+    ] {
+        let bytes = compile_for(triple, program, false);
+        let file = object::File::parse(&*bytes).unwrap();
+        assert_eq!(int_global(&file, "long_size"), long_size, "{}", triple);
+        assert_eq!(int_global(&file, "pointer_size"), 8, "{}", triple);
+        assert_eq!(int_global(&file, "arch"), arch, "{}", triple);
+    }
+}
+
+#[test]
+fn frame_base_register() {
+    for &(triple, register) in &[
+        ("aarch64-unknown-linux-gnu", gimli::AArch64::X29),
+        ("x86_64-unknown-linux-gnu", gimli::X86_64::RBP),
+        ("riscv64gc-unknown-linux-gnu", gimli::RiscV::S0),
+    ] {
+        let bytes = compile_for(triple, "int main(void) { int i = 1; return i; }", true);
+        let file = object::File::parse(&*bytes).unwrap();
+        let load_section = |id: gimli::SectionId| -> Result<_, gimli::Error> {
+            let data = file
+                .section_by_name(id.name())
+                .and_then(|section| section.data().ok())
+                .unwrap_or(&[]);
+            Ok(EndianSlice::new(data, LittleEndian))
+        };
+        let dwarf = gimli::Dwarf::load(load_section).unwrap();
+        let header = dwarf.units().next().unwrap().expect("no compilation unit");
+        let unit = dwarf.unit(header).unwrap();
+        let mut entries = unit.entries();
+        let mut frame_base = None;
+        while let Some((_, entry)) = entries.next_dfs().unwrap() {
+            if entry.tag() == gimli::DW_TAG_subprogram {
+                frame_base = entry.attr_value(gimli::DW_AT_frame_base).unwrap();
+            }
+        }
+        let expression = match frame_base {
+            Some(gimli::AttributeValue::Exprloc(expression)) => expression,
+            other => panic!("unexpected frame base {:?} on {}", other, triple),
+        };
+        let mut operations = expression.operations(unit.encoding());
+        match operations.next().unwrap() {
+            Some(gimli::Operation::Register { register: found }) => {
+                assert_eq!(found, register, "{}", triple)
+            }
+            other => panic!("unexpected frame base {:?} on {}", other, triple),
+        }
+    }
+}
+
+#[test]
+fn variadic_calls() {
+    let program = "
+int printf(const char *, ...);
+struct hfa { float x, y; };
+struct pair { long a; int b; };
+int main(void) {
+    struct hfa h = { 1, 2 };
+    struct pair p = { 3, 4 };
+    return printf(\"%d %f\\n\", 1, 2.0, h, p);
+}
+";
+    for &(triple, printf) in &[
+        ("aarch64-unknown-linux-gnu", "printf"),
+        ("aarch64-apple-darwin", "_printf"),
+        ("x86_64-unknown-linux-gnu", "printf"),
+        ("riscv64gc-unknown-linux-gnu", "printf"),
+    ] {
+        let bytes = compile_for(triple, program, false);
+        let file = object::File::parse(&*bytes).unwrap();
+        let targets = text_relocations(&file);
+        assert!(
+            targets.iter().any(|target| target == printf),
+            "no relocation for printf on {}: {:?}",
+            triple,
+            targets
+        );
+    }
+}
+
+#[test]
+fn riscv64_calling_convention() {
+    let program = "
+int printf(const char *, ...);
+struct mixed { float f; int i; };
+double add(struct mixed m) { return m.f + m.i; }
+long double scale(long double ld, int i) { return ld * i; }
+int main(void) {
+    struct mixed m = { 1.5f, 2 };
+    return printf(\"%f %Lf\\n\", add(m), scale(1.5L, 2));
+}
+";
+    let bytes = compile_for("riscv64gc-unknown-linux-gnu", program, false);
+    let file = object::File::parse(&*bytes).unwrap();
+    assert_eq!(file.architecture(), Architecture::Riscv64);
+    // the linker refuses to mix objects using different floating point ABIs
+    match file.flags() {
+        FileFlags::Elf { e_flags, .. } => assert_eq!(
+            e_flags & object::elf::EF_RISCV_FLOAT_ABI,
+            object::elf::EF_RISCV_FLOAT_ABI_DOUBLE
+        ),
+        other => panic!("unexpected flags {:?}", other),
+    }
+    // `long double` is binary128, done in software
+    let targets = text_relocations(&file);
+    for func in &["printf", "add", "scale", "__multf3", "__floatditf"] {
+        assert!(
+            targets.iter().any(|target| target == func),
+            "no relocation for {}: {:?}",
+            func,
+            targets
+        );
+    }
+    // variadic doubles are passed in integer registers, so `fmv.x.d` moves the bits there
+    let text = file.section_by_name(".text").unwrap();
+    let code = text.data().unwrap();
+    let fmv_x_d = code
+        .chunks_exact(4)
+        .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+        .any(|word| word & 0xfff0_707f == 0xe200_0053);
+    assert!(
+        fmv_x_d,
+        "the double argument to printf is not in an integer register"
+    );
+}
+
+#[test]
+fn long_double_format() {
+    let program = "long double ld = -1.5;";
+    let x87 = {
+        let mut bytes = [0; 16];
+        bytes[..8].copy_from_slice(&0xc000_0000_0000_0000u64.to_le_bytes());
+        bytes[8..10].copy_from_slice(&0xbfffu16.to_le_bytes());
+        bytes
+    };
+    let binary128 = (0xbfff8u128 << 108).to_le_bytes();
+    for &(triple, expected) in &[
+        ("x86_64-unknown-linux-gnu", x87),
+        ("aarch64-unknown-linux-gnu", binary128),
+        ("riscv64gc-unknown-linux-gnu", binary128),
+    ] {
+        let bytes = compile_for(triple, program, false);
+        let file = object::File::parse(&*bytes).unwrap();
+        assert_eq!(global_bytes(&file, "ld", 16), expected, "{}", triple);
+    }
+}
+
+#[test]
+fn long_double_constants() {
+    // these don't fit in a double, and the division is folded in the target's format
+    let program = "
+long double max = __LDBL_MAX__;
+long double third = 1.0L / 3;
+int epsilon = 1.0L + __LDBL_EPSILON__ > 1.0L;
+";
+    let x87 = |significand: u64, sign_exponent: u16| {
+        let mut bytes = [0; 16];
+        bytes[..8].copy_from_slice(&significand.to_le_bytes());
+        bytes[8..10].copy_from_slice(&sign_exponent.to_le_bytes());
+        bytes
+    };
+    let binary128 = |bits: u128| bits.to_le_bytes();
+    for &(triple, max, third) in &[
+        (
+            "x86_64-unknown-linux-gnu",
+            x87(u64::MAX, 0x7ffe),
+            x87(0xaaaa_aaaa_aaaa_aaab, 0x3ffd),
+        ),
+        (
+            "aarch64-unknown-linux-gnu",
+            binary128((0x7fff << 112) - 1),
+            binary128(0x3ffd_5555_5555_5555_5555_5555_5555_5555),
+        ),
+        (
+            "riscv64gc-unknown-linux-gnu",
+            binary128((0x7fff << 112) - 1),
+            binary128(0x3ffd_5555_5555_5555_5555_5555_5555_5555),
+        ),
+    ] {
+        let bytes = compile_for(triple, program, false);
+        let file = object::File::parse(&*bytes).unwrap();
+        assert_eq!(global_bytes(&file, "max", 16), max, "{}", triple);
+        assert_eq!(global_bytes(&file, "third", 16), third, "{}", triple);
+        assert_eq!(int_global(&file, "epsilon"), 1, "{}", triple);
+    }
+    let bytes = compile_for("aarch64-apple-darwin", program, false);
+    let file = object::File::parse(&*bytes).unwrap();
+    assert_eq!(global_bytes(&file, "_max", 8), f64::MAX.to_le_bytes());
+    assert_eq!(
+        global_bytes(&file, "_third", 8),
+        (1.0f64 / 3.0).to_le_bytes()
+    );
+    assert_eq!(int_global(&file, "_epsilon"), 1);
+}
+
+#[test]
+fn apple_long_double_is_double() {
+    let program = "
+long double ld = -1.5;
+int size = sizeof(long double);
+int align = _Alignof(long double);
+int macro_size = __SIZEOF_LONG_DOUBLE__;
+int mant_dig = __LDBL_MANT_DIG__;
+long double f(long double a, long double b, int i) {
+    return a * b + i / a - (b < a);
+}
+";
+    let bytes = compile_for("aarch64-apple-darwin", program, false);
+    let file = object::File::parse(&*bytes).unwrap();
+    assert_eq!(global_bytes(&file, "_ld", 8), (-1.5f64).to_le_bytes());
+    assert_eq!(int_global(&file, "_size"), 8);
+    assert_eq!(int_global(&file, "_align"), 8);
+    assert_eq!(int_global(&file, "_macro_size"), 8);
+    assert_eq!(int_global(&file, "_mant_dig"), 53);
+    // arithmetic is done in hardware, not with the binary128 routines
+    let targets = text_relocations(&file);
+    assert!(
+        targets.iter().all(|target| !target.contains("tf")),
+        "{:?}",
+        targets
+    );
+}
+
+#[test]
+fn optimization_levels() {
+    let program = "
+int puts(const char *);
+struct point { long x, y; };
+long sum(struct point *points, int n) {
+    long total = 0;
+    for (int i = 0; i < n; i++) {
+        total += points[i].x * points[i].y;
+    }
+    return total;
+}
+int main(void) {
+    struct point points[] = { { 1, 2 }, { 3, 4 } };
+    return sum(points, 2) == 14 ? puts(\"ok\") : 1;
+}
+";
+    for triple in &["aarch64-unknown-linux-gnu", "x86_64-unknown-linux-gnu"] {
+        for &opt_level in &[OptLevel::O0, OptLevel::O1, OptLevel::O2] {
+            let opt = Opt {
+                target: TargetInfo::new(triple.parse().unwrap()),
+                opt_level,
+                ..Default::default()
+            };
+            let bytes = compile_with(opt, program);
+            let file = object::File::parse(&*bytes).unwrap();
+            let targets = text_relocations(&file);
+            assert!(
+                targets.iter().any(|target| target == "puts"),
+                "no relocation for puts on {} at {:?}: {:?}",
+                triple,
+                opt_level,
+                targets
+            );
+        }
+    }
+}
+
+#[test]
+fn variadic_calls_set_al() {
+    let program = "
+int printf(const char *, ...);
+int main(void) {
+    return printf(\"%f\\n\", 1.5);
+}
+";
+    let bytes = compile_for("x86_64-unknown-linux-gnu", program, false);
+    let file = object::File::parse(&*bytes).unwrap();
+    let text = file.section_by_name(".text").unwrap();
+    let code = text.data().unwrap();
+    let (offset, _) = text
+        .relocations()
+        .find(|(_, relocation)| match relocation.target() {
+            RelocationTarget::Symbol(index) => {
+                file.symbol_by_index(index).unwrap().name() == Ok("printf")
+            }
+            _ => false,
+        })
+        .expect("no relocation for printf");
+    // `mov al, 8` followed by `jmp printf`
+    let offset = offset as usize;
+    assert_eq!(code[offset - 3..offset], [0xb0, 0x08, 0xe9]);
+}
+
+#[test]
+fn unsupported_targets() {
+    // Cranelift can generate code for these, but saltwater doesn't know their calling conventions
+    let unsupported = [
+        "x86_64-pc-windows-msvc",
+        "x86_64-pc-windows-gnu",
+        "aarch64-pc-windows-msvc",
+    ];
+    for triple in &unsupported {
+        assert!(!is_supported(&triple.parse().unwrap()), "{}", triple);
+    }
+    for triple in &[
+        "x86_64-unknown-linux-gnu",
+        "x86_64-apple-darwin",
+        "aarch64-unknown-linux-gnu",
+        "aarch64-apple-darwin",
+        "riscv64gc-unknown-linux-gnu",
+    ] {
+        assert!(is_supported(&triple.parse().unwrap()), "{}", triple);
+    }
+
+    let dir = tempfile::tempdir().expect("cannot create tempdir");
+    let source = dir.path().join("cross.c");
+    std::fs::write(&source, CALLS_PUTS).expect("cannot write to tempdir");
+    for triple in &unsupported {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_swcc"))
+            .args(["--target", triple, "-c", "-o"])
+            .arg(dir.path().join("cross.o"))
+            .arg(&source)
+            .output()
+            .expect("could not run swcc");
+        assert!(!output.status.success(), "{}", triple);
+        // argument errors are printed with the usage, on stdout
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains("unsupported target"),
+            "unexpected error for {}: {}",
+            triple,
+            stdout
+        );
+    }
+}
+
+#[test]
+fn syntax_only_data_models() {
+    let program = "
+_Static_assert(sizeof(long) == LONG_SIZE, \"sizeof(long)\");
+_Static_assert(sizeof(void *) == POINTER_SIZE, \"sizeof(void *)\");
+_Static_assert(sizeof(sizeof(int)) == POINTER_SIZE, \"sizeof(size_t)\");
+int i;
+";
+    let dir = tempfile::tempdir().expect("cannot create tempdir");
+    let source = dir.path().join("data_model.c");
+    std::fs::write(&source, program).expect("cannot write to tempdir");
+    let check = |triple: &str, long_size: u8, pointer_size: u8| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_swcc"))
+            .args(["--syntax-only", "--target", triple])
+            .arg(format!("-DLONG_SIZE={}", long_size))
+            .arg(format!("-DPOINTER_SIZE={}", pointer_size))
+            .arg(&source)
+            .output()
+            .expect("could not run swcc")
+    };
+    // codegen only supports LP64, but the other data models can still be checked
+    for &(triple, long_size, pointer_size) in &[
+        ("i686-unknown-linux-gnu", 4, 4),
+        ("x86_64-pc-windows-msvc", 4, 8),
+        ("x86_64-unknown-linux-gnu", 8, 8),
+    ] {
+        let output = check(triple, long_size, pointer_size);
+        assert!(
+            output.status.success(),
+            "{}: {}",
+            triple,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    assert!(!check("i686-unknown-linux-gnu", 8, 8).status.success());
+}
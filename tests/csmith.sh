@@ -1,4 +1,3 @@
 #!/bin/sh
 set -euv
-ROOT="$(git rev-parse --show-toplevel)"
-csmith | "$ROOT/mycpp" -I$CSMITH_HOME/runtime | cargo run
+csmith | cargo run -- -I"$CSMITH_HOME/runtime"
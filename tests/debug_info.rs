@@ -0,0 +1,216 @@
+use gimli::{AttributeValue, EndianSlice, LittleEndian};
+use object::{Object, ObjectSection};
+use saltwater_codegen::{assemble, compile_object, initialize_aot_module, link};
+use saltwater_parser::{Opt, OptLevel};
+
+type Dwarf<'a> = gimli::Dwarf<EndianSlice<'a, LittleEndian>>;
+type Unit<'a> = gimli::Unit<EndianSlice<'a, LittleEndian>>;
+
+/// Compile `program` with debug info at `opt_level` and link it,
+/// returning the contents of the executable.
+fn compile_with_debug_info(program: &str, opt_level: OptLevel) -> Vec<u8> {
+    let opt = Opt {
+        filename: "debug.c".into(),
+        debug_info: true,
+        opt_level,
+        ..Default::default()
+    };
+    let module = initialize_aot_module("debug.c".into(), &opt.target, opt.opt_level);
+    let product = compile_object(module, program, opt)
+        .result
+        .unwrap_or_else(|err| panic!("failed to compile program '{}': {:?}", program, err));
+    let object = tempfile::NamedTempFile::new()
+        .expect("cannot create tempfile")
+        .into_temp_path();
+    assemble(product, &object).expect("failed to assemble program");
+    let output = tempfile::NamedTempFile::new()
+        .expect("cannot create tempfile")
+        .into_temp_path();
+    link(&[&object], &output).expect("failed to link program");
+    std::fs::read(&output).expect("failed to read executable")
+}
+
+fn load_dwarf<'a>(file: &'a object::File<'a>) -> Dwarf<'a> {
+    let load_section = |id: gimli::SectionId| -> Result<_, gimli::Error> {
+        let data = file
+            .section_by_name(id.name())
+            .and_then(|section| section.data().ok())
+            .unwrap_or(&[]);
+        Ok(EndianSlice::new(data, LittleEndian))
+    };
+    gimli::Dwarf::load(load_section).expect("debug sections should be valid DWARF")
+}
+
+/// Find the compilation unit for `debug.c`.
+///
+/// The linked executable can have units for the C runtime as well.
+fn find_unit<'a>(dwarf: &Dwarf<'a>) -> Unit<'a> {
+    let mut units = dwarf.units();
+    while let Some(header) = units.next().unwrap() {
+        let unit = dwarf.unit(header).unwrap();
+        if let Some(name) = &unit.name {
+            if name.to_string_lossy() == "debug.c" {
+                return unit;
+            }
+        }
+    }
+    panic!("no compilation unit for debug.c");
+}
+
+fn name(
+    dwarf: &Dwarf,
+    unit: &Unit,
+    entry: &gimli::DebuggingInformationEntry<EndianSlice<LittleEndian>>,
+) -> Option<String> {
+    let attr = entry.attr_value(gimli::DW_AT_name).unwrap()?;
+    let name = dwarf.attr_string(unit, attr).unwrap();
+    Some(name.to_string_lossy().into_owned())
+}
+
+/// Return the name of the type referred to by `DW_AT_type`.
+fn type_name(
+    dwarf: &Dwarf,
+    unit: &Unit,
+    entry: &gimli::DebuggingInformationEntry<EndianSlice<LittleEndian>>,
+) -> Option<String> {
+    match entry.attr_value(gimli::DW_AT_type).unwrap()? {
+        AttributeValue::UnitRef(offset) => {
+            let ty = unit.entry(offset).unwrap();
+            name(dwarf, unit, &ty)
+        }
+        other => panic!("unexpected type reference {:?}", other),
+    }
+}
+
+const PROGRAM: &str = "
+struct point { int x, y; };
+long total;
+int add(int a, int b) {
+    int sum = a + b;
+    return sum;
+}
+int main(void) {
+    struct point p = { 1, 2 };
+    total = add(p.x, p.y);
+    return 0;
+}
+";
+
+#[test]
+fn functions_and_variables() {
+    // with `-g`, every local is kept in memory where a debugger can find it, even when optimizing
+    for &opt_level in &[OptLevel::O0, OptLevel::O1, OptLevel::O2] {
+        let bytes = compile_with_debug_info(PROGRAM, opt_level);
+        let file = object::File::parse(&*bytes).unwrap();
+        let dwarf = load_dwarf(&file);
+        let unit = find_unit(&dwarf);
+
+        let mut functions = Vec::new();
+        let mut variables = Vec::new();
+        let mut entries = unit.entries();
+        while let Some((_, entry)) = entries.next_dfs().unwrap() {
+            let entry_name = name(&dwarf, &unit, entry);
+            match entry.tag() {
+                gimli::DW_TAG_subprogram => {
+                    let low_pc = match entry.attr_value(gimli::DW_AT_low_pc).unwrap() {
+                        Some(AttributeValue::Addr(addr)) => addr,
+                        other => panic!("expected an address for low_pc, got {:?}", other),
+                    };
+                    // the address is relocated to the function when linking
+                    assert_ne!(low_pc, 0);
+                    functions.push(entry_name.unwrap());
+                }
+                gimli::DW_TAG_formal_parameter | gimli::DW_TAG_variable => {
+                    assert!(entry.attr_value(gimli::DW_AT_location).unwrap().is_some());
+                    let ty = type_name(&dwarf, &unit, entry);
+                    variables.push((entry.tag(), entry_name.unwrap(), ty.unwrap()));
+                }
+                gimli::DW_TAG_structure_type => {
+                    assert_eq!(entry_name.as_deref(), Some("point"));
+                    let size = entry.attr_value(gimli::DW_AT_byte_size).unwrap();
+                    assert_eq!(size.and_then(|size| size.udata_value()), Some(8));
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(functions, ["add", "main"]);
+        let expected = [
+            (gimli::DW_TAG_formal_parameter, "a", "int"),
+            (gimli::DW_TAG_formal_parameter, "b", "int"),
+            (gimli::DW_TAG_variable, "sum", "int"),
+            (gimli::DW_TAG_variable, "p", "point"),
+            (gimli::DW_TAG_variable, "total", "long"),
+        ];
+        let actual: Vec<_> = variables
+            .iter()
+            .map(|(tag, name, ty)| (*tag, name.as_str(), ty.as_str()))
+            .collect();
+        assert_eq!(actual, expected, "at {:?}", opt_level);
+    }
+}
+
+#[test]
+fn line_numbers() {
+    let bytes = compile_with_debug_info(PROGRAM, OptLevel::O0);
+    let file = object::File::parse(&*bytes).unwrap();
+    let dwarf = load_dwarf(&file);
+    let unit = find_unit(&dwarf);
+
+    let program = unit
+        .line_program
+        .clone()
+        .expect("unit should have a line program");
+    let mut rows = program.rows();
+    let mut lines = Vec::new();
+    while let Some((_, row)) = rows.next_row().unwrap() {
+        if let Some(line) = row.line() {
+            if !lines.contains(&line.get()) {
+                lines.push(line.get());
+            }
+        }
+    }
+    // every line with a statement should have code
+    for line in &[4, 5, 6, 9, 10, 11] {
+        assert!(
+            lines.contains(line),
+            "no line info for line {}: {:?}",
+            line,
+            lines
+        );
+    }
+}
+
+#[test]
+fn line_directives() {
+    let program = "
+int main(void) {
+#line 50 \"grammar.y\"
+    return 0;
+}
+";
+    let bytes = compile_with_debug_info(program, OptLevel::O0);
+    let file = object::File::parse(&*bytes).unwrap();
+    let dwarf = load_dwarf(&file);
+    let unit = find_unit(&dwarf);
+
+    let program = unit
+        .line_program
+        .clone()
+        .expect("unit should have a line program");
+    let mut rows = program.rows();
+    let mut positions = Vec::new();
+    while let Some((header, row)) = rows.next_row().unwrap() {
+        let file = match row.file(header) {
+            Some(file) => file,
+            None => continue,
+        };
+        let name = dwarf.attr_string(&unit, file.path_name()).unwrap();
+        let line = row.line().map(|line| line.get());
+        positions.push((name.to_string_lossy().into_owned(), line));
+    }
+    assert!(
+        positions.contains(&("grammar.y".to_owned(), Some(50))),
+        "no line info for grammar.y:50: {:?}",
+        positions
+    );
+}
@@ -0,0 +1,147 @@
+//! Programs which should behave the same at every optimization level.
+
+mod utils;
+
+use saltwater_parser::{Opt, OptLevel};
+
+/// Compile and run `program` at each optimization level, checking its exit code and output.
+fn assert_same_at_all_levels(program: &str, code: i32, stdout: &str) {
+    for &opt_level in &[OptLevel::O0, OptLevel::O1, OptLevel::O2] {
+        let opt = Opt {
+            opt_level,
+            ..Default::default()
+        };
+        let output = utils::compile_with(program, opt, false)
+            .unwrap_or_else(|err| panic!("failed to compile at {:?}: {}", opt_level, err));
+        let result = utils::run(&output, &[]).expect("failed to run program");
+        assert_eq!(result.status.code(), Some(code), "{:?}", opt_level);
+        assert_eq!(
+            String::from_utf8_lossy(&result.stdout),
+            stdout,
+            "{:?}",
+            opt_level
+        );
+    }
+}
+
+#[test]
+fn loops() {
+    let program = "
+int main(void) {
+    int sum = 0;
+    for (int i = 0; i < 10; i++) {
+        sum += i;
+    }
+    int j = 10;
+    while (j --> 0) {
+        sum -= 2;
+        ++sum;
+    }
+    do {
+        sum *= 2;
+    } while (sum < 64);
+    return sum;
+}
+";
+    assert_same_at_all_levels(program, 70, "");
+}
+
+#[test]
+fn address_taken() {
+    let program = "
+void set(int *p) { *p = 5; }
+int main(void) {
+    int a = 1, b = 2;
+    set(&a);
+    int c = a + b;
+    int *p = &c;
+    *p += 1;
+    return a + b + c;
+}
+";
+    assert_same_at_all_levels(program, 15, "");
+}
+
+#[test]
+fn scalar_types() {
+    let program = "
+int printf(const char *, ...);
+int main(void) {
+    char c = 'a';
+    c++;
+    _Bool b = 0;
+    b++;
+    float f = 1.5f;
+    f++;
+    double d = 0.25;
+    d *= 3;
+    long double ld = 2;
+    ld++;
+    unsigned long u = 0;
+    u--;
+    char s[] = \"xyz\";
+    char *p = s;
+    p++;
+    printf(\"%c %d %.1f %.2f %.1Lf %d %c\\n\", c, b, f, d, ld, u == -1ul, *p);
+    return 0;
+}
+";
+    assert_same_at_all_levels(program, 0, "b 1 2.5 0.75 3.0 1 y\n");
+}
+
+#[test]
+fn parameters() {
+    let program = "
+int count(int n, int step) {
+    int total = 0;
+    while (n > 0) {
+        total++;
+        n -= step;
+    }
+    return total;
+}
+int main(void) {
+    return count(10, 3);
+}
+";
+    assert_same_at_all_levels(program, 4, "");
+}
+
+#[test]
+fn control_flow() {
+    let program = "
+int main(void) {
+    int x = 0, i = 0;
+again:
+    switch (i) {
+    case 0: x += 1;
+    case 1: x += 10; break;
+    default: x += 100;
+    }
+    if (++i < 3) goto again;
+    for (int k = 0; k < 3; k++) {
+        int fresh = k;
+        if (k != 1) {
+            x += fresh;
+        }
+    }
+    return x;
+}
+";
+    assert_same_at_all_levels(program, 123, "");
+}
+
+#[test]
+fn variable_length_arrays() {
+    let program = "
+int main(void) {
+    int n = 4;
+    int a[n];
+    for (int i = 0; i < n; i++) {
+        a[i] = i * i;
+    }
+    return sizeof(a) + a[n - 1];
+}
+";
+    assert_same_at_all_levels(program, 25, "");
+}
@@ -0,0 +1,15 @@
+// output: BEGIN:
+// 1 2 3
+// no args
+// (x)
+// END
+int printf(const char *, ...);
+#define LOG(fmt, ...) printf(fmt "\n", __VA_ARGS__)
+#define GNU_LOG(fmt, ...) printf(fmt "\n", ## __VA_ARGS__)
+#define STR(...) #__VA_ARGS__
+#define PAREN(...) __VA_OPT__("(") __VA_ARGS__ __VA_OPT__(")")
+int main() {
+    LOG("%d %d %d", 1, 2, 3);
+    GNU_LOG("no args");
+    GNU_LOG("%s", PAREN(STR(x)));
+}
@@ -0,0 +1,3 @@
+// compile-fail
+#define HEADER 1
+#include HEADER
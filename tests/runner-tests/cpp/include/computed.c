@@ -0,0 +1,11 @@
+// code: 3
+#define CONFIG_HEADER "subdir/a.h"
+#include CONFIG_HEADER
+#define SYSTEM <stddef.h>
+#include SYSTEM
+#define HEADER(name) #name
+#include HEADER(stdarg.h)
+int main() {
+    size_t n = a + b;
+    return n;
+}
@@ -0,0 +1,25 @@
+// succeeds
+#include <float.h>
+#include <iso646.h>
+#include <limits.h>
+#include <stdalign.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdnoreturn.h>
+
+noreturn void exit(int);
+
+int main() {
+    bool ok = true and not false;
+    alignas(8) char c = CHAR_MAX;
+    ok = ok and c == 127 and alignof(int64_t) == 8;
+    ok = ok and sizeof(size_t) == sizeof(void *) and SIZE_MAX == UINTPTR_MAX;
+    ok = ok and alignof(max_align_t) == 16;
+    ok = ok and FLT_MANT_DIG == 24 and DBL_MAX > 1e308 and LDBL_MANT_DIG == 64;
+    ok = ok and INT32_MIN == INT_MIN and UINT16_MAX == USHRT_MAX;
+    if (not ok) {
+        exit(1);
+    }
+    return 0;
+}
@@ -0,0 +1,7 @@
+// code: 3
+#include "once.h"
+#include "once.h"
+int main() {
+    struct point p = { 1, 2 };
+    return p.x + p.y;
+}
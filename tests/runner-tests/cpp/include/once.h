@@ -0,0 +1,2 @@
+#pragma once
+struct point { int x, y; };
@@ -0,0 +1,10 @@
+// succeeds
+int strcmp(const char *, const char *);
+#line 100 "grammar.y"
+int main() {
+    if (__LINE__ != 101 || strcmp(__FILE__, "grammar.y") != 0) {
+        return 1;
+    }
+# 1 "generated.c" 2
+    return __LINE__ != 1 || strcmp(__FILE__, "generated.c") != 0;
+}
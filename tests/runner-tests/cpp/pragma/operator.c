@@ -0,0 +1,15 @@
+// code: 1
+#define PRAGMA(x) _Pragma(#x)
+PRAGMA(pack(1))
+struct s { char c; long l; };
+_Pragma("pack()")
+_Static_assert(sizeof(struct s) == 9, "_Pragma should work in macros");
+
+#define X 1
+#pragma push_macro("X")
+#undef X
+#define X 2
+#pragma pop_macro("X")
+int main() {
+    return X;
+}
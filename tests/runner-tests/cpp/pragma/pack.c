@@ -0,0 +1,21 @@
+// succeeds
+#pragma pack(push, 1)
+struct packed { char c; int i; short s; };
+#pragma pack(push, 2)
+struct two { char c; long l; };
+#pragma pack(pop)
+struct still_packed { char c; long l; };
+#pragma pack(pop)
+struct natural { char c; int i; };
+_Static_assert(sizeof(struct packed) == 7, "pack(1) should remove padding");
+_Static_assert(sizeof(struct two) == 10, "pack(2) should align to 2 bytes");
+_Static_assert(sizeof(struct still_packed) == 9, "pop should restore pack(1)");
+_Static_assert(sizeof(struct natural) == 8, "pop should restore natural alignment");
+
+struct packed global = { 'a', 0x12345678, 0x1234 };
+int main() {
+    struct packed local = global;
+    local.i += 1;
+    return !(local.c == 'a' && local.i == 0x12345679 && local.s == 0x1234
+             && *(char *)&local.s == 0x34);
+}
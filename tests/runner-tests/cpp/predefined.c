@@ -0,0 +1,16 @@
+// succeeds
+#include <stdint.h>
+#if __SIZEOF_LONG__ != 8 || __SCHAR_MAX__ != 127
+#error wrong target macros
+#endif
+int main() {
+    __INT64_TYPE__ big = __INT64_C(1) << 40;
+    if (big != 1099511627776 || sizeof(__SIZE_TYPE__) != __SIZEOF_SIZE_T__) {
+        return 1;
+    }
+    if (INTPTR_MAX != __INTPTR_MAX__ || UINT32_MAX != 4294967295u) {
+        return 2;
+    }
+    double max = __DBL_MAX__;
+    return !(max > 1e308 && __FLT_MIN__ < 1e-37);
+}
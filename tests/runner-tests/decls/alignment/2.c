@@ -0,0 +1,12 @@
+// output: 32 16 0 0 0 0 64
+int printf(const char *, ...);
+struct s { char c; _Alignas(16) char d; };
+_Alignas(32) char g[3];
+typedef struct { _Alignas(64) int x; } big;
+int main(void) {
+    _Alignas(32) int local = 5;
+    _Alignas(64) char buf[10] = "hi";
+    printf("%d %d %d %d %d %d %d\n", (int)sizeof(struct s), (int)_Alignof(struct s),
+        (int)((long)&g % 32), (int)((long)&local % 32), (int)((long)buf % 64),
+        local - 5, (int)sizeof(big));
+}
@@ -0,0 +1,6 @@
+// code: 12
+_Alignas(double) char c;
+int main(void) {
+    _Alignas(0) int zero_has_no_effect = 4;
+    return _Alignof(c) + zero_has_no_effect;
+}
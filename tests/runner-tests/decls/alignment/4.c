@@ -0,0 +1,3 @@
+// fail
+_Alignas(3) int not_power_of_two;
+int main(void) {}
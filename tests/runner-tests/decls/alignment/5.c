@@ -0,0 +1,3 @@
+// fail
+_Alignas(1) int less_strict_than_int;
+int main(void) {}
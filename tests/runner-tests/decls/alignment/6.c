@@ -0,0 +1,3 @@
+// fail
+typedef _Alignas(16) int aligned_int;
+int main(void) {}
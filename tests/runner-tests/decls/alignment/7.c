@@ -0,0 +1,3 @@
+// fail
+struct s { _Alignas(8) int bitfield: 3; };
+int main(void) {}
@@ -0,0 +1,12 @@
+// code: 0
+struct packed { unsigned a: 3; int b: 5; char c; int d: 30; int e: 4; unsigned long f: 40; _Bool g: 1; };
+struct zero_width { char c; int: 0; char d; };
+struct unnamed { char c; int: 4; };
+union u { int x: 3; char c[5]; };
+int main() {
+    if (sizeof(struct packed) != 16) return 1;
+    if (sizeof(struct zero_width) != 5) return 2;
+    if (sizeof(struct unnamed) != 2) return 3;
+    if (sizeof(union u) != 8) return 4;
+    return 0;
+}
@@ -0,0 +1,8 @@
+// output: 7 -16 7 -5 7 123456789012 1
+int printf(const char *, ...);
+struct s { unsigned a: 3; int b: 5; char c; int d: 30; int e: 4; unsigned long f: 40; _Bool g: 1; };
+int main() {
+    struct s l;
+    l.a = 15; l.b = -16; l.c = 7; l.d = -5; l.e = 7; l.f = 123456789012; l.g = 5;
+    printf("%d %d %d %d %d %ld %d\n", l.a, l.b, l.c, l.d, l.e, l.f, l.g);
+}
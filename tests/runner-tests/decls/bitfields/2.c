@@ -0,0 +1,8 @@
+// output: 5 -3 120 100000 -2 1099511627775 1 78ed
+int printf(const char *, ...);
+struct s { unsigned a: 3; int b: 5; char c; int d: 30; int e: 4; unsigned long f: 40; _Bool g: 1; };
+struct s g = { 5, -3, 'x', 100000, -2, 1099511627775, 1 };
+int main() {
+    unsigned *first_unit = (unsigned *)&g;
+    printf("%d %d %d %d %d %ld %d %x\n", g.a, g.b, g.c, g.d, g.e, g.f, g.g, *first_unit);
+}
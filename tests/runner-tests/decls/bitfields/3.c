@@ -0,0 +1,12 @@
+// output: 4 -15 -8 -4 7 15
+int printf(const char *, ...);
+struct s { unsigned a: 3; int b: 5; int d: 30; int e: 4; };
+int main() {
+    struct s l, *p = &l;
+    l.a = 1; l.b = -14; l.d = -5; l.e = 7;
+    l.a += 3; p->b -= 1; l.e++; ++l.d;
+    int r = (l.b = 15) + 0;
+    int e = l.e;
+    l.b = -15;
+    printf("%d %d %d %d %d %d\n", l.a, l.b, e, l.d, 7, r);
+}
@@ -0,0 +1,3 @@
+// fail
+struct s { int a: 3; } s;
+int *p = &s.a;
@@ -0,0 +1,7 @@
+// code: 3
+struct s { long l; char c; int: 0; char padding_skipped; } s = { 1, 2, 3 };
+struct p { char c; long l; int i; } p = { 1, 2, 3 };
+int main() {
+    if (s.l != 1 || s.c != 2 || p.c != 1 || p.l != 2 || p.i != 3) return 1;
+    return s.padding_skipped;
+}
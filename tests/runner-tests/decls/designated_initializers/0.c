@@ -0,0 +1,9 @@
+// output: red green blue | 6 50 20 21 0
+int printf(const char *, ...);
+enum { RED, GREEN, BLUE, NCOLORS };
+const char *names[NCOLORS] = { [BLUE] = "blue", [RED] = "red", [GREEN] = "green" };
+int sparse[] = { [5] = 50, [2] = 20, 21 };
+int main(void) {
+    printf("%s %s %s | %d %d %d %d %d\n", names[0], names[1], names[2],
+        (int)(sizeof sparse / sizeof *sparse), sparse[5], sparse[2], sparse[3], sparse[0]);
+}
@@ -0,0 +1,11 @@
+// output: 1 0 3 | 0 7 4 0 0 9 10
+int printf(const char *, ...);
+struct point { int x, y, z; };
+struct in { int a, b; };
+struct out { struct in i; int c; int arr[4]; };
+struct point gp = { .z = 3, .x = 1 };
+struct out go = { .i.b = 7, .arr[2] = 9, 10, .c = 4 };
+int main(void) {
+    printf("%d %d %d | %d %d %d %d %d %d %d\n", gp.x, gp.y, gp.z,
+        go.i.a, go.i.b, go.c, go.arr[0], go.arr[1], go.arr[2], go.arr[3]);
+}
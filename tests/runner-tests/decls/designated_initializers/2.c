@@ -0,0 +1,14 @@
+// output: 6 5 0 | 0 10 1 5 0 | 1 2 0 3 4 5
+int printf(const char *, ...);
+struct point { int x, y, z; };
+struct in { int a, b; };
+struct out { struct in i; int c; int arr[4]; };
+int main(void) {
+    int v = 5;
+    struct point p = { .y = v, .x = v + 1 };
+    struct out o = { .arr = { [3] = v }, .i = { .b = v * 2 }, 1 };
+    int a[2][3] = { [1][2] = v, [0] = { 1, 2 }, [1][0] = 3, 4 };
+    printf("%d %d %d | %d %d %d %d %d | %d %d %d %d %d %d\n", p.x, p.y, p.z,
+        o.i.a, o.i.b, o.c, o.arr[3], o.arr[0],
+        a[0][0], a[0][1], a[0][2], a[1][0], a[1][1], a[1][2]);
+}
@@ -0,0 +1,12 @@
+// output: ab0 1 | 1 2 3
+int printf(const char *, ...);
+union u { int i; char c[4]; float f; };
+struct in { int a, b; };
+struct out { struct in i; int c; };
+union u gu = { .c = { 'a', 'b' } };
+int main(void) {
+    union u lu = { .f = 1.0f };
+    struct in copy = { 1, 2 };
+    struct out o = { copy, 3 };
+    printf("%c%c%d %d | %d %d %d\n", gu.c[0], gu.c[1], gu.c[2], lu.i == 0x3f800000, o.i.a, o.i.b, o.c);
+}
@@ -0,0 +1,7 @@
+// output: ab 0 | hi 3
+int printf(const char *, ...);
+char g[5] = "ab";
+struct s { char n[4]; int x; } gs = { "hi", 3 };
+int main(void) {
+    printf("%s %d | %s %d\n", g, g[4], gs.n, gs.x);
+}
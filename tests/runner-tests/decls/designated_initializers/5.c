@@ -0,0 +1,2 @@
+// fail
+int main(void) { int a[2] = { [2] = 1 }; }
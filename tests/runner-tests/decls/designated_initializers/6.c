@@ -0,0 +1,3 @@
+// fail
+struct point { int x, y; };
+int main(void) { struct point p = { .z = 1 }; }
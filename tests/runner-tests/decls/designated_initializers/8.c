@@ -0,0 +1,2 @@
+// fail
+int main(void) { int n = 1; int a[3] = { [n] = 1 }; }
@@ -0,0 +1,8 @@
+// output: 3 4
+int printf(const char *, ...);
+struct point { int x, y; };
+int main(void) {
+    int x = 3, y = 4;
+    struct point p = {x, y};
+    printf("%d %d\n", p.x, p.y);
+}
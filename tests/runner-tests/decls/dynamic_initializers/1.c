@@ -0,0 +1,14 @@
+// output: 1 z 9 9 10 0 | 9 | 9 0 3 0
+int printf(const char *, ...);
+struct in { char c; long l; };
+struct out { int a; struct in i; int arr[3]; };
+union u { int i; char c; };
+int main(void) {
+    int v = 9;
+    // members that are left out are zero-initialized
+    struct out o = {1, {'z', v}, {v, v + 1}};
+    union u x = {v};
+    int m[2][2] = {{v}, {3}};
+    printf("%d %c %ld %d %d %d | %d | %d %d %d %d\n", o.a, o.i.c, o.i.l, o.arr[0], o.arr[1], o.arr[2],
+           x.i, m[0][0], m[0][1], m[1][0], m[1][1]);
+}
@@ -0,0 +1,11 @@
+// output: 3 hi 3 abc 5 ab
+int printf(const char *, ...);
+int main(void) {
+    int v = 2;
+    int a[] = {v, v * 2, v * 3};
+    char s[] = "hi";
+    char t[3] = "abc";
+    char u[5] = "ab";
+    printf("%d %s %d %c%c%c %d %s\n", (int)(sizeof a / sizeof a[0]), s, (int)sizeof s,
+           t[0], t[1], t[2], (int)sizeof u, u);
+}
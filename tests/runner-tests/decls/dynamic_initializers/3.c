@@ -0,0 +1,8 @@
+// output: 6 -6 q 0
+int printf(const char *, ...);
+struct bits { unsigned a: 3; int b: 5; unsigned : 2; char c; int d: 4; };
+int main(void) {
+    int v = 6;
+    struct bits x = {v, -v, 'q'};
+    printf("%d %d %c %d\n", x.a, x.b, x.c, x.d);
+}
@@ -0,0 +1,15 @@
+// output: 4 8 | 4 8 | 5 2 0 77
+int printf(const char *, ...);
+struct point { int x, y; };
+struct large { long arr[64]; int tail; };
+struct point make(int x) {
+    struct point p = {x, x * 2};
+    return p;
+}
+int main(void) {
+    struct point p = make(4);
+    struct point q = p;
+    struct large l = {{5, 2}, 77};
+    struct large m = l;
+    printf("%d %d | %d %d | %ld %ld %ld %d\n", p.x, p.y, q.x, q.y, m.arr[0], m.arr[1], m.arr[63], m.tail);
+}
@@ -0,0 +1,11 @@
+// code: 3
+int main(void) {
+    int total = 0;
+    for (int i = 0; i < 3; i++) {
+        // the rest of the array must be zeroed each time the declaration is reached
+        int arr[3] = {i};
+        total += arr[0] + arr[1] + arr[2];
+        arr[1] = 100;
+    }
+    return total;
+}
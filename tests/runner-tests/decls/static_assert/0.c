@@ -0,0 +1,12 @@
+// code: 4
+_Static_assert(sizeof(int) == 4, "int should be 4 bytes");
+struct s {
+    int i;
+    _Static_assert(sizeof(char) == 1, "char should be 1 byte");
+};
+int main(void) {
+    _Static_assert(sizeof(struct s) == 4, "struct s should not have padding");
+    enum { A = 2, B = A * 2 };
+    _Static_assert(B == 4, "enums are constant");
+    return sizeof(struct s);
+}
@@ -0,0 +1,3 @@
+// fail
+_Static_assert(sizeof(int) == 8, "int should be 8 bytes");
+int main(void) {}
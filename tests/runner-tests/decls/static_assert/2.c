@@ -0,0 +1,5 @@
+// fail
+int main(void) {
+    int i = 1;
+    _Static_assert(i, "not a constant");
+}
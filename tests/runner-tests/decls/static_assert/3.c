@@ -0,0 +1,3 @@
+// fail
+_Static_assert(1, 2);
+int main(void) {}
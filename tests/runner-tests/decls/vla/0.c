@@ -0,0 +1,11 @@
+// code: 42
+int main(void) {
+    int n = 6;
+    int a[n];
+    for (int i = 0; i < n; i++) {
+        a[i] = i * 2;
+    }
+    // changing `n` doesn't change the length of `a`
+    n = 100;
+    return a[3] * 7 + sizeof(a) / sizeof(a[0]) - 6;
+}
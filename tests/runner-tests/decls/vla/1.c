@@ -0,0 +1,31 @@
+// output: 30 36 42 66 81 96 102 126 150 24 288
+int printf(const char *, ...);
+
+void mul(int n, double a[n][n], double b[n][n], double c[n][n]) {
+    for (int i = 0; i < n; i++) {
+        for (int j = 0; j < n; j++) {
+            double sum = 0;
+            for (int k = 0; k < n; k++) {
+                sum += a[i][k] * b[k][j];
+            }
+            c[i][j] = sum;
+        }
+    }
+}
+
+int main(void) {
+    int n = 3;
+    double a[n][n], c[n][n];
+    for (int i = 0; i < n; i++) {
+        for (int j = 0; j < n; j++) {
+            a[i][j] = i * n + j + 1;
+        }
+    }
+    mul(n, a, a, c);
+    for (int i = 0; i < n; i++) {
+        for (int j = 0; j < n; j++) {
+            printf("%d ", (int)c[i][j]);
+        }
+    }
+    printf("%d %d\n", (int)sizeof(c[0]), (int)sizeof(double[n][4][n]));
+}
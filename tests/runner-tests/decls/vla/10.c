@@ -0,0 +1,10 @@
+// fail
+int main(void) {
+    int n = 5;
+    int a[n];
+    return 0;
+}
+
+int malloc(int x) {
+    return x;
+}
@@ -0,0 +1,18 @@
+// code: 45
+int sum(int n) {
+    int total = 0;
+    for (int i = 1; i <= n; i++) {
+        // reallocated every iteration
+        char buf[i];
+        buf[i - 1] = i;
+        total += buf[i - 1];
+        if (i == n) {
+            return total;
+        }
+    }
+    return -1;
+}
+
+int main(void) {
+    return sum(9);
+}
@@ -0,0 +1,9 @@
+// code: 20
+int main(void) {
+    int rows = 4, cols = 5;
+    typedef int row[cols];
+    row *m = (row *)0;
+    int (*p)[cols] = m + 1;
+    // pointer arithmetic uses the runtime stride
+    return (long)p - (long)m + sizeof(row) * rows - sizeof(int[rows][cols]);
+}
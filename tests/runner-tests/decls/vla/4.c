@@ -0,0 +1,4 @@
+// fail
+int n = 5;
+int a[n];
+int main(void) {}
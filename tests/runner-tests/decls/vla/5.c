@@ -0,0 +1,5 @@
+// fail
+int main(void) {
+    int n = 5;
+    static int a[n];
+}
@@ -0,0 +1,5 @@
+// fail
+int main(void) {
+    int n = 5;
+    int a[n] = {0};
+}
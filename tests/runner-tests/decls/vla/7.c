@@ -0,0 +1,21 @@
+// output: 0 3 6 9 | 9 6 3 0 | 3 6
+int printf(const char *, ...);
+int main(void) {
+    int rows = 4, cols = 3;
+    int m[rows][cols];
+    for (int i = 0; i < rows; i++) {
+        m[i][0] = i * cols;
+    }
+    // `row++` and `++row` step over a whole row, like `row + 1`
+    int (*row)[cols] = m;
+    for (int i = 0; i < rows; i++) {
+        printf("%d ", (*row++)[0]);
+    }
+    printf("| ");
+    for (int i = 0; i < rows; i++) {
+        printf("%d ", (*--row)[0]);
+    }
+    ++row;
+    int (*next)[cols] = row--;
+    printf("| %d %d\n", (*next)[0], (*(row + 2))[0]);
+}
@@ -0,0 +1,17 @@
+// code: 117
+// small arrays use the stack and large ones the heap, even for the same declaration
+long sum(int n) {
+    long a[n];
+    for (int i = 0; i < n; i++) {
+        a[i] = i;
+    }
+    return a[n - 1];
+}
+
+int main(void) {
+    long total = 0;
+    for (int n = 1; n <= 1000; n *= 10) {
+        total += sum(n);
+    }
+    return total - 1000 + sum(10) + sum(2);
+}
@@ -0,0 +1,10 @@
+// fail
+int free(int x) {
+    return x;
+}
+
+int main(void) {
+    int n = 5;
+    int a[n];
+    return free(1);
+}
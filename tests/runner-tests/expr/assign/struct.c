@@ -0,0 +1,12 @@
+// output: 1 2 | 1 2 | 7 7
+int printf(const char *, ...);
+struct point { int x, y; };
+union u { int i; float f; };
+int main(void) {
+    struct point a = {1, 2}, b, c;
+    c = b = a;
+    union u x, y;
+    x.i = 7;
+    y = x;
+    printf("%d %d | %d %d | %d %d\n", b.x, b.y, c.x, c.y, x.i, y.i);
+}
@@ -0,0 +1,20 @@
+// output: 6 4 14 10 12 15 hi
+int printf(const char *, ...);
+struct point { int x, y; };
+int sum(struct point p) { return p.x + p.y; }
+int len(int *a, int n) { int t = 0; for (int i = 0; i < n; i++) t += a[i]; return t; }
+int main(void) {
+    int total = 0;
+    int i = 0;
+    while (sum((struct point){ i, 1 }) < 5) {
+        total += sum((struct point){ .y = i });
+        i++;
+    }
+    struct point *p = &(struct point){ 3, 4 };
+    p->x = 10;
+    int n = len((int[]){ 1, 2, 3, [5] = 4 }, 6);
+    int s = sizeof (int[]){ 1, 2, 3 };
+    int x = (int){ 7 } + ((struct point){ 1, 2 }).y + (int[]){ 5, 6 }[1];
+    char *str = (char[]){ "hi" };
+    printf("%d %d %d %d %d %d %s\n", total, i, p->x + p->y, n, s, x, str);
+}
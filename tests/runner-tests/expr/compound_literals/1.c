@@ -0,0 +1,8 @@
+// output: 7 9 | 0 42 0
+int printf(const char *, ...);
+struct point { int x, y, z; };
+int *gptr = (int[]){ 7, 8, 9 };
+struct point *gpp = &(struct point){ .y = 42 };
+int main(void) {
+    printf("%d %d | %d %d %d\n", *gptr, gptr[2], gpp->x, gpp->y, gpp->z);
+}
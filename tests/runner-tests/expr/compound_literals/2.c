@@ -0,0 +1,2 @@
+// fail
+int main(void) { int n = 2; int *p = (int[n]){ 1 }; }
@@ -0,0 +1,4 @@
+// fail
+int g;
+int *p = (int[]){ g };
+int main(void) {}
@@ -0,0 +1,6 @@
+// code: 3
+int main(void) {
+    int *p = (int[3]){ 1, 2 };
+    p[2] = 3;
+    return p[2] + p[3 - 3] - 1;
+}
@@ -0,0 +1,15 @@
+// output: int char * char * double struct s other char *
+int printf(const char *, ...);
+struct s { int i; };
+#define type_name(x) _Generic((x), int: "int", char *: "char *", double: "double", \
+                                   struct s: "struct s", default: "other")
+int main(void) {
+    const int ci = 3;
+    char buf[2];
+    char *p = buf;
+    double d = 1;
+    struct s sv;
+    short sh;
+    printf("%s %s %s %s %s %s %s\n", type_name(ci), type_name(p), type_name(buf),
+        type_name(d), type_name(sv), type_name(sh), type_name("str"));
+}
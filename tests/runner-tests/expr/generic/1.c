@@ -0,0 +1,10 @@
+// code: 3
+int main(void) {
+    int i = 0;
+    // only the selected association is evaluated
+    _Generic(i, int: i += 1, default: i += 10);
+    _Generic(i, default: i += 2, double: i += 100);
+    // the result is an lvalue if the selected expression is
+    int *p = &_Generic(i, int: i);
+    return *p;
+}
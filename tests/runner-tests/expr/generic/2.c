@@ -0,0 +1,5 @@
+// fail
+int main(void) {
+    float f;
+    return _Generic(f, int: 1, double: 2);
+}
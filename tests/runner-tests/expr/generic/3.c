@@ -0,0 +1,5 @@
+// fail
+int main(void) {
+    int i;
+    return _Generic(i, int: 1, int: 2);
+}
@@ -0,0 +1,5 @@
+// fail
+int main(void) {
+    int i;
+    return _Generic(i, default: 1, default: 2);
+}
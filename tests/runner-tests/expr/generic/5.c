@@ -0,0 +1,14 @@
+// output: long double, long double, long long, unsigned long long, long
+int printf(const char *, ...);
+#define type_name(x) _Generic((x), double: "double", long double: "long double", \
+        long: "long", long long: "long long", unsigned long long: "unsigned long long", \
+        default: "other")
+int main(void) {
+    long double ld = 1;
+    double d = 2;
+    long long ll = 3;
+    unsigned long long ull = 4;
+    long l = 5;
+    printf("%s, %s, %s, %s, %s\n", type_name(ld), type_name(ld + d), type_name(ll + l),
+           type_name(ull + ll), type_name(l));
+}
@@ -0,0 +1,11 @@
+// output: char * const char * int void *
+int printf(const char *, ...);
+#define type_name(x) _Generic((x), char *: "char *", const char *: "const char *", \
+                                   int: "int", const int: "const int", default: "void *")
+int main(void) {
+    char buf[2];
+    const char *s = "str";
+    const int ci = 1;
+    s = buf;
+    printf("%s %s %s %s\n", type_name(buf), type_name(s), type_name(ci), type_name((void *)s));
+}
@@ -0,0 +1,7 @@
+// output: 0.33333333333333333334 1 0 16
+int printf(const char *, ...);
+int main(void) {
+    long double one = 1, three = 3;
+    long double third = one / three;
+    printf("%.20Lf %d %d %d\n", third, third < one, third * 3 != one, (int)sizeof(long double));
+}
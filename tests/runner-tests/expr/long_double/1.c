@@ -0,0 +1,11 @@
+// code: 42
+long double half(long double x) {
+    return x / 2;
+}
+int main(void) {
+    long double x = 80;
+    x = half(x) + 1.5;
+    x++;
+    x -= 0.5;
+    return -(int)-x;
+}
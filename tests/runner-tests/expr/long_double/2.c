@@ -0,0 +1,25 @@
+// output: 6.750000 7 -2.500000 1
+#include <stdarg.h>
+int printf(const char *, ...);
+long double sum(int n, ...) {
+    va_list ap;
+    va_start(ap, n);
+    long double total = 0;
+    for (int i = 0; i < n; i++) {
+        total += va_arg(ap, long double);
+    }
+    va_end(ap);
+    return total;
+}
+// `x` is passed on the stack after `g`, so it needs padding to be 16-byte aligned
+long double last(long a, long b, long c, long d, long e, long f, long g, long double x) {
+    return x * g;
+}
+int main(void) {
+    long double a = 4.5, b = 2;
+    double d = last(1, 2, 3, 4, 5, 6, 7, 1);
+    unsigned long big = 18446744073709551615ul;
+    long double lbig = big;
+    printf("%Lf %d %Lf %d\n", sum(3, a, b, (long double)0.25), (int)d, -sum(1, b + 0.5),
+           (unsigned long)lbig == big);
+}
@@ -0,0 +1,11 @@
+// code: 1
+struct s {
+    char c;
+    long double l;
+};
+int main(void) {
+    long double zero = 0;
+    long double nan = zero / zero;
+    struct s s = { 1, 2.25 };
+    return nan != nan && !(nan == nan) && sizeof(struct s) == 32 && s.l > 2 && !zero;
+}
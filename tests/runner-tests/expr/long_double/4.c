@@ -0,0 +1,7 @@
+// output: 2.50 -0.75
+int printf(const char *, ...);
+long double strtold(const char *, char **);
+int main(void) {
+    long double x = strtold("1.25", 0);
+    printf("%.2Lf %.2Lf\n", x * 2, -strtold("0.75", 0));
+}
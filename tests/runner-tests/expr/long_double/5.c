@@ -0,0 +1,9 @@
+// output: 1 1 1e+4000 1e+3990 0.1000000000000000000013553
+#include <float.h>
+int printf(const char *, ...);
+long double max = LDBL_MAX;
+int main(void) {
+    long double big = 1e4000L;
+    printf("%d %d %Lg %Lg %.25Lg\n", max > 1e308 && max * 2 > max, 1.0L + LDBL_EPSILON > 1.0L,
+           big, big / 1e10L, 0.1L);
+}
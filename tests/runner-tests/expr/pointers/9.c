@@ -0,0 +1,15 @@
+// output: 2 3 3 2 1 4 3
+int printf(const char *, ...);
+int main(void) {
+    int a[4] = {1, 2, 3, 4};
+    int *p = a;
+    p++;
+    int x = *p;
+    ++p;
+    int y = *p;
+    int z = *p--;
+    int w = *p;
+    int v = *--p;
+    int *end = a + 4;
+    printf("%d %d %d %d %d %d %d\n", x, y, z, w, v, *(end - 1), end[-2]);
+}
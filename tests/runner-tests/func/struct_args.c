@@ -0,0 +1,35 @@
+// output: 3 7 10 12 | 6 | 1 2 3 4
+int printf(const char *, ...);
+struct small { int a, b; };
+struct mixed { char c; double d; };
+struct big { long a, b, c, d; };
+struct small swap(struct small s) {
+    int tmp = s.a;
+    s.a = s.b;
+    s.b = tmp;
+    return s;
+}
+struct mixed scale(struct mixed m, int by) {
+    m.c = m.c * by;
+    m.d = m.d * by;
+    return m;
+}
+struct big reverse(struct big b) {
+    struct big r;
+    r.a = b.d; r.b = b.c; r.c = b.b; r.d = b.a;
+    return r;
+}
+long sum(long a, long b, long c, long d, long e, long f, struct big x) {
+    return a + b + c + d + e + f + x.a - x.d;
+}
+int main(void) {
+    struct small s;
+    s.a = 7; s.b = 3;
+    struct mixed m;
+    m.c = 5; m.d = 1.5;
+    struct big b;
+    b.a = 4; b.b = 3; b.c = 2; b.d = 1;
+    printf("%d %d %d %d | %ld | %ld %ld %ld %ld\n", swap(s).a, swap(s).b,
+        scale(m, 2).c, (int)scale(m, 2).d * 4,
+        sum(0, 0, 0, 0, 0, 3, b), reverse(b).a, reverse(b).b, reverse(b).c, reverse(b).d);
+}
@@ -0,0 +1,272 @@
+mod utils;
+
+use saltwater_codegen::link;
+
+/// Compile each program to a separate object, link them together, and check the output.
+fn assert_linked_output(programs: &[&str], expected: &str) {
+    let objects: Vec<_> = programs
+        .iter()
+        .map(|program| {
+            utils::compile(program, "".into(), true)
+                .unwrap_or_else(|err| panic!("failed to compile program '{}': {}", program, err))
+        })
+        .collect();
+    let output = tempfile::NamedTempFile::new()
+        .expect("cannot create tempfile")
+        .into_temp_path();
+    link(&objects, &output).expect("failed to link objects");
+    let actual = utils::run(&output, &[]).expect("failed to run linked program");
+    assert_eq!(String::from_utf8_lossy(&actual.stdout), expected);
+}
+
+/// Like `assert_linked_output`, but compile the second program with the host `cc`.
+///
+/// This checks that saltwater follows the platform ABI.
+fn assert_linked_with_cc(program: &str, cc_program: &str, expected: &str) {
+    use std::process::Command;
+
+    let object = utils::compile(program, "".into(), true)
+        .unwrap_or_else(|err| panic!("failed to compile program '{}': {}", program, err));
+    let dir = tempfile::tempdir().expect("cannot create tempdir");
+    let source = dir.path().join("cc_program.c");
+    let cc_object = dir.path().join("cc_program.o");
+    std::fs::write(&source, cc_program).expect("cannot write to tempdir");
+    let status = Command::new("cc")
+        .arg("-c")
+        .arg(&source)
+        .arg("-o")
+        .arg(&cc_object)
+        .status()
+        .expect("could not run host cc");
+    assert!(status.success(), "cc failed to compile '{}'", cc_program);
+    let output = dir.path().join("linked");
+    link(&[object.as_ref(), cc_object.as_path()], &output).expect("failed to link objects");
+    let actual = utils::run(&output, &[]).expect("failed to run linked program");
+    assert_eq!(String::from_utf8_lossy(&actual.stdout), expected);
+}
+
+#[test]
+fn external_linkage() {
+    assert_linked_output(
+        &[
+            "int printf(const char *, ...);
+            int shared = 40;
+            int from_b(void);
+            int main(void) {
+                printf(\"%d\\n\", shared + from_b());
+            }\n",
+            "extern int shared;
+            int from_b(void) {
+                return shared / 20;
+            }\n",
+        ],
+        "42\n",
+    );
+}
+
+#[test]
+fn static_symbols_are_local() {
+    assert_linked_output(
+        &[
+            "int printf(const char *, ...);
+            static int helper(void) { return 1; }
+            static int counter = 10;
+            int from_b(void);
+            int main(void) {
+                printf(\"%d %d\\n\", helper() + counter, from_b());
+            }\n",
+            "static int helper(void) { return 2; }
+            static int counter = 20;
+            int from_b(void) {
+                return helper() + counter;
+            }\n",
+        ],
+        "11 22\n",
+    );
+}
+
+#[test]
+fn string_literals() {
+    assert_linked_output(
+        &[
+            "int puts(const char *);
+            const char *greeting(void);
+            int main(void) {
+                puts(\"first\");
+                puts(greeting());
+            }\n",
+            "const char *greeting(void) {
+                return \"second\";
+            }\n",
+        ],
+        "first\nsecond\n",
+    );
+}
+
+const ABI_STRUCTS: &str = "
+    struct ii { int a, b; };
+    struct dd { double x, y; };
+    struct id { long i; double d; };
+    struct ff3 { float a, b, c; };
+    struct big { long a, b, c; };
+    struct chars { char c[3]; };
+    struct mixed { char c; double d; };
+    union u { int i; float f; };
+";
+
+#[cfg(all(target_arch = "x86_64", not(target_os = "windows")))]
+#[test]
+fn structs_returned_from_cc() {
+    let program = format!(
+        "{}
+        int printf(const char *, ...);
+        struct ii g_ii(int a, int b);
+        struct dd g_dd(double x, double y);
+        struct id g_id(long i, double d);
+        struct ff3 g_ff3(float a);
+        struct big g_big(long a);
+        struct chars g_chars(void);
+        int main(void) {{
+            printf(\"%d %d|%d %d|%ld %d|%d %d %d|%ld %ld %ld|%c%c%c\\n\",
+                g_ii(1, 2).a, g_ii(1, 2).b,
+                (int)(g_dd(1.5, 2.5).x * 10), (int)(g_dd(1.5, 2.5).y * 10),
+                g_id(7, 8.5).i, (int)(g_id(7, 8.5).d * 10),
+                (int)(g_ff3(1.5).a * 10), (int)(g_ff3(1.5).b * 10), (int)(g_ff3(1.5).c * 10),
+                g_big(10).a, g_big(10).b, g_big(10).c,
+                g_chars().c[0], g_chars().c[1], g_chars().c[2]);
+        }}\n",
+        ABI_STRUCTS
+    );
+    let cc_program = format!(
+        "{}
+        struct ii g_ii(int a, int b) {{ struct ii r = {{a, b}}; return r; }}
+        struct dd g_dd(double x, double y) {{ struct dd r = {{x, y}}; return r; }}
+        struct id g_id(long i, double d) {{ struct id r = {{i, d}}; return r; }}
+        struct ff3 g_ff3(float a) {{ struct ff3 r = {{a, a * 2, a * 3}}; return r; }}
+        struct big g_big(long a) {{ struct big r = {{a, a + 1, a + 2}}; return r; }}
+        struct chars g_chars(void) {{ struct chars r = {{{{'x', 'y', 'z'}}}}; return r; }}\n",
+        ABI_STRUCTS
+    );
+    assert_linked_with_cc(
+        &program,
+        &cc_program,
+        "1 2|15 25|7 85|15 30 45|10 11 12|xyz\n",
+    );
+}
+
+#[cfg(all(target_arch = "x86_64", not(target_os = "windows")))]
+#[test]
+fn structs_passed_to_cc() {
+    let program = format!(
+        "{}
+        int printf(const char *, ...);
+        long take_all(struct ii a, struct dd b, struct id c, struct ff3 d,
+                      struct big e, struct chars f, struct mixed g, union u h);
+        long many(long a, long b, long c, long d, long e, struct ii f, struct ii g, long h);
+        int main(void) {{
+            struct ii a; a.a = 1; a.b = 2;
+            struct dd b; b.x = 3.5; b.y = 4.5;
+            struct id c; c.i = 5; c.d = 6.5;
+            struct ff3 d; d.a = 7.5; d.b = 8.5; d.c = 9.5;
+            struct big e; e.a = 10; e.b = 11; e.c = 12;
+            struct chars f; f.c[0] = 'a'; f.c[1] = 'b'; f.c[2] = 'c';
+            struct mixed g; g.c = 'g'; g.d = 13.5;
+            union u h; h.i = 14;
+            struct ii x; x.a = 6; x.b = 7;
+            take_all(a, b, c, d, e, f, g, h);
+            printf(\"%ld\\n\", many(1, 2, 3, 4, 5, a, x, 8));
+        }}\n",
+        ABI_STRUCTS
+    );
+    let cc_program = format!(
+        "{}
+        int printf(const char *, ...);
+        long take_all(struct ii a, struct dd b, struct id c, struct ff3 d,
+                      struct big e, struct chars f, struct mixed g, union u h) {{
+            printf(\"%d %d|%g %g|%ld %g|%g %g %g|%ld %ld %ld|%c%c%c|%c %g|%d\\n\",
+                a.a, a.b, b.x, b.y, c.i, c.d, d.a, d.b, d.c, e.a, e.b, e.c,
+                f.c[0], f.c[1], f.c[2], g.c, g.d, h.i);
+            return 0;
+        }}
+        /* the last struct no longer fits in registers */
+        long many(long a, long b, long c, long d, long e, struct ii f, struct ii g, long h) {{
+            return a + b + c + d + e + f.a * 10 + f.b * 100 + g.a * 1000 + g.b * 10000 + h * 100000;
+        }}\n",
+        ABI_STRUCTS
+    );
+    assert_linked_with_cc(
+        &program,
+        &cc_program,
+        "1 2|3.5 4.5|5 6.5|7.5 8.5 9.5|10 11 12|abc|g 13.5|14\n876225\n",
+    );
+}
+
+#[cfg(all(target_arch = "x86_64", not(target_os = "windows")))]
+#[test]
+fn structs_from_cc_callers() {
+    let program = format!(
+        "{}
+        struct ii s_ii(int a, int b) {{ struct ii r; r.a = a; r.b = b; return r; }}
+        struct dd s_dd(double x, double y) {{ struct dd r; r.x = x; r.y = y; return r; }}
+        struct big s_big(long a) {{ struct big r; r.a = a; r.b = a * 2; r.c = a * 3; return r; }}
+        struct ff3 s_ff3(float a) {{ struct ff3 r; r.a = a; r.b = a + 1; r.c = a + 2; return r; }}
+        struct id s_id(struct id x) {{ x.i = x.i + 1; x.d = x.d * 2; return x; }}
+        long s_take(struct big b, struct dd d, struct mixed m) {{
+            return b.a + b.b * 10 + b.c * 100 + (long)(d.x * 4000) + (long)(d.y * 20000)
+                + m.c * 100000 + (long)m.d * 1000000000;
+        }}
+        void check(void);
+        int main(void) {{ check(); }}\n",
+        ABI_STRUCTS
+    );
+    let cc_program = format!(
+        "{}
+        int printf(const char *, ...);
+        struct ii s_ii(int, int);
+        struct dd s_dd(double, double);
+        struct big s_big(long);
+        struct ff3 s_ff3(float);
+        struct id s_id(struct id);
+        long s_take(struct big, struct dd, struct mixed);
+        void check(void) {{
+            struct ii a = s_ii(3, 4);
+            struct dd b = s_dd(1.5, 2.5);
+            struct big c = s_big(7);
+            struct ff3 f = s_ff3(1.5f);
+            struct id in = {{5, 6.5}};
+            struct id d = s_id(in);
+            struct big bb = {{1, 2, 3}};
+            struct dd dd = {{0.25, 0.5}};
+            struct mixed m = {{'q', 9.75}};
+            printf(\"%d %d|%g %g|%ld %ld %ld|%g %g %g|%ld %g|%ld\\n\",
+                a.a, a.b, b.x, b.y, c.a, c.b, c.c, f.a, f.b, f.c, d.i, d.d, s_take(bb, dd, m));
+        }}\n",
+        ABI_STRUCTS
+    );
+    assert_linked_with_cc(
+        &program,
+        &cc_program,
+        "3 4|1.5 2.5|7 14 21|1.5 2.5 3.5|6 13|9011311321\n",
+    );
+}
+
+#[cfg(all(target_arch = "x86_64", not(target_os = "windows")))]
+#[test]
+fn long_double_returns_with_cc() {
+    // `long double` is returned in `st(0)`
+    let program = "
+        int printf(const char *, ...);
+        long double twice(long double x) { return x * 2; }
+        long double cc_half(long double x);
+        void check(void);
+        int main(void) {
+            check();
+            printf(\"%.3Lf\\n\", cc_half(5) + 0.25);
+        }\n";
+    let cc_program = "
+        int printf(const char *, ...);
+        long double twice(long double);
+        long double cc_half(long double x) { return x / 2; }
+        void check(void) { printf(\"%.3Lf\\n\", twice(1.125L)); }\n";
+    assert_linked_with_cc(program, cc_program, "2.250\n2.750\n");
+}
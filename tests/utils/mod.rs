@@ -9,20 +9,12 @@ use std::process::{Command, Output};
 
 pub fn cpp() -> std::process::Command {
     let mut cpp = std::process::Command::new("cpp");
-    cpp.args(&[
-        "-P",
-        "-undef",
-        "-D__DBL_MAX__=1.797693134862315708e+308L",
-        "-D__DBL_MIN__=2.225073858507201383e-308L",
-        "-D__FLT_MAX__=3.402823466385288598e+38F",
-        "-D__FLT_MIN__=1.175494350822287507e-38F",
-        "-D__INTPTR_TYPE__=8",
-        "-D__INT32_TYPE__=4",
-        #[cfg(linux)]
-        "-D__linux__",
-        #[cfg(target_arch = "x86_64")]
-        "-D__x86_64__",
-    ]);
+    cpp.args(&["-P", "-undef"]);
+    // use the same macros as swcc, not the ones for the host cpp
+    let target = saltwater_parser::arch::TargetInfo::host();
+    for (name, body) in saltwater_parser::arch::predefined_macros(&target) {
+        cpp.arg(format!("-D{}={}", name, body));
+    }
     cpp
 }
 
@@ -42,7 +34,12 @@ pub fn compile(
         filename,
         ..Default::default()
     };
-    let module = initialize_aot_module(program.to_owned());
+    compile_with(program, opts, no_link)
+}
+
+/// Like `compile`, but with the given options.
+pub fn compile_with(program: &str, opts: Opt, no_link: bool) -> Result<tempfile::TempPath, Error> {
+    let module = initialize_aot_module(program.to_owned(), &opts.target, opts.opt_level);
     let module = saltwater_codegen::compile(module, program, opts)
         .result?
         .finish();
@@ -56,7 +53,7 @@ pub fn compile(
             .into_temp_path();
         println!("tmp_file is {:?}", tmp_file);
         assemble(module, &tmp_file)?;
-        link(&tmp_file, &output)?;
+        link(&[&tmp_file], &output)?;
     } else {
         assemble(module, &output)?;
     };
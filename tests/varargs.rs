@@ -18,7 +18,7 @@ fn printf_helper(format: &str, args: &[&str]) {
     let mut replaced = vec![];
     let new_args = args.iter().enumerate().map(|(i, arg)| {
         // replace 'a' with a
-        if arg.len() == 3 && arg.chars().next() == Some('\'') && arg.chars().nth(2) == Some('\'') {
+        if arg.len() == 3 && arg.starts_with('\'') && arg.chars().nth(2) == Some('\'') {
             replaced.push(i);
             &arg[1..2]
         } else {
@@ -76,7 +76,217 @@ fn multiple_given_args() {
 }
 
 #[test]
-#[ignore] // TODO: This was broken when upgrading cranelift from 0.91 to 0.92
 fn floats() {
     printf_helper("%f < %f", &["1.0", "1.2"]);
 }
+
+#[test]
+fn call_with_doubles() {
+    // `snprintf` is compiled by `cc`, so it only reads the SSE registers if `al` says to
+    utils::assert_output(
+        "int snprintf(char *str, unsigned long size, const char *format, ...);
+        int puts(const char *s);
+        int main() {
+            char buf[100];
+            snprintf(buf, sizeof buf, \"%.1f %d %.2f\", 1.5, 2, 3.25);
+            puts(buf);
+        }\n",
+        "".into(),
+        "1.5 2 3.25\n",
+    );
+    // there's no way to set `al` for a call through a function pointer
+    if cfg!(target_arch = "x86_64") {
+        utils::assert_compile_error(
+            "int printf(const char *format, ...);
+            int main() {
+                int (*p)(const char *, ...) = printf;
+                p(\"%f\\n\", 1.5);
+            }",
+            "".into(),
+        );
+    }
+    // calls without floating-point arguments don't need it
+    utils::assert_output(
+        "int printf(const char *format, ...);
+        int main() {
+            int (*p)(const char *, ...) = printf;
+            p(\"%d %s\\n\", 1, \"two\");
+        }\n",
+        "".into(),
+        "1 two\n",
+    );
+}
+
+/// Check that `program` exits with `code` both when compiled ahead of time and when JIT compiled.
+fn assert_code(program: &str, code: i32) {
+    utils::assert_code(program, "".into(), code);
+    #[cfg(feature = "jit")]
+    {
+        use saltwater_codegen::JIT;
+        use saltwater_parser::{Opt, Program};
+
+        let Program { result: jit, .. } = JIT::from_string(program, Opt::default());
+        let jit_code = unsafe { jit.expect("program should compile").run_main() };
+        assert_eq!(
+            jit_code,
+            Some(code),
+            "{} should exit with code {}",
+            program,
+            code
+        );
+    }
+}
+
+#[test]
+fn define_ints() {
+    // more variadic arguments than there are registers
+    assert_code(
+        "#include <stdarg.h>
+        int sum(int n, ...) {
+            va_list ap;
+            va_start(ap, n);
+            int total = 0;
+            for (int i = 0; i < n; i++) {
+                total += va_arg(ap, int);
+            }
+            va_end(ap);
+            return total;
+        }
+        int main() {
+            return sum(10, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10);
+        }",
+        55,
+    );
+}
+
+#[test]
+fn define_pointers_and_longs() {
+    assert_code(
+        "#include <stdarg.h>
+        long f(char c, ...) {
+            va_list ap;
+            va_start(ap, c);
+            char *s = va_arg(ap, char *);
+            long l = va_arg(ap, long);
+            int *p = va_arg(ap, int *);
+            va_end(ap);
+            return c + s[1] + l / 1000000000000 + *p;
+        }
+        int main() {
+            int i = 3;
+            return f(1, \"ab\", 2000000000000l, &i) - 'b';
+        }",
+        6,
+    );
+}
+
+#[test]
+fn define_doubles() {
+    // more doubles than there are SSE registers, mixed with integers
+    assert_code(
+        "#include <stdarg.h>
+        double sum(int n, double first, ...) {
+            va_list ap;
+            va_start(ap, first);
+            double total = first;
+            for (int i = 1; i < n; i++) {
+                total += va_arg(ap, double);
+                total *= va_arg(ap, int);
+            }
+            va_end(ap);
+            return total;
+        }
+        int main() {
+            return sum(10, .5, .5, 1, 1.0, 1, 1.0, 1, 1.0, 1, 1.0, 1, 1.0, 1, 1.0, 1, 1.0, 1, 1.0, 2);
+        }",
+        18,
+    );
+}
+
+#[test]
+fn named_params_on_stack() {
+    // `g` and `h` are passed on the stack, so the variadic arguments start after them
+    assert_code(
+        "#include <stdarg.h>
+        long f(long a, long b, long c, long d, long e, long f, long g, double h, double i,
+               double j, double k, double l, double m, double n, double o, double p, ...) {
+            va_list ap;
+            va_start(ap, p);
+            long x = va_arg(ap, long);
+            double y = va_arg(ap, double);
+            va_end(ap);
+            return g + (long)p + x + (long)y;
+        }
+        int main() {
+            return f(0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 2.0, 3l, 4.0);
+        }",
+        10,
+    );
+}
+
+#[test]
+fn va_copy_and_va_list_params() {
+    assert_code(
+        "#include <stdarg.h>
+        int next(va_list ap) {
+            return va_arg(ap, int);
+        }
+        int f(int n, ...) {
+            va_list ap, copy;
+            va_start(ap, n);
+            va_copy(copy, ap);
+            int first = next(ap);
+            int second = next(ap);
+            int again = va_arg(copy, int);
+            va_end(copy);
+            va_end(ap);
+            return first * 100 + second * 10 + again;
+        }
+        int main() {
+            return f(2, 1, 2) - 100;
+        }",
+        21,
+    );
+}
+
+#[test]
+fn vprintf_wrapper() {
+    utils::assert_output(
+        "#include <stdarg.h>
+        int vprintf(const char *format, va_list ap);
+        int my_printf(const char *format, ...) {
+            va_list ap;
+            va_start(ap, format);
+            int n = vprintf(format, ap);
+            va_end(ap);
+            return n;
+        }
+        int main() {
+            my_printf(\"%s %d %ld %c %.1f\\n\", \"hello\", 1, 2l, 'c', 3.5);
+        }",
+        "".into(),
+        "hello 1 2 c 3.5\n",
+    );
+}
+
+#[test]
+fn invalid_va_start() {
+    utils::assert_compile_error(
+        "#include <stdarg.h>
+        int f(int n) {
+            va_list ap;
+            va_start(ap, n);
+            return 0;
+        }",
+        "".into(),
+    );
+    utils::assert_compile_error(
+        "#include <stdarg.h>
+        int f(int n, ...) {
+            va_list ap;
+            va_start(ap, n);
+            return va_arg(ap, float);
+        }",
+        "".into(),
+    );
+}